@@ -65,6 +65,11 @@ pub fn initialize_pool_instr(
     init_amount_0: u64,
     init_amount_1: u64,
     open_time: u64,
+    curve_type: u8,
+    curve_parameter: u64,
+    fee_type: u8,
+    launch_fee_rate: u64,
+    launch_fee_duration: u64,
 ) -> Result<Vec<Instruction>> {
     let payer = read_keypair_file(&config.payer_path)?;
     let user_pubkey = payer.pubkey();
@@ -157,12 +162,17 @@ pub fn initialize_pool_instr(
             system_program: system_program::id(),
             rent: sysvar::rent::id(),
         })
-        .args(gamma_instructions::Initialize {
+        .args(gamma_instructions::InitializeWithCurve {
             init_amount_0,
             init_amount_1,
             open_time,
             max_trade_fee_rate: 1000000,
             volatility_factor: 0,
+            curve_type,
+            curve_parameter,
+            fee_type,
+            launch_fee_rate,
+            launch_fee_duration,
         })
         .instructions()?;
     Ok(instructions)
@@ -247,6 +257,7 @@ pub fn deposit_instr(
             vault_0_mint: token_0_mint,
             vault_1_mint: token_1_mint,
             // lp_mint: token_lp_mint,
+            pool_partners: None,
         })
         .args(gamma_instructions::Deposit {
             lp_token_amount,
@@ -307,6 +318,7 @@ pub fn withdraw_instr(
             vault_1_mint: token_1_mint,
             // lp_mint: token_lp_mint,
             memo_program: spl_memo::id(),
+            pool_partners: None,
         })
         .args(gamma_instructions::Withdraw {
             lp_token_amount,
@@ -444,9 +456,7 @@ pub fn init_user_pool_liquidity_instr(
             user_pool_liquidity,
             system_program: system_program::id(),
         })
-        .args(gamma_instructions::InitUserPoolLiquidity {
-            partner: None,
-        })
+        .args(gamma_instructions::InitUserPoolLiquidity { partner: None })
         .instructions()?;
     Ok(instructions)
 }
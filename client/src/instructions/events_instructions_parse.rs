@@ -415,6 +415,23 @@ pub fn handle_program_instruction(
             }
             println!("{:#?}", CollectFundFee::from(ix));
         }
+        instruction::CollectCreatorFee::DISCRIMINATOR => {
+            let ix = decode_instruction::<instruction::CollectCreatorFee>(&mut ix_data).unwrap();
+            #[derive(Debug)]
+            pub struct CollectCreatorFee {
+                pub amount_0_requested: u64,
+                pub amount_1_requested: u64,
+            }
+            impl From<instruction::CollectCreatorFee> for CollectCreatorFee {
+                fn from(instr: instruction::CollectCreatorFee) -> CollectCreatorFee {
+                    CollectCreatorFee {
+                        amount_0_requested: instr.amount_0_requested,
+                        amount_1_requested: instr.amount_1_requested,
+                    }
+                }
+            }
+            println!("{:#?}", CollectCreatorFee::from(ix));
+        }
         instruction::Deposit::DISCRIMINATOR => {
             let ix = decode_instruction::<instruction::Deposit>(&mut ix_data).unwrap();
             #[derive(Debug)]
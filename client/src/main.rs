@@ -143,6 +143,21 @@ pub enum GammaCommands {
         init_amount_1: u64,
         #[clap(short, long, default_value_t = 0)]
         open_time: u64,
+        /// 0: ConstantProduct, 1: StableSwap, 2: Weighted
+        #[clap(long, default_value_t = 0)]
+        curve_type: u8,
+        /// Amplification coefficient for StableSwap, token_0 weight out of 1_000_000 for Weighted
+        #[clap(long, default_value_t = 0)]
+        curve_parameter: u64,
+        /// 0: Volatility, 1: LaunchLinearDecay, 2: LaunchExponentialDecay, 3: Directional
+        #[clap(long, default_value_t = 0)]
+        fee_type: u8,
+        /// Fee rate at open time for the launch fee types
+        #[clap(long, default_value_t = 0)]
+        launch_fee_rate: u64,
+        /// Seconds for the launch fee to decay to the trade fee rate
+        #[clap(long, default_value_t = 0)]
+        launch_fee_duration: u64,
     },
     InitUserPoolLiquidity {
         pool_id: Pubkey,
@@ -256,6 +271,11 @@ fn main() -> Result<()> {
             init_amount_0,
             init_amount_1,
             open_time,
+            curve_type,
+            curve_parameter,
+            fee_type,
+            launch_fee_rate,
+            launch_fee_duration,
         } => {
             let (mint0, mint1, init_amount_0, init_amount_1) = if mint0 > mint1 {
                 (mint1, mint0, init_amount_1, init_amount_0)
@@ -279,6 +299,11 @@ fn main() -> Result<()> {
                 init_amount_0,
                 init_amount_1,
                 open_time,
+                curve_type,
+                curve_parameter,
+                fee_type,
+                launch_fee_rate,
+                launch_fee_duration,
             )?;
 
             let signers = vec![&payer];
@@ -329,6 +354,7 @@ fn main() -> Result<()> {
                 pool_state.vault_amount_without_fee()?;
             // calculate amount
             let results = gamma::curve::CurveCalculator::lp_tokens_to_trading_tokens(
+                pool_state.curve_type()?,
                 u128::from(lp_token_amount),
                 u128::from(pool_state.lp_supply),
                 u128::from(total_token_0_amount),
@@ -435,6 +461,7 @@ fn main() -> Result<()> {
                 pool_state.vault_amount_without_fee()?;
             // calculate amount
             let results = gamma::curve::CurveCalculator::lp_tokens_to_trading_tokens(
+                pool_state.curve_type()?,
                 u128::from(lp_token_amount),
                 u128::from(pool_state.lp_supply),
                 u128::from(total_token_0_amount),
@@ -621,18 +648,20 @@ fn main() -> Result<()> {
                 .as_secs();
 
             // here we make a RPC call again, we can optimize this later by making it part of `get_multiple_accounts` call.
-            let observation: gamma::states::ObservationState =
-                program.account(pool_state.observation_key)?;
+            // Expanded observation accounts can not be deserialized as `ObservationState`, read the raw data instead.
+            let observation_data = rpc_client.get_account_data(&pool_state.observation_key)?;
+            let observation = gamma::states::Observations::from_account_data(&observation_data)?;
 
             let result = gamma::curve::CurveCalculator::swap_base_input(
                 u128::from(actual_amount_in),
                 u128::from(total_input_token_amount),
                 u128::from(total_output_token_amount),
+                trade_direction,
                 &amm_config_state,
                 &pool_state,
                 current_unix_timestamp,
                 &observation,
-                false,
+                gamma::fees::FeePath::UnsignedFlow,
             )?;
 
             let amount_out = u64::try_from(result.destination_amount_swapped).unwrap();
@@ -782,18 +811,20 @@ fn main() -> Result<()> {
                 .as_secs();
 
             // here we make a RPC call again, we can optimize this later by making it part of `get_multiple_accounts` call.
-            let observation: gamma::states::ObservationState =
-                program.account(pool_state.observation_key)?;
+            // Expanded observation accounts can not be deserialized as `ObservationState`, read the raw data instead.
+            let observation_data = rpc_client.get_account_data(&pool_state.observation_key)?;
+            let observation = gamma::states::Observations::from_account_data(&observation_data)?;
 
             let result = gamma::curve::CurveCalculator::swap_base_output(
                 u128::from(actual_amount_out),
                 u128::from(total_input_token_amount),
                 u128::from(total_output_token_amount),
+                trade_direction,
                 &amm_config_state,
                 &pool_state,
                 current_unix_timestamp,
                 &observation,
-                false,
+                gamma::fees::FeePath::UnsignedFlow,
             )?;
 
             let source_amount_swapped = u64::try_from(result.source_amount_swapped).unwrap();
@@ -1,12 +1,27 @@
 //! Swap calculations
 
 use crate::error::GammaError;
-use crate::fees::{DynamicFee, FeeType};
-use crate::states::{AmmConfig, ObservationState, PoolState};
-use crate::{curve::constant_product::ConstantProductCurve, fees::StaticFee};
+use crate::fees::{ceil_div, DynamicFee, FeePath, FeeType, FEE_RATE_DENOMINATOR_VALUE};
+use crate::states::{AmmConfig, Observations, PoolState, Q32};
+use crate::utils::math::U256;
+use crate::{
+    curve::{
+        constant_product::ConstantProductCurve, stable_swap::StableSwapCurve,
+        weighted::WeightedCurve,
+    },
+    fees::StaticFee,
+};
 use anchor_lang::prelude::*;
+use rust_decimal::Decimal;
 use std::fmt::Debug;
 
+/// Max iterations of the search for the swap amount of a single sided deposit,
+/// the remaining error is left with the depositor as source tokens that are not transferred
+pub const SINGLE_SIDED_DEPOSIT_SEARCH_ITERATIONS: u32 = 40;
+/// Iterations of the search for the swap amount of a single sided deposit that do not halve
+/// the bracket of the amount before it is bisected
+const SINGLE_SIDED_DEPOSIT_STALLED_ITERATIONS: u32 = 3;
+
 /// Helper function for mapping to GammaError::CalculationFailure
 pub fn map_zero_to_none(x: u128) -> Option<u128> {
     if x == 0 {
@@ -37,6 +52,42 @@ impl TradeDirection {
     }
 }
 
+/// The invariant used by a pool for pricing swaps
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[repr(u8)]
+pub enum CurveType {
+    /// Uniswap style x * y = k
+    #[default]
+    ConstantProduct = 0,
+    /// Curve style StableSwap with an amplification coefficient(`curve_parameter`)
+    StableSwap = 1,
+    /// Balancer style weighted constant mean x^w_0 * y^w_1 = k, with the token_0 weight(`curve_parameter`)
+    Weighted = 2,
+}
+
+impl CurveType {
+    pub fn new(value: u8) -> Result<Self> {
+        match value {
+            0 => Ok(CurveType::ConstantProduct),
+            1 => Ok(CurveType::StableSwap),
+            2 => Ok(CurveType::Weighted),
+            _ => err!(GammaError::InvalidInput),
+        }
+    }
+
+    /// Validates the curve parameter of a new pool
+    pub fn validate_curve_parameter(&self, curve_parameter: u64) -> Result<()> {
+        match self {
+            CurveType::ConstantProduct => {
+                require_eq!(curve_parameter, 0, GammaError::InvalidInput);
+                Ok(())
+            }
+            CurveType::StableSwap => StableSwapCurve::validate_amp(curve_parameter),
+            CurveType::Weighted => WeightedCurve::validate_weight(curve_parameter),
+        }
+    }
+}
+
 /// The direction to round.  Used for pool token to trading token conversions to
 /// avoid losing value on any deposit or withdrawal.
 #[repr(C)]
@@ -74,10 +125,106 @@ pub struct SwapResult {
     pub protocol_fee: u128,
     /// Amount of source tokens going to protocol team
     pub fund_fee: u128,
+    /// Amount of source tokens going to the pool creator
+    pub creator_fee: u128,
     /// Dynamic fee rate
     pub dynamic_fee_rate: u64,
 }
 
+impl SwapResult {
+    /// Part of the dynamic fee kept in the vault for the liquidity providers
+    pub fn lp_fee(&self) -> Option<u128> {
+        self.dynamic_fee
+            .checked_sub(self.protocol_fee)?
+            .checked_sub(self.fund_fee)?
+            .checked_sub(self.creator_fee)
+    }
+}
+
+/// Price of a single raw unit of a token, in terms of the reference asset of the oracle:
+/// `price * 10^exponent`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct OraclePrice {
+    pub price: u64,
+    pub exponent: i32,
+}
+
+/// The curve of a pool with the terms that do not depend on the swapped amount computed once,
+/// for the repeated swaps from the same reserves of `CurveCalculator::single_sided_deposit_swap_amount`
+enum PreparedCurve {
+    ConstantProduct,
+    /// The invariant D of the reserves
+    StableSwap {
+        amp: u64,
+        d: u128,
+    },
+    /// The exponent w_x / w_y of the swap
+    Weighted {
+        exponent: Decimal,
+    },
+}
+
+impl PreparedCurve {
+    fn new(
+        swap_source_amount: u128,
+        swap_destination_amount: u128,
+        trade_direction: TradeDirection,
+        pool_state: &PoolState,
+    ) -> Result<Self> {
+        Ok(match pool_state.curve_type()? {
+            CurveType::ConstantProduct => PreparedCurve::ConstantProduct,
+            CurveType::StableSwap => PreparedCurve::StableSwap {
+                amp: pool_state.curve_parameter,
+                d: StableSwapCurve::compute_d(
+                    pool_state.curve_parameter,
+                    swap_source_amount,
+                    swap_destination_amount,
+                )?,
+            },
+            CurveType::Weighted => {
+                let (source_weight, destination_weight) =
+                    CurveCalculator::weights(trade_direction, pool_state)?;
+                PreparedCurve::Weighted {
+                    exponent: WeightedCurve::exponent(source_weight, destination_weight)?,
+                }
+            }
+        })
+    }
+
+    /// `CurveCalculator::swap_base_input_without_fees` from the reserves the curve was prepared with
+    fn swap_base_input_without_fees(
+        &self,
+        source_amount_to_be_swapped: u128,
+        swap_source_amount: u128,
+        swap_destination_amount: u128,
+    ) -> Result<u128> {
+        match *self {
+            PreparedCurve::ConstantProduct => ConstantProductCurve::swap_base_input_without_fees(
+                source_amount_to_be_swapped,
+                swap_source_amount,
+                swap_destination_amount,
+            ),
+            PreparedCurve::StableSwap { amp, d } => {
+                StableSwapCurve::swap_base_input_without_fees_with_d(
+                    source_amount_to_be_swapped,
+                    swap_source_amount,
+                    swap_destination_amount,
+                    amp,
+                    d,
+                )
+            }
+            PreparedCurve::Weighted { exponent } => {
+                WeightedCurve::swap_base_input_without_fees_with_exponent(
+                    source_amount_to_be_swapped,
+                    swap_source_amount,
+                    swap_destination_amount,
+                    exponent,
+                )
+            }
+        }
+    }
+}
+
 /// Concrete struct to wrap around the trait object which performs calculation.
 #[derive(Clone, Debug, Default, PartialEq)]
 pub struct CurveCalculator {}
@@ -100,37 +247,65 @@ impl CurveCalculator {
         source_amount_to_be_swapped: u128,
         swap_source_amount: u128,
         swap_destination_amount: u128,
+        trade_direction: TradeDirection,
         amm_config: &AmmConfig,
         pool_state: &PoolState,
         block_timestamp: u64,
-        observation_state: &ObservationState,
-        // This is to indicate that the trade is not a toxic trade and is coming to us from a signed dflow segmenter.
-        // It is planed to charge an additional fee for this trade if it is false in future.
-        is_invoked_by_signed_segmenter: bool,
-        // TODO: add fee type here once that is configurable on pool level/ or we can use it from pool_state
+        observation_state: &Observations,
+        // Signed segmenters get a discount, the flow of others is charged a surcharge
+        fee_path: FeePath,
     ) -> Result<SwapResult> {
+        let fee_type = pool_state.fee_type()?;
+        let token_0_price_after_swap_x32 = if fee_type == FeeType::Directional {
+            // The fee depends on the price after the swap, it is estimated without fees
+            let destination_amount_without_fees = Self::swap_base_input_without_fees(
+                source_amount_to_be_swapped,
+                swap_source_amount,
+                swap_destination_amount,
+                trade_direction,
+                pool_state,
+            )?;
+            Some(Self::token_0_price_after_swap_x32(
+                source_amount_to_be_swapped,
+                destination_amount_without_fees,
+                swap_source_amount,
+                swap_destination_amount,
+                trade_direction,
+                pool_state,
+            )?)
+        } else {
+            None
+        };
         let (dynamic_fee, dynamic_fee_rate) = DynamicFee::dynamic_fee(
             source_amount_to_be_swapped,
             block_timestamp,
             observation_state,
-            FeeType::Volatility,
-            amm_config.trade_fee_rate,
+            fee_type,
+            pool_state.trade_fee_rate(amm_config),
             pool_state,
-            is_invoked_by_signed_segmenter,
+            token_0_price_after_swap_x32,
+            amm_config,
+            fee_path,
         )?;
 
-        let protocol_fee = StaticFee::protocol_fee(dynamic_fee, amm_config.protocol_fee_rate)
-            .ok_or(GammaError::InvalidFee)?;
-        let fund_fee = StaticFee::fund_fee(dynamic_fee, amm_config.fund_fee_rate)
+        let protocol_fee =
+            StaticFee::protocol_fee(dynamic_fee, pool_state.protocol_fee_rate(amm_config))
+                .ok_or(GammaError::InvalidFee)?;
+        let fund_fee = StaticFee::fund_fee(dynamic_fee, pool_state.fund_fee_rate(amm_config))
             .ok_or(GammaError::InvalidFee)?;
+        let creator_fee =
+            StaticFee::creator_fee(dynamic_fee, u64::from(pool_state.creator_fee_rate))
+                .ok_or(GammaError::InvalidFee)?;
 
         let source_amount_after_fees = source_amount_to_be_swapped
             .checked_sub(dynamic_fee)
             .ok_or(GammaError::MathOverflow)?;
-        let destination_amount_swapped = ConstantProductCurve::swap_base_input_without_fees(
+        let destination_amount_swapped = Self::swap_base_input_without_fees(
             source_amount_after_fees,
             swap_source_amount,
             swap_destination_amount,
+            trade_direction,
+            pool_state,
         )?;
 
         #[cfg(feature = "enable-log")]
@@ -148,44 +323,450 @@ impl CurveCalculator {
             dynamic_fee,
             protocol_fee,
             fund_fee,
+            creator_fee,
+            dynamic_fee_rate,
+        })
+    }
+
+    /// Find how much of a single sided deposit of `source_amount` has to be swapped so the
+    /// rest of it and the swapped destination tokens are in the ratio of the reserves after the swap.
+    /// Returns the largest swap amount for which the destination tokens are the limiting side,
+    /// so none of the swapped tokens are left over after the deposit.
+    /// The fee rate is the one of the swapped amount, it is estimated from `source_amount` first
+    /// and the swap amount is searched again with the fee rate of the amount found.
+    pub fn single_sided_deposit_swap_amount(
+        source_amount: u128,
+        swap_source_amount: u128,
+        swap_destination_amount: u128,
+        trade_direction: TradeDirection,
+        amm_config: &AmmConfig,
+        pool_state: &PoolState,
+        block_timestamp: u64,
+        observation_state: &Observations,
+        fee_path: FeePath,
+    ) -> Result<u128> {
+        let curve = PreparedCurve::new(
+            swap_source_amount,
+            swap_destination_amount,
+            trade_direction,
+            pool_state,
+        )?;
+        let fee_type = pool_state.fee_type()?;
+        let dynamic_fee_rate = |amount_to_swap: u128| -> Result<u64> {
+            let token_0_price_after_swap_x32 = if fee_type == FeeType::Directional {
+                // The swapped amount depends on the fee, the price is estimated without fees
+                let destination_amount_without_fees = curve.swap_base_input_without_fees(
+                    amount_to_swap,
+                    swap_source_amount,
+                    swap_destination_amount,
+                )?;
+                Some(Self::token_0_price_after_swap_x32(
+                    amount_to_swap,
+                    destination_amount_without_fees,
+                    swap_source_amount,
+                    swap_destination_amount,
+                    trade_direction,
+                    pool_state,
+                )?)
+            } else {
+                None
+            };
+            let (_, dynamic_fee_rate) = DynamicFee::dynamic_fee(
+                amount_to_swap,
+                block_timestamp,
+                observation_state,
+                fee_type,
+                pool_state.trade_fee_rate(amm_config),
+                pool_state,
+                token_0_price_after_swap_x32,
+                amm_config,
+                fee_path,
+            )?;
+            Ok(dynamic_fee_rate)
+        };
+
+        let estimated_fee_rate = dynamic_fee_rate(source_amount)?;
+        let amount_to_swap = Self::single_sided_deposit_swap_amount_with_fee_rate(
+            source_amount,
+            swap_source_amount,
+            swap_destination_amount,
+            amm_config,
+            pool_state,
+            &curve,
+            estimated_fee_rate,
+        )?;
+        if amount_to_swap == 0 {
+            return Ok(0);
+        }
+        let fee_rate = dynamic_fee_rate(amount_to_swap)?;
+        if fee_rate == estimated_fee_rate {
+            return Ok(amount_to_swap);
+        }
+        Self::single_sided_deposit_swap_amount_with_fee_rate(
+            source_amount,
+            swap_source_amount,
+            swap_destination_amount,
+            amm_config,
+            pool_state,
+            &curve,
+            fee_rate,
+        )
+    }
+
+    /// The swap amount of `single_sided_deposit_swap_amount` for a fixed fee rate.
+    /// The destination side grows and the source side shrinks with the swap amount, the amount where they
+    /// cross is searched with the Illinois variant of the false position method, which keeps it bracketed.
+    /// The bracket is bisected when it has not been halved for `SINGLE_SIDED_DEPOSIT_STALLED_ITERATIONS`,
+    /// and the constant product pools start from the amount solved in closed form.
+    fn single_sided_deposit_swap_amount_with_fee_rate(
+        source_amount: u128,
+        swap_source_amount: u128,
+        swap_destination_amount: u128,
+        amm_config: &AmmConfig,
+        pool_state: &PoolState,
+        curve: &PreparedCurve,
+        dynamic_fee_rate: u64,
+    ) -> Result<u128> {
+        let protocol_fee_rate = pool_state.protocol_fee_rate(amm_config);
+        let fund_fee_rate = pool_state.fund_fee_rate(amm_config);
+        let creator_fee_rate = u64::from(pool_state.creator_fee_rate);
+
+        // (source_amount - amount_to_swap) / new_swap_source_amount >= destination_amount_swapped / new_swap_destination_amount,
+        // returns whether the destination tokens are limiting and the difference of both sides
+        let destination_is_limiting = |amount_to_swap: u128| -> Result<(bool, U256)> {
+            let dynamic_fee = ceil_div(
+                amount_to_swap,
+                u128::from(dynamic_fee_rate),
+                u128::from(FEE_RATE_DENOMINATOR_VALUE),
+            )
+            .ok_or(GammaError::MathOverflow)?;
+            let protocol_fee = StaticFee::protocol_fee(dynamic_fee, protocol_fee_rate)
+                .ok_or(GammaError::InvalidFee)?;
+            let fund_fee =
+                StaticFee::fund_fee(dynamic_fee, fund_fee_rate).ok_or(GammaError::InvalidFee)?;
+            let creator_fee = StaticFee::creator_fee(dynamic_fee, creator_fee_rate)
+                .ok_or(GammaError::InvalidFee)?;
+            let destination_amount_swapped = curve.swap_base_input_without_fees(
+                amount_to_swap
+                    .checked_sub(dynamic_fee)
+                    .ok_or(GammaError::MathOverflow)?,
+                swap_source_amount,
+                swap_destination_amount,
+            )?;
+            let new_swap_source_amount = swap_source_amount
+                .checked_add(amount_to_swap)
+                .and_then(|amount| amount.checked_sub(protocol_fee))
+                .and_then(|amount| amount.checked_sub(fund_fee))
+                .and_then(|amount| amount.checked_sub(creator_fee))
+                .ok_or(GammaError::MathOverflow)?;
+            let new_swap_destination_amount = swap_destination_amount
+                .checked_sub(destination_amount_swapped)
+                .ok_or(GammaError::MathOverflow)?;
+
+            let source_side = U256::from(source_amount - amount_to_swap)
+                .checked_mul(U256::from(new_swap_destination_amount))
+                .ok_or(GammaError::MathOverflow)?;
+            let destination_side = U256::from(destination_amount_swapped)
+                .checked_mul(U256::from(new_swap_source_amount))
+                .ok_or(GammaError::MathOverflow)?;
+            Ok(if source_side >= destination_side {
+                (true, source_side - destination_side)
+            } else {
+                (false, destination_side - source_side)
+            })
+        };
+
+        // The destination tokens are always limiting without a swap
+        let (mut low, mut low_difference) = (0u128, destination_is_limiting(0)?.1);
+        let (is_limiting, mut high_difference) = destination_is_limiting(source_amount)?;
+        if is_limiting {
+            return Ok(source_amount);
+        }
+        let mut high = source_amount;
+
+        let mut next_amount = match curve {
+            PreparedCurve::ConstantProduct => {
+                Self::constant_product_single_sided_deposit_swap_amount(
+                    source_amount,
+                    swap_source_amount,
+                    dynamic_fee_rate,
+                    protocol_fee_rate
+                        .saturating_add(fund_fee_rate)
+                        .saturating_add(creator_fee_rate),
+                )
+            }
+            _ => None,
+        };
+        let (mut last_side, mut stalled_iterations, mut previous_width) = (None, 0, high - low);
+        for _ in 0..SINGLE_SIDED_DEPOSIT_SEARCH_ITERATIONS {
+            if high - low <= 1 {
+                break;
+            }
+            let amount = match next_amount.take() {
+                Some(amount) => amount,
+                None if stalled_iterations >= SINGLE_SIDED_DEPOSIT_STALLED_ITERATIONS
+                    || (low_difference.is_zero() && high_difference.is_zero()) =>
+                {
+                    low + (high - low) / 2
+                }
+                None => {
+                    // low + (high - low) * low_difference / (low_difference + high_difference)
+                    let step = U256::from(high - low)
+                        .checked_mul(low_difference)
+                        .and_then(|value| {
+                            value.checked_div(low_difference.checked_add(high_difference)?)
+                        })
+                        .ok_or(GammaError::MathOverflow)?;
+                    low + step.as_u128()
+                }
+            }
+            .clamp(low + 1, high - 1);
+
+            let (is_limiting, difference) = destination_is_limiting(amount)?;
+            if is_limiting {
+                low = amount;
+                low_difference = difference;
+                // The same end moved twice, the other one is weighted down so the next amount gets past the root
+                if last_side == Some(true) {
+                    high_difference /= 2;
+                }
+            } else {
+                high = amount;
+                high_difference = difference;
+                if last_side == Some(false) {
+                    low_difference /= 2;
+                }
+            }
+            last_side = Some(is_limiting);
+
+            let width = high - low;
+            stalled_iterations = if width > previous_width / 2 {
+                stalled_iterations + 1
+            } else {
+                0
+            };
+            previous_width = width;
+        }
+        Ok(low)
+    }
+
+    /// The swap amount of a single sided deposit to a constant product pool, without the rounding of the fees and the swap.
+    /// With a fee rate f, of which the share p leaves the reserves, s solves (a - s) * x = s * (1 - f) * (x + s * (1 - p * f)),
+    /// s = (sqrt(x^2 * (2 - f)^2 + 4 * (1 - f) * (1 - p * f) * a * x) - x * (2 - f)) / (2 * (1 - f) * (1 - p * f))
+    fn constant_product_single_sided_deposit_swap_amount(
+        source_amount: u128,
+        swap_source_amount: u128,
+        dynamic_fee_rate: u64,
+        fee_share_rate: u64,
+    ) -> Option<u128> {
+        // With G = (1 - f) * D and H = (1 - p * f) * D^2,
+        // s = (sqrt((x * (D + G) * D^2)^2 + 4 * G * H * a * x * D^3) - x * (D + G) * D^2) / (2 * G * H)
+        let denominator = U256::from(FEE_RATE_DENOMINATOR_VALUE);
+        let g = denominator.checked_sub(U256::from(dynamic_fee_rate))?;
+        let h = denominator
+            .checked_mul(denominator)?
+            .checked_sub(U256::from(dynamic_fee_rate).checked_mul(U256::from(fee_share_rate))?)?;
+        if g.is_zero() || h.is_zero() {
+            return None;
+        }
+        let x = U256::from(swap_source_amount);
+        let denominator_squared = denominator.checked_mul(denominator)?;
+        let x_term = x
+            .checked_mul(denominator.checked_add(g)?)?
+            .checked_mul(denominator_squared)?;
+        let discriminant = x_term.checked_mul(x_term)?.checked_add(
+            U256::from(4)
+                .checked_mul(g)?
+                .checked_mul(h)?
+                .checked_mul(U256::from(source_amount))?
+                .checked_mul(x)?
+                .checked_mul(denominator_squared)?
+                .checked_mul(denominator)?,
+        )?;
+        let amount = discriminant
+            .integer_sqrt()
+            .checked_sub(x_term)?
+            .checked_div(U256::from(2).checked_mul(g)?.checked_mul(h)?)?;
+        u128::try_from(amount).ok()
+    }
+
+    /// Subtract fees and calculate how much destination token will be received
+    /// for a given amount of source token, priced by the oracle instead of the curve.
+    /// Pool reserves are not used for pricing, `swap_destination_amount` is only the capacity limit.
+    pub fn oracle_swap_base_input(
+        source_amount_to_be_swapped: u128,
+        swap_source_amount: u128,
+        swap_destination_amount: u128,
+        source_price: OraclePrice,
+        destination_price: OraclePrice,
+        trade_direction: TradeDirection,
+        amm_config: &AmmConfig,
+        pool_state: &PoolState,
+        block_timestamp: u64,
+        observation_state: &Observations,
+        fee_path: FeePath,
+    ) -> Result<SwapResult> {
+        let fee_type = pool_state.fee_type()?;
+        let token_0_price_after_swap_x32 = if fee_type == FeeType::Directional {
+            let destination_amount_without_fees = Self::oracle_swap_without_fees(
+                source_amount_to_be_swapped,
+                source_price,
+                destination_price,
+            )?;
+            Some(Self::token_0_price_after_swap_x32(
+                source_amount_to_be_swapped,
+                destination_amount_without_fees.min(swap_destination_amount),
+                swap_source_amount,
+                swap_destination_amount,
+                trade_direction,
+                pool_state,
+            )?)
+        } else {
+            None
+        };
+        let (dynamic_fee, dynamic_fee_rate) = DynamicFee::dynamic_fee(
+            source_amount_to_be_swapped,
+            block_timestamp,
+            observation_state,
+            fee_type,
+            pool_state.trade_fee_rate(amm_config),
+            pool_state,
+            token_0_price_after_swap_x32,
+            amm_config,
+            fee_path,
+        )?;
+
+        let protocol_fee =
+            StaticFee::protocol_fee(dynamic_fee, pool_state.protocol_fee_rate(amm_config))
+                .ok_or(GammaError::InvalidFee)?;
+        let fund_fee = StaticFee::fund_fee(dynamic_fee, pool_state.fund_fee_rate(amm_config))
+            .ok_or(GammaError::InvalidFee)?;
+        let creator_fee =
+            StaticFee::creator_fee(dynamic_fee, u64::from(pool_state.creator_fee_rate))
+                .ok_or(GammaError::InvalidFee)?;
+
+        let source_amount_after_fees = source_amount_to_be_swapped
+            .checked_sub(dynamic_fee)
+            .ok_or(GammaError::MathOverflow)?;
+        let destination_amount_swapped = Self::oracle_swap_without_fees(
+            source_amount_after_fees,
+            source_price,
+            destination_price,
+        )?;
+        if destination_amount_swapped >= swap_destination_amount {
+            return err!(GammaError::InsufficientLiquidity);
+        }
+
+        Ok(SwapResult {
+            new_swap_source_amount: swap_source_amount
+                .checked_add(source_amount_to_be_swapped)
+                .ok_or(GammaError::MathOverflow)?,
+            new_swap_destination_amount: swap_destination_amount
+                .checked_sub(destination_amount_swapped)
+                .ok_or(GammaError::MathOverflow)?,
+            source_amount_swapped: source_amount_to_be_swapped,
+            destination_amount_swapped,
+            dynamic_fee,
+            protocol_fee,
+            fund_fee,
+            creator_fee,
             dynamic_fee_rate,
         })
     }
 
+    /// destination_amount = source_amount * source_price / destination_price, rounded down
+    pub fn oracle_swap_without_fees(
+        source_amount_to_be_swapped: u128,
+        source_price: OraclePrice,
+        destination_price: OraclePrice,
+    ) -> Result<u128> {
+        let exponent = source_price
+            .exponent
+            .checked_sub(destination_price.exponent)
+            .ok_or(GammaError::MathOverflow)?;
+        let scale = U256::from(10)
+            .checked_pow(U256::from(exponent.unsigned_abs()))
+            .ok_or(GammaError::MathOverflow)?;
+
+        let mut numerator = U256::from(source_amount_to_be_swapped)
+            .checked_mul(U256::from(source_price.price))
+            .ok_or(GammaError::MathOverflow)?;
+        let mut denominator = U256::from(destination_price.price);
+        if exponent >= 0 {
+            numerator = numerator
+                .checked_mul(scale)
+                .ok_or(GammaError::MathOverflow)?;
+        } else {
+            denominator = denominator
+                .checked_mul(scale)
+                .ok_or(GammaError::MathOverflow)?;
+        }
+
+        let destination_amount_swapped = numerator
+            .checked_div(denominator)
+            .ok_or(GammaError::MathOverflow)?;
+        if destination_amount_swapped > U256::from(u128::MAX) {
+            return err!(GammaError::MathOverflow);
+        }
+        Ok(destination_amount_swapped.as_u128())
+    }
+
     /// Subtract fees and calculate how much source token will be required
     pub fn swap_base_output(
         destination_amount_to_be_swapped: u128,
         swap_source_amount: u128,
         swap_destination_amount: u128,
+        trade_direction: TradeDirection,
         amm_config: &AmmConfig,
         pool_state: &PoolState,
         block_timestamp: u64,
-        observation_state: &ObservationState,
-        is_invoked_by_signed_segmenter: bool,
+        observation_state: &Observations,
+        fee_path: FeePath,
     ) -> Result<SwapResult> {
-        let source_amount_swapped = ConstantProductCurve::swap_base_output_without_fees(
+        let source_amount_swapped = Self::swap_base_output_without_fees(
             destination_amount_to_be_swapped,
             swap_source_amount,
             swap_destination_amount,
+            trade_direction,
+            pool_state,
         )?;
 
+        let fee_type = pool_state.fee_type()?;
+        let token_0_price_after_swap_x32 = if fee_type == FeeType::Directional {
+            Some(Self::token_0_price_after_swap_x32(
+                source_amount_swapped,
+                destination_amount_to_be_swapped,
+                swap_source_amount,
+                swap_destination_amount,
+                trade_direction,
+                pool_state,
+            )?)
+        } else {
+            None
+        };
         let (source_amount, dynamic_fee_rate) = DynamicFee::calculate_pre_fee_amount(
             block_timestamp,
             source_amount_swapped,
             observation_state,
-            FeeType::Volatility,
-            amm_config.trade_fee_rate,
+            fee_type,
+            pool_state.trade_fee_rate(amm_config),
             pool_state,
-            is_invoked_by_signed_segmenter,
+            token_0_price_after_swap_x32,
+            amm_config,
+            fee_path,
         )?;
 
         let dynamic_fee = source_amount
             .checked_sub(source_amount_swapped)
             .ok_or(GammaError::MathOverflow)?;
-        let protocol_fee = StaticFee::protocol_fee(dynamic_fee, amm_config.protocol_fee_rate)
-            .ok_or(GammaError::MathOverflow)?;
-        let fund_fee = StaticFee::fund_fee(dynamic_fee, amm_config.fund_fee_rate)
+        let protocol_fee =
+            StaticFee::protocol_fee(dynamic_fee, pool_state.protocol_fee_rate(amm_config))
+                .ok_or(GammaError::MathOverflow)?;
+        let fund_fee = StaticFee::fund_fee(dynamic_fee, pool_state.fund_fee_rate(amm_config))
             .ok_or(GammaError::MathOverflow)?;
+        let creator_fee =
+            StaticFee::creator_fee(dynamic_fee, u64::from(pool_state.creator_fee_rate))
+                .ok_or(GammaError::MathOverflow)?;
 
         Ok(SwapResult {
             new_swap_source_amount: swap_source_amount
@@ -198,26 +779,257 @@ impl CurveCalculator {
             destination_amount_swapped: destination_amount_to_be_swapped,
             protocol_fee,
             fund_fee,
+            creator_fee,
             dynamic_fee,
             dynamic_fee_rate,
         })
     }
 
+    /// Get the (source, destination) weights of a weighted pool for the trade direction
+    fn weights(trade_direction: TradeDirection, pool_state: &PoolState) -> Result<(u64, u64)> {
+        let (token_0_weight, token_1_weight) = WeightedCurve::weights(pool_state.curve_parameter)?;
+        Ok(match trade_direction {
+            TradeDirection::ZeroForOne => (token_0_weight, token_1_weight),
+            TradeDirection::OneForZero => (token_1_weight, token_0_weight),
+        })
+    }
+
+    /// The token_0 price of the pool after a swap without fees, Q32.32 like `PoolState::token_price_x32`
+    fn token_0_price_after_swap_x32(
+        source_amount_swapped: u128,
+        destination_amount_swapped: u128,
+        swap_source_amount: u128,
+        swap_destination_amount: u128,
+        trade_direction: TradeDirection,
+        pool_state: &PoolState,
+    ) -> Result<u128> {
+        let new_swap_source_amount = swap_source_amount
+            .checked_add(source_amount_swapped)
+            .ok_or(GammaError::MathOverflow)?;
+        let new_swap_destination_amount = swap_destination_amount
+            .checked_sub(destination_amount_swapped)
+            .ok_or(GammaError::MathOverflow)?;
+        let (token_0_amount, token_1_amount) = match trade_direction {
+            TradeDirection::ZeroForOne => (new_swap_source_amount, new_swap_destination_amount),
+            TradeDirection::OneForZero => (new_swap_destination_amount, new_swap_source_amount),
+        };
+        let (token_0_value, token_1_value) =
+            pool_state.price_values(token_0_amount, token_1_amount)?;
+        token_1_value
+            .checked_mul(Q32)
+            .ok_or(GammaError::MathOverflow)?
+            .checked_div(token_0_value)
+            .ok_or(GammaError::MathOverflow.into())
+    }
+
+    /// Calculate how much destination token will be received for a given amount of
+    /// source token, using the curve of the pool
+    pub fn swap_base_input_without_fees(
+        source_amount_to_be_swapped: u128,
+        swap_source_amount: u128,
+        swap_destination_amount: u128,
+        trade_direction: TradeDirection,
+        pool_state: &PoolState,
+    ) -> Result<u128> {
+        match pool_state.curve_type()? {
+            CurveType::ConstantProduct => ConstantProductCurve::swap_base_input_without_fees(
+                source_amount_to_be_swapped,
+                swap_source_amount,
+                swap_destination_amount,
+            ),
+            CurveType::StableSwap => StableSwapCurve::swap_base_input_without_fees(
+                source_amount_to_be_swapped,
+                swap_source_amount,
+                swap_destination_amount,
+                pool_state.curve_parameter,
+            ),
+            CurveType::Weighted => {
+                let (source_weight, destination_weight) =
+                    Self::weights(trade_direction, pool_state)?;
+                WeightedCurve::swap_base_input_without_fees(
+                    source_amount_to_be_swapped,
+                    swap_source_amount,
+                    swap_destination_amount,
+                    source_weight,
+                    destination_weight,
+                )
+            }
+        }
+    }
+
+    /// Calculate how much source token will be required for a given amount of
+    /// destination token, using the curve of the pool
+    pub fn swap_base_output_without_fees(
+        destination_amount_to_be_swapped: u128,
+        swap_source_amount: u128,
+        swap_destination_amount: u128,
+        trade_direction: TradeDirection,
+        pool_state: &PoolState,
+    ) -> Result<u128> {
+        match pool_state.curve_type()? {
+            CurveType::ConstantProduct => ConstantProductCurve::swap_base_output_without_fees(
+                destination_amount_to_be_swapped,
+                swap_source_amount,
+                swap_destination_amount,
+            ),
+            CurveType::StableSwap => StableSwapCurve::swap_base_output_without_fees(
+                destination_amount_to_be_swapped,
+                swap_source_amount,
+                swap_destination_amount,
+                pool_state.curve_parameter,
+            ),
+            CurveType::Weighted => {
+                let (source_weight, destination_weight) =
+                    Self::weights(trade_direction, pool_state)?;
+                WeightedCurve::swap_base_output_without_fees(
+                    destination_amount_to_be_swapped,
+                    swap_source_amount,
+                    swap_destination_amount,
+                    source_weight,
+                    destination_weight,
+                )
+            }
+        }
+    }
+
+    /// Checks that a swap of the pool from the amounts before the swap to the new amounts without fees
+    /// did not decrease the invariant of the curve.
+    pub fn check_invariant(
+        swap_source_amount: u128,
+        swap_destination_amount: u128,
+        new_swap_source_amount: u128,
+        new_swap_destination_amount: u128,
+        trade_direction: TradeDirection,
+        pool_state: &PoolState,
+    ) -> Result<()> {
+        let (constant_before, constant_after) = match pool_state.curve_type()? {
+            CurveType::ConstantProduct => (
+                swap_source_amount
+                    .checked_mul(swap_destination_amount)
+                    .ok_or(GammaError::MathOverflow)?,
+                new_swap_source_amount
+                    .checked_mul(new_swap_destination_amount)
+                    .ok_or(GammaError::MathOverflow)?,
+            ),
+            CurveType::StableSwap => (
+                StableSwapCurve::compute_d(
+                    pool_state.curve_parameter,
+                    swap_source_amount,
+                    swap_destination_amount,
+                )?,
+                StableSwapCurve::compute_d(
+                    pool_state.curve_parameter,
+                    new_swap_source_amount,
+                    new_swap_destination_amount,
+                )?,
+            ),
+            CurveType::Weighted => {
+                let (source_weight, destination_weight) =
+                    Self::weights(trade_direction, pool_state)?;
+                return WeightedCurve::check_swap(
+                    swap_source_amount,
+                    swap_destination_amount,
+                    new_swap_source_amount,
+                    new_swap_destination_amount,
+                    source_weight,
+                    destination_weight,
+                );
+            }
+        };
+        #[cfg(feature = "enable-log")]
+        msg!(
+            "constant_before:{}, constant_after:{}",
+            constant_before,
+            constant_after
+        );
+        require_gte!(constant_after, constant_before);
+        Ok(())
+    }
+
+    /// Get the amount of lp tokens minted when a pool is initialized with the given amounts
+    pub fn initial_lp_tokens(
+        curve_type: CurveType,
+        curve_parameter: u64,
+        token_0_amount: u128,
+        token_1_amount: u128,
+    ) -> Result<u128> {
+        match curve_type {
+            CurveType::ConstantProduct => {
+                ConstantProductCurve::initial_lp_tokens(token_0_amount, token_1_amount)
+            }
+            CurveType::StableSwap => {
+                StableSwapCurve::initial_lp_tokens(curve_parameter, token_0_amount, token_1_amount)
+            }
+            CurveType::Weighted => {
+                WeightedCurve::initial_lp_tokens(curve_parameter, token_0_amount, token_1_amount)
+            }
+        }
+    }
+
     /// Get the amount of trading tokens for the given amount of pool tokens
     /// provided the total trading tokens and supply of pool tokens
     pub fn lp_tokens_to_trading_tokens(
+        curve_type: CurveType,
         lp_token_amount_to_be_exchanged: u128,
         lp_token_supply: u128,
         swap_token_0_amount: u128,
         swap_token_1_amount: u128,
         round_direction: RoundDirection,
     ) -> Option<TradingTokenResult> {
-        ConstantProductCurve::lp_tokens_to_trading_tokens(
-            lp_token_amount_to_be_exchanged,
-            lp_token_supply,
-            swap_token_0_amount,
-            swap_token_1_amount,
-            round_direction,
-        )
+        match curve_type {
+            // Balanced deposits and withdrawals do not move the price,
+            // so they are proportional to the reserves for all curves
+            CurveType::ConstantProduct | CurveType::StableSwap | CurveType::Weighted => {
+                ConstantProductCurve::lp_tokens_to_trading_tokens(
+                    lp_token_amount_to_be_exchanged,
+                    lp_token_supply,
+                    swap_token_0_amount,
+                    swap_token_1_amount,
+                    round_direction,
+                )
+            }
+        }
+    }
+
+    /// Get the amount of lp tokens for the given amount of token_0,
+    /// provided the total token_0 amount and supply of lp tokens
+    pub fn token_0_to_lp_tokens(
+        curve_type: CurveType,
+        trading_token_0_amount: u128,
+        total_token_0_amount: u128,
+        lp_token_supply: u128,
+        round_direction: RoundDirection,
+    ) -> Option<u128> {
+        match curve_type {
+            CurveType::ConstantProduct | CurveType::StableSwap | CurveType::Weighted => {
+                ConstantProductCurve::token_0_to_lp_tokens(
+                    trading_token_0_amount,
+                    total_token_0_amount,
+                    lp_token_supply,
+                    round_direction,
+                )
+            }
+        }
+    }
+
+    /// Get the amount of lp tokens for the given amount of token_1,
+    /// provided the total token_1 amount and supply of lp tokens
+    pub fn token_1_to_lp_tokens(
+        curve_type: CurveType,
+        trading_token_1_amount: u128,
+        total_token_1_amount: u128,
+        lp_token_supply: u128,
+        round_direction: RoundDirection,
+    ) -> Option<u128> {
+        match curve_type {
+            CurveType::ConstantProduct | CurveType::StableSwap | CurveType::Weighted => {
+                ConstantProductCurve::token_1_to_lp_tokens(
+                    trading_token_1_amount,
+                    total_token_1_amount,
+                    lp_token_supply,
+                    round_direction,
+                )
+            }
+        }
     }
 }
@@ -1,6 +1,6 @@
 //! The Uniswap invariantConstantProductCurve::
 
-use crate::utils::math::CheckedCeilDiv;
+use crate::utils::math::{CheckedCeilDiv, U256};
 use crate::{
     curve::calculator::{RoundDirection, TradingTokenResult},
     error::GammaError,
@@ -56,6 +56,15 @@ impl ConstantProductCurve {
         Ok(source_amount_swapped)
     }
 
+    /// The initial lp tokens of a pool are the geometric mean of the token amounts
+    pub fn initial_lp_tokens(token_0_amount: u128, token_1_amount: u128) -> Result<u128> {
+        Ok(U256::from(token_0_amount)
+            .checked_mul(U256::from(token_1_amount))
+            .ok_or(GammaError::MathOverflow)?
+            .integer_sqrt()
+            .as_u128())
+    }
+
     /// Get the amount of trading tokens(token_0 and token_1) for a given amount of pool tokens(lp_tokens)
     /// provided the total trading tokens and supply of pool tokens
     ///
@@ -111,10 +120,16 @@ impl ConstantProductCurve {
         trading_token_0_amount: u128,
         total_token_0_amount: u128,
         lp_token_supply: u128,
+        round_direction: RoundDirection,
     ) -> Option<u128> {
-        let lp_token_amount = trading_token_0_amount
-            .checked_mul(lp_token_supply)?
-            .checked_div(total_token_0_amount)?;
+        let numerator = trading_token_0_amount.checked_mul(lp_token_supply)?;
+        let lp_token_amount = match round_direction {
+            RoundDirection::Floor => numerator.checked_div(total_token_0_amount)?,
+            RoundDirection::Ceiling => numerator
+                .checked_add(total_token_0_amount)?
+                .checked_sub(1)?
+                .checked_div(total_token_0_amount)?,
+        };
         Some(lp_token_amount)
     }
 
@@ -122,10 +137,16 @@ impl ConstantProductCurve {
         trading_token_1_amount: u128,
         total_token_1_amount: u128,
         lp_token_supply: u128,
+        round_direction: RoundDirection,
     ) -> Option<u128> {
-        let lp_token_amount = trading_token_1_amount
-            .checked_mul(lp_token_supply)?
-            .checked_div(total_token_1_amount)?;
+        let numerator = trading_token_1_amount.checked_mul(lp_token_supply)?;
+        let lp_token_amount = match round_direction {
+            RoundDirection::Floor => numerator.checked_div(total_token_1_amount)?,
+            RoundDirection::Ceiling => numerator
+                .checked_add(total_token_1_amount)?
+                .checked_sub(1)?
+                .checked_div(total_token_1_amount)?,
+        };
         Some(lp_token_amount)
     }
 }
@@ -0,0 +1,245 @@
+//! The StableSwap invariant, A * n^n * sum(x_i) + D = A * D * n^n + D^(n+1) / (n^n * prod(x_i))
+
+use crate::error::GammaError;
+use crate::utils::math::U256;
+use anchor_lang::prelude::*;
+
+/// Number of tokens in the pool
+const N_COINS: u8 = 2;
+/// N_COINS^N_COINS
+const N_COINS_SQUARED: u8 = 4;
+/// Max iterations of the newton method, it usually converges in less than 10 iterations
+const ITERATIONS: u8 = 32;
+
+/// Minimum amplification coefficient
+pub const MIN_AMP: u64 = 1;
+/// Maximum amplification coefficient
+pub const MAX_AMP: u64 = 1_000_000;
+
+/// StableSwapCurve struct implementing the StableSwap invariant with an amplification coefficient.
+/// Both tokens are valued 1:1, so pools are only allowed for tokens with the same decimals.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct StableSwapCurve;
+
+impl StableSwapCurve {
+    /// Validates the amplification coefficient of a new pool
+    pub fn validate_amp(amp: u64) -> Result<()> {
+        require_gte!(amp, MIN_AMP, GammaError::InvalidInput);
+        require_gte!(MAX_AMP, amp, GammaError::InvalidInput);
+        Ok(())
+    }
+
+    /// A * n^n, the amplification coefficient is stored as A
+    fn compute_leverage(amp: u64) -> Result<U256> {
+        Ok(U256::from(amp)
+            .checked_mul(U256::from(N_COINS_SQUARED))
+            .ok_or(GammaError::MathOverflow)?)
+    }
+
+    /// D = (leverage * sum_x + d_product * n) * D / ((leverage - 1) * D + (n + 1) * d_product)
+    fn compute_next_d(d: U256, leverage: U256, sum_x: U256, d_product: U256) -> Option<U256> {
+        let numerator = leverage
+            .checked_mul(sum_x)?
+            .checked_add(d_product.checked_mul(U256::from(N_COINS))?)?
+            .checked_mul(d)?;
+        let denominator = leverage
+            .checked_sub(U256::one())?
+            .checked_mul(d)?
+            .checked_add(d_product.checked_mul(U256::from(N_COINS + 1))?)?;
+        numerator.checked_div(denominator)
+    }
+
+    /// Computes the invariant D for the given token amounts
+    pub fn compute_d(amp: u64, token_0_amount: u128, token_1_amount: u128) -> Result<u128> {
+        let sum_x = U256::from(token_0_amount)
+            .checked_add(U256::from(token_1_amount))
+            .ok_or(GammaError::MathOverflow)?;
+        if sum_x.is_zero() {
+            return Ok(0);
+        }
+        let token_0_times_coins = U256::from(token_0_amount)
+            .checked_mul(U256::from(N_COINS))
+            .ok_or(GammaError::MathOverflow)?;
+        let token_1_times_coins = U256::from(token_1_amount)
+            .checked_mul(U256::from(N_COINS))
+            .ok_or(GammaError::MathOverflow)?;
+        let leverage = Self::compute_leverage(amp)?;
+
+        let mut d = sum_x;
+        for _ in 0..ITERATIONS {
+            // d_product = D^(n+1) / (n^n * prod(x_i))
+            let d_product = d
+                .checked_mul(d)
+                .and_then(|value| value.checked_div(token_0_times_coins))
+                .and_then(|value| value.checked_mul(d))
+                .and_then(|value| value.checked_div(token_1_times_coins))
+                .ok_or(GammaError::MathOverflow)?;
+            let d_previous = d;
+            d = Self::compute_next_d(d, leverage, sum_x, d_product)
+                .ok_or(GammaError::MathOverflow)?;
+            if d.abs_diff(d_previous) <= U256::one() {
+                break;
+            }
+        }
+
+        u128::try_from(d).map_err(|_| error!(GammaError::MathOverflow))
+    }
+
+    /// Computes the amount of the other token for the given new amount of one token,
+    /// such that the invariant D is preserved. The result is rounded up.
+    fn compute_new_amount(amp: u64, new_amount: u128, d: u128) -> Result<u128> {
+        let leverage = Self::compute_leverage(amp)?;
+        let new_amount = U256::from(new_amount);
+        let d = U256::from(d);
+
+        // c = D^(n+1) / (n^n * x * leverage)
+        let c = d
+            .checked_pow(U256::from(N_COINS + 1))
+            .and_then(|value| {
+                value.checked_div(
+                    new_amount
+                        .checked_mul(U256::from(N_COINS_SQUARED))?
+                        .checked_mul(leverage)?,
+                )
+            })
+            .ok_or(GammaError::MathOverflow)?;
+        // b = x + D / leverage
+        let b = d
+            .checked_div(leverage)
+            .and_then(|value| value.checked_add(new_amount))
+            .ok_or(GammaError::MathOverflow)?;
+
+        // Solve y^2 + (b - D) * y = c with newton method, y = (y^2 + c) / (2 * y + b - D)
+        let mut y = d;
+        for _ in 0..ITERATIONS {
+            let numerator = y
+                .checked_mul(y)
+                .and_then(|value| value.checked_add(c))
+                .ok_or(GammaError::MathOverflow)?;
+            let denominator = y
+                .checked_mul(U256::from(2))
+                .and_then(|value| value.checked_add(b))
+                .and_then(|value| value.checked_sub(d))
+                .ok_or(GammaError::MathOverflow)?;
+            let y_new = numerator
+                .checked_add(denominator)
+                .and_then(|value| value.checked_sub(U256::one()))
+                .and_then(|value| value.checked_div(denominator))
+                .ok_or(GammaError::MathOverflow)?;
+            let y_previous = y;
+            y = y_new;
+            if y.abs_diff(y_previous) <= U256::one() {
+                break;
+            }
+        }
+
+        u128::try_from(y).map_err(|_| error!(GammaError::MathOverflow))
+    }
+
+    pub fn swap_base_input_without_fees(
+        source_amount_to_be_swapped: u128,
+        swap_source_amount: u128,
+        swap_destination_amount: u128,
+        amp: u64,
+    ) -> Result<u128> {
+        if source_amount_to_be_swapped == 0 {
+            return Ok(0);
+        }
+        let d = Self::compute_d(amp, swap_source_amount, swap_destination_amount)?;
+        Self::swap_base_input_without_fees_with_d(
+            source_amount_to_be_swapped,
+            swap_source_amount,
+            swap_destination_amount,
+            amp,
+            d,
+        )
+    }
+
+    /// `swap_base_input_without_fees` with the invariant D of the reserves computed beforehand,
+    /// for the repeated swaps from the same reserves
+    pub fn swap_base_input_without_fees_with_d(
+        source_amount_to_be_swapped: u128,
+        swap_source_amount: u128,
+        swap_destination_amount: u128,
+        amp: u64,
+        d: u128,
+    ) -> Result<u128> {
+        if source_amount_to_be_swapped == 0 {
+            return Ok(0);
+        }
+        let new_swap_source_amount = swap_source_amount
+            .checked_add(source_amount_to_be_swapped)
+            .ok_or(GammaError::MathOverflow)?;
+        let new_swap_destination_amount = Self::compute_new_amount(amp, new_swap_source_amount, d)?;
+
+        // Rounding up the new destination amount rounds down the destination amount swapped
+        Ok(swap_destination_amount.saturating_sub(new_swap_destination_amount))
+    }
+
+    pub fn swap_base_output_without_fees(
+        destination_amount_to_be_swapped: u128,
+        swap_source_amount: u128,
+        swap_destination_amount: u128,
+        amp: u64,
+    ) -> Result<u128> {
+        let d = Self::compute_d(amp, swap_source_amount, swap_destination_amount)?;
+        let new_swap_destination_amount = swap_destination_amount
+            .checked_sub(destination_amount_to_be_swapped)
+            .ok_or(GammaError::MathOverflow)?;
+        require_gt!(new_swap_destination_amount, 0, GammaError::MathOverflow);
+        let new_swap_source_amount = Self::compute_new_amount(amp, new_swap_destination_amount, d)?;
+
+        // Rounding up the new source amount rounds up the source amount swapped
+        Ok(new_swap_source_amount
+            .checked_sub(swap_source_amount)
+            .ok_or(GammaError::MathOverflow)?)
+    }
+
+    /// The amounts of the tokens scaled for pricing, see `PoolState::price_values`. The token_0 price is
+    /// the marginal price -dy/dx at the invariant D, y * (n^n * leverage * x^2 * y + D^3) / (x * (n^n * leverage * x * y^2 + D^3)).
+    pub fn price_values(
+        amp: u64,
+        token_0_amount: u128,
+        token_1_amount: u128,
+    ) -> Result<(u128, u128)> {
+        if token_0_amount == 0 || token_1_amount == 0 {
+            return Ok((token_0_amount, token_1_amount));
+        }
+        let leverage = Self::compute_leverage(amp)?;
+        let d = U256::from(Self::compute_d(amp, token_0_amount, token_1_amount)?);
+        let token_0_amount = U256::from(token_0_amount);
+        let token_1_amount = U256::from(token_1_amount);
+
+        // n^n * leverage * x * y, both values are divided by D^2 to keep them in range
+        let leveraged_product = leverage
+            .checked_mul(U256::from(N_COINS_SQUARED))
+            .and_then(|value| value.checked_mul(token_0_amount))
+            .and_then(|value| value.checked_mul(token_1_amount))
+            .ok_or(GammaError::MathOverflow)?;
+        let price_value = |amount: U256, other_amount: U256| -> Option<U256> {
+            leveraged_product
+                .checked_mul(other_amount)?
+                .checked_div(d)?
+                .checked_div(d)?
+                .checked_add(d)?
+                .checked_mul(amount)
+        };
+        let token_0_value =
+            price_value(token_0_amount, token_1_amount).ok_or(GammaError::MathOverflow)?;
+        let token_1_value =
+            price_value(token_1_amount, token_0_amount).ok_or(GammaError::MathOverflow)?;
+
+        // Only the ratio matters, the values are shifted to fit in 64 bits like the token amounts
+        let shift = token_0_value.max(token_1_value).bits().saturating_sub(64);
+        Ok((
+            (token_0_value >> shift).as_u128(),
+            (token_1_value >> shift).as_u128(),
+        ))
+    }
+
+    /// The initial lp tokens of a pool are the invariant D, i.e. the total value of the pool
+    /// when both tokens are balanced.
+    pub fn initial_lp_tokens(amp: u64, token_0_amount: u128, token_1_amount: u128) -> Result<u128> {
+        Self::compute_d(amp, token_0_amount, token_1_amount)
+    }
+}
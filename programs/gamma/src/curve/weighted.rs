@@ -0,0 +1,220 @@
+//! The Balancer style weighted invariant, x^w_0 * y^w_1 = k
+
+use crate::error::GammaError;
+use anchor_lang::prelude::*;
+use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
+use rust_decimal::{Decimal, MathematicalOps};
+
+/// Denominator of the token weights, the weights of both tokens add up to it
+pub const WEIGHT_DENOMINATOR: u64 = 1_000_000;
+/// Minimum weight of a token, 1%
+pub const MIN_WEIGHT: u64 = 10_000;
+/// Maximum weight of a token, 99%
+pub const MAX_WEIGHT: u64 = WEIGHT_DENOMINATOR - MIN_WEIGHT;
+
+/// `powd` is an approximation, the result is moved against the trader by this relative amount
+/// so the invariant can never decrease because of the approximation.
+const MAX_POW_RELATIVE_ERROR: Decimal = Decimal::from_parts(1, 0, 0, false, 12);
+
+/// WeightedCurve struct implementing the weighted constant mean invariant.
+/// The weight of token_0 is stored in `curve_parameter`, token_1 gets the rest.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct WeightedCurve;
+
+impl WeightedCurve {
+    /// Validates the token_0 weight of a new pool
+    pub fn validate_weight(token_0_weight: u64) -> Result<()> {
+        require_gte!(token_0_weight, MIN_WEIGHT, GammaError::InvalidInput);
+        require_gte!(MAX_WEIGHT, token_0_weight, GammaError::InvalidInput);
+        Ok(())
+    }
+
+    /// Returns the (token_0, token_1) weights for the given token_0 weight
+    pub fn weights(token_0_weight: u64) -> Result<(u64, u64)> {
+        let token_1_weight = WEIGHT_DENOMINATOR
+            .checked_sub(token_0_weight)
+            .ok_or(GammaError::MathOverflow)?;
+        Ok((token_0_weight, token_1_weight))
+    }
+
+    fn to_decimal(value: u128) -> Result<Decimal> {
+        Decimal::from_u128(value).ok_or(error!(GammaError::MathOverflow))
+    }
+
+    fn to_u128(value: Decimal) -> Result<u128> {
+        value.to_u128().ok_or(error!(GammaError::MathOverflow))
+    }
+
+    /// The exponent numerator / denominator of a power of the swap
+    pub fn exponent(exponent_numerator: u64, exponent_denominator: u64) -> Result<Decimal> {
+        Ok(Decimal::from(exponent_numerator)
+            .checked_div(Decimal::from(exponent_denominator))
+            .ok_or(GammaError::MathOverflow)?)
+    }
+
+    /// base^exponent, rounded up by `relative_error`
+    fn pow_up(base: Decimal, exponent: Decimal, relative_error: Decimal) -> Result<Decimal> {
+        let power = base
+            .checked_powd(exponent)
+            .ok_or(GammaError::MathOverflow)?;
+        Ok(power
+            .checked_mul(Decimal::ONE + relative_error)
+            .ok_or(GammaError::MathOverflow)?)
+    }
+
+    /// delta_y = y * (1 - (x / (x + delta_x))^(w_x / w_y)), rounded down
+    pub fn swap_base_input_without_fees(
+        source_amount_to_be_swapped: u128,
+        swap_source_amount: u128,
+        swap_destination_amount: u128,
+        source_weight: u64,
+        destination_weight: u64,
+    ) -> Result<u128> {
+        Self::swap_base_input_without_fees_with_exponent(
+            source_amount_to_be_swapped,
+            swap_source_amount,
+            swap_destination_amount,
+            Self::exponent(source_weight, destination_weight)?,
+        )
+    }
+
+    /// `swap_base_input_without_fees` with the exponent w_x / w_y computed beforehand,
+    /// for the repeated swaps of the same pool
+    pub fn swap_base_input_without_fees_with_exponent(
+        source_amount_to_be_swapped: u128,
+        swap_source_amount: u128,
+        swap_destination_amount: u128,
+        exponent: Decimal,
+    ) -> Result<u128> {
+        if source_amount_to_be_swapped == 0 {
+            return Ok(0);
+        }
+        let new_swap_source_amount = swap_source_amount
+            .checked_add(source_amount_to_be_swapped)
+            .ok_or(GammaError::MathOverflow)?;
+        let base = Self::to_decimal(swap_source_amount)?
+            .checked_div(Self::to_decimal(new_swap_source_amount)?)
+            .ok_or(GammaError::MathOverflow)?;
+        let power = Self::pow_up(base, exponent, MAX_POW_RELATIVE_ERROR)?;
+        if power >= Decimal::ONE {
+            return Ok(0);
+        }
+
+        let destination_amount_swapped = Self::to_decimal(swap_destination_amount)?
+            .checked_mul(Decimal::ONE - power)
+            .ok_or(GammaError::MathOverflow)?
+            .floor();
+        Self::to_u128(destination_amount_swapped)
+    }
+
+    /// delta_x = x * ((y / (y - delta_y))^(w_y / w_x) - 1), rounded up
+    pub fn swap_base_output_without_fees(
+        destination_amount_to_be_swapped: u128,
+        swap_source_amount: u128,
+        swap_destination_amount: u128,
+        source_weight: u64,
+        destination_weight: u64,
+    ) -> Result<u128> {
+        let new_swap_destination_amount = swap_destination_amount
+            .checked_sub(destination_amount_to_be_swapped)
+            .ok_or(GammaError::MathOverflow)?;
+        require_gt!(new_swap_destination_amount, 0, GammaError::MathOverflow);
+        let base = Self::to_decimal(swap_destination_amount)?
+            .checked_div(Self::to_decimal(new_swap_destination_amount)?)
+            .ok_or(GammaError::MathOverflow)?;
+        // The swap is checked by `check_swap` with the power of x / x' to w_x / w_y,
+        // the relative error of that power is amplified by w_y / w_x here
+        let relative_error = Decimal::from(destination_weight)
+            .checked_mul(Decimal::from(4))
+            .and_then(|value| value.checked_div(Decimal::from(source_weight)))
+            .and_then(|value| value.checked_add(Decimal::TWO))
+            .and_then(|value| value.checked_mul(MAX_POW_RELATIVE_ERROR))
+            .ok_or(GammaError::MathOverflow)?;
+        let power = Self::pow_up(
+            base,
+            Self::exponent(destination_weight, source_weight)?,
+            relative_error,
+        )?;
+
+        let source_amount_swapped = Self::to_decimal(swap_source_amount)?
+            .checked_mul(power - Decimal::ONE)
+            .ok_or(GammaError::MathOverflow)?
+            .ceil();
+        Self::to_u128(source_amount_swapped)
+    }
+
+    /// Checks a swap of the pool from (x, y) to (x', y') without fees: y - y' can not be more than
+    /// `swap_base_input_without_fees` gives for x' - x, which is rounded against the trader.
+    /// The invariants before and after the swap are not compared, `powd` is not exact and would need
+    /// four of them.
+    pub fn check_swap(
+        swap_source_amount: u128,
+        swap_destination_amount: u128,
+        new_swap_source_amount: u128,
+        new_swap_destination_amount: u128,
+        source_weight: u64,
+        destination_weight: u64,
+    ) -> Result<()> {
+        let source_amount_swapped = new_swap_source_amount
+            .checked_sub(swap_source_amount)
+            .ok_or(GammaError::MathOverflow)?;
+        let destination_amount_swapped = swap_destination_amount
+            .checked_sub(new_swap_destination_amount)
+            .ok_or(GammaError::MathOverflow)?;
+        let max_destination_amount_swapped = Self::swap_base_input_without_fees(
+            source_amount_swapped,
+            swap_source_amount,
+            swap_destination_amount,
+            source_weight,
+            destination_weight,
+        )?;
+        require_gte!(max_destination_amount_swapped, destination_amount_swapped);
+        Ok(())
+    }
+
+    /// Computes the invariant x^w_x * y^w_y, rounded down
+    pub fn invariant(
+        token_0_amount: u128,
+        token_1_amount: u128,
+        token_0_weight: u64,
+        token_1_weight: u64,
+    ) -> Result<u128> {
+        if token_0_amount == 0 || token_1_amount == 0 {
+            return Ok(0);
+        }
+        let denominator = Decimal::from(WEIGHT_DENOMINATOR);
+        let token_0_exponent = Decimal::from(token_0_weight)
+            .checked_div(denominator)
+            .ok_or(GammaError::MathOverflow)?;
+        let token_1_exponent = Decimal::from(token_1_weight)
+            .checked_div(denominator)
+            .ok_or(GammaError::MathOverflow)?;
+        let invariant = Self::to_decimal(token_0_amount)?
+            .checked_powd(token_0_exponent)
+            .and_then(|value| {
+                value.checked_mul(
+                    Self::to_decimal(token_1_amount)
+                        .ok()?
+                        .checked_powd(token_1_exponent)?,
+                )
+            })
+            .ok_or(GammaError::MathOverflow)?
+            .floor();
+        Self::to_u128(invariant)
+    }
+
+    /// The initial lp tokens of a pool are the weighted geometric mean of the token amounts
+    pub fn initial_lp_tokens(
+        token_0_weight: u64,
+        token_0_amount: u128,
+        token_1_amount: u128,
+    ) -> Result<u128> {
+        let (token_0_weight, token_1_weight) = Self::weights(token_0_weight)?;
+        Self::invariant(
+            token_0_amount,
+            token_1_amount,
+            token_0_weight,
+            token_1_weight,
+        )
+    }
+}
@@ -48,4 +48,35 @@ pub enum GammaError {
 
     #[msg("Invalid reward time")]
     InvalidRewardTime,
+    #[msg("Invalid reward amount")]
+    InvalidRewardAmount,
+
+    #[msg("Invalid oracle price feed")]
+    InvalidPriceFeed,
+    #[msg("Oracle price is stale")]
+    StalePrice,
+    #[msg("Oracle price confidence is too wide")]
+    PriceConfidenceTooWide,
+    #[msg("Not enough liquidity in the pool")]
+    InsufficientLiquidity,
+
+    #[msg("Invalid swap route")]
+    InvalidRoute,
+
+    #[msg("Flash loan must be repaid by a flash_repay in the same transaction")]
+    InvalidFlashLoan,
+
+    #[msg("Observation state can not be expanded to this capacity")]
+    InvalidObservationCapacity,
+
+    #[msg("Too many active reward infos for the pool")]
+    TooManyActiveRewardInfos,
+
+    #[msg("Lp tokens are locked")]
+    LpTokensLocked,
+    #[msg("Invalid lp lock")]
+    InvalidLpLock,
+
+    #[msg("Invalid partner")]
+    InvalidPartner,
 }
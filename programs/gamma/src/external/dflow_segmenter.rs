@@ -22,8 +22,14 @@ impl Registry {
     }
 }
 
-pub fn is_invoked_by_segmenter(registry: &AccountInfo<'_>, segmenter: &AccountInfo<'_>) -> bool {
-    if *registry.owner != Registry::PROGRAM_ID {
+/// Returns true if `segmenter` signs and is registered in `registry`,
+/// a registry is only trusted if it is owned by one of `registry_programs`.
+pub fn is_invoked_by_segmenter(
+    registry: &AccountInfo<'_>,
+    segmenter: &AccountInfo<'_>,
+    registry_programs: &[Pubkey],
+) -> bool {
+    if !registry_programs.contains(registry.owner) {
         return false;
     }
     if !segmenter.is_signer {
@@ -3,6 +3,7 @@
 pub mod dflow_segmenter;
 pub mod dlmm;
 pub mod kamino;
+pub mod pyth;
 pub mod raydium_clmm;
 pub mod raydium_cp;
 pub mod whirlpool;
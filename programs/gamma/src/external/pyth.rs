@@ -0,0 +1,48 @@
+use anchor_lang::prelude::*;
+
+#[derive(AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VerificationLevel {
+    Partial { num_signatures: u8 },
+    Full,
+}
+
+#[allow(dead_code)]
+#[derive(AnchorDeserialize, Clone, Copy, Debug)]
+pub struct PriceFeedMessage {
+    pub feed_id: [u8; 32],
+    pub price: i64,
+    pub conf: u64,
+    pub exponent: i32,
+    pub publish_time: i64,
+    pub prev_publish_time: i64,
+    pub ema_price: i64,
+    pub ema_conf: u64,
+}
+
+// Price update account posted by the pyth solana receiver program (pull oracle).
+#[allow(dead_code)]
+#[derive(AnchorDeserialize, Clone, Copy, Debug)]
+pub struct PriceUpdateV2 {
+    pub write_authority: Pubkey,
+    pub verification_level: VerificationLevel,
+    pub price_message: PriceFeedMessage,
+    pub posted_slot: u64,
+}
+
+impl PriceUpdateV2 {
+    pub const PROGRAM_ID: Pubkey = pubkey!("rec5EKMGg6MxZYaMdyBfgwp4d5rB9T1VQH5pJv5LtFJ");
+    pub const DISCRIMINATOR: [u8; 8] = [34, 241, 35, 99, 157, 126, 244, 205];
+
+    pub fn try_from_account_info(price_update: &AccountInfo<'_>) -> Option<Self> {
+        if *price_update.owner != Self::PROGRAM_ID {
+            return None;
+        }
+
+        let price_update_data = price_update.data.borrow();
+        if price_update_data.len() < 8 || price_update_data[..8] != Self::DISCRIMINATOR {
+            return None;
+        }
+
+        Self::deserialize(&mut &price_update_data[8..]).ok()
+    }
+}
@@ -1,11 +1,11 @@
 use super::{ceil_div, FEE_RATE_DENOMINATOR_VALUE};
 use crate::{
     error::GammaError,
-    fees::ONE_BASIS_POINT,
-    states::{Observation, ObservationState, PoolState, OBSERVATION_NUM},
+    states::{AmmConfig, Observations, PoolState},
+    utils::{checked_log2, U256},
 };
 use anchor_lang::prelude::*;
-use rust_decimal::prelude::*;
+use fixed::types::U64F64;
 
 // Volatility-based fee constants
 pub const MAX_FEE_VOLATILITY: u64 = 10000; // 1% max fee
@@ -14,13 +14,87 @@ pub const VOLATILITY_WINDOW: u64 = 3600; // 1 hour window for volatility calcula
 const DEFAULT_MAX_FEE: u64 = 100000; // 10% max fee
 const DEFAULT_VOLATILITY_FACTOR: u64 = 300_000; // Adjust based on desired sensitivity
 
+// Launch fee constants
+/// Number of times the launch fee above the base fee is halved over the launch fee duration
+/// with `FeeType::LaunchExponentialDecay`
+pub const LAUNCH_FEE_HALVINGS: u64 = 10;
+
+/// The fee charged by a pool on swaps
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[repr(u8)]
 pub enum FeeType {
-    Volatility,
+    /// Base fee increased with the volatility of the price
+    #[default]
+    Volatility = 0,
+    /// `launch_fee_rate` at `open_time` decreasing linearly to the base fee over `launch_fee_duration`
+    LaunchLinearDecay = 1,
+    /// `launch_fee_rate` at `open_time` decreasing exponentially to the base fee over `launch_fee_duration`,
+    /// the part above the base fee is halved `LAUNCH_FEE_HALVINGS` times
+    LaunchExponentialDecay = 2,
+    /// Base fee increased when the swap moves the price further from the TWAP,
+    /// trades moving the price back toward the TWAP only pay the base fee
+    Directional = 3,
+}
+
+impl FeeType {
+    pub fn new(value: u8) -> Result<Self> {
+        match value {
+            0 => Ok(FeeType::Volatility),
+            1 => Ok(FeeType::LaunchLinearDecay),
+            2 => Ok(FeeType::LaunchExponentialDecay),
+            3 => Ok(FeeType::Directional),
+            _ => err!(GammaError::InvalidInput),
+        }
+    }
+
+    /// Validates the launch fee parameters of a new pool
+    pub fn validate_launch_fee(
+        &self,
+        launch_fee_rate: u64,
+        launch_fee_duration: u64,
+    ) -> Result<()> {
+        match self {
+            FeeType::Volatility | FeeType::Directional => {
+                require_eq!(launch_fee_rate, 0, GammaError::InvalidInput);
+                require_eq!(launch_fee_duration, 0, GammaError::InvalidInput);
+            }
+            FeeType::LaunchLinearDecay | FeeType::LaunchExponentialDecay => {
+                require_gt!(launch_fee_rate, 0, GammaError::InvalidFee);
+                require_gt!(
+                    FEE_RATE_DENOMINATOR_VALUE,
+                    launch_fee_rate,
+                    GammaError::InvalidFee
+                );
+                require_gt!(launch_fee_duration, 0, GammaError::InvalidInput);
+            }
+        }
+        Ok(())
+    }
 }
 
-struct ObservationWithIndex {
-    observation: Observation,
-    index: u16,
+/// Where a swap comes from, the fee rate is adjusted for it with the rates of the `AmmConfig`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[repr(u8)]
+pub enum FeePath {
+    /// The fee rate is not adjusted, used by the swaps of single sided deposits and withdrawals
+    #[default]
+    Standard = 0,
+    /// Swap invoked by a segmenter signing the transaction and registered in a segmenter registry,
+    /// the fee rate is lowered by `segmenter_discount_rate` but not below the base fee
+    SignedSegmenter = 1,
+    /// Swap not invoked by a signed segmenter, treated as potentially toxic flow,
+    /// the fee rate is raised by `unsigned_flow_surcharge_rate` but not above the max fee
+    UnsignedFlow = 2,
+}
+
+impl FeePath {
+    pub fn swap(is_invoked_by_signed_segmenter: bool) -> Self {
+        if is_invoked_by_signed_segmenter {
+            FeePath::SignedSegmenter
+        } else {
+            FeePath::UnsignedFlow
+        }
+    }
 }
 
 pub struct DynamicFee {}
@@ -34,6 +108,9 @@ impl DynamicFee {
     /// * `observation_state` - Historical price observations
     /// * `fee_type` - The type of fee calculation to use
     /// * `base_fees` - The base fee rate
+    /// * `token_0_price_after_swap_x32` - The token_0 price after the swap, only needed by `FeeType::Directional`
+    /// * `amm_config` - The config with the segmenter discount and unsigned flow surcharge rates
+    /// * `fee_path` - Where the swap comes from
     ///
     /// # Returns
     /// The fee amount as a u128, or None if calculation fails
@@ -41,20 +118,23 @@ impl DynamicFee {
     pub fn dynamic_fee(
         amount: u128,
         block_timestamp: u64,
-        observation_state: &ObservationState,
+        observation_state: &Observations,
         fee_type: FeeType,
         base_fees: u64,
         pool_state: &PoolState,
-        is_invoked_by_signed_segmenter: bool,
+        token_0_price_after_swap_x32: Option<u128>,
+        amm_config: &AmmConfig,
+        fee_path: FeePath,
     ) -> Result<(u128, u64)> {
-        // TODO: use is_invoked_by_signed_segmenter to charge less fees for signed segmenter, once they are implemented across all protocols and this is also used by the segmenter.
         let dynamic_fee_rate = Self::calculate_dynamic_fee(
             block_timestamp,
             observation_state,
             fee_type,
             base_fees,
             pool_state,
-            is_invoked_by_signed_segmenter,
+            token_0_price_after_swap_x32,
+            amm_config,
+            fee_path,
         )?;
 
         Ok((
@@ -76,98 +156,249 @@ impl DynamicFee {
     /// * `vault_0` - Amount of token 0 in the vault
     /// * `vault_1` - Amount of token 1 in the vault
     /// * `fee_type` - The type of fee calculation to use
+    /// * `amm_config` - The config with the segmenter discount and unsigned flow surcharge rates
+    /// * `fee_path` - Where the swap comes from
     ///
     /// # Returns
     /// A fee rate as a u64, where 10000 represents 1%
     fn calculate_dynamic_fee(
         block_timestamp: u64,
-        observation_state: &ObservationState,
+        observation_state: &Observations,
         fee_type: FeeType,
         base_fees: u64,
         pool_state: &PoolState,
-        is_invoked_by_signed_segmenter: bool,
+        token_0_price_after_swap_x32: Option<u128>,
+        amm_config: &AmmConfig,
+        fee_path: FeePath,
     ) -> Result<u64> {
-        match fee_type {
+        let fee_rate = match fee_type {
             FeeType::Volatility => Self::calculate_volatile_fee(
                 block_timestamp,
                 observation_state,
                 base_fees,
                 pool_state,
-                is_invoked_by_signed_segmenter,
             ),
+            FeeType::LaunchLinearDecay | FeeType::LaunchExponentialDecay => {
+                Self::calculate_launch_fee(block_timestamp, fee_type, base_fees, pool_state)
+            }
+            FeeType::Directional => Self::calculate_directional_fee(
+                block_timestamp,
+                observation_state,
+                base_fees,
+                pool_state,
+                token_0_price_after_swap_x32.ok_or(GammaError::InvalidFee)?,
+            ),
+        }?;
+
+        Self::apply_fee_path(fee_rate, base_fees, pool_state, amm_config, fee_path)
+    }
+
+    /// Adjusts a fee rate for where the swap comes from
+    ///
+    /// # Arguments
+    /// * `fee_rate` - The fee rate of the fee type of the pool
+    /// * `base_fees` - The base fee rate
+    /// * `pool_state` - The current state of the pool
+    /// * `amm_config` - The config with the segmenter discount and unsigned flow surcharge rates
+    /// * `fee_path` - Where the swap comes from
+    ///
+    /// # Returns
+    /// A fee rate as a u64, where 10000 represents 1%
+    fn apply_fee_path(
+        fee_rate: u64,
+        base_fees: u64,
+        pool_state: &PoolState,
+        amm_config: &AmmConfig,
+        fee_path: FeePath,
+    ) -> Result<u64> {
+        #[cfg(feature = "enable-log")]
+        msg!("fee_path: {:?}, fee_rate: {}", fee_path, fee_rate);
+
+        match fee_path {
+            FeePath::Standard => Ok(fee_rate),
+            // The discount never takes the fee below the base fee
+            FeePath::SignedSegmenter => Ok(std::cmp::max(
+                std::cmp::min(base_fees, fee_rate),
+                fee_rate.saturating_sub(amm_config.segmenter_discount_rate()),
+            )),
+            // The surcharge never takes the fee above the max fee, a launch fee may already be above it
+            FeePath::UnsignedFlow => Ok(std::cmp::min(
+                fee_rate
+                    .checked_add(amm_config.unsigned_flow_surcharge_rate)
+                    .ok_or(GammaError::MathOverflow)?,
+                std::cmp::max(Self::max_fee(pool_state), fee_rate),
+            )),
         }
     }
 
-    /// Calculates a dynamic fee based on price volatility
+    /// The max fee rate of the pool, `DEFAULT_MAX_FEE` when it is not set
+    fn max_fee(pool_state: &PoolState) -> u64 {
+        if pool_state.max_trade_fee_rate == 0 {
+            DEFAULT_MAX_FEE
+        } else {
+            pool_state.max_trade_fee_rate
+        }
+    }
+
+    /// Calculates a fee based on how far the swap moves the price from the TWAP
     ///
     /// # Arguments
     /// * `block_timestamp` - The current block timestamp
     /// * `observation_state` - Historical price observations
     /// * `base_fees` - The base fee rate
+    /// * `pool_state` - The current state of the pool, before the swap
+    /// * `token_0_price_after_swap_x32` - The token_0 price of the pool after the swap
     ///
     /// # Returns
     /// A fee rate as a u64, where 10000 represents 1%
-    fn calculate_volatile_fee(
+    fn calculate_directional_fee(
         block_timestamp: u64,
-        observation_state: &ObservationState,
+        observation_state: &Observations,
         base_fees: u64,
         pool_state: &PoolState,
-        is_invoked_by_signed_segmenter: bool,
+        token_0_price_after_swap_x32: u128,
     ) -> Result<u64> {
-        // 1. Price volatility calculation:
-        //    - Get min, max and TWAP (Time-Weighted Average Price) over the volatility window
-        //    - Volatility = |ln(max_price) - ln(min_price)| / |ln(twap_price)|
+        // 1. Deviation calculation:
+        //    - Deviation = |price - twap_price| for the prices before and after the swap
+        //    - Swaps reducing the deviation only pay base_fees
         //
-        // 2. Volatility component calculation:
-        //    - Scale volatility by FEE_RATE_DENOMINATOR_VALUE (1_000_000)
-        //    - Multiply by VOLATILITY_FACTOR (30_000) to adjust sensitivity
-        //    - Cap the component at (MAX_FEE - BASE_FEE) to ensure total fee doesn't exceed MAX_FEE
+        // 2. Directional component calculation:
+        //    - Scale the increase of the deviation by FEE_RATE_DENOMINATOR_VALUE / twap_price
+        //    - Multiply by volatility_factor / FEE_RATE_DENOMINATOR_VALUE to adjust sensitivity
         //
         // 3. Final fee calculation:
-        //    - Add base_fees to volatility_component
+        //    - Add base_fees to directional_component
         //    - Ensure final fee doesn't exceed MAX_FEE (100_000 = 10%)
-        //    - Result is a fee rate where 10_000 represents 1%
-
-        let (min_price, max_price, twap_price) =
+        let (_, _, twap_price) =
             Self::get_price_range(observation_state, block_timestamp, VOLATILITY_WINDOW)?;
         // Handle case where no valid observations were found
-        if min_price == 0 || max_price == 0 || twap_price == 0 || twap_price == 1 {
-            // If twap is 1 we will get ln(1) = 0, so we can't divide by 0
+        if twap_price == 0 {
             return Ok(base_fees);
         }
 
-        // Compute logarithms
-        let log_max_price = (max_price as f64).ln();
-        let log_min_price = (min_price as f64).ln();
-        let log_twap_price = (twap_price as f64).ln();
+        let (token_0_price_before_swap_x32, _) = pool_state.token_price_x32()?;
+        let deviation_before_swap = token_0_price_before_swap_x32.abs_diff(twap_price);
+        let deviation_after_swap = token_0_price_after_swap_x32.abs_diff(twap_price);
         #[cfg(feature = "enable-log")]
         msg!(
-            "log_max_price: {},log_min_price={},log_twap_price={}  ",
-            log_max_price,
-            log_min_price,
-            log_twap_price
+            "twap_price: {}, deviation_before_swap: {}, deviation_after_swap: {}",
+            twap_price,
+            deviation_before_swap,
+            deviation_after_swap
         );
-
-        // Compute volatility numerator and denominator
-        let volatility_numerator = (log_max_price - log_min_price).abs();
-        let volatility_denominator = log_twap_price.abs();
-
-        // Check if volatility_denominator is zero to avoid division by zero
-        if volatility_denominator.is_zero() {
+        if deviation_after_swap <= deviation_before_swap {
             return Ok(base_fees);
         }
 
-        // Compute volatility: volatility = volatility_numerator / volatility_denominator
-        // Dividing f64 with f64. We want to know the decimals so we keep the
-        let volatility = volatility_numerator / volatility_denominator;
+        let volatility_factor = if pool_state.volatility_factor == 0 {
+            DEFAULT_VOLATILITY_FACTOR
+        } else {
+            pool_state.volatility_factor
+        };
+        let max_fee = Self::max_fee(pool_state);
+
+        // Saturating, the fee is capped at max_fee anyway
+        let deviation_increase_rate = U256::from(deviation_after_swap - deviation_before_swap)
+            .saturating_mul(U256::from(FEE_RATE_DENOMINATOR_VALUE))
+            / U256::from(twap_price);
+        let directional_component = deviation_increase_rate
+            .saturating_mul(U256::from(volatility_factor))
+            / U256::from(FEE_RATE_DENOMINATOR_VALUE);
         #[cfg(feature = "enable-log")]
-        msg!("volatility: {} ", volatility);
+        msg!("directional_component: {}", directional_component);
+
+        let dynamic_fee = U256::from(base_fees)
+            .saturating_add(directional_component)
+            .min(U256::from(max_fee));
+        Ok(dynamic_fee.as_u64())
+    }
 
+    /// Calculates the decaying fee charged after the launch of a pool, to make sniping the first slots expensive
+    ///
+    /// # Arguments
+    /// * `block_timestamp` - The current block timestamp
+    /// * `fee_type` - `FeeType::LaunchLinearDecay` or `FeeType::LaunchExponentialDecay`
+    /// * `base_fees` - The base fee rate, charged once the launch fee duration is over
+    /// * `pool_state` - The pool with the launch fee rate and duration
+    ///
+    /// # Returns
+    /// A fee rate as a u64, where 10000 represents 1%
+    fn calculate_launch_fee(
+        block_timestamp: u64,
+        fee_type: FeeType,
+        base_fees: u64,
+        pool_state: &PoolState,
+    ) -> Result<u64> {
+        let elapsed_time = block_timestamp.saturating_sub(pool_state.open_time);
+        let duration = u64::from(pool_state.launch_fee_duration);
+        if elapsed_time >= duration {
+            return Ok(base_fees);
+        }
+        let (elapsed_time, duration) = (u128::from(elapsed_time), u128::from(duration));
+        let launch_fee_above_base =
+            u128::from(u64::from(pool_state.launch_fee_rate).saturating_sub(base_fees));
+
+        let remaining_fee_above_base = match fee_type {
+            FeeType::LaunchExponentialDecay => {
+                // Halved every duration / LAUNCH_FEE_HALVINGS, linear between two halvings
+                let scaled_elapsed_time = elapsed_time
+                    .checked_mul(u128::from(LAUNCH_FEE_HALVINGS))
+                    .ok_or(GammaError::MathOverflow)?;
+                let halvings = scaled_elapsed_time / duration;
+                let fee_before_halving = launch_fee_above_base >> halvings;
+                let fee_after_halving = fee_before_halving >> 1;
+                fee_before_halving
+                    - (fee_before_halving - fee_after_halving) * (scaled_elapsed_time % duration)
+                        / duration
+            }
+            _ => {
+                launch_fee_above_base
+                    .checked_mul(duration - elapsed_time)
+                    .ok_or(GammaError::MathOverflow)?
+                    / duration
+            }
+        };
         #[cfg(feature = "enable-log")]
-        msg!(
-            "is_invoked_by_signed_segmenter: {}",
-            is_invoked_by_signed_segmenter
-        );
+        msg!("remaining_fee_above_base: {}", remaining_fee_above_base);
+
+        let remaining_fee_above_base =
+            u64::try_from(remaining_fee_above_base).or(err!(GammaError::MathOverflow))?;
+        Ok(base_fees
+            .checked_add(remaining_fee_above_base)
+            .ok_or(GammaError::MathOverflow)?)
+    }
+
+    /// Calculates a dynamic fee based on price volatility
+    ///
+    /// # Arguments
+    /// * `block_timestamp` - The current block timestamp
+    /// * `observation_state` - Historical price observations
+    /// * `base_fees` - The base fee rate
+    ///
+    /// # Returns
+    /// A fee rate as a u64, where 10000 represents 1%
+    fn calculate_volatile_fee(
+        block_timestamp: u64,
+        observation_state: &Observations,
+        base_fees: u64,
+        pool_state: &PoolState,
+    ) -> Result<u64> {
+        // 1. Price volatility calculation:
+        //    - Get min, max and TWAP (Time-Weighted Average Price) over the volatility window
+        //    - Volatility = |ln(max_price) - ln(min_price)| / |ln(twap_price)|
+        //
+        // 2. Volatility component calculation:
+        //    - Scale volatility by FEE_RATE_DENOMINATOR_VALUE (1_000_000)
+        //    - Multiply by VOLATILITY_FACTOR (30_000) to adjust sensitivity
+        //    - Cap the component at (MAX_FEE - BASE_FEE) to ensure total fee doesn't exceed MAX_FEE
+        //
+        // 3. Final fee calculation:
+        //    - Add base_fees to volatility_component
+        //    - Ensure final fee doesn't exceed MAX_FEE (100_000 = 10%)
+        //    - Result is a fee rate where 10_000 represents 1%
+
+        let (min_price, max_price, twap_price) =
+            Self::get_price_range(observation_state, block_timestamp, VOLATILITY_WINDOW)?;
 
         let volatility_factor = if pool_state.volatility_factor == 0 {
             DEFAULT_VOLATILITY_FACTOR
@@ -176,9 +407,15 @@ impl DynamicFee {
         };
 
         // Calculate volatility component
-        let volatility_component_calculated = (volatility_factor as f64 * volatility)
-            .to_u64()
-            .ok_or(GammaError::MathOverflow)?;
+        let volatility_component_calculated = match Self::volatility_component(
+            min_price,
+            max_price,
+            twap_price,
+            volatility_factor,
+        )? {
+            Some(volatility_component) => volatility_component,
+            None => return Ok(base_fees),
+        };
         #[cfg(feature = "enable-log")]
         msg!(
             "volatility_component_calculated: {} ",
@@ -190,23 +427,70 @@ impl DynamicFee {
             .checked_add(volatility_component_calculated)
             .ok_or(GammaError::MathOverflow)?;
 
-        let max_fee = if pool_state.max_trade_fee_rate == 0 {
-            DEFAULT_MAX_FEE
-        } else {
-            pool_state.max_trade_fee_rate
-        };
+        let max_fee = Self::max_fee(pool_state);
 
         #[cfg(feature = "enable-log")]
         msg!("dynamic_fee: {}", dynamic_fee);
 
-        if is_invoked_by_signed_segmenter {
-            return Ok(std::cmp::max(
-                base_fees,
-                std::cmp::min(dynamic_fee, max_fee).saturating_sub(ONE_BASIS_POINT),
-            ));
+        Ok(std::cmp::min(dynamic_fee, max_fee))
+    }
+
+    /// Calculates the volatility component of the fee from the prices observed in the volatility window,
+    /// `volatility_factor * |ln(max_price) - ln(min_price)| / |ln(twap_price)|` rounded down.
+    /// Fixed point logarithms are used so quoters get the exact fee charged on-chain.
+    ///
+    /// # Arguments
+    /// * `min_price` - The min price observed in the window
+    /// * `max_price` - The max price observed in the window
+    /// * `twap_price` - The TWAP over the window
+    /// * `volatility_factor` - The sensitivity of the fee to the volatility
+    ///
+    /// # Returns
+    /// The volatility component as a fee rate, or None if there are not enough observations
+    pub fn volatility_component(
+        min_price: u128,
+        max_price: u128,
+        twap_price: u128,
+        volatility_factor: u64,
+    ) -> Result<Option<u64>> {
+        // Handle case where no valid observations were found
+        if min_price == 0 || max_price == 0 || twap_price == 0 || twap_price == 1 {
+            // If twap is 1 we will get ln(1) = 0, so we can't divide by 0
+            return Ok(None);
         }
 
-        Ok(std::cmp::min(dynamic_fee, max_fee))
+        // Compute logarithms, ln(a) / ln(b) == log2(a) / log2(b) so base 2 is used
+        let log_max_price = checked_log2(max_price).ok_or(GammaError::MathOverflow)?;
+        let log_min_price = checked_log2(min_price).ok_or(GammaError::MathOverflow)?;
+        let log_twap_price = checked_log2(twap_price).ok_or(GammaError::MathOverflow)?;
+        #[cfg(feature = "enable-log")]
+        msg!(
+            "log_max_price: {},log_min_price={},log_twap_price={}  ",
+            log_max_price,
+            log_min_price,
+            log_twap_price
+        );
+
+        // Compute volatility numerator and denominator, the logarithms of prices >= 1 are positive
+        let volatility_numerator = log_max_price.dist(log_min_price);
+        let volatility_denominator = log_twap_price;
+
+        // Check if volatility_denominator is zero to avoid division by zero
+        if volatility_denominator == U64F64::ZERO {
+            return Ok(None);
+        }
+
+        let volatility = volatility_numerator
+            .checked_div(volatility_denominator)
+            .ok_or(GammaError::MathOverflow)?;
+        #[cfg(feature = "enable-log")]
+        msg!("volatility: {} ", volatility);
+
+        let volatility_component = volatility
+            .checked_mul_int(u128::from(volatility_factor))
+            .and_then(|volatility_component| volatility_component.checked_to_num::<u64>())
+            .ok_or(GammaError::MathOverflow)?;
+        Ok(Some(volatility_component))
     }
 
     /// Gets the price range within a specified time window and computes TWAP
@@ -219,111 +503,16 @@ impl DynamicFee {
     /// # Returns
     /// A tuple of (min_price, max_price, twap_price) observed within the window
     fn get_price_range(
-        observation_state: &ObservationState,
+        observation_state: &Observations,
         current_time: u64,
         window: u64,
     ) -> Result<(u128, u128, u128)> {
-        let mut min_price = u128::MAX;
-        let mut max_price = 0u128;
-        // Filter and sort observations:
-        // 1. Remove invalid observations (zero timestamps or prices)
-        // 2. Keep only observations within our time window
-        // 3. Sort from newest to oldest
-        let mut descending_order_observations = observation_state
-            .observations
-            .iter()
-            .enumerate()
-            .filter(|(_, observation)| {
-                observation.block_timestamp != 0
-                    && observation.cumulative_token_0_price_x32 != 0
-                    && observation.cumulative_token_1_price_x32 != 0
-                    && current_time.saturating_sub(observation.block_timestamp) <= window
-            })
-            .map(|(index, observation)| ObservationWithIndex {
-                index: index as u16,
-                observation: *observation,
-            })
-            .collect::<Vec<_>>();
-
-        // Sort observations by timestamp (newest first)
-        descending_order_observations.sort_by(|a, b| {
-            { b.observation.block_timestamp }.cmp(&{ a.observation.block_timestamp })
-        });
-
-        // Need at least 2 observations to calculate prices
-        if descending_order_observations.len() < 2 {
-            // Not enough data points to compute TWAP
-            return Ok((0, 0, 0));
-        }
-
-        // For TWAP: use first and last observations within our window
-        // Get newest and oldest observations in our filtered set
-        let newest_obs = descending_order_observations.first().unwrap();
-        let oldest_obs = descending_order_observations.last().unwrap();
-
-        // Calculate time delta using the correct start time
-        let total_time_delta = newest_obs
-            .observation
-            .block_timestamp
-            .saturating_sub(oldest_obs.observation.block_timestamp)
-            as u128;
-
-        if total_time_delta == 0 {
-            return Ok((0, 0, 0));
-        }
-
-        // Calculate TWAP using real observations only
-        let twap_price = newest_obs
-            .observation
-            .cumulative_token_0_price_x32
-            .checked_sub(oldest_obs.observation.cumulative_token_0_price_x32)
-            .ok_or(GammaError::MathOverflow)?
-            .checked_div(total_time_delta)
-            .ok_or(GammaError::MathOverflow)?;
-
-        // Iterate to find min/max spot prices
-        for observation_with_index in descending_order_observations {
-            let last_observation_index = if observation_with_index.index == 0 {
-                OBSERVATION_NUM - 1
-            } else {
-                observation_with_index.index as usize - 1
-            };
-
-            // if last observation is not valid, skip this observation
-            if observation_state.observations[last_observation_index].block_timestamp == 0 {
-                continue;
-            }
-
-            if observation_state.observations[last_observation_index].block_timestamp
-                > observation_with_index.observation.block_timestamp
-            {
-                // Break if current observation is older than the last observation.
-                break;
-            }
-
-            let obs = observation_state.observations[last_observation_index];
-            let next_obs = observation_with_index.observation;
-
-            let time_delta = next_obs.block_timestamp.saturating_sub(obs.block_timestamp) as u128;
-
-            if time_delta == 0 {
-                continue;
-            }
-
-            // Calculate spot price for this interval
-            let price = next_obs
-                .cumulative_token_0_price_x32
-                .checked_sub(obs.cumulative_token_0_price_x32)
-                .ok_or(GammaError::MathOverflow)?
-                .checked_div(time_delta)
-                .ok_or(GammaError::MathOverflow)?;
-
-            // Update min and max prices
-            min_price = min_price.min(price);
-            max_price = max_price.max(price);
-        }
-
-        Ok((min_price, max_price, twap_price))
+        let twap = observation_state.get_twap(current_time, window)?;
+        Ok((
+            twap.token_0_min_price_x32,
+            twap.token_0_max_price_x32,
+            twap.token_0_twap_price_x32,
+        ))
     }
 
     /// Calculates the pre-fee amount given a post-fee amount
@@ -341,11 +530,13 @@ impl DynamicFee {
     pub fn calculate_pre_fee_amount(
         block_timestamp: u64,
         post_fee_amount: u128,
-        observation_state: &ObservationState,
+        observation_state: &Observations,
         fee_type: FeeType,
         base_fees: u64,
         pool_state: &PoolState,
-        is_invoked_by_signed_segmenter: bool,
+        token_0_price_after_swap_x32: Option<u128>,
+        amm_config: &AmmConfig,
+        fee_path: FeePath,
     ) -> Result<(u128, u64)> {
         // x = pre_fee_amount (has to be calculated)
         // y = post_fee_amount
@@ -368,7 +559,9 @@ impl DynamicFee {
             fee_type,
             base_fees,
             pool_state,
-            is_invoked_by_signed_segmenter,
+            token_0_price_after_swap_x32,
+            amm_config,
+            fee_path,
         )?;
         if dynamic_fee_rate == 0 {
             Ok((post_fee_amount, 0))
@@ -394,20 +587,23 @@ impl DynamicFee {
 
     pub fn dynamic_fee_rate(
         block_timestamp: u64,
-        observation_state: &ObservationState,
+        observation_state: &Observations,
         fee_type: FeeType,
         base_fees: u64,
         pool_state: &PoolState,
-        is_invoked_by_signed_segmenter: bool,
+        token_0_price_after_swap_x32: Option<u128>,
+        amm_config: &AmmConfig,
+        fee_path: FeePath,
     ) -> Result<u64> {
-        // TODO: use is_invoked_by_signed_segmenter to charge less fees for signed segmenter, once they are implemented across all protocols and this is also used by the segmenter.
         let dynamic_fee_rate = Self::calculate_dynamic_fee(
             block_timestamp,
             observation_state,
             fee_type,
             base_fees,
             pool_state,
-            is_invoked_by_signed_segmenter,
+            token_0_price_after_swap_x32,
+            amm_config,
+            fee_path,
         )?;
 
         Ok(dynamic_fee_rate)
@@ -21,6 +21,15 @@ impl StaticFee {
         )
     }
 
+    /// Calculate the pool creator fee in trading tokens
+    pub fn creator_fee(amount: u128, creator_fee_rate: u64) -> Option<u128> {
+        floor_div(
+            amount,
+            u128::from(creator_fee_rate),
+            u128::from(FEE_RATE_DENOMINATOR_VALUE),
+        )
+    }
+
     /// Calculate the fund fee in trading tokens
     pub fn fund_fee(amount: u128, fund_fee_rate: u64) -> Option<u128> {
         floor_div(
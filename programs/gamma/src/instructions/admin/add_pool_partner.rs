@@ -0,0 +1,95 @@
+use crate::{
+    error::GammaError,
+    states::{
+        is_pool_state_partner, Partner, PartnerInfo, PoolPartnerInfo, PoolPartners, PoolState,
+        MAX_POOL_PARTNERS, POOL_PARTNERS_SEED,
+    },
+};
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+
+#[derive(Accounts)]
+pub struct AddPoolPartner<'info> {
+    /// Only admin can add partners to a pool
+    #[account(
+        mut,
+        address = crate::admin::id() @ GammaError::InvalidOwner
+    )]
+    pub owner: Signer<'info>,
+
+    #[account(mut)]
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    /// The registered partner added to the pool
+    pub partner: Account<'info, Partner>,
+
+    /// The partners of the pool, grows with every partner added
+    #[account(
+        init_if_needed,
+        seeds = [
+            POOL_PARTNERS_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+        ],
+        bump,
+        payer = owner,
+        space = PoolPartners::space(0),
+    )]
+    pub pool_partners: Account<'info, PoolPartners>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Adds a registered partner to the pool, so its users can provide liquidity linked with it.
+/// AssetDash in `PoolState::partners` of the pools created before `PoolPartners` is moved with its lp tokens and fees,
+/// its fees accrue from then on.
+pub fn add_pool_partner(ctx: Context<AddPoolPartner>) -> Result<()> {
+    let partner = ctx.accounts.partner.partner_type();
+    let pool_state = &mut ctx.accounts.pool_state.load_mut()?;
+    let pool_partners = &mut ctx.accounts.pool_partners;
+    pool_partners.pool_state = ctx.accounts.pool_state.key();
+    require!(!pool_partners.contains(partner), GammaError::InvalidPartner);
+    require_gt!(
+        MAX_POOL_PARTNERS,
+        pool_partners.partners.len(),
+        GammaError::InvalidPartner
+    );
+    let mut partner_info = PoolPartnerInfo {
+        partner_id: partner.0,
+        protocol_fee_per_lp_checkpoint_token_0: pool_state.protocol_fee_per_lp_token_0,
+        protocol_fee_per_lp_checkpoint_token_1: pool_state.protocol_fee_per_lp_token_1,
+        ..Default::default()
+    };
+    if is_pool_state_partner(pool_state, partner) {
+        let pool_state_partner = pool_state.partners[0];
+        partner_info.lp_token_linked_with_partner = pool_state_partner.lp_token_linked_with_partner;
+        partner_info.cumulative_fee_total_times_tvl_share_token_0 =
+            pool_state_partner.cumulative_fee_total_times_tvl_share_token_0;
+        partner_info.cumulative_fee_total_times_tvl_share_token_1 =
+            pool_state_partner.cumulative_fee_total_times_tvl_share_token_1;
+        pool_state.partners = [PartnerInfo {
+            partner_id: PartnerInfo::MOVED_TO_POOL_PARTNERS,
+            ..Default::default()
+        }; 1];
+    }
+    pool_partners.partners.push(partner_info);
+
+    let account_info = pool_partners.to_account_info();
+    let new_len = PoolPartners::space(pool_partners.partners.len());
+    let rent_lamports = Rent::get()?
+        .minimum_balance(new_len)
+        .saturating_sub(account_info.lamports());
+    if rent_lamports > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.owner.to_account_info(),
+                    to: account_info.clone(),
+                },
+            ),
+            rent_lamports,
+        )?;
+    }
+    account_info.realloc(new_len, false)?;
+    Ok(())
+}
@@ -0,0 +1,41 @@
+use crate::{
+    error::GammaError,
+    states::{Partner, MAX_PARTNER_NAME_LEN, PARTNER_SEED},
+};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+#[instruction(partner_id: u8)]
+pub struct CreatePartner<'info> {
+    /// Only admin can register partners
+    #[account(
+        mut,
+        address = crate::admin::id() @ GammaError::InvalidOwner
+    )]
+    pub owner: Signer<'info>,
+
+    #[account(
+        init,
+        seeds = [
+            PARTNER_SEED.as_bytes(),
+            &[partner_id],
+        ],
+        bump,
+        payer = owner,
+        space = Partner::LEN,
+    )]
+    pub partner: Account<'info, Partner>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn create_partner(ctx: Context<CreatePartner>, partner_id: u8, name: String) -> Result<()> {
+    require!(
+        !name.is_empty() && name.len() <= MAX_PARTNER_NAME_LEN,
+        GammaError::InvalidInput
+    );
+    let partner = &mut ctx.accounts.partner;
+    partner.partner_id = partner_id;
+    partner.name = name;
+    Ok(())
+}
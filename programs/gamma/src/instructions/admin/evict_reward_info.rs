@@ -0,0 +1,55 @@
+use crate::{
+    error::GammaError,
+    instructions::update_reward_info,
+    states::{PoolState, RewardInfo},
+};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct EvictRewardInfo<'info> {
+    /// Only admin can evict a reward
+    #[account(address = crate::admin::id() @ GammaError::InvalidOwner)]
+    pub owner: Signer<'info>,
+
+    #[account(mut)]
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    #[account(
+        mut,
+        seeds = [
+            crate::REWARD_INFO_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+            reward_info.start_at.to_le_bytes().as_ref(),
+            reward_info.mint.as_ref(),
+        ],
+        bump,
+    )]
+    pub reward_info: Account<'info, RewardInfo>,
+}
+
+/// Ends an active reward now and frees its slot of the active rewards of the pool.
+/// The rewards released until now stay claimable, the others can be refunded to the reward provider.
+pub fn evict_reward_info(ctx: Context<EvictRewardInfo>) -> Result<()> {
+    let current_time = Clock::get()?.unix_timestamp as u64;
+    let pool_state = &mut ctx.accounts.pool_state.load_mut()?;
+    let reward_info_key = ctx.accounts.reward_info.key();
+    require!(
+        pool_state.is_active_reward_info(reward_info_key),
+        GammaError::InvalidInput
+    );
+    let reward_lp_supply = pool_state.reward_lp_supply()?;
+    update_reward_info(
+        &mut ctx.accounts.reward_info,
+        reward_info_key,
+        pool_state,
+        reward_lp_supply,
+        current_time,
+    )?;
+
+    let reward_info = &mut ctx.accounts.reward_info;
+    if reward_info.is_ended() {
+        return Ok(());
+    }
+    reward_info.end_now(current_time)?;
+    pool_state.remove_active_reward_info(reward_info_key)
+}
@@ -1,13 +1,21 @@
+pub mod add_pool_partner;
 pub mod collect_fund_fee;
 pub mod collect_protocol_fee;
 pub mod create_config;
+pub mod create_partner;
 pub mod create_referral_project;
+pub mod evict_reward_info;
+pub mod set_price_feed_config;
 pub mod update_config;
 pub mod update_pool;
 
+pub use add_pool_partner::*;
 pub use collect_fund_fee::*;
 pub use collect_protocol_fee::*;
 pub use create_config::*;
+pub use create_partner::*;
 pub use create_referral_project::*;
+pub use evict_reward_info::*;
+pub use set_price_feed_config::*;
 pub use update_config::*;
 pub use update_pool::*;
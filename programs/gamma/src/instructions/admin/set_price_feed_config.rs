@@ -0,0 +1,57 @@
+use crate::{
+    error::GammaError,
+    fees::FEE_RATE_DENOMINATOR_VALUE,
+    states::{PoolState, PriceFeedConfig, PRICE_FEED_CONFIG_SEED},
+};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct SetPriceFeedConfig<'info> {
+    #[account(
+        mut,
+        address = crate::admin::id() @ GammaError::InvalidOwner
+    )]
+    pub owner: Signer<'info>,
+
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    /// Oracle configuration of the pool, used by oracle based swaps
+    #[account(
+        init_if_needed,
+        seeds = [
+            PRICE_FEED_CONFIG_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+        ],
+        bump,
+        payer = owner,
+        space = PriceFeedConfig::LEN
+    )]
+    pub price_feed_config: Account<'info, PriceFeedConfig>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn set_price_feed_config(
+    ctx: Context<SetPriceFeedConfig>,
+    token_0_feed_id: [u8; 32],
+    token_1_feed_id: [u8; 32],
+    max_price_age: u64,
+    max_confidence_rate: u64,
+) -> Result<()> {
+    require!(token_0_feed_id != [0u8; 32], GammaError::InvalidInput);
+    require_gt!(max_price_age, 0, GammaError::InvalidInput);
+    require_gt!(
+        FEE_RATE_DENOMINATOR_VALUE,
+        max_confidence_rate,
+        GammaError::InvalidInput
+    );
+
+    let price_feed_config = &mut ctx.accounts.price_feed_config;
+    price_feed_config.pool_state = ctx.accounts.pool_state.key();
+    price_feed_config.token_0_feed_id = token_0_feed_id;
+    price_feed_config.token_1_feed_id = token_1_feed_id;
+    price_feed_config.max_price_age = max_price_age;
+    price_feed_config.max_confidence_rate = max_confidence_rate;
+
+    Ok(())
+}
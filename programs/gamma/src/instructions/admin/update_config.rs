@@ -3,7 +3,7 @@ use anchor_lang::prelude::*;
 use crate::{
     error::GammaError,
     fees::FEE_RATE_DENOMINATOR_VALUE,
-    states::{validate_config_rates, AmmConfig},
+    states::{validate_config_rates, AmmConfig, SEGMENTER_DISCOUNT_RATE_DISABLED},
 };
 
 #[derive(Accounts)]
@@ -47,6 +47,22 @@ pub fn update_amm_config(ctx: Context<UpdateAmmConfig>, param: u16, value: u64)
             };
             set_new_secondary_admin(amm_config, new_secondary_admin)?;
         }
+        9 => amm_config.flash_loan_fee_rate = value,
+        10 => {
+            amm_config.segmenter_discount_rate = if value == 0 {
+                SEGMENTER_DISCOUNT_RATE_DISABLED
+            } else {
+                value
+            }
+        }
+        11 => amm_config.unsigned_flow_surcharge_rate = value,
+        12 => {
+            let new_segmenter_registry_program = match ctx.remaining_accounts.iter().next() {
+                Some(account) => account.key(),
+                None => return err!(GammaError::InvalidInput),
+            };
+            amm_config.segmenter_registry_program = new_segmenter_registry_program;
+        }
         _ => return err!(GammaError::InvalidInput),
     }
 
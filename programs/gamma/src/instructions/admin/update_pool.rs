@@ -38,6 +38,11 @@ pub fn update_pool(ctx: Context<UpdatePool>, param: u32, value: u64) -> Result<(
         3 => update_max_shared_token0(ctx, value),
         4 => update_max_shared_token1(ctx, value),
         5 => update_open_time(ctx),
+        6 => update_observation_update_duration(ctx, value),
+        7 => update_trade_fee_rate_override(ctx, value),
+        8 => update_protocol_fee_rate_override(ctx, value),
+        9 => update_fund_fee_rate_override(ctx, value),
+        10 => update_creator_fee_rate(ctx, value),
         _ => Err(GammaError::InvalidInput.into()),
     }
 }
@@ -49,6 +54,101 @@ fn update_open_time(ctx: Context<UpdatePool>) -> Result<()> {
     Ok(())
 }
 
+fn update_observation_update_duration(
+    ctx: Context<UpdatePool>,
+    observation_update_duration: u64,
+) -> Result<()> {
+    let mut pool_state = ctx.accounts.pool_state.load_mut()?;
+    pool_state.observation_update_duration =
+        u32::try_from(observation_update_duration).or(err!(GammaError::InvalidInput))?;
+    Ok(())
+}
+
+fn update_trade_fee_rate_override(
+    ctx: Context<UpdatePool>,
+    trade_fee_rate_override: u64,
+) -> Result<()> {
+    require_gt!(FEE_RATE_DENOMINATOR_VALUE, trade_fee_rate_override);
+    let amm_config = &ctx.accounts.amm_config;
+    let mut pool_state = ctx.accounts.pool_state.load_mut()?;
+    if trade_fee_rate_override == 0 {
+        pool_state.protocol_fee_rate_override = 0;
+        pool_state.fund_fee_rate_override = 0;
+    } else if pool_state.trade_fee_rate_override == 0 {
+        // The protocol and fund fee rates start from the ones of the amm config
+        pool_state.protocol_fee_rate_override =
+            u32::try_from(amm_config.protocol_fee_rate).or(err!(GammaError::InvalidFee))?;
+        pool_state.fund_fee_rate_override =
+            u32::try_from(amm_config.fund_fee_rate).or(err!(GammaError::InvalidFee))?;
+    }
+    pool_state.trade_fee_rate_override =
+        u32::try_from(trade_fee_rate_override).or(err!(GammaError::InvalidFee))?;
+    Ok(())
+}
+
+fn update_protocol_fee_rate_override(
+    ctx: Context<UpdatePool>,
+    protocol_fee_rate_override: u64,
+) -> Result<()> {
+    let mut pool_state = ctx.accounts.pool_state.load_mut()?;
+    require_neq!(
+        pool_state.trade_fee_rate_override,
+        0,
+        GammaError::InvalidInput
+    );
+    require_gt!(
+        FEE_RATE_DENOMINATOR_VALUE,
+        protocol_fee_rate_override
+            + u64::from(pool_state.fund_fee_rate_override)
+            + u64::from(pool_state.creator_fee_rate),
+        GammaError::InvalidFee
+    );
+    pool_state.protocol_fee_rate_override =
+        u32::try_from(protocol_fee_rate_override).or(err!(GammaError::InvalidFee))?;
+    Ok(())
+}
+
+fn update_fund_fee_rate_override(
+    ctx: Context<UpdatePool>,
+    fund_fee_rate_override: u64,
+) -> Result<()> {
+    let mut pool_state = ctx.accounts.pool_state.load_mut()?;
+    require_neq!(
+        pool_state.trade_fee_rate_override,
+        0,
+        GammaError::InvalidInput
+    );
+    require_gt!(
+        FEE_RATE_DENOMINATOR_VALUE,
+        fund_fee_rate_override
+            + u64::from(pool_state.protocol_fee_rate_override)
+            + u64::from(pool_state.creator_fee_rate),
+        GammaError::InvalidFee
+    );
+    pool_state.fund_fee_rate_override =
+        u32::try_from(fund_fee_rate_override).or(err!(GammaError::InvalidFee))?;
+    Ok(())
+}
+
+fn update_creator_fee_rate(ctx: Context<UpdatePool>, creator_fee_rate: u64) -> Result<()> {
+    let amm_config = &ctx.accounts.amm_config;
+    let mut pool_state = ctx.accounts.pool_state.load_mut()?;
+    // The creator fee is carved out of the trade fee with the protocol and fund fees
+    let fee_rates = pool_state
+        .protocol_fee_rate(amm_config)
+        .checked_add(pool_state.fund_fee_rate(amm_config))
+        .and_then(|fee_rates| fee_rates.checked_add(creator_fee_rate))
+        .ok_or(GammaError::MathOverflow)?;
+    require_gt!(
+        FEE_RATE_DENOMINATOR_VALUE,
+        fee_rates,
+        GammaError::InvalidFee
+    );
+    pool_state.creator_fee_rate =
+        u32::try_from(creator_fee_rate).or(err!(GammaError::InvalidFee))?;
+    Ok(())
+}
+
 fn update_max_trade_fee_rate(ctx: Context<UpdatePool>, max_trade_fee_rate: u64) -> Result<()> {
     let mut pool_state = ctx.accounts.pool_state.load_mut()?;
     pool_state.max_trade_fee_rate = max_trade_fee_rate;
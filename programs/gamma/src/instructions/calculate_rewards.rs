@@ -7,6 +7,7 @@ use anchor_lang::prelude::*;
 
 #[derive(Accounts)]
 pub struct CalculateRewards<'info> {
+    /// Pays for the user reward info if needed, anyone can calculate the rewards of a user
     #[account(mut)]
     pub signer: Signer<'info>,
 
@@ -14,10 +15,11 @@ pub struct CalculateRewards<'info> {
     /// CHECK: Does not require any validation
     pub user: AccountInfo<'info>,
 
-    #[account()]
+    #[account(mut)]
     pub pool_state: AccountLoader<'info, PoolState>,
 
     #[account(
+        mut,
         seeds = [
             crate::REWARD_INFO_SEED.as_bytes(),
             pool_state.key().as_ref(),
@@ -30,7 +32,7 @@ pub struct CalculateRewards<'info> {
 
     #[account(
         init_if_needed,
-        space = 8 + std::mem::size_of::<UserRewardInfo>(),
+        space = UserRewardInfo::LEN,
         payer = signer,
         seeds = [
             USER_REWARD_INFO_SEED.as_bytes(),
@@ -56,34 +58,150 @@ pub struct CalculateRewards<'info> {
 }
 
 pub fn calculate_rewards(ctx: Context<CalculateRewards>) -> Result<()> {
-    #[cfg(not(feature = "test-sbf"))]
-    if ctx.accounts.signer.key() != crate::CALCULATE_REWARDS_ADMIN {
-        return err!(GammaError::InvalidOwner);
-    }
-
-    let pool_state = &mut ctx.accounts.pool_state.load()?;
+    let pool_state = &mut ctx.accounts.pool_state.load_mut()?;
+    let reward_lp_supply = pool_state.reward_lp_supply()?;
     let current_time = Clock::get()?.unix_timestamp as u64;
-    if ctx.accounts.user_reward_info.rewards_last_calculated_at >= current_time {
-        return Ok(());
+    let reward_info_key = ctx.accounts.reward_info.key();
+    // Whether the reward was settled whenever the lp tokens changed until now, the update removes it when it ends
+    let is_active_reward = pool_state.is_active_reward_info(reward_info_key);
+    update_reward_info(
+        &mut ctx.accounts.reward_info,
+        reward_info_key,
+        pool_state,
+        reward_lp_supply,
+        current_time,
+    )?;
+
+    let user_pool_liquidity = &ctx.accounts.user_pool_liquidity;
+    let reward_weight =
+        u64::try_from(user_pool_liquidity.reward_weight(user_pool_liquidity.lp_tokens_owned)?)
+            .map_err(|_| error!(GammaError::MathOverflow))?;
+    let user_reward_info = &mut ctx.accounts.user_reward_info;
+    if user_reward_info.reward_info == Pubkey::default() {
+        user_reward_info.initialize(
+            ctx.accounts.user.key(),
+            reward_info_key,
+            &ctx.accounts.reward_info,
+            is_active_reward,
+            reward_weight,
+            user_pool_liquidity.lp_tokens_changed_at,
+        );
+        ctx.accounts.reward_info.user_reward_infos = ctx
+            .accounts
+            .reward_info
+            .user_reward_infos
+            .checked_add(1)
+            .ok_or(GammaError::MathOverflow)?;
     }
-    // Start accrual of rewards from the time user first deposit.
-    // This prevents the user from creating a invest at the end of rewards and getting
-    // boosted rewards for the full period.
-    if ctx.accounts.user_reward_info.rewards_last_calculated_at == 0 {
-        ctx.accounts.user_reward_info.rewards_last_calculated_at =
-            ctx.accounts.user_pool_liquidity.first_investment_at;
+    user_reward_info.calculate_claimable_rewards(reward_weight, &ctx.accounts.reward_info)
+}
+
+/// Accrues the rewards of the reward info up to now, it is removed from the active reward infos
+/// of the pool when the reward ends.
+pub fn update_reward_info(
+    reward_info: &mut RewardInfo,
+    reward_info_key: Pubkey,
+    pool_state: &mut PoolState,
+    reward_lp_supply: u64,
+    current_time: u64,
+) -> Result<()> {
+    if reward_info.update_reward_per_lp_share(reward_lp_supply, current_time)? {
+        pool_state.remove_active_reward_info(reward_info_key)?;
     }
+    Ok(())
+}
 
-    let user_reward_info = &mut ctx.accounts.user_reward_info;
-    user_reward_info.calculate_claimable_rewards(
-        ctx.accounts.user_pool_liquidity.lp_tokens_owned as u64,
-        pool_state.lp_supply as u64,
-        &ctx.accounts.reward_info,
+/// Loads and updates an active reward info of the pool passed in the remaining accounts,
+/// it must be written back with `exit`.
+pub fn update_active_reward_info<'info>(
+    account_info: &'info AccountInfo<'info>,
+    pool_id: Pubkey,
+    pool_state: &mut PoolState,
+    reward_lp_supply: u64,
+    current_time: u64,
+) -> Result<Account<'info, RewardInfo>> {
+    require!(account_info.is_writable, ErrorCode::ConstraintMut);
+    require!(
+        pool_state.is_active_reward_info(account_info.key()),
+        GammaError::InvalidInput
+    );
+    let mut reward_info = Account::<RewardInfo>::try_from(account_info)?;
+    require_keys_eq!(reward_info.pool, pool_id, GammaError::InvalidInput);
+    update_reward_info(
+        &mut reward_info,
+        account_info.key(),
+        pool_state,
+        reward_lp_supply,
+        current_time,
     )?;
+    Ok(reward_info)
+}
+
+/// Settles the rewards of the user for all the active reward infos of the pool, before the lp tokens of the user
+/// or the lp supply of the pool change. `lp_tokens_owned` are the lp tokens of the user after the change,
+/// the lock of `user_pool_liquidity` must already be the one after the change.
+///
+/// The last `2 * active_reward_infos` remaining accounts are pairs of (reward_info, user_reward_info),
+/// one for each of the `active_reward_info_keys` of the pool. The user reward infos are created by `calculate_rewards`.
+pub fn settle_rewards<'c, 'info>(
+    remaining_accounts: &'c [AccountInfo<'info>],
+    pool_id: Pubkey,
+    pool_state: &mut PoolState,
+    user_pool_liquidity: &UserPoolLiquidity,
+    lp_tokens_owned: u128,
+) -> Result<()>
+where
+    'c: 'info,
+{
+    let reward_accounts_len = usize::try_from(pool_state.active_reward_infos)
+        .map_err(|_| error!(GammaError::MathOverflow))?
+        .checked_mul(2)
+        .ok_or(GammaError::MathOverflow)?;
+    require_gte!(
+        remaining_accounts.len(),
+        reward_accounts_len,
+        ErrorCode::AccountNotEnoughKeys
+    );
+    let reward_accounts = &remaining_accounts[remaining_accounts.len() - reward_accounts_len..];
 
-    user_reward_info.reward_info = ctx.accounts.reward_info.key();
-    user_reward_info.user = ctx.accounts.user.key();
-    user_reward_info.pool_state = ctx.accounts.pool_state.key();
+    let reward_weight = u64::try_from(user_pool_liquidity.reward_weight(lp_tokens_owned)?)
+        .map_err(|_| error!(GammaError::MathOverflow))?;
+    let reward_lp_supply = pool_state.reward_lp_supply()?;
+    let current_time = Clock::get()?.unix_timestamp as u64;
+    for (index, accounts) in reward_accounts.chunks(2).enumerate() {
+        // Every active reward info must be settled once
+        require!(
+            reward_accounts[..index * 2]
+                .iter()
+                .step_by(2)
+                .all(|settled| settled.key() != accounts[0].key()),
+            GammaError::InvalidInput
+        );
+        let reward_info = update_active_reward_info(
+            &accounts[0],
+            pool_id,
+            pool_state,
+            reward_lp_supply,
+            current_time,
+        )?;
+
+        require!(accounts[1].is_writable, ErrorCode::ConstraintMut);
+        let mut user_reward_info = Account::<UserRewardInfo>::try_from(&accounts[1])?;
+        require_keys_eq!(
+            user_reward_info.reward_info,
+            reward_info.key(),
+            GammaError::InvalidInput
+        );
+        require_keys_eq!(
+            user_reward_info.user,
+            user_pool_liquidity.user,
+            GammaError::InvalidInput
+        );
+        user_reward_info.calculate_claimable_rewards(reward_weight, &reward_info)?;
+
+        reward_info.exit(&crate::id())?;
+        user_reward_info.exit(&crate::id())?;
+    }
 
     Ok(())
 }
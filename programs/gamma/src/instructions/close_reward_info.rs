@@ -0,0 +1,143 @@
+use crate::{
+    error::GammaError,
+    instructions::update_reward_info,
+    states::{PoolState, RewardInfo},
+    utils::transfer_from_pool_vault_to_user,
+    REWARD_VAULT_SEED,
+};
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    token::Token,
+    token_interface::{self, CloseAccount, Mint, Token2022, TokenAccount},
+};
+
+#[derive(Accounts)]
+pub struct CloseRewardInfo<'info> {
+    /// The provider of the rewards, receives the rent
+    #[account(
+        mut,
+        address = reward_info.rewarded_by @ GammaError::InvalidOwner
+    )]
+    pub reward_provider: Signer<'info>,
+
+    /// CHECK: pool vault authority
+    #[account(
+        seeds = [
+            crate::AUTH_SEED.as_bytes(),
+        ],
+        bump,
+    )]
+    pub authority: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    #[account(
+        mut,
+        seeds = [
+            crate::REWARD_INFO_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+            reward_info.start_at.to_le_bytes().as_ref(),
+            reward_info.mint.as_ref(),
+        ],
+        bump,
+        close = reward_provider,
+    )]
+    pub reward_info: Account<'info, RewardInfo>,
+
+    /// Receives the rewards left in the reward vault
+    #[account(
+        mut,
+        token::mint = reward_mint,
+        token::authority = reward_provider,
+    )]
+    pub reward_providers_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// The reward vault, the rewards left are swept once every user reward info is closed
+    #[account(
+        mut,
+        seeds = [
+            REWARD_VAULT_SEED.as_bytes(),
+            reward_info.key().as_ref(),
+        ],
+        bump,
+        token::mint = reward_mint,
+        token::authority = authority,
+    )]
+    pub reward_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(address = reward_info.mint)]
+    pub reward_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// token Program
+    pub token_program: Program<'info, Token>,
+
+    /// Token program 2022
+    pub token_program_2022: Program<'info, Token2022>,
+}
+
+/// Closes a finished reward and its vault, the reward is accrued up to its end and is no longer active.
+/// Every `UserRewardInfo` of the reward must be closed first, see `close_user_reward_info`, the rewards left
+/// in the vault are then the unallocated rewards and the rounding dust, they are swept to the reward provider.
+/// The user reward infos created before the accumulator are not counted, so a reward created before it
+/// can only be closed once its vault is empty.
+pub fn close_reward_info(ctx: Context<CloseRewardInfo>) -> Result<()> {
+    let current_time = Clock::get()?.unix_timestamp as u64;
+    if current_time <= ctx.accounts.reward_info.end_rewards_at {
+        return err!(GammaError::InvalidRewardTime);
+    }
+
+    let pool_state = &mut ctx.accounts.pool_state.load_mut()?;
+    let reward_info_key = ctx.accounts.reward_info.key();
+    let reward_lp_supply = pool_state.reward_lp_supply()?;
+    update_reward_info(
+        &mut ctx.accounts.reward_info,
+        reward_info_key,
+        pool_state,
+        reward_lp_supply,
+        current_time,
+    )?;
+    if pool_state.is_active_reward_info(reward_info_key) {
+        pool_state.remove_active_reward_info(reward_info_key)?;
+    }
+
+    let reward_info = &ctx.accounts.reward_info;
+    require_eq!(reward_info.user_reward_infos, 0, GammaError::InvalidInput);
+    let remaining_rewards = ctx.accounts.reward_vault.amount;
+    if reward_info.legacy_rewards_until != 0 {
+        require_eq!(remaining_rewards, 0, GammaError::InvalidInput);
+    }
+
+    let auth_bump = pool_state.auth_bump;
+    if remaining_rewards > 0 {
+        transfer_from_pool_vault_to_user(
+            ctx.accounts.authority.to_account_info(),
+            ctx.accounts.reward_vault.to_account_info(),
+            ctx.accounts
+                .reward_providers_token_account
+                .to_account_info(),
+            ctx.accounts.reward_mint.to_account_info(),
+            if ctx.accounts.reward_mint.to_account_info().owner == ctx.accounts.token_program.key {
+                ctx.accounts.token_program.to_account_info()
+            } else {
+                ctx.accounts.token_program_2022.to_account_info()
+            },
+            remaining_rewards,
+            ctx.accounts.reward_mint.decimals,
+            &[&[crate::AUTH_SEED.as_bytes(), &[auth_bump]]],
+        )?;
+    }
+    token_interface::close_account(CpiContext::new_with_signer(
+        if ctx.accounts.reward_vault.to_account_info().owner == ctx.accounts.token_program.key {
+            ctx.accounts.token_program.to_account_info()
+        } else {
+            ctx.accounts.token_program_2022.to_account_info()
+        },
+        CloseAccount {
+            account: ctx.accounts.reward_vault.to_account_info(),
+            destination: ctx.accounts.reward_provider.to_account_info(),
+            authority: ctx.accounts.authority.to_account_info(),
+        },
+        &[&[crate::AUTH_SEED.as_bytes(), &[auth_bump]]],
+    ))
+}
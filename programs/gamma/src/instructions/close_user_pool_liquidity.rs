@@ -0,0 +1,32 @@
+use crate::{
+    error::GammaError,
+    states::{PoolState, UserPoolLiquidity, USER_POOL_LIQUIDITY_SEED},
+};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct CloseUserPoolLiquidity<'info> {
+    /// Owner of the user pool liquidity account, receives the rent
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    /// User pool liquidity account, it can only be closed when no liquidity is left
+    #[account(
+        mut,
+        seeds = [
+            USER_POOL_LIQUIDITY_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+            owner.key().as_ref(),
+        ],
+        bump,
+        constraint = user_pool_liquidity.lp_tokens_owned == 0 @ GammaError::InvalidLpTokenAmount,
+        close = owner,
+    )]
+    pub user_pool_liquidity: Account<'info, UserPoolLiquidity>,
+}
+
+pub fn close_user_pool_liquidity(_ctx: Context<CloseUserPoolLiquidity>) -> Result<()> {
+    Ok(())
+}
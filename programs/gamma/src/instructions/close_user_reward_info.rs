@@ -0,0 +1,86 @@
+use crate::{
+    error::GammaError,
+    states::{RewardInfo, UserPoolLiquidity, UserRewardInfo, USER_POOL_LIQUIDITY_SEED},
+    USER_REWARD_INFO_SEED,
+};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct CloseUserRewardInfo<'info> {
+    /// The user of the reward info, receives the rent
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    /// CHECK: The reward info, either closed with `close_reward_info` or ended, see `close_user_reward_info`
+    #[account(
+        mut,
+        address = user_reward_info.reward_info,
+    )]
+    pub reward_info: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [
+            USER_REWARD_INFO_SEED.as_bytes(),
+            reward_info.key().as_ref(),
+            user.key().as_ref(),
+        ],
+        bump,
+        close = user,
+    )]
+    pub user_reward_info: Account<'info, UserRewardInfo>,
+
+    /// User pool liquidity account, required when the reward info is not closed
+    #[account(
+        mut,
+        seeds = [
+            USER_POOL_LIQUIDITY_SEED.as_bytes(),
+            user_reward_info.pool_state.as_ref(),
+            user.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub user_pool_liquidity: Option<Account<'info, UserPoolLiquidity>>,
+}
+
+/// Closes the user reward info once the reward info is closed, or once the reward has ended and the rewards
+/// of the user are calculated up to its end and claimed.
+/// A user reward info created again for a reward that is not closed must not earn its rewards twice, so the reward
+/// is seen as started before the lp tokens of the user changed, see `UserRewardInfo::initialize`.
+pub fn close_user_reward_info(ctx: Context<CloseUserRewardInfo>) -> Result<()> {
+    let reward_info_account = ctx.accounts.reward_info.to_account_info();
+    if reward_info_account.data_is_empty() {
+        return Ok(());
+    }
+
+    let mut reward_info = Account::<RewardInfo>::try_from(&reward_info_account)?;
+    let user_reward_info = &ctx.accounts.user_reward_info;
+    if !reward_info.is_ended()
+        || user_reward_info.rewards_last_calculated_at < reward_info.end_rewards_at
+    {
+        return err!(GammaError::InvalidRewardTime);
+    }
+    require_eq!(
+        user_reward_info.get_total_claimable_rewards(),
+        0,
+        GammaError::InvalidInput
+    );
+
+    let user_pool_liquidity = ctx
+        .accounts
+        .user_pool_liquidity
+        .as_deref_mut()
+        .ok_or(ErrorCode::AccountNotEnoughKeys)?;
+    user_pool_liquidity.lp_tokens_changed_at = user_pool_liquidity.lp_tokens_changed_at.max(
+        reward_info
+            .start_at
+            .checked_add(1)
+            .ok_or(GammaError::MathOverflow)?,
+    );
+
+    reward_info.user_reward_infos = reward_info
+        .user_reward_infos
+        .checked_sub(1)
+        .ok_or(GammaError::MathOverflow)?;
+    reward_info.exit(&crate::id())
+}
@@ -0,0 +1,56 @@
+use crate::{
+    error::GammaError,
+    states::{PoolState, LP_MINT_DECIMALS, POOL_LP_MINT_SEED},
+};
+use anchor_lang::prelude::*;
+use anchor_spl::{token::Token, token_interface::Mint};
+
+#[derive(Accounts)]
+pub struct CreateLpMint<'info> {
+    /// Address paying to create the lp mint. It can be anyone.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// CHECK: pda authority of the pool, mint authority of the lp mint
+    #[account(
+        seeds = [
+            crate::AUTH_SEED.as_bytes(),
+        ],
+        bump,
+    )]
+    pub authority: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    /// Spl mint of the pool tokens
+    #[account(
+        init,
+        seeds = [
+            POOL_LP_MINT_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+        ],
+        bump,
+        mint::decimals = LP_MINT_DECIMALS,
+        mint::authority = authority,
+        mint::token_program = token_program,
+        payer = payer,
+    )]
+    pub lp_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    pub token_program: Program<'info, Token>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn create_lp_mint(ctx: Context<CreateLpMint>) -> Result<()> {
+    let pool_state = &mut ctx.accounts.pool_state.load_mut()?;
+    if pool_state.has_lp_mint() {
+        return err!(GammaError::IncorrectLpMint);
+    }
+
+    pool_state.lp_mint = ctx.accounts.lp_mint.key();
+    pool_state.lp_mint_decimals = ctx.accounts.lp_mint.decimals;
+
+    Ok(())
+}
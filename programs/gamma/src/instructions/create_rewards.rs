@@ -25,13 +25,13 @@ pub struct CreateRewards<'info> {
     )]
     pub authority: UncheckedAccount<'info>,
 
-    #[account()]
+    #[account(mut)]
     pub pool_state: AccountLoader<'info, PoolState>,
 
     #[account(
         init,
         payer = reward_provider,
-        space = 8 + std::mem::size_of::<RewardInfo>(),
+        space = RewardInfo::LEN,
         seeds = [
             crate::REWARD_INFO_SEED.as_bytes(),
             pool_state.key().as_ref(),
@@ -119,5 +119,21 @@ pub fn create_rewards(
 
     reward_info.pool = ctx.accounts.pool_state.key();
 
+    reward_info.reward_per_lp_share = 0;
+    reward_info.last_updated_at = current_time;
+
+    reward_info.total_released = 0;
+    reward_info.unallocated_rewards = 0;
+    reward_info.legacy_reward_per_lp_share = 0;
+    reward_info.legacy_rewards_until = 0;
+    reward_info.user_reward_infos = 0;
+    reward_info.validate_reward_rate(current_time)?;
+
+    // The reward must be settled whenever the lp tokens change until it ends
+    ctx.accounts
+        .pool_state
+        .load_mut()?
+        .add_active_reward_info(reward_info.key())?;
+
     Ok(())
 }
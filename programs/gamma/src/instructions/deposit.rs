@@ -1,11 +1,12 @@
 use crate::{
     curve::{CurveCalculator, RoundDirection},
     error::GammaError,
+    instructions::settle_rewards,
     states::{
-        LpChangeEvent, PartnerType, PoolState, PoolStatusBitIndex, UserPoolLiquidity,
-        USER_POOL_LIQUIDITY_SEED,
+        update_partner_lp_tokens, LpChangeEvent, PoolPartners, PoolState, PoolStatusBitIndex,
+        UserPoolLiquidity, POOL_PARTNERS_SEED, USER_POOL_LIQUIDITY_SEED,
     },
-    utils::{get_transfer_inverse_fee, transfer_from_user_to_pool_vault},
+    utils::{get_transfer_fee, get_transfer_inverse_fee, transfer_from_user_to_pool_vault},
 };
 use anchor_lang::prelude::*;
 use anchor_spl::{
@@ -88,14 +89,94 @@ pub struct Deposit<'info> {
         address = token_1_vault.mint
     )]
     pub vault_1_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// The partners of the pool, required when the user is linked with a partner, see `update_partner_lp_tokens`
+    #[account(
+        mut,
+        seeds = [
+            POOL_PARTNERS_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub pool_partners: Option<Account<'info, PoolPartners>>,
 }
 
-pub fn deposit(
-    ctx: Context<Deposit>,
+/// The remaining accounts are the active rewards of the pool, see `settle_rewards`.
+pub fn deposit<'c, 'info>(
+    ctx: Context<'_, '_, 'c, 'info, Deposit<'info>>,
     lp_token_amount: u64,
     maximum_token_0_amount: u64,
     maximum_token_1_amount: u64,
-) -> Result<()> {
+) -> Result<()>
+where
+    'c: 'info,
+{
+    settle_deposit_rewards(&ctx, lp_token_amount)?;
+    deposit_to_gamma_pool(
+        ctx.accounts,
+        lp_token_amount,
+        maximum_token_0_amount,
+        maximum_token_1_amount,
+    )
+}
+
+/// Deposits up to the given token amounts, the lp token amount is the largest one they can pay for.
+/// The maximum amounts include the transfer fees of the tokens.
+pub fn deposit_exact_tokens<'c, 'info>(
+    ctx: Context<'_, '_, 'c, 'info, Deposit<'info>>,
+    maximum_token_0_amount: u64,
+    maximum_token_1_amount: u64,
+    minimum_lp_token_amount: u64,
+) -> Result<()>
+where
+    'c: 'info,
+{
+    let lp_token_amount = {
+        let pool_state = ctx.accounts.pool_state.load()?;
+        let curve_type = pool_state.curve_type()?;
+        let (total_token_0_amount, total_token_1_amount) = pool_state.vault_amount_without_fee()?;
+
+        // The pool only receives the amounts left after the transfer fees
+        let token_0_amount = maximum_token_0_amount
+            .checked_sub(get_transfer_fee(
+                &ctx.accounts.vault_0_mint.to_account_info(),
+                maximum_token_0_amount,
+            )?)
+            .ok_or(GammaError::MathOverflow)?;
+        let token_1_amount = maximum_token_1_amount
+            .checked_sub(get_transfer_fee(
+                &ctx.accounts.vault_1_mint.to_account_info(),
+                maximum_token_1_amount,
+            )?)
+            .ok_or(GammaError::MathOverflow)?;
+
+        let lp_tokens_for_token_0 = CurveCalculator::token_0_to_lp_tokens(
+            curve_type,
+            u128::from(token_0_amount),
+            u128::from(total_token_0_amount),
+            u128::from(pool_state.lp_supply),
+            RoundDirection::Floor,
+        )
+        .ok_or(GammaError::ZeroTradingTokens)?;
+        let lp_tokens_for_token_1 = CurveCalculator::token_1_to_lp_tokens(
+            curve_type,
+            u128::from(token_1_amount),
+            u128::from(total_token_1_amount),
+            u128::from(pool_state.lp_supply),
+            RoundDirection::Floor,
+        )
+        .ok_or(GammaError::ZeroTradingTokens)?;
+        u64::try_from(lp_tokens_for_token_0.min(lp_tokens_for_token_1))
+            .map_err(|_| GammaError::MathOverflow)?
+    };
+    require_gte!(
+        lp_token_amount,
+        minimum_lp_token_amount,
+        GammaError::ExceededSlippage
+    );
+
+    settle_deposit_rewards(&ctx, lp_token_amount)?;
     deposit_to_gamma_pool(
         ctx.accounts,
         lp_token_amount,
@@ -104,6 +185,28 @@ pub fn deposit(
     )
 }
 
+/// Settles the rewards of the owner before `lp_token_amount` is deposited.
+fn settle_deposit_rewards<'c, 'info>(
+    ctx: &Context<'_, '_, 'c, 'info, Deposit<'info>>,
+    lp_token_amount: u64,
+) -> Result<()>
+where
+    'c: 'info,
+{
+    let pool_state = &mut ctx.accounts.pool_state.load_mut()?;
+    settle_rewards(
+        ctx.remaining_accounts,
+        ctx.accounts.pool_state.key(),
+        pool_state,
+        &ctx.accounts.user_pool_liquidity,
+        ctx.accounts
+            .user_pool_liquidity
+            .lp_tokens_owned
+            .checked_add(u128::from(lp_token_amount))
+            .ok_or(GammaError::MathOverflow)?,
+    )
+}
+
 pub fn deposit_to_gamma_pool(
     accounts: &mut Deposit,
     lp_token_amount: u64,
@@ -118,6 +221,7 @@ pub fn deposit_to_gamma_pool(
     }
     let (total_token_0_amount, total_token_1_amount) = pool_state.vault_amount_without_fee()?;
     let results = CurveCalculator::lp_tokens_to_trading_tokens(
+        pool_state.curve_type()?,
         u128::from(lp_token_amount),
         u128::from(pool_state.lp_supply),
         u128::from(total_token_0_amount),
@@ -234,24 +338,20 @@ pub fn deposit_to_gamma_pool(
         .token_1_deposited
         .checked_add(u128::from(token_1_amount))
         .ok_or(GammaError::MathOverflow)?;
+    user_pool_liquidity.update_fees_earned(pool_state)?;
+    user_pool_liquidity.update_lp_tokens_changed_at()?;
     user_pool_liquidity.lp_tokens_owned = user_pool_liquidity
         .lp_tokens_owned
         .checked_add(u128::from(lp_token_amount))
         .ok_or(GammaError::MathOverflow)?;
     pool_state.recent_epoch = Clock::get()?.epoch;
 
-    if let Some(user_pool_liquidity_partner) = user_pool_liquidity.partner {
-        let mut pool_state_partners = pool_state.partners;
-        let partner: Option<&mut crate::states::PartnerInfo> = pool_state_partners
-            .iter_mut()
-            .find(|p| PartnerType::new(p.partner_id) == user_pool_liquidity_partner);
-        if let Some(partner) = partner {
-            partner.lp_token_linked_with_partner = partner
-                .lp_token_linked_with_partner
-                .checked_add(lp_token_amount)
-                .ok_or(GammaError::MathOverflow)?;
-        }
-        pool_state.partners = pool_state_partners;
-    }
+    update_partner_lp_tokens(
+        pool_state,
+        accounts.pool_partners.as_deref_mut(),
+        user_pool_liquidity.partner,
+        lp_token_amount,
+        0,
+    )?;
     Ok(())
 }
@@ -0,0 +1,403 @@
+use crate::{
+    curve::{CurveCalculator, RoundDirection, TradeDirection},
+    error::GammaError,
+    fees::FeePath,
+    instructions::{settle_rewards, BaseInputSwap},
+    states::{
+        oracle, update_partner_lp_tokens, AmmConfig, LpChangeEvent, ObservationState,
+        ObservationsMut, PoolPartners, PoolState, PoolStatusBitIndex, UserPoolLiquidity,
+        POOL_PARTNERS_SEED, USER_POOL_LIQUIDITY_SEED,
+    },
+    utils::{get_transfer_fee, get_transfer_inverse_fee, transfer_from_user_to_pool_vault},
+};
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+#[derive(Accounts)]
+pub struct DepositSingleSided<'info> {
+    /// Owner of the liquidity provided
+    pub owner: Signer<'info>,
+
+    /// CHECK: pool vault authority
+    #[account(
+        seeds = [
+            crate::AUTH_SEED.as_bytes(),
+        ],
+        bump,
+    )]
+    pub authority: UncheckedAccount<'info>,
+
+    /// The factory state to read protocol fees
+    #[account(address = pool_state.load()?.amm_config)]
+    pub amm_config: Box<Account<'info, AmmConfig>>,
+
+    /// Pool state the owner is depositing into
+    #[account(mut)]
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    #[account(
+        mut,
+        seeds = [
+            USER_POOL_LIQUIDITY_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+            owner.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub user_pool_liquidity: Account<'info, UserPoolLiquidity>,
+
+    /// The owner's token account to deposit the input token from
+    #[account(
+        mut,
+        token::mint = input_vault.mint,
+        token::authority = owner
+    )]
+    pub input_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The vault token account for input token
+    #[account(
+        mut,
+        constraint = input_vault.key() == pool_state.load()?.token_0_vault || input_vault.key() == pool_state.load()?.token_1_vault
+    )]
+    pub input_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The vault token account for the other token of the pool, nothing is transferred out of it
+    #[account(
+        constraint = output_vault.key() == pool_state.load()?.token_0_vault || output_vault.key() == pool_state.load()?.token_1_vault
+    )]
+    pub output_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// SPL program for input token transfers
+    pub input_token_program: Interface<'info, TokenInterface>,
+
+    /// The mint of input token
+    #[account(
+        address = input_vault.mint
+    )]
+    pub input_token_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// The program account for the most recent oracle observation
+    #[account(mut, address = pool_state.load()?.observation_key)]
+    pub observation_state: AccountLoader<'info, ObservationState>,
+
+    /// The partners of the pool, required when the user is linked with a partner, see `update_partner_lp_tokens`
+    #[account(
+        mut,
+        seeds = [
+            POOL_PARTNERS_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub pool_partners: Option<Account<'info, PoolPartners>>,
+}
+
+/// Deposits a single token into the pool.
+/// Part of `amount_in` is swapped through the curve of the pool, charging the dynamic fee,
+/// and the rest is deposited together with the swapped tokens.
+/// The swapped tokens never leave the pool vault, only the input token is transferred.
+/// The remaining accounts are the active rewards of the pool, see `settle_rewards`.
+pub fn deposit_single_sided<'c, 'info>(
+    ctx: Context<'_, '_, 'c, 'info, DepositSingleSided<'info>>,
+    amount_in: u64,
+    minimum_lp_token_amount: u64,
+) -> Result<()>
+where
+    'c: 'info,
+{
+    require_gt!(amount_in, 0);
+    let block_timestamp = Clock::get()?.unix_timestamp as u64;
+    let pool_id = ctx.accounts.pool_state.key();
+    let pool_state = &mut ctx.accounts.pool_state.load_mut()?;
+    if !pool_state.get_status_by_bit(PoolStatusBitIndex::Deposit)
+        || !pool_state.get_status_by_bit(PoolStatusBitIndex::Swap)
+        || block_timestamp < pool_state.open_time
+    {
+        return err!(GammaError::NotApproved);
+    }
+
+    let (token_0_price_x64_before_swap, token_1_price_x64_before_swap) =
+        pool_state.token_price_x32()?;
+
+    let (trade_direction, total_input_token_amount, total_output_token_amount) =
+        if ctx.accounts.input_vault.key() == pool_state.token_0_vault
+            && ctx.accounts.output_vault.key() == pool_state.token_1_vault
+        {
+            let (total_input_token_amount, total_output_token_amount) =
+                pool_state.vault_amount_without_fee()?;
+            (
+                TradeDirection::ZeroForOne,
+                total_input_token_amount,
+                total_output_token_amount,
+            )
+        } else if ctx.accounts.input_vault.key() == pool_state.token_1_vault
+            && ctx.accounts.output_vault.key() == pool_state.token_0_vault
+        {
+            let (total_output_token_amount, total_input_token_amount) =
+                pool_state.vault_amount_without_fee()?;
+            (
+                TradeDirection::OneForZero,
+                total_input_token_amount,
+                total_output_token_amount,
+            )
+        } else {
+            return err!(GammaError::InvalidVault);
+        };
+
+    let transfer_fee =
+        get_transfer_fee(&ctx.accounts.input_token_mint.to_account_info(), amount_in)?;
+    // Take transfer fees into account for actual amount transferred in
+    let actual_amount_in = amount_in.saturating_sub(transfer_fee);
+    require_gt!(actual_amount_in, 0);
+
+    let mut observation_state = ObservationsMut::load(&ctx.accounts.observation_state)?;
+
+    let amount_to_swap = CurveCalculator::single_sided_deposit_swap_amount(
+        u128::from(actual_amount_in),
+        u128::from(total_input_token_amount),
+        u128::from(total_output_token_amount),
+        trade_direction,
+        &ctx.accounts.amm_config,
+        &pool_state,
+        block_timestamp,
+        &observation_state.observations(),
+        FeePath::Standard,
+    )?;
+    if amount_to_swap == 0 {
+        return err!(GammaError::ZeroTradingTokens);
+    }
+
+    let result = match CurveCalculator::swap_base_input(
+        amount_to_swap,
+        u128::from(total_input_token_amount),
+        u128::from(total_output_token_amount),
+        trade_direction,
+        &ctx.accounts.amm_config,
+        &pool_state,
+        block_timestamp,
+        &observation_state.observations(),
+        FeePath::Standard,
+    ) {
+        Ok(value) => value,
+        Err(_) => return err!(GammaError::ZeroTradingTokens),
+    };
+    CurveCalculator::check_invariant(
+        u128::from(total_input_token_amount),
+        u128::from(total_output_token_amount),
+        result
+            .new_swap_source_amount
+            .checked_sub(result.dynamic_fee)
+            .ok_or(GammaError::MathOverflow)?,
+        result.new_swap_destination_amount,
+        trade_direction,
+        &pool_state,
+    )?;
+
+    let source_amount_swapped =
+        u64::try_from(result.source_amount_swapped).or(err!(GammaError::MathOverflow))?;
+    let destination_amount_swapped =
+        u64::try_from(result.destination_amount_swapped).or(err!(GammaError::MathOverflow))?;
+    let swap = BaseInputSwap {
+        amount_in: source_amount_swapped,
+        // The swap is settled inside the pool, the transfer fee is reported with the deposit
+        transfer_fee: 0,
+        minimum_amount_out: 0,
+        trade_direction,
+        total_input_token_amount,
+        total_output_token_amount,
+        token_0_price_x32: token_0_price_x64_before_swap,
+        token_1_price_x32: token_1_price_x64_before_swap,
+        fee_path: FeePath::Standard,
+        result,
+    };
+    swap.accrue(pool_state, 0, 0)?;
+    swap.emit_event(
+        pool_id,
+        ctx.accounts.input_vault.mint,
+        ctx.accounts.output_vault.mint,
+        0,
+    )?;
+
+    // Reserves after the swap, the protocol, fund and creator fees are not part of the reserves
+    let (token_0_vault_after_swap, token_1_vault_after_swap) =
+        pool_state.vault_amount_without_fee()?;
+    let input_amount_available = actual_amount_in
+        .checked_sub(source_amount_swapped)
+        .ok_or(GammaError::MathOverflow)?;
+
+    let (token_0_available, token_1_available) = match trade_direction {
+        TradeDirection::ZeroForOne => (input_amount_available, destination_amount_swapped),
+        TradeDirection::OneForZero => (destination_amount_swapped, input_amount_available),
+    };
+
+    let curve_type = pool_state.curve_type()?;
+    let lp_token_amount = CurveCalculator::token_0_to_lp_tokens(
+        curve_type,
+        u128::from(token_0_available),
+        u128::from(token_0_vault_after_swap),
+        u128::from(pool_state.lp_supply),
+        RoundDirection::Floor,
+    )
+    .ok_or(GammaError::ZeroTradingTokens)?
+    .min(
+        CurveCalculator::token_1_to_lp_tokens(
+            curve_type,
+            u128::from(token_1_available),
+            u128::from(token_1_vault_after_swap),
+            u128::from(pool_state.lp_supply),
+            RoundDirection::Floor,
+        )
+        .ok_or(GammaError::ZeroTradingTokens)?,
+    );
+    let lp_token_amount = u64::try_from(lp_token_amount).or(err!(GammaError::MathOverflow))?;
+    require_gt!(lp_token_amount, 0, GammaError::ZeroTradingTokens);
+    require_gte!(
+        lp_token_amount,
+        minimum_lp_token_amount,
+        GammaError::ExceededSlippage
+    );
+    settle_rewards(
+        ctx.remaining_accounts,
+        pool_id,
+        pool_state,
+        &ctx.accounts.user_pool_liquidity,
+        ctx.accounts
+            .user_pool_liquidity
+            .lp_tokens_owned
+            .checked_add(u128::from(lp_token_amount))
+            .ok_or(GammaError::MathOverflow)?,
+    )?;
+
+    let results = CurveCalculator::lp_tokens_to_trading_tokens(
+        curve_type,
+        u128::from(lp_token_amount),
+        u128::from(pool_state.lp_supply),
+        u128::from(token_0_vault_after_swap),
+        u128::from(token_1_vault_after_swap),
+        RoundDirection::Ceiling,
+    )
+    .ok_or(GammaError::ZeroTradingTokens)?;
+    let token_0_amount =
+        u64::try_from(results.token_0_amount).map_err(|_| GammaError::MathOverflow)?;
+    let token_1_amount =
+        u64::try_from(results.token_1_amount).map_err(|_| GammaError::MathOverflow)?;
+    if token_0_amount == 0 || token_1_amount == 0 {
+        return err!(GammaError::ZeroTradingTokens);
+    }
+    require_gte!(
+        token_0_available,
+        token_0_amount,
+        GammaError::ExceededSlippage
+    );
+    require_gte!(
+        token_1_available,
+        token_1_amount,
+        GammaError::ExceededSlippage
+    );
+
+    let input_amount_deposited = match trade_direction {
+        TradeDirection::ZeroForOne => token_0_amount,
+        TradeDirection::OneForZero => token_1_amount,
+    };
+    // Only the input tokens that are swapped or deposited are transferred, the rest stays with the owner
+    let input_amount_used = source_amount_swapped
+        .checked_add(input_amount_deposited)
+        .ok_or(GammaError::MathOverflow)?;
+    let input_transfer_fee = get_transfer_inverse_fee(
+        &ctx.accounts.input_token_mint.to_account_info(),
+        input_amount_used,
+    )?;
+    let input_transfer_amount = input_amount_used
+        .checked_add(input_transfer_fee)
+        .ok_or(GammaError::MathOverflow)?;
+    require_gte!(
+        amount_in,
+        input_transfer_amount,
+        GammaError::ExceededSlippage
+    );
+
+    let (token_0_transfer_fee, token_1_transfer_fee) = match trade_direction {
+        TradeDirection::ZeroForOne => (input_transfer_fee, 0),
+        TradeDirection::OneForZero => (0, input_transfer_fee),
+    };
+    emit!(LpChangeEvent {
+        pool_id,
+        lp_amount_before: pool_state.lp_supply,
+        token_0_vault_before: token_0_vault_after_swap,
+        token_1_vault_before: token_1_vault_after_swap,
+        token_0_amount,
+        token_1_amount,
+        token_0_transfer_fee,
+        token_1_transfer_fee,
+        change_type: 0
+    });
+
+    // The swapped tokens never leave the vault, the ones not needed for the deposit stay in the pool
+    let (token_0_deposited, token_1_deposited) = match trade_direction {
+        TradeDirection::ZeroForOne => (input_amount_deposited, destination_amount_swapped),
+        TradeDirection::OneForZero => (destination_amount_swapped, input_amount_deposited),
+    };
+    pool_state.token_0_vault_amount = token_0_vault_after_swap
+        .checked_add(token_0_deposited)
+        .ok_or(GammaError::MathOverflow)?;
+    pool_state.token_1_vault_amount = token_1_vault_after_swap
+        .checked_add(token_1_deposited)
+        .ok_or(GammaError::MathOverflow)?;
+
+    pool_state.lp_supply = pool_state
+        .lp_supply
+        .checked_add(lp_token_amount)
+        .ok_or(GammaError::MathOverflow)?;
+    let user_pool_liquidity = &mut ctx.accounts.user_pool_liquidity;
+    user_pool_liquidity.token_0_deposited = user_pool_liquidity
+        .token_0_deposited
+        .checked_add(u128::from(token_0_amount))
+        .ok_or(GammaError::MathOverflow)?;
+    user_pool_liquidity.token_1_deposited = user_pool_liquidity
+        .token_1_deposited
+        .checked_add(u128::from(token_1_amount))
+        .ok_or(GammaError::MathOverflow)?;
+    user_pool_liquidity.update_fees_earned(pool_state)?;
+    user_pool_liquidity.update_lp_tokens_changed_at()?;
+    user_pool_liquidity.lp_tokens_owned = user_pool_liquidity
+        .lp_tokens_owned
+        .checked_add(u128::from(lp_token_amount))
+        .ok_or(GammaError::MathOverflow)?;
+
+    update_partner_lp_tokens(
+        pool_state,
+        ctx.accounts.pool_partners.as_deref_mut(),
+        user_pool_liquidity.partner,
+        lp_token_amount,
+        0,
+    )?;
+
+    #[cfg(feature = "enable-log")]
+    msg!(
+        "amount_to_swap:{}, destination_amount_swapped:{}, lp_token_amount:{}",
+        amount_to_swap,
+        destination_amount_swapped,
+        lp_token_amount
+    );
+
+    transfer_from_user_to_pool_vault(
+        ctx.accounts.owner.to_account_info(),
+        ctx.accounts.input_token_account.to_account_info(),
+        ctx.accounts.input_vault.to_account_info(),
+        ctx.accounts.input_token_mint.to_account_info(),
+        ctx.accounts.input_token_program.to_account_info(),
+        input_transfer_amount,
+        ctx.accounts.input_token_mint.decimals,
+    )?;
+
+    observation_state.update(
+        oracle::block_timestamp()?,
+        u64::from(pool_state.observation_update_duration),
+        swap.token_0_price_x32,
+        swap.token_1_price_x32,
+    )?;
+
+    pool_state.recent_epoch = Clock::get()?.epoch;
+
+    Ok(())
+}
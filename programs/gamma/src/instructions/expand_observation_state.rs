@@ -0,0 +1,78 @@
+use crate::error::GammaError;
+use crate::states::{Observation, ObservationState, Observations, PoolState, MAX_OBSERVATION_NUM};
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::entrypoint::MAX_PERMITTED_DATA_INCREASE;
+use anchor_lang::system_program;
+
+#[derive(Accounts)]
+pub struct ExpandObservationState<'info> {
+    /// Pays the rent of the expanded account, anyone can expand the observations of a pool
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// The program account of the pool
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    /// The program account for the oracle observations of the pool
+    #[account(mut, address = pool_state.load()?.observation_key)]
+    pub observation_state: AccountLoader<'info, ObservationState>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Grows the observation buffer of the pool by `additional_observations`, up to `MAX_OBSERVATION_NUM`.
+/// An account can only grow by `MAX_PERMITTED_DATA_INCREASE` bytes per instruction.
+pub fn expand_observation_state(
+    ctx: Context<ExpandObservationState>,
+    additional_observations: u16,
+) -> Result<()> {
+    require_gt!(additional_observations, 0);
+    let account_info = ctx.accounts.observation_state.to_account_info();
+    let capacity = Observations::from_account_data(&account_info.try_borrow_data()?)?.capacity();
+    let new_capacity = capacity
+        .checked_add(usize::from(additional_observations))
+        .ok_or(GammaError::MathOverflow)?;
+    require_gte!(
+        MAX_OBSERVATION_NUM,
+        new_capacity,
+        GammaError::InvalidObservationCapacity
+    );
+    let additional_len = usize::from(additional_observations)
+        .checked_mul(Observation::LEN)
+        .ok_or(GammaError::MathOverflow)?;
+    require_gte!(
+        MAX_PERMITTED_DATA_INCREASE,
+        additional_len,
+        GammaError::InvalidObservationCapacity
+    );
+    let new_len = account_info
+        .data_len()
+        .checked_add(additional_len)
+        .ok_or(GammaError::MathOverflow)?;
+
+    let rent_lamports = Rent::get()?
+        .minimum_balance(new_len)
+        .saturating_sub(account_info.lamports());
+    if rent_lamports > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.payer.to_account_info(),
+                    to: account_info.clone(),
+                },
+            ),
+            rent_lamports,
+        )?;
+    }
+    // New observations are zeroed, they are skipped until the ring buffer reaches them
+    account_info.realloc(new_len, true)?;
+
+    #[cfg(feature = "enable-log")]
+    msg!(
+        "observation capacity:{}, new capacity:{}",
+        capacity,
+        new_capacity
+    );
+    Ok(())
+}
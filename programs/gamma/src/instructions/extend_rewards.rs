@@ -0,0 +1,53 @@
+use crate::{
+    error::GammaError,
+    instructions::update_reward_info,
+    states::{PoolState, RewardInfo},
+};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct ExtendRewards<'info> {
+    /// The provider of the rewards
+    #[account(address = reward_info.rewarded_by @ GammaError::InvalidOwner)]
+    pub reward_provider: Signer<'info>,
+
+    #[account(mut)]
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    #[account(
+        mut,
+        seeds = [
+            crate::REWARD_INFO_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+            reward_info.start_at.to_le_bytes().as_ref(),
+            reward_info.mint.as_ref(),
+        ],
+        bump,
+    )]
+    pub reward_info: Account<'info, RewardInfo>,
+}
+
+/// Postpones the end of a reward that has not ended, the rewards not released yet
+/// are released until the new end time.
+pub fn extend_rewards(ctx: Context<ExtendRewards>, end_time: u64) -> Result<()> {
+    let current_time = Clock::get()?.unix_timestamp as u64;
+    let pool_state = &mut ctx.accounts.pool_state.load_mut()?;
+    let reward_lp_supply = pool_state.reward_lp_supply()?;
+    let reward_info_key = ctx.accounts.reward_info.key();
+    update_reward_info(
+        &mut ctx.accounts.reward_info,
+        reward_info_key,
+        pool_state,
+        reward_lp_supply,
+        current_time,
+    )?;
+
+    let reward_info = &mut ctx.accounts.reward_info;
+    if reward_info.is_ended() || end_time <= reward_info.end_rewards_at {
+        return err!(GammaError::InvalidRewardTime);
+    }
+    reward_info.end_rewards_at = end_time;
+    reward_info.validate_reward_rate(current_time)?;
+
+    Ok(())
+}
@@ -0,0 +1,239 @@
+use crate::error::GammaError;
+use crate::fees::{ceil_div, StaticFee, FEE_RATE_DENOMINATOR_VALUE};
+use crate::states::{AmmConfig, FlashLoanEvent, PoolState, PoolStatusBitIndex};
+use crate::utils::token::*;
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::sysvar::instructions::{
+    load_current_index_checked, load_instruction_at_checked, ID as INSTRUCTION_SYSVAR_ID,
+};
+use anchor_lang::Discriminator;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+/// Positions of `pool_state` and `token_vault` in the accounts of `FlashLoan`,
+/// a loan and its repayment are matched with them.
+const POOL_STATE_ACCOUNT_INDEX: usize = 3;
+const TOKEN_VAULT_ACCOUNT_INDEX: usize = 4;
+
+/// Accounts of both `flash_loan` and `flash_repay`.
+#[derive(Accounts)]
+pub struct FlashLoan<'info> {
+    /// The user borrowing or repaying the tokens
+    pub payer: Signer<'info>,
+
+    /// CHECK: pool vault authority
+    #[account(
+        seeds = [
+            crate::AUTH_SEED.as_bytes(),
+        ],
+        bump,
+    )]
+    pub authority: UncheckedAccount<'info>,
+
+    /// The factory state to read the flash loan and protocol fees
+    #[account(address = pool_state.load()?.amm_config)]
+    pub amm_config: Box<Account<'info, AmmConfig>>,
+
+    /// The program account of the pool lending the tokens
+    #[account(mut)]
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    /// The vault token account of the borrowed token
+    #[account(
+        mut,
+        constraint = token_vault.key() == pool_state.load()?.token_0_vault || token_vault.key() == pool_state.load()?.token_1_vault
+    )]
+    pub token_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The user token account receiving the loan and paying it back
+    #[account(mut)]
+    pub token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// SPL program for the borrowed token transfers
+    pub token_program: Interface<'info, TokenInterface>,
+
+    /// The mint of the borrowed token
+    #[account(
+        address = token_vault.mint
+    )]
+    pub token_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(address = INSTRUCTION_SYSVAR_ID )]
+    /// CHECK: The native instructions sysvar
+    pub instruction_sysvar_account: UncheckedAccount<'info>,
+}
+
+/// Lends `amount` of the vault to the user, a `flash_repay` with the index of this instruction
+/// and the same pool and vault has to come later in the transaction.
+pub fn flash_loan(ctx: Context<FlashLoan>, amount: u64) -> Result<()> {
+    require_gt!(amount, 0);
+    let pool_state = ctx.accounts.pool_state.load()?;
+    if !pool_state.get_status_by_bit(PoolStatusBitIndex::Swap)
+        || ctx.accounts.amm_config.flash_loan_fee_rate == 0
+    {
+        return err!(GammaError::NotApproved);
+    }
+
+    let instruction_sysvar = ctx.accounts.instruction_sysvar_account.to_account_info();
+    let current_index = load_current_index_checked(&instruction_sysvar)?;
+    // Only top level instructions are visible in the sysvar, a program calling us through cpi
+    // could otherwise point us to a repayment it never executes.
+    let current_instruction =
+        load_instruction_at_checked(usize::from(current_index), &instruction_sysvar)?;
+    require_keys_eq!(
+        current_instruction.program_id,
+        crate::id(),
+        GammaError::InvalidFlashLoan
+    );
+
+    let mut index = usize::from(current_index) + 1;
+    let mut is_repaid = false;
+    while let Ok(instruction) = load_instruction_at_checked(index, &instruction_sysvar) {
+        if flash_repay_loan_index(&instruction) == Some(current_index)
+            && is_same_loan(&instruction, &ctx.accounts)
+        {
+            is_repaid = true;
+            break;
+        }
+        index += 1;
+    }
+    require!(is_repaid, GammaError::InvalidFlashLoan);
+
+    transfer_from_pool_vault_to_user(
+        ctx.accounts.authority.to_account_info(),
+        ctx.accounts.token_vault.to_account_info(),
+        ctx.accounts.token_account.to_account_info(),
+        ctx.accounts.token_mint.to_account_info(),
+        ctx.accounts.token_program.to_account_info(),
+        amount,
+        ctx.accounts.token_mint.decimals,
+        &[&[crate::AUTH_SEED.as_bytes(), &[pool_state.auth_bump]]],
+    )
+}
+
+/// Pays back the loan of the `flash_loan` instruction at `flash_loan_instruction_index` with the flash loan fee.
+/// The protocol share of the fee goes to the protocol fees, the rest is added to the vault amount of the liquidity providers.
+pub fn flash_repay(ctx: Context<FlashLoan>, flash_loan_instruction_index: u16) -> Result<()> {
+    let instruction_sysvar = ctx.accounts.instruction_sysvar_account.to_account_info();
+    let current_index = load_current_index_checked(&instruction_sysvar)?;
+    require_gt!(
+        current_index,
+        flash_loan_instruction_index,
+        GammaError::InvalidFlashLoan
+    );
+    let loan_instruction = load_instruction_at_checked(
+        usize::from(flash_loan_instruction_index),
+        &instruction_sysvar,
+    )?;
+    let amount = flash_loan_amount(&loan_instruction).ok_or(GammaError::InvalidFlashLoan)?;
+    require!(
+        is_same_loan(&loan_instruction, &ctx.accounts),
+        GammaError::InvalidFlashLoan
+    );
+
+    let amm_config = &ctx.accounts.amm_config;
+    let protocol_fee_rate = ctx
+        .accounts
+        .pool_state
+        .load()?
+        .protocol_fee_rate(amm_config);
+    let fee = ceil_div(
+        u128::from(amount),
+        u128::from(amm_config.flash_loan_fee_rate),
+        u128::from(FEE_RATE_DENOMINATOR_VALUE),
+    )
+    .ok_or(GammaError::MathOverflow)?;
+    let protocol_fee =
+        StaticFee::protocol_fee(fee, protocol_fee_rate).ok_or(GammaError::MathOverflow)?;
+    let fee = u64::try_from(fee).or(err!(GammaError::MathOverflow))?;
+    let protocol_fee = u64::try_from(protocol_fee).or(err!(GammaError::MathOverflow))?;
+    let lp_fee = fee
+        .checked_sub(protocol_fee)
+        .ok_or(GammaError::MathOverflow)?;
+
+    // The vault has to receive the loan and the fee, transfer fees are paid on top of it
+    let repay_amount = amount.checked_add(fee).ok_or(GammaError::MathOverflow)?;
+    let transfer_fee =
+        get_transfer_inverse_fee(&ctx.accounts.token_mint.to_account_info(), repay_amount)?;
+    transfer_from_user_to_pool_vault(
+        ctx.accounts.payer.to_account_info(),
+        ctx.accounts.token_account.to_account_info(),
+        ctx.accounts.token_vault.to_account_info(),
+        ctx.accounts.token_mint.to_account_info(),
+        ctx.accounts.token_program.to_account_info(),
+        repay_amount
+            .checked_add(transfer_fee)
+            .ok_or(GammaError::MathOverflow)?,
+        ctx.accounts.token_mint.decimals,
+    )?;
+
+    let pool_id = ctx.accounts.pool_state.key();
+    let pool_state = &mut ctx.accounts.pool_state.load_mut()?;
+    if ctx.accounts.token_vault.key() == pool_state.token_0_vault {
+        pool_state.protocol_fees_token_0 = pool_state
+            .protocol_fees_token_0
+            .checked_add(protocol_fee)
+            .ok_or(GammaError::MathOverflow)?;
+        pool_state.token_0_vault_amount = pool_state
+            .token_0_vault_amount
+            .checked_add(lp_fee)
+            .ok_or(GammaError::MathOverflow)?;
+        pool_state.accrue_lp_fees(lp_fee, 0)?;
+        pool_state.accrue_protocol_fees(protocol_fee, 0)?;
+    } else {
+        pool_state.protocol_fees_token_1 = pool_state
+            .protocol_fees_token_1
+            .checked_add(protocol_fee)
+            .ok_or(GammaError::MathOverflow)?;
+        pool_state.token_1_vault_amount = pool_state
+            .token_1_vault_amount
+            .checked_add(lp_fee)
+            .ok_or(GammaError::MathOverflow)?;
+        pool_state.accrue_lp_fees(0, lp_fee)?;
+        pool_state.accrue_protocol_fees(0, protocol_fee)?;
+    }
+
+    emit!(FlashLoanEvent {
+        pool_id,
+        mint: ctx.accounts.token_mint.key(),
+        amount,
+        fee,
+        protocol_fee,
+    });
+
+    Ok(())
+}
+
+/// Returns the borrowed amount if the instruction is a `flash_loan` of this program.
+fn flash_loan_amount(instruction: &Instruction) -> Option<u64> {
+    if instruction.program_id != crate::id()
+        || !instruction
+            .data
+            .starts_with(&crate::instruction::FlashLoan::DISCRIMINATOR)
+    {
+        return None;
+    }
+    crate::instruction::FlashLoan::try_from_slice(&instruction.data[8..])
+        .ok()
+        .map(|args| args.amount)
+}
+
+/// Returns the index of the repaid loan if the instruction is a `flash_repay` of this program.
+fn flash_repay_loan_index(instruction: &Instruction) -> Option<u16> {
+    if instruction.program_id != crate::id()
+        || !instruction
+            .data
+            .starts_with(&crate::instruction::FlashRepay::DISCRIMINATOR)
+    {
+        return None;
+    }
+    crate::instruction::FlashRepay::try_from_slice(&instruction.data[8..])
+        .ok()
+        .map(|args| args.flash_loan_instruction_index)
+}
+
+fn is_same_loan(instruction: &Instruction, accounts: &FlashLoan) -> bool {
+    let account_key = |index: usize| instruction.accounts.get(index).map(|meta| meta.pubkey);
+    account_key(POOL_STATE_ACCOUNT_INDEX) == Some(accounts.pool_state.key())
+        && account_key(TOKEN_VAULT_ACCOUNT_INDEX) == Some(accounts.token_vault.key())
+}
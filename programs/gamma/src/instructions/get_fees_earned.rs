@@ -0,0 +1,25 @@
+use crate::{
+    error::GammaError,
+    states::{FeesEarned, PoolState, UserPoolLiquidity},
+};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct GetFeesEarned<'info> {
+    /// The program account of the pool
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    /// User pool liquidity account of the liquidity provider
+    #[account(
+        constraint = user_pool_liquidity.pool_state == pool_state.key() @ GammaError::InvalidInput,
+    )]
+    pub user_pool_liquidity: Account<'info, UserPoolLiquidity>,
+}
+
+/// Returns the trade fees earned by the lp tokens of the user up to now, see `UserPoolLiquidity::get_fees_earned`.
+pub fn get_fees_earned(ctx: Context<GetFeesEarned>) -> Result<FeesEarned> {
+    let pool_state = ctx.accounts.pool_state.load()?;
+    ctx.accounts
+        .user_pool_liquidity
+        .get_fees_earned(&pool_state)
+}
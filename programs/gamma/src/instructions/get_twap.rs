@@ -0,0 +1,19 @@
+use crate::states::{oracle, ObservationState, Observations, PoolState, Twap};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct GetTwap<'info> {
+    /// The program account of the pool to price
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    /// The program account for the oracle observations of the pool
+    #[account(address = pool_state.load()?.observation_key)]
+    pub observation_state: AccountLoader<'info, ObservationState>,
+}
+
+/// Returns the prices observed in the last `window_seconds`, see `Observations::get_twap`.
+pub fn get_twap(ctx: Context<GetTwap>, window_seconds: u64) -> Result<Twap> {
+    require_gt!(window_seconds, 0);
+    let data = ctx.accounts.observation_state.as_ref().try_borrow_data()?;
+    Observations::from_account_data(&data)?.get_twap(oracle::block_timestamp()?, window_seconds)
+}
@@ -1,6 +1,10 @@
 use anchor_lang::prelude::*;
 
-use crate::states::{PartnerType, PoolState, UserPoolLiquidity, USER_POOL_LIQUIDITY_SEED};
+use crate::error::GammaError;
+use crate::states::{
+    is_pool_state_partner, Partner, PartnerType, PoolPartners, PoolState, UserPoolLiquidity,
+    POOL_PARTNERS_SEED, USER_POOL_LIQUIDITY_SEED,
+};
 
 #[derive(Accounts)]
 pub struct InitUserPoolLiquidity<'info> {
@@ -26,15 +30,20 @@ pub struct InitUserPoolLiquidity<'info> {
     pub system_program: Program<'info, System>,
 }
 
+/// Creates the position of the user in the pool. AssetDash users are only linked with it while the pool
+/// tracks it in `PoolState::partners`, see `init_user_pool_liquidity_with_partner` for the other pools.
 pub fn init_user_pool_liquidity(
     ctx: Context<InitUserPoolLiquidity>,
     partner: Option<String>,
 ) -> Result<()> {
     let user_pool_liquidity = &mut ctx.accounts.user_pool_liquidity;
 
+    let pool_state = ctx.accounts.pool_state.load()?;
     let partner = match partner {
         Some(partner_value) => match partner_value.as_str() {
-            "AssetDash" => Some(PartnerType::AssetDash),
+            "AssetDash" if is_pool_state_partner(&pool_state, PartnerType::ASSET_DASH) => {
+                Some(PartnerType::ASSET_DASH)
+            }
             _ => None,
         },
         None => None,
@@ -49,3 +58,66 @@ pub fn init_user_pool_liquidity(
     );
     Ok(())
 }
+
+#[derive(Accounts)]
+pub struct InitUserPoolLiquidityWithPartner<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    #[account(
+        init,
+        seeds = [
+            USER_POOL_LIQUIDITY_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+            user.key().as_ref(),
+        ],
+        bump,
+        payer = user,
+        space = UserPoolLiquidity::LEN,
+    )]
+    pub user_pool_liquidity: Box<Account<'info, UserPoolLiquidity>>,
+
+    /// To create a new program account
+    pub system_program: Program<'info, System>,
+
+    /// The registered partner the user is coming from
+    pub partner: Account<'info, Partner>,
+
+    /// The partners of the pool, required unless the partner is tracked in `PoolState::partners`
+    #[account(
+        seeds = [
+            POOL_PARTNERS_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub pool_partners: Option<Account<'info, PoolPartners>>,
+}
+
+/// Creates the position of the user in the pool, linked with a registered `partner`.
+/// The partner must have been added to the pool with `add_pool_partner`, see `is_pool_state_partner` for AssetDash.
+pub fn init_user_pool_liquidity_with_partner(
+    ctx: Context<InitUserPoolLiquidityWithPartner>,
+) -> Result<()> {
+    let partner = ctx.accounts.partner.partner_type();
+    if !is_pool_state_partner(&ctx.accounts.pool_state.load()?, partner) {
+        let pool_partners = ctx
+            .accounts
+            .pool_partners
+            .as_ref()
+            .ok_or(GammaError::InvalidPartner)?;
+        require!(pool_partners.contains(partner), GammaError::InvalidPartner);
+    }
+    let current_time = Clock::get()?.unix_timestamp as u64;
+
+    let user_pool_liquidity = &mut ctx.accounts.user_pool_liquidity;
+    user_pool_liquidity.initialize(
+        ctx.accounts.user.key(),
+        ctx.accounts.pool_state.key(),
+        Some(partner),
+        current_time,
+    );
+    Ok(())
+}
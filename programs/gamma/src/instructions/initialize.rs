@@ -1,13 +1,14 @@
 use std::ops::Deref;
 
 use crate::{
-    curve::CurveCalculator,
+    curve::{CurveCalculator, CurveType},
     error::GammaError,
+    fees::FeeType,
     states::{
         AmmConfig, ObservationState, PoolState, UserPoolLiquidity, OBSERVATION_SEED, POOL_SEED,
         POOL_VAULT_SEED, USER_POOL_LIQUIDITY_SEED,
     },
-    utils::{create_token_account, is_supported_mint, transfer_from_user_to_pool_vault, U128},
+    utils::{create_token_account, is_supported_mint, transfer_from_user_to_pool_vault},
     LOCK_LP_AMOUNT,
 };
 use anchor_lang::{
@@ -164,6 +165,11 @@ pub fn initialize(
     mut open_time: u64,
     max_trade_fee_rate: u64,
     volatility_factor: u64,
+    curve_type: u8,
+    curve_parameter: u64,
+    fee_type: u8,
+    launch_fee_rate: u64,
+    launch_fee_duration: u64,
 ) -> Result<()> {
     if !(is_supported_mint(&ctx.accounts.token_0_mint)?
         && is_supported_mint(&ctx.accounts.token_1_mint)?)
@@ -174,6 +180,21 @@ pub fn initialize(
     if ctx.accounts.amm_config.disable_create_pool {
         return err!(GammaError::NotApproved);
     }
+    let curve_type = CurveType::new(curve_type)?;
+    curve_type.validate_curve_parameter(curve_parameter)?;
+    if curve_type == CurveType::StableSwap {
+        // StableSwap values both tokens 1:1 in raw amounts
+        require_eq!(
+            ctx.accounts.token_0_mint.decimals,
+            ctx.accounts.token_1_mint.decimals,
+            GammaError::NotSupportMint
+        );
+    }
+    let fee_type = FeeType::new(fee_type)?;
+    fee_type.validate_launch_fee(launch_fee_rate, launch_fee_duration)?;
+    let launch_fee_rate = u32::try_from(launch_fee_rate).or(err!(GammaError::InvalidFee))?;
+    let launch_fee_duration =
+        u32::try_from(launch_fee_duration).or(err!(GammaError::InvalidInput))?;
     let block_timestamp = clock::Clock::get()?.unix_timestamp as u64;
     if open_time <= block_timestamp {
         open_time = block_timestamp + 1;
@@ -259,11 +280,13 @@ pub fn initialize(
 
     CurveCalculator::validate_supply(token_0_vault.amount, token_1_vault.amount)?;
 
-    let liquidity = U128::from(token_0_vault.amount)
-        .checked_mul(token_1_vault.amount.into())
-        .ok_or(GammaError::MathOverflow)?
-        .integer_sqrt()
-        .as_u64();
+    let liquidity = u64::try_from(CurveCalculator::initial_lp_tokens(
+        curve_type,
+        curve_parameter,
+        u128::from(token_0_vault.amount),
+        u128::from(token_1_vault.amount),
+    )?)
+    .or(err!(GammaError::MathOverflow))?;
     #[cfg(feature = "enable-log")]
     msg!(
         "liquidity: {}, vault_0_amount: {}, vault_1_amount: {}",
@@ -306,6 +329,11 @@ pub fn initialize(
         open_time,
         max_trade_fee_rate,
         volatility_factor,
+        curve_type,
+        curve_parameter,
+        fee_type,
+        launch_fee_rate,
+        launch_fee_duration,
         ctx.accounts.creator.key(),
         ctx.accounts.amm_config.key(),
         ctx.accounts.token_0_vault.key(),
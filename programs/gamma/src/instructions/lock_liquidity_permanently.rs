@@ -0,0 +1,115 @@
+use crate::{
+    error::GammaError,
+    instructions::settle_rewards,
+    states::{
+        update_partner_lp_tokens, PermanentLock, PoolPartners, PoolState, UserPoolLiquidity,
+        PERMANENT_LOCK_SEED, POOL_PARTNERS_SEED, USER_POOL_LIQUIDITY_SEED,
+    },
+};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct LockLiquidityPermanently<'info> {
+    /// Owner of the liquidity provided, owner of the permanent lock
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(mut)]
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    /// User pool liquidity account
+    #[account(
+        mut,
+        seeds = [
+            USER_POOL_LIQUIDITY_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+            owner.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub user_pool_liquidity: Account<'info, UserPoolLiquidity>,
+
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = PermanentLock::LEN,
+        seeds = [
+            PERMANENT_LOCK_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+            owner.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub permanent_lock: Account<'info, PermanentLock>,
+
+    pub system_program: Program<'info, System>,
+
+    /// The partners of the pool, required when the user is linked with a partner, see `update_partner_lp_tokens`
+    #[account(
+        mut,
+        seeds = [
+            POOL_PARTNERS_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub pool_partners: Option<Account<'info, PoolPartners>>,
+}
+
+/// Moves pool tokens of `UserPoolLiquidity` into the permanent lock of the owner, they can never be withdrawn.
+/// The fees earned by the lock so far are kept in its fees owed.
+/// The remaining accounts are the active rewards of the pool, see `settle_rewards`.
+pub fn lock_liquidity_permanently<'c, 'info>(
+    ctx: Context<'_, '_, 'c, 'info, LockLiquidityPermanently<'info>>,
+    lp_token_amount: u64,
+) -> Result<()>
+where
+    'c: 'info,
+{
+    require_gt!(lp_token_amount, 0);
+    let pool_id = ctx.accounts.pool_state.key();
+    let pool_state = &mut ctx.accounts.pool_state.load_mut()?;
+    let user_pool_liquidity = &mut ctx.accounts.user_pool_liquidity;
+    user_pool_liquidity.check_unlocked_lp_tokens(lp_token_amount)?;
+
+    let permanent_lock = &mut ctx.accounts.permanent_lock;
+    if permanent_lock.owner == Pubkey::default() {
+        permanent_lock.initialize(ctx.accounts.owner.key(), pool_id, pool_state);
+    } else {
+        permanent_lock.update_fees_owed(pool_state)?;
+    }
+    permanent_lock.locked_lp_tokens = permanent_lock
+        .locked_lp_tokens
+        .checked_add(lp_token_amount)
+        .ok_or(GammaError::MathOverflow)?;
+
+    let lp_tokens_owned = user_pool_liquidity
+        .lp_tokens_owned
+        .checked_sub(u128::from(lp_token_amount))
+        .ok_or(GammaError::InvalidLpTokenAmount)?;
+    settle_rewards(
+        ctx.remaining_accounts,
+        pool_id,
+        pool_state,
+        user_pool_liquidity,
+        lp_tokens_owned,
+    )?;
+
+    user_pool_liquidity.update_fees_earned(pool_state)?;
+    user_pool_liquidity.update_lp_tokens_changed_at()?;
+    user_pool_liquidity.lp_tokens_owned = lp_tokens_owned;
+    pool_state.permanent_lp_supply = pool_state
+        .permanent_lp_supply
+        .checked_add(lp_token_amount)
+        .ok_or(GammaError::MathOverflow)?;
+
+    update_partner_lp_tokens(
+        pool_state,
+        ctx.accounts.pool_partners.as_deref_mut(),
+        user_pool_liquidity.partner,
+        0,
+        lp_token_amount,
+    )?;
+
+    Ok(())
+}
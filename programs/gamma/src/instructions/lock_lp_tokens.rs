@@ -0,0 +1,78 @@
+use crate::{
+    error::GammaError,
+    instructions::settle_rewards,
+    states::{PoolState, UserPoolLiquidity, USER_POOL_LIQUIDITY_SEED},
+};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct LockLpTokens<'info> {
+    /// Owner of the liquidity provided
+    pub owner: Signer<'info>,
+
+    #[account(mut)]
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    /// User pool liquidity account
+    #[account(
+        mut,
+        seeds = [
+            USER_POOL_LIQUIDITY_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+            owner.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub user_pool_liquidity: Account<'info, UserPoolLiquidity>,
+}
+
+/// Locks pool tokens of `UserPoolLiquidity` until `unlock_at`, a position has at most one lock.
+/// The locked pool tokens earn boosted rewards depending on the lock duration.
+/// The remaining accounts are the active rewards of the pool, see `settle_rewards`.
+pub fn lock_lp_tokens<'c, 'info>(
+    ctx: Context<'_, '_, 'c, 'info, LockLpTokens<'info>>,
+    lp_token_amount: u64,
+    unlock_at: u64,
+) -> Result<()>
+where
+    'c: 'info,
+{
+    require_gt!(lp_token_amount, 0);
+    let current_time = Clock::get()?.unix_timestamp as u64;
+    require_gt!(unlock_at, current_time, GammaError::InvalidLpLock);
+
+    let pool_id = ctx.accounts.pool_state.key();
+    let pool_state = &mut ctx.accounts.pool_state.load_mut()?;
+    let user_pool_liquidity = &mut ctx.accounts.user_pool_liquidity;
+    require_eq!(
+        user_pool_liquidity.locked_lp_tokens,
+        0,
+        GammaError::InvalidLpLock
+    );
+    require_gte!(
+        user_pool_liquidity.lp_tokens_owned,
+        u128::from(lp_token_amount),
+        GammaError::InvalidLpTokenAmount
+    );
+
+    user_pool_liquidity.locked_lp_tokens = lp_token_amount;
+    user_pool_liquidity.lp_unlock_at = unlock_at;
+    user_pool_liquidity.lp_lock_boost_rate =
+        UserPoolLiquidity::get_lp_lock_boost_rate(unlock_at - current_time);
+    user_pool_liquidity.update_lp_tokens_changed_at()?;
+
+    // The rewards are settled with the reward weight of the pool before the lock
+    settle_rewards(
+        ctx.remaining_accounts,
+        pool_id,
+        pool_state,
+        user_pool_liquidity,
+        user_pool_liquidity.lp_tokens_owned,
+    )?;
+    pool_state.lp_lock_boost_weight = pool_state
+        .lp_lock_boost_weight
+        .checked_add(user_pool_liquidity.lp_lock_boost_weight()?)
+        .ok_or(GammaError::MathOverflow)?;
+
+    Ok(())
+}
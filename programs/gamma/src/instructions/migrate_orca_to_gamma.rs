@@ -64,7 +64,6 @@ pub struct MigrateOrcaToGamma<'info> {
     /// Token program
     pub token_program: Program<'info, Token>,
     pub whirlpools_program: Program<'info, Whirlpools>,
-
 }
 
 pub fn migrate(ctx: Context<MigrateOrcaToGamma>, liquidity: u64) -> Result<()> {
@@ -110,7 +109,9 @@ pub fn migrate(ctx: Context<MigrateOrcaToGamma>, liquidity: u64) -> Result<()> {
         let transfer_fee =
             get_transfer_inverse_fee(&ctx.accounts.vault_0_mint.to_account_info(), token_0_amount)?;
         (
-            token_0_amount.checked_add(transfer_fee).ok_or(GammaError::MathOverflow)?,
+            token_0_amount
+                .checked_add(transfer_fee)
+                .ok_or(GammaError::MathOverflow)?,
             transfer_fee,
         )
     };
@@ -123,7 +124,9 @@ pub fn migrate(ctx: Context<MigrateOrcaToGamma>, liquidity: u64) -> Result<()> {
         let transfer_fee =
             get_transfer_inverse_fee(&ctx.accounts.vault_1_mint.to_account_info(), token_1_amount)?;
         (
-            token_1_amount.checked_add(transfer_fee).ok_or(GammaError::MathOverflow)?,
+            token_1_amount
+                .checked_add(transfer_fee)
+                .ok_or(GammaError::MathOverflow)?,
             transfer_fee,
         )
     };
@@ -186,7 +189,10 @@ pub fn migrate(ctx: Context<MigrateOrcaToGamma>, liquidity: u64) -> Result<()> {
         ctx.accounts.vault_1_mint.decimals,
     )?;
 
-    pool_state.lp_supply = pool_state.lp_supply.checked_add(lp_token_amount).ok_or(GammaError::MathOverflow)?;
+    pool_state.lp_supply = pool_state
+        .lp_supply
+        .checked_add(lp_token_amount)
+        .ok_or(GammaError::MathOverflow)?;
     let user_pool_liquidity = &mut ctx.accounts.user_pool_liquidity;
     user_pool_liquidity.token_0_deposited = user_pool_liquidity
         .token_0_deposited
@@ -196,6 +202,8 @@ pub fn migrate(ctx: Context<MigrateOrcaToGamma>, liquidity: u64) -> Result<()> {
         .token_1_deposited
         .checked_add(u128::from(transfer_token_1_amount))
         .ok_or(GammaError::MathOverflow)?;
+    user_pool_liquidity.update_fees_earned(pool_state)?;
+    user_pool_liquidity.update_lp_tokens_changed_at()?;
     user_pool_liquidity.lp_tokens_owned = user_pool_liquidity
         .lp_tokens_owned
         .checked_add(u128::from(lp_token_amount))
@@ -203,4 +211,4 @@ pub fn migrate(ctx: Context<MigrateOrcaToGamma>, liquidity: u64) -> Result<()> {
     pool_state.recent_epoch = Clock::get()?.epoch;
 
     Ok(())
-}
\ No newline at end of file
+}
@@ -0,0 +1,76 @@
+use crate::{
+    error::GammaError,
+    states::{PoolState, RewardInfo},
+};
+use anchor_lang::prelude::*;
+use anchor_lang::{system_program, Discriminator};
+
+#[derive(Accounts)]
+pub struct MigrateRewardInfo<'info> {
+    /// Pays the rent of the resized account, anyone can migrate a reward info
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(mut)]
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    /// CHECK: The reward info account, it can not be deserialized before the migration
+    #[account(mut, owner = crate::id())]
+    pub reward_info: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Grows a reward info created before the accumulator to `RewardInfo::LEN` and starts its accumulator,
+/// see `RewardInfo::migrate`. The reward is active until it ends, like the rewards created since.
+/// Reward infos that already have the current size are left unchanged.
+pub fn migrate_reward_info(ctx: Context<MigrateRewardInfo>) -> Result<()> {
+    let account_info = ctx.accounts.reward_info.to_account_info();
+    let len = account_info.data_len();
+    if len >= RewardInfo::LEN {
+        return Ok(());
+    }
+    require!(
+        account_info
+            .try_borrow_data()?
+            .starts_with(&RewardInfo::DISCRIMINATOR),
+        ErrorCode::AccountDiscriminatorMismatch
+    );
+
+    let rent_lamports = Rent::get()?
+        .minimum_balance(RewardInfo::LEN)
+        .saturating_sub(account_info.lamports());
+    if rent_lamports > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.payer.to_account_info(),
+                    to: account_info.clone(),
+                },
+            ),
+            rent_lamports,
+        )?;
+    }
+    account_info.realloc(RewardInfo::LEN, true)?;
+
+    let mut reward_info = RewardInfo::try_deserialize(&mut &account_info.try_borrow_data()?[..])?;
+    require_keys_eq!(
+        reward_info.pool,
+        ctx.accounts.pool_state.key(),
+        GammaError::InvalidInput
+    );
+    let pool_state = &mut ctx.accounts.pool_state.load_mut()?;
+    let current_time = Clock::get()?.unix_timestamp as u64;
+    if !reward_info.migrate(pool_state.reward_lp_supply()?, current_time)?
+        && !pool_state.is_active_reward_info(account_info.key())
+    {
+        // The reward must be settled whenever the lp tokens change until it ends
+        pool_state.add_active_reward_info(account_info.key())?;
+    }
+    reward_info.try_serialize(&mut &mut account_info.try_borrow_mut_data()?[..])?;
+
+    #[cfg(feature = "enable-log")]
+    msg!("reward info len:{}, new len:{}", len, RewardInfo::LEN);
+    Ok(())
+}
@@ -0,0 +1,121 @@
+use crate::{
+    error::GammaError,
+    states::{PoolState, RewardInfo, UserPoolLiquidity, UserRewardInfo, USER_POOL_LIQUIDITY_SEED},
+    USER_REWARD_INFO_SEED,
+};
+use anchor_lang::prelude::*;
+use anchor_lang::{system_program, Discriminator};
+
+#[derive(Accounts)]
+pub struct MigrateUserRewardInfo<'info> {
+    /// Pays the rent of the resized account, anyone can migrate a user reward info
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// CHECK: The user of the reward info, does not require any validation
+    pub user: UncheckedAccount<'info>,
+
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    /// The reward info must be migrated with `migrate_reward_info` first
+    #[account(
+        mut,
+        seeds = [
+            crate::REWARD_INFO_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+            reward_info.start_at.to_le_bytes().as_ref(),
+            reward_info.mint.as_ref(),
+        ],
+        bump,
+    )]
+    pub reward_info: Account<'info, RewardInfo>,
+
+    /// CHECK: The user reward info account, it can not be deserialized before the migration
+    #[account(
+        mut,
+        owner = crate::id(),
+        seeds = [
+            USER_REWARD_INFO_SEED.as_bytes(),
+            reward_info.key().as_ref(),
+            user.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub user_reward_info: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [
+            USER_POOL_LIQUIDITY_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+            user.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub user_pool_liquidity: Account<'info, UserPoolLiquidity>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Grows a user reward info created before the accumulator to `UserRewardInfo::LEN` and moves it to the accumulator,
+/// see `UserRewardInfo::migrate`. User reward infos that already have the current size are left unchanged.
+pub fn migrate_user_reward_info(ctx: Context<MigrateUserRewardInfo>) -> Result<()> {
+    let account_info = ctx.accounts.user_reward_info.to_account_info();
+    let len = account_info.data_len();
+    if len >= UserRewardInfo::LEN {
+        return Ok(());
+    }
+    require!(
+        account_info
+            .try_borrow_data()?
+            .starts_with(&UserRewardInfo::DISCRIMINATOR),
+        ErrorCode::AccountDiscriminatorMismatch
+    );
+
+    let rent_lamports = Rent::get()?
+        .minimum_balance(UserRewardInfo::LEN)
+        .saturating_sub(account_info.lamports());
+    if rent_lamports > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.payer.to_account_info(),
+                    to: account_info.clone(),
+                },
+            ),
+            rent_lamports,
+        )?;
+    }
+    account_info.realloc(UserRewardInfo::LEN, true)?;
+
+    let mut user_reward_info =
+        UserRewardInfo::try_deserialize(&mut &account_info.try_borrow_data()?[..])?;
+    let reward_info_key = ctx.accounts.reward_info.key();
+    require_keys_eq!(
+        user_reward_info.reward_info,
+        reward_info_key,
+        GammaError::InvalidInput
+    );
+    let pool_state = ctx.accounts.pool_state.load()?;
+    let user_pool_liquidity = &ctx.accounts.user_pool_liquidity;
+    user_reward_info.migrate(
+        &ctx.accounts.reward_info,
+        pool_state.is_active_reward_info(reward_info_key),
+        u64::try_from(user_pool_liquidity.reward_weight(user_pool_liquidity.lp_tokens_owned)?)
+            .map_err(|_| error!(GammaError::MathOverflow))?,
+    )?;
+    user_reward_info.try_serialize(&mut &mut account_info.try_borrow_mut_data()?[..])?;
+    let reward_info = &mut ctx.accounts.reward_info;
+    reward_info.user_reward_infos = reward_info
+        .user_reward_infos
+        .checked_add(1)
+        .ok_or(GammaError::MathOverflow)?;
+
+    #[cfg(feature = "enable-log")]
+    msg!(
+        "user reward info len:{}, new len:{}",
+        len,
+        UserRewardInfo::LEN
+    );
+    Ok(())
+}
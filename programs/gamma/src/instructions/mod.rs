@@ -1,27 +1,81 @@
 pub mod admin;
 pub mod calculate_rewards;
+pub mod claim_permanent_lock_fees;
 pub mod claim_rewards;
+pub mod close_reward_info;
+pub mod close_user_pool_liquidity;
+pub mod close_user_reward_info;
+pub mod collect_creator_fee;
+pub mod create_lp_mint;
 pub mod create_rewards;
 pub mod deposit;
+pub mod deposit_single_sided;
+pub mod expand_observation_state;
+pub mod extend_rewards;
+pub mod flash_loan;
+pub mod get_fees_earned;
+pub mod get_twap;
 pub mod init_user_pool_liquidity;
 pub mod initialize;
+pub mod lock_liquidity_permanently;
+pub mod lock_lp_tokens;
+pub mod migrate_reward_info;
+pub mod migrate_user_reward_info;
 // pub mod migrate_orca_to_gamma;
 // pub mod migrate_raydium_to_gamma;
+pub mod oracle_swap_base_input;
 pub mod rebalance;
+pub mod refund_unallocated_rewards;
+pub mod resize_pool_state;
+pub mod resize_user_pool_liquidity;
 pub mod swap_base_input;
 pub mod swap_base_output;
+pub mod swap_route;
+pub mod top_up_rewards;
+pub mod transfer_liquidity;
+pub mod unlock_lp_tokens;
+pub mod unwrap_lp_tokens;
 pub mod withdraw;
+pub mod withdraw_single_sided;
+pub mod wrap_lp_tokens;
 
 pub use admin::*;
 pub use deposit::*;
+pub use deposit_single_sided::*;
+pub use expand_observation_state::*;
+pub use extend_rewards::*;
+pub use flash_loan::*;
+pub use get_fees_earned::*;
+pub use get_twap::*;
 pub use init_user_pool_liquidity::*;
 pub use initialize::*;
+pub use lock_liquidity_permanently::*;
+pub use lock_lp_tokens::*;
+pub use migrate_reward_info::*;
+pub use migrate_user_reward_info::*;
 // pub use migrate_orca_to_gamma::*;
 // pub use migrate_raydium_to_gamma::*;
 pub use calculate_rewards::*;
+pub use claim_permanent_lock_fees::*;
 pub use claim_rewards::*;
+pub use close_reward_info::*;
+pub use close_user_pool_liquidity::*;
+pub use close_user_reward_info::*;
+pub use collect_creator_fee::*;
+pub use create_lp_mint::*;
 pub use create_rewards::*;
+pub use oracle_swap_base_input::*;
 pub use rebalance::*;
+pub use refund_unallocated_rewards::*;
+pub use resize_pool_state::*;
+pub use resize_user_pool_liquidity::*;
 pub use swap_base_input::*;
 pub use swap_base_output::*;
+pub use swap_route::*;
+pub use top_up_rewards::*;
+pub use transfer_liquidity::*;
+pub use unlock_lp_tokens::*;
+pub use unwrap_lp_tokens::*;
 pub use withdraw::*;
+pub use withdraw_single_sided::*;
+pub use wrap_lp_tokens::*;
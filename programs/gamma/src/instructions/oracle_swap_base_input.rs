@@ -0,0 +1,206 @@
+use super::swap_base_input::{settle_swap_base_input, BaseInputSwap, Swap};
+use crate::curve::calculator::CurveCalculator;
+use crate::curve::{OraclePrice, TradeDirection};
+use crate::error::GammaError;
+use crate::external::dflow_segmenter::is_invoked_by_segmenter;
+use crate::fees::FeePath;
+use crate::states::{ObservationsMut, PoolStatusBitIndex, PriceFeedConfig};
+use crate::utils::{swap_referral::extract_referral_info, token::get_transfer_fee};
+use crate::{decode_account_info, SwapRemainingAccounts};
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program;
+
+/// Accounts used for pricing oracle based swaps, they are expected after the accounts of `SwapRemainingAccounts`.
+pub struct OracleRemainingAccounts<'info> {
+    pub price_feed_config: AccountInfo<'info>,
+    pub token_0_price_update: AccountInfo<'info>,
+    pub token_1_price_update: Option<AccountInfo<'info>>,
+}
+
+impl<'info> OracleRemainingAccounts<'info> {
+    pub fn new(remaining_accounts: &[AccountInfo<'info>]) -> Result<Self> {
+        Ok(Self {
+            price_feed_config: decode_account_info(remaining_accounts, 4)
+                .ok_or(GammaError::InvalidPriceFeed)?,
+            token_0_price_update: decode_account_info(remaining_accounts, 5)
+                .ok_or(GammaError::InvalidPriceFeed)?,
+            token_1_price_update: decode_account_info(remaining_accounts, 6),
+        })
+    }
+}
+
+pub fn oracle_swap_base_input<'c, 'info>(
+    ctx: Context<'_, '_, 'c, 'info, Swap<'info>>,
+    amount_in: u64,
+    minimum_amount_out: u64,
+) -> Result<()> {
+    let swap_remaining_accounts = SwapRemainingAccounts::new(&ctx.remaining_accounts);
+    let referral_info = extract_referral_info(
+        ctx.accounts.input_token_mint.key(),
+        ctx.accounts.amm_config.referral_project,
+        &swap_remaining_accounts.referral_account,
+        &swap_remaining_accounts.referral_token_account,
+    )?;
+    let block_timestamp = solana_program::clock::Clock::get()?.unix_timestamp as u64;
+    let pool_id = ctx.accounts.pool_state.key();
+    let pool_state = &mut ctx.accounts.pool_state.load_mut()?;
+    if !pool_state.get_status_by_bit(PoolStatusBitIndex::Swap)
+        || block_timestamp < pool_state.open_time
+    {
+        return err!(GammaError::NotApproved);
+    }
+
+    let (token_0_price_x64_before_swap, token_1_price_x64_before_swap) =
+        if ctx.accounts.input_vault.key() == pool_state.token_0_vault
+            && ctx.accounts.output_vault.key() == pool_state.token_1_vault
+        {
+            pool_state.token_price_x32()?
+        } else if ctx.accounts.input_vault.key() == pool_state.token_1_vault
+            && ctx.accounts.output_vault.key() == pool_state.token_0_vault
+        {
+            pool_state.token_price_x32()?
+        } else {
+            return err!(GammaError::InvalidVault);
+        };
+
+    let transfer_fee =
+        get_transfer_fee(&ctx.accounts.input_token_mint.to_account_info(), amount_in)?;
+    // Take transfer fees into account for actual amount transferred in
+    let actual_amount_in = amount_in.saturating_sub(transfer_fee);
+    require_gt!(actual_amount_in, 0);
+
+    // Calculate the trade amounts
+    let (trade_direction, total_input_token_amount, total_output_token_amount) =
+        if ctx.accounts.input_vault.key() == pool_state.token_0_vault
+            && ctx.accounts.output_vault.key() == pool_state.token_1_vault
+        {
+            let (total_input_token_amount, total_output_token_amount) =
+                pool_state.vault_amount_without_fee()?;
+
+            (
+                TradeDirection::ZeroForOne,
+                total_input_token_amount,
+                total_output_token_amount,
+            )
+        } else if ctx.accounts.input_vault.key() == pool_state.token_1_vault
+            && ctx.accounts.output_vault.key() == pool_state.token_0_vault
+        {
+            let (total_output_token_amount, total_input_token_amount) =
+                pool_state.vault_amount_without_fee()?;
+
+            (
+                TradeDirection::OneForZero,
+                total_input_token_amount,
+                total_output_token_amount,
+            )
+        } else {
+            return err!(GammaError::InvalidVault);
+        };
+    // Price the trade with the oracle, the confidence interval is always applied against the trader.
+    let oracle_remaining_accounts = OracleRemainingAccounts::new(&ctx.remaining_accounts)?;
+    require_keys_eq!(
+        *oracle_remaining_accounts.price_feed_config.owner,
+        crate::id(),
+        GammaError::InvalidPriceFeed
+    );
+    let price_feed_config = PriceFeedConfig::try_deserialize(
+        &mut &oracle_remaining_accounts.price_feed_config.data.borrow()[..],
+    )?;
+    require_keys_eq!(
+        price_feed_config.pool_state,
+        pool_id,
+        GammaError::InvalidPriceFeed
+    );
+    let is_zero_for_one = trade_direction == TradeDirection::ZeroForOne;
+    let token_0_price = price_feed_config.get_oracle_price(
+        &oracle_remaining_accounts.token_0_price_update,
+        &price_feed_config.token_0_feed_id,
+        pool_state.mint_0_decimals,
+        block_timestamp,
+        !is_zero_for_one,
+    )?;
+    let token_1_price = if price_feed_config.has_token_1_feed() {
+        price_feed_config.get_oracle_price(
+            oracle_remaining_accounts
+                .token_1_price_update
+                .as_ref()
+                .ok_or(GammaError::InvalidPriceFeed)?,
+            &price_feed_config.token_1_feed_id,
+            pool_state.mint_1_decimals,
+            block_timestamp,
+            is_zero_for_one,
+        )?
+    } else {
+        // token_0 feed is quoted in whole units of token_1
+        OraclePrice {
+            price: 1,
+            exponent: -i32::from(pool_state.mint_1_decimals),
+        }
+    };
+    let (source_price, destination_price) = match trade_direction {
+        TradeDirection::ZeroForOne => (token_0_price, token_1_price),
+        TradeDirection::OneForZero => (token_1_price, token_0_price),
+    };
+
+    let mut observation_state = ObservationsMut::load(&ctx.accounts.observation_state)?;
+
+    let mut is_invoked_by_signed_segmenter = false;
+
+    if swap_remaining_accounts.registered_segmenter.is_some()
+        && swap_remaining_accounts.registry.is_some()
+    {
+        is_invoked_by_signed_segmenter = is_invoked_by_segmenter(
+            &swap_remaining_accounts.registry.as_ref().unwrap(),
+            &swap_remaining_accounts
+                .registered_segmenter
+                .as_ref()
+                .unwrap(),
+            &ctx.accounts.amm_config.segmenter_registry_programs(),
+        );
+    }
+    let fee_path = FeePath::swap(is_invoked_by_signed_segmenter);
+
+    let result = CurveCalculator::oracle_swap_base_input(
+        u128::from(actual_amount_in),
+        u128::from(total_input_token_amount),
+        u128::from(total_output_token_amount),
+        source_price,
+        destination_price,
+        trade_direction,
+        &ctx.accounts.amm_config,
+        &pool_state,
+        block_timestamp,
+        &observation_state.observations(),
+        fee_path,
+    )?;
+
+    #[cfg(feature = "enable-log")]
+    msg!(
+        "actual_amount_in:{} source_amount_swapped:{}, destination_amount_swapped:{}, dynamic_fee: {}, source_price:{:?}, destination_price:{:?}",
+        actual_amount_in,
+        result.source_amount_swapped,
+        result.destination_amount_swapped,
+        result.dynamic_fee,
+        source_price,
+        destination_price
+    );
+    settle_swap_base_input(
+        &ctx.accounts,
+        pool_id,
+        pool_state,
+        &mut observation_state,
+        referral_info,
+        BaseInputSwap {
+            amount_in,
+            transfer_fee,
+            minimum_amount_out,
+            trade_direction,
+            total_input_token_amount,
+            total_output_token_amount,
+            token_0_price_x32: token_0_price_x64_before_swap,
+            token_1_price_x32: token_1_price_x64_before_swap,
+            fee_path,
+            result,
+        },
+    )
+}
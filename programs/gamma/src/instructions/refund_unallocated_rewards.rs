@@ -0,0 +1,121 @@
+use crate::{
+    error::GammaError,
+    instructions::update_reward_info,
+    states::{PoolState, RewardInfo},
+    utils::transfer_from_pool_vault_to_user,
+    REWARD_VAULT_SEED,
+};
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    token::Token,
+    token_interface::{Mint, Token2022, TokenAccount},
+};
+
+#[derive(Accounts)]
+pub struct RefundUnallocatedRewards<'info> {
+    /// The provider of the rewards, receives the unallocated rewards
+    #[account(
+        mut,
+        address = reward_info.rewarded_by @ GammaError::InvalidOwner
+    )]
+    pub reward_provider: Signer<'info>,
+
+    /// CHECK: pool vault authority
+    #[account(
+        seeds = [
+            crate::AUTH_SEED.as_bytes(),
+        ],
+        bump,
+    )]
+    pub authority: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    #[account(
+        mut,
+        seeds = [
+            crate::REWARD_INFO_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+            reward_info.start_at.to_le_bytes().as_ref(),
+            reward_info.mint.as_ref(),
+        ],
+        bump,
+    )]
+    pub reward_info: Account<'info, RewardInfo>,
+
+    #[account(
+        mut,
+        token::mint = reward_mint,
+        token::authority = reward_provider,
+    )]
+    pub reward_providers_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [
+            REWARD_VAULT_SEED.as_bytes(),
+            reward_info.key().as_ref(),
+        ],
+        bump,
+        token::mint = reward_mint,
+        token::authority = authority,
+    )]
+    pub reward_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(address = reward_info.mint)]
+    pub reward_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// token Program
+    pub token_program: Program<'info, Token>,
+
+    /// Token program 2022
+    pub token_program_2022: Program<'info, Token2022>,
+}
+
+/// Returns the rewards released while no lp tokens were eligible to the reward provider
+/// once the reward has ended.
+pub fn refund_unallocated_rewards(ctx: Context<RefundUnallocatedRewards>) -> Result<()> {
+    let current_time = Clock::get()?.unix_timestamp as u64;
+    let pool_state = &mut ctx.accounts.pool_state.load_mut()?;
+    let reward_lp_supply = pool_state.reward_lp_supply()?;
+    let reward_info_key = ctx.accounts.reward_info.key();
+    update_reward_info(
+        &mut ctx.accounts.reward_info,
+        reward_info_key,
+        pool_state,
+        reward_lp_supply,
+        current_time,
+    )?;
+
+    let reward_info = &mut ctx.accounts.reward_info;
+    if !reward_info.is_ended() {
+        return err!(GammaError::InvalidRewardTime);
+    }
+
+    let refund_amount = reward_info.unallocated_rewards;
+    if refund_amount == 0 {
+        return Ok(());
+    }
+    reward_info.unallocated_rewards = reward_info
+        .unallocated_rewards
+        .checked_sub(refund_amount)
+        .ok_or(GammaError::MathOverflow)?;
+
+    transfer_from_pool_vault_to_user(
+        ctx.accounts.authority.to_account_info(),
+        ctx.accounts.reward_vault.to_account_info(),
+        ctx.accounts
+            .reward_providers_token_account
+            .to_account_info(),
+        ctx.accounts.reward_mint.to_account_info(),
+        if ctx.accounts.reward_mint.to_account_info().owner == ctx.accounts.token_program.key {
+            ctx.accounts.token_program.to_account_info()
+        } else {
+            ctx.accounts.token_program_2022.to_account_info()
+        },
+        refund_amount,
+        ctx.accounts.reward_mint.decimals,
+        &[&[crate::AUTH_SEED.as_bytes(), &[pool_state.auth_bump]]],
+    )
+}
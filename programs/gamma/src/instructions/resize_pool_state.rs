@@ -0,0 +1,47 @@
+use crate::states::PoolState;
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+
+#[derive(Accounts)]
+pub struct ResizePoolState<'info> {
+    /// Pays the rent of the resized account, anyone can resize a pool
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// The program account of the pool, it can not be loaded before the resize
+    #[account(mut)]
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Grows a pool created before fields were added to `PoolState` to `PoolState::LEN`,
+/// the new fields are zeroed. Pools that already have the current size are left unchanged.
+pub fn resize_pool_state(ctx: Context<ResizePoolState>) -> Result<()> {
+    let account_info = ctx.accounts.pool_state.to_account_info();
+    let len = account_info.data_len();
+    if len >= PoolState::LEN {
+        return Ok(());
+    }
+
+    let rent_lamports = Rent::get()?
+        .minimum_balance(PoolState::LEN)
+        .saturating_sub(account_info.lamports());
+    if rent_lamports > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.payer.to_account_info(),
+                    to: account_info.clone(),
+                },
+            ),
+            rent_lamports,
+        )?;
+    }
+    account_info.realloc(PoolState::LEN, true)?;
+
+    #[cfg(feature = "enable-log")]
+    msg!("pool state len:{}, new len:{}", len, PoolState::LEN);
+    Ok(())
+}
@@ -0,0 +1,80 @@
+use crate::{
+    error::GammaError,
+    states::{PoolState, UserPoolLiquidity},
+};
+use anchor_lang::prelude::*;
+use anchor_lang::{system_program, Discriminator};
+
+#[derive(Accounts)]
+pub struct ResizeUserPoolLiquidity<'info> {
+    /// Pays the rent of the resized account, anyone can resize a user pool liquidity
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    /// CHECK: The user pool liquidity account, it can not be deserialized before the resize
+    #[account(mut, owner = crate::id())]
+    pub user_pool_liquidity: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Grows a user pool liquidity created before fields were added to `UserPoolLiquidity` to `UserPoolLiquidity::LEN`.
+/// The fees earned of the accounts created before the fee growth accumulator are only tracked from the resize on,
+/// the checkpoints start at the current fee growth of the pool.
+/// Accounts that already have the current size are left unchanged.
+pub fn resize_user_pool_liquidity(ctx: Context<ResizeUserPoolLiquidity>) -> Result<()> {
+    let account_info = ctx.accounts.user_pool_liquidity.to_account_info();
+    let len = account_info.data_len();
+    if len >= UserPoolLiquidity::LEN {
+        return Ok(());
+    }
+    require!(
+        account_info
+            .try_borrow_data()?
+            .starts_with(&UserPoolLiquidity::DISCRIMINATOR),
+        ErrorCode::AccountDiscriminatorMismatch
+    );
+
+    let rent_lamports = Rent::get()?
+        .minimum_balance(UserPoolLiquidity::LEN)
+        .saturating_sub(account_info.lamports());
+    if rent_lamports > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.payer.to_account_info(),
+                    to: account_info.clone(),
+                },
+            ),
+            rent_lamports,
+        )?;
+    }
+    account_info.realloc(UserPoolLiquidity::LEN, true)?;
+
+    let mut user_pool_liquidity =
+        UserPoolLiquidity::try_deserialize(&mut &account_info.try_borrow_data()?[..])?;
+    require_keys_eq!(
+        user_pool_liquidity.pool_state,
+        ctx.accounts.pool_state.key(),
+        GammaError::InvalidInput
+    );
+    if len <= UserPoolLiquidity::LEGACY_LEN {
+        let pool_state = ctx.accounts.pool_state.load()?;
+        user_pool_liquidity.fee_growth_checkpoint_token_0 = pool_state.fee_growth_per_lp_token_0;
+        user_pool_liquidity.fee_growth_checkpoint_token_1 = pool_state.fee_growth_per_lp_token_1;
+    }
+    // When the lp tokens last changed is unknown, the rewards that ended are not calculated from their start
+    user_pool_liquidity.update_lp_tokens_changed_at()?;
+    user_pool_liquidity.try_serialize(&mut &mut account_info.try_borrow_mut_data()?[..])?;
+
+    #[cfg(feature = "enable-log")]
+    msg!(
+        "user pool liquidity len:{}, new len:{}",
+        len,
+        UserPoolLiquidity::LEN
+    );
+    Ok(())
+}
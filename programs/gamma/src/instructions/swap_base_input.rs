@@ -1,10 +1,12 @@
-use crate::curve::calculator::CurveCalculator;
+use crate::curve::calculator::{CurveCalculator, SwapResult};
 use crate::curve::TradeDirection;
 use crate::error::GammaError;
 use crate::external::dflow_segmenter::is_invoked_by_segmenter;
+use crate::fees::FeePath;
 use crate::states::oracle;
 use crate::states::AmmConfig;
 use crate::states::ObservationState;
+use crate::states::ObservationsMut;
 use crate::states::PoolState;
 use crate::states::PoolStatusBitIndex;
 use crate::states::SwapEvent;
@@ -151,7 +153,7 @@ pub fn swap_base_input<'c, 'info>(
     let transfer_fee =
         get_transfer_fee(&ctx.accounts.input_token_mint.to_account_info(), amount_in)?;
     // Take transfer fees into account for actual amount transferred in
-    let mut actual_amount_in = amount_in.saturating_sub(transfer_fee);
+    let actual_amount_in = amount_in.saturating_sub(transfer_fee);
     require_gt!(actual_amount_in, 0);
 
     // Calculate the trade amounts
@@ -181,11 +183,8 @@ pub fn swap_base_input<'c, 'info>(
         } else {
             return err!(GammaError::InvalidVault);
         };
-    let constant_before = u128::from(total_input_token_amount)
-        .checked_mul(u128::from(total_output_token_amount))
-        .ok_or(GammaError::MathOverflow)?;
 
-    let mut observation_state = ctx.accounts.observation_state.load_mut()?;
+    let mut observation_state = ObservationsMut::load(&ctx.accounts.observation_state)?;
 
     let mut is_invoked_by_signed_segmenter = false;
 
@@ -198,74 +197,288 @@ pub fn swap_base_input<'c, 'info>(
                 .registered_segmenter
                 .as_ref()
                 .unwrap(),
+            &ctx.accounts.amm_config.segmenter_registry_programs(),
         );
     }
+    let fee_path = FeePath::swap(is_invoked_by_signed_segmenter);
 
     let result = match CurveCalculator::swap_base_input(
         u128::from(actual_amount_in),
         u128::from(total_input_token_amount),
         u128::from(total_output_token_amount),
+        trade_direction,
         &ctx.accounts.amm_config,
         &pool_state,
         block_timestamp,
-        &observation_state,
-        is_invoked_by_signed_segmenter,
+        &observation_state.observations(),
+        fee_path,
     ) {
         Ok(value) => value,
         Err(_) => return err!(GammaError::ZeroTradingTokens),
     };
 
-    let constant_after = u128::from(
+    CurveCalculator::check_invariant(
+        u128::from(total_input_token_amount),
+        u128::from(total_output_token_amount),
         result
             .new_swap_source_amount
             .checked_sub(result.dynamic_fee)
             .ok_or(GammaError::MathOverflow)?,
-    )
-    .checked_mul(u128::from(result.new_swap_destination_amount))
-    .ok_or(GammaError::MathOverflow)?;
+        result.new_swap_destination_amount,
+        trade_direction,
+        &pool_state,
+    )?;
     // #[cfg(feature = "enable-log")]
     msg!(
-        "actual_amount_in:{} source_amount_swapped:{}, destination_amount_swapped:{}, dynamic_fee: {}, constant_before:{},constant_after:{}",
+        "actual_amount_in:{} source_amount_swapped:{}, destination_amount_swapped:{}, dynamic_fee: {}",
         actual_amount_in,
         result.source_amount_swapped,
         result.destination_amount_swapped,
-        result.dynamic_fee,
-        constant_before,
-        constant_after
+        result.dynamic_fee
     );
-    let source_amount_swapped = match u64::try_from(result.source_amount_swapped) {
-        Ok(value) => value,
-        Err(_) => return err!(GammaError::MathOverflow),
-    };
-    require_eq!(source_amount_swapped, actual_amount_in);
-    let (mut input_transfer_amount, input_transfer_fee) = (amount_in, transfer_fee);
-    let (output_transfer_amount, output_transfer_fee) = {
-        let amount_out = match u64::try_from(result.destination_amount_swapped) {
+
+    settle_swap_base_input(
+        &ctx.accounts,
+        pool_id,
+        pool_state,
+        &mut observation_state,
+        referral_info,
+        BaseInputSwap {
+            amount_in,
+            transfer_fee,
+            minimum_amount_out,
+            trade_direction,
+            total_input_token_amount,
+            total_output_token_amount,
+            token_0_price_x32: token_0_price_x64_before_swap,
+            token_1_price_x32: token_1_price_x64_before_swap,
+            fee_path,
+            result,
+        },
+    )
+}
+
+/// A swap by exact input amount priced by the curve or by the oracle, settled by `settle_swap_base_input`.
+/// The swaps of routes and single sided deposits and withdrawals are accounted with it as well.
+pub struct BaseInputSwap {
+    /// Amount sent by the user, transfer fees included
+    pub amount_in: u64,
+    /// Transfer fee of the input mint on `amount_in`
+    pub transfer_fee: u64,
+    pub minimum_amount_out: u64,
+    pub trade_direction: TradeDirection,
+    pub total_input_token_amount: u64,
+    pub total_output_token_amount: u64,
+    /// Prices of the pool before the swap, written to the observations
+    pub token_0_price_x32: u128,
+    pub token_1_price_x32: u128,
+    pub fee_path: FeePath,
+    pub result: SwapResult,
+}
+
+impl BaseInputSwap {
+    /// Checks the swap against the input amount and the slippage,
+    /// returns the amount sent out of the output vault and its transfer fee.
+    pub fn amount_out(&self, output_token_mint: &AccountInfo) -> Result<(u64, u64)> {
+        let actual_amount_in = self.amount_in.saturating_sub(self.transfer_fee);
+        let source_amount_swapped = match u64::try_from(self.result.source_amount_swapped) {
             Ok(value) => value,
             Err(_) => return err!(GammaError::MathOverflow),
         };
-        let transfer_fee = get_transfer_fee(
-            &ctx.accounts.output_token_mint.to_account_info(),
-            amount_out,
-        )?;
+        require_eq!(source_amount_swapped, actual_amount_in);
+        let amount_out = match u64::try_from(self.result.destination_amount_swapped) {
+            Ok(value) => value,
+            Err(_) => return err!(GammaError::MathOverflow),
+        };
+        let transfer_fee = get_transfer_fee(output_token_mint, amount_out)?;
         let amount_received = amount_out
             .checked_sub(transfer_fee)
             .ok_or(GammaError::MathOverflow)?;
         require_gt!(amount_received, 0);
         require_gte!(
             amount_received,
-            minimum_amount_out,
+            self.minimum_amount_out,
             GammaError::ExceededSlippage
         );
-        (amount_out, transfer_fee)
-    };
+        Ok((amount_out, transfer_fee))
+    }
 
-    let mut protocol_fee = u64::try_from(result.protocol_fee).or(err!(GammaError::MathOverflow))?;
-    let mut fund_fee = u64::try_from(result.fund_fee).or(err!(GammaError::MathOverflow))?;
-    let dynamic_fee = u64::try_from(result.dynamic_fee).or(err!(GammaError::MathOverflow))?;
+    /// Accounts the swap in the pool: the protocol, fund, creator and lp fees, the volumes and the vault amounts.
+    /// The referral shares of the protocol and fund fees are sent by the user to the referrer, they never reach the vault.
+    pub fn accrue(
+        &self,
+        pool_state: &mut PoolState,
+        protocol_fee_referral: u64,
+        fund_fee_referral: u64,
+    ) -> Result<()> {
+        let result = &self.result;
+        let protocol_fee = u64::try_from(result.protocol_fee)
+            .or(err!(GammaError::MathOverflow))?
+            .checked_sub(protocol_fee_referral)
+            .ok_or(GammaError::MathOverflow)?;
+        let fund_fee = u64::try_from(result.fund_fee)
+            .or(err!(GammaError::MathOverflow))?
+            .checked_sub(fund_fee_referral)
+            .ok_or(GammaError::MathOverflow)?;
+        let creator_fee = u64::try_from(result.creator_fee).or(err!(GammaError::MathOverflow))?;
+        let dynamic_fee = u64::try_from(result.dynamic_fee).or(err!(GammaError::MathOverflow))?;
+        let lp_fee = u64::try_from(result.lp_fee().ok_or(GammaError::MathOverflow)?)
+            .or(err!(GammaError::MathOverflow))?;
+        let actual_amount_in = self
+            .amount_in
+            .saturating_sub(self.transfer_fee)
+            .checked_sub(protocol_fee_referral)
+            .and_then(|amount| amount.checked_sub(fund_fee_referral))
+            .ok_or(GammaError::MathOverflow)?;
+        let amount_out =
+            u64::try_from(result.destination_amount_swapped).or(err!(GammaError::MathOverflow))?;
+
+        match self.trade_direction {
+            TradeDirection::ZeroForOne => {
+                pool_state.protocol_fees_token_0 = pool_state
+                    .protocol_fees_token_0
+                    .checked_add(protocol_fee)
+                    .ok_or(GammaError::MathOverflow)?;
+                pool_state.fund_fees_token_0 = pool_state
+                    .fund_fees_token_0
+                    .checked_add(fund_fee)
+                    .ok_or(GammaError::MathOverflow)?;
+                pool_state.creator_fees_token_0 = pool_state
+                    .creator_fees_token_0
+                    .checked_add(creator_fee)
+                    .ok_or(GammaError::MathOverflow)?;
+                pool_state.cumulative_trade_fees_token_0 = pool_state
+                    .cumulative_trade_fees_token_0
+                    .checked_add((dynamic_fee) as u128)
+                    .ok_or(GammaError::MathOverflow)?;
+                pool_state.accrue_protocol_fees(protocol_fee, 0)?;
+                pool_state.cumulative_volume_token_0 = pool_state
+                    .cumulative_volume_token_0
+                    .checked_add(actual_amount_in as u128)
+                    .ok_or(GammaError::MathOverflow)?;
+                pool_state.cumulative_volume_token_1 = pool_state
+                    .cumulative_volume_token_1
+                    .checked_add(amount_out as u128)
+                    .ok_or(GammaError::MathOverflow)?;
+
+                pool_state.token_0_vault_amount = pool_state
+                    .token_0_vault_amount
+                    .checked_add(actual_amount_in)
+                    .ok_or(GammaError::MathOverflow)?
+                    .checked_sub(fund_fee)
+                    .ok_or(GammaError::MathOverflow)?
+                    .checked_sub(creator_fee)
+                    .ok_or(GammaError::MathOverflow)?
+                    .checked_sub(protocol_fee)
+                    .ok_or(GammaError::MathOverflow)?;
+                pool_state.token_1_vault_amount = pool_state
+                    .token_1_vault_amount
+                    .checked_sub(amount_out)
+                    .ok_or(GammaError::MathOverflow)?;
+                // The fees of the permanent locks are set apart from the updated vault amounts
+                pool_state.accrue_lp_fees(lp_fee, 0)?;
+            }
+            TradeDirection::OneForZero => {
+                pool_state.protocol_fees_token_1 = pool_state
+                    .protocol_fees_token_1
+                    .checked_add(protocol_fee)
+                    .ok_or(GammaError::MathOverflow)?;
+                pool_state.fund_fees_token_1 = pool_state
+                    .fund_fees_token_1
+                    .checked_add(fund_fee)
+                    .ok_or(GammaError::MathOverflow)?;
+                pool_state.creator_fees_token_1 = pool_state
+                    .creator_fees_token_1
+                    .checked_add(creator_fee)
+                    .ok_or(GammaError::MathOverflow)?;
+                pool_state.cumulative_trade_fees_token_1 = pool_state
+                    .cumulative_trade_fees_token_1
+                    .checked_add((dynamic_fee) as u128)
+                    .ok_or(GammaError::MathOverflow)?;
+                pool_state.accrue_protocol_fees(0, protocol_fee)?;
+                pool_state.cumulative_volume_token_1 = pool_state
+                    .cumulative_volume_token_1
+                    .checked_add(actual_amount_in as u128)
+                    .ok_or(GammaError::MathOverflow)?;
+                pool_state.cumulative_volume_token_0 = pool_state
+                    .cumulative_volume_token_0
+                    .checked_add(amount_out as u128)
+                    .ok_or(GammaError::MathOverflow)?;
+
+                pool_state.token_1_vault_amount = pool_state
+                    .token_1_vault_amount
+                    .checked_add(actual_amount_in)
+                    .ok_or(GammaError::MathOverflow)?
+                    .checked_sub(fund_fee)
+                    .ok_or(GammaError::MathOverflow)?
+                    .checked_sub(creator_fee)
+                    .ok_or(GammaError::MathOverflow)?
+                    .checked_sub(protocol_fee)
+                    .ok_or(GammaError::MathOverflow)?;
+                pool_state.token_0_vault_amount = pool_state
+                    .token_0_vault_amount
+                    .checked_sub(amount_out)
+                    .ok_or(GammaError::MathOverflow)?;
+                // The fees of the permanent locks are set apart from the updated vault amounts
+                pool_state.accrue_lp_fees(0, lp_fee)?;
+            }
+        };
+        pool_state.latest_dynamic_fee_rate = result.dynamic_fee_rate;
+        Ok(())
+    }
 
+    /// Emits the `SwapEvent` of the swap
+    pub fn emit_event(
+        &self,
+        pool_id: Pubkey,
+        input_mint: Pubkey,
+        output_mint: Pubkey,
+        output_transfer_fee: u64,
+    ) -> Result<()> {
+        emit!(SwapEvent {
+            pool_id,
+            input_vault_before: self.total_input_token_amount,
+            output_vault_before: self.total_output_token_amount,
+            input_amount: match u64::try_from(self.result.source_amount_swapped) {
+                Ok(value) => value,
+                Err(_) => return err!(GammaError::MathOverflow),
+            },
+            output_amount: match u64::try_from(self.result.destination_amount_swapped) {
+                Ok(value) => value,
+                Err(_) => return err!(GammaError::MathOverflow),
+            },
+            input_mint,
+            output_mint,
+            input_transfer_fee: self.transfer_fee,
+            output_transfer_fee,
+            base_input: true,
+            dynamic_fee: self.result.dynamic_fee,
+            fee_path: self.fee_path as u8,
+        });
+        Ok(())
+    }
+}
+
+/// Checks the output of a swap by exact input against the slippage, then accounts its fees in the pool,
+/// transfers the tokens and the referral share and updates the observations.
+pub fn settle_swap_base_input<'c, 'info>(
+    accounts: &Swap<'info>,
+    pool_id: Pubkey,
+    pool_state: &mut PoolState,
+    observation_state: &mut ObservationsMut,
+    referral_info: Option<ReferralDetails<'c, 'info>>,
+    swap: BaseInputSwap,
+) -> Result<()> {
+    let (output_transfer_amount, output_transfer_fee) =
+        swap.amount_out(&accounts.output_token_mint.to_account_info())?;
+    let mut input_transfer_amount = swap.amount_in;
+
+    let (mut protocol_fee_referral, mut fund_fee_referral) = (0, 0);
     let mut transfer_referral_amount = None;
     if let Some(ref info) = referral_info {
+        let protocol_fee =
+            u64::try_from(swap.result.protocol_fee).or(err!(GammaError::MathOverflow))?;
+        let fund_fee = u64::try_from(swap.result.fund_fee).or(err!(GammaError::MathOverflow))?;
         let referral_result_from_protocol_fee = info.get_referral_amount(protocol_fee)?;
         let referral_result_from_fund_fee = info.get_referral_amount(fund_fee)?;
         let referral_amount = referral_result_from_protocol_fee
@@ -274,175 +487,55 @@ pub fn swap_base_input<'c, 'info>(
             .ok_or(GammaError::MathOverflow)?;
 
         let referral_transfer_fee = get_transfer_fee(
-            &ctx.accounts.input_token_mint.to_account_info(),
+            &accounts.input_token_mint.to_account_info(),
             referral_amount,
         )?;
 
         #[cfg(feature = "enable-log")]
-        msg!("referral_amount:{}", referral_amount, referral_transfer_fee);
+        msg!(
+            "referral_amount:{}, referral_transfer_fee:{}",
+            referral_amount,
+            referral_transfer_fee
+        );
 
         // We are aware of the fact that when referral fees are very small the referee will not get any tokens
         if referral_amount != 0 && referral_transfer_fee < referral_amount {
-            protocol_fee = referral_result_from_protocol_fee.amount_after_referral;
-            fund_fee = referral_result_from_fund_fee.amount_after_referral;
+            protocol_fee_referral = referral_result_from_protocol_fee.referral_amount;
+            fund_fee_referral = referral_result_from_fund_fee.referral_amount;
 
             // we subtract the input transfer amount that these tokens are directly transferred from user to lp pool.
             input_transfer_amount = input_transfer_amount
                 .checked_sub(referral_amount)
                 .ok_or(GammaError::MathOverflow)?;
-            actual_amount_in = actual_amount_in
-                .checked_sub(referral_amount)
-                .ok_or(GammaError::MathOverflow)?;
 
             transfer_referral_amount = Some(referral_amount)
         }
     }
-    // Save fees metric for the pool partners.
-    let mut partners = pool_state.partners;
-    for partner in partners.iter_mut() {
-        // we multiply by 100000 to keep decimals.
-        let decimal_number = 100000;
-        let tvl_share = partner
-            .lp_token_linked_with_partner
-            .checked_mul(decimal_number)
-            .ok_or(GammaError::MathOverflow)?
-            .checked_div(pool_state.lp_supply)
-            .ok_or(GammaError::MathOverflow)?;
-
-        let partner_fee = protocol_fee
-            .checked_mul(tvl_share)
-            .ok_or(GammaError::MathOverflow)?
-            .checked_div(decimal_number)
-            .ok_or(GammaError::MathOverflow)?;
-
-        match trade_direction {
-            TradeDirection::ZeroForOne => {
-                partner.cumulative_fee_total_times_tvl_share_token_0 = partner
-                    .cumulative_fee_total_times_tvl_share_token_0
-                    .checked_add(partner_fee)
-                    .ok_or(GammaError::MathOverflow)?;
-            }
-            TradeDirection::OneForZero => {
-                partner.cumulative_fee_total_times_tvl_share_token_1 = partner
-                    .cumulative_fee_total_times_tvl_share_token_1
-                    .checked_add(partner_fee)
-                    .ok_or(GammaError::MathOverflow)?;
-            }
-        }
-    }
-    pool_state.partners = partners;
-
-    match trade_direction {
-        TradeDirection::ZeroForOne => {
-            pool_state.protocol_fees_token_0 = pool_state
-                .protocol_fees_token_0
-                .checked_add(protocol_fee)
-                .ok_or(GammaError::MathOverflow)?;
-            pool_state.fund_fees_token_0 = pool_state
-                .fund_fees_token_0
-                .checked_add(fund_fee)
-                .ok_or(GammaError::MathOverflow)?;
-            pool_state.cumulative_trade_fees_token_0 = pool_state
-                .cumulative_trade_fees_token_0
-                .checked_add((dynamic_fee) as u128)
-                .ok_or(GammaError::MathOverflow)?;
-            pool_state.cumulative_volume_token_0 = pool_state
-                .cumulative_volume_token_0
-                .checked_add(actual_amount_in as u128)
-                .ok_or(GammaError::MathOverflow)?;
-            pool_state.cumulative_volume_token_1 = pool_state
-                .cumulative_volume_token_1
-                .checked_add(output_transfer_amount as u128)
-                .ok_or(GammaError::MathOverflow)?;
-
-            pool_state.token_0_vault_amount = pool_state
-                .token_0_vault_amount
-                .checked_add(actual_amount_in)
-                .ok_or(GammaError::MathOverflow)?
-                .checked_sub(fund_fee)
-                .ok_or(GammaError::MathOverflow)?
-                .checked_sub(protocol_fee)
-                .ok_or(GammaError::MathOverflow)?;
-            pool_state.token_1_vault_amount = pool_state
-                .token_1_vault_amount
-                .checked_sub(output_transfer_amount)
-                .ok_or(GammaError::MathOverflow)?;
-        }
-        TradeDirection::OneForZero => {
-            pool_state.protocol_fees_token_1 = pool_state
-                .protocol_fees_token_1
-                .checked_add(protocol_fee)
-                .ok_or(GammaError::MathOverflow)?;
-            pool_state.fund_fees_token_1 = pool_state
-                .fund_fees_token_1
-                .checked_add(fund_fee)
-                .ok_or(GammaError::MathOverflow)?;
-            pool_state.cumulative_trade_fees_token_1 = pool_state
-                .cumulative_trade_fees_token_1
-                .checked_add((dynamic_fee) as u128)
-                .ok_or(GammaError::MathOverflow)?;
-            pool_state.cumulative_volume_token_1 = pool_state
-                .cumulative_volume_token_1
-                .checked_add(actual_amount_in as u128)
-                .ok_or(GammaError::MathOverflow)?;
-            pool_state.cumulative_volume_token_0 = pool_state
-                .cumulative_volume_token_0
-                .checked_add(output_transfer_amount as u128)
-                .ok_or(GammaError::MathOverflow)?;
-
-            pool_state.token_1_vault_amount = pool_state
-                .token_1_vault_amount
-                .checked_add(actual_amount_in)
-                .ok_or(GammaError::MathOverflow)?
-                .checked_sub(fund_fee)
-                .ok_or(GammaError::MathOverflow)?
-                .checked_sub(protocol_fee)
-                .ok_or(GammaError::MathOverflow)?;
-            pool_state.token_0_vault_amount = pool_state
-                .token_0_vault_amount
-                .checked_sub(output_transfer_amount)
-                .ok_or(GammaError::MathOverflow)?;
-        }
-    };
-    pool_state.latest_dynamic_fee_rate = result.dynamic_fee_rate;
-
-    emit!(SwapEvent {
+    swap.accrue(pool_state, protocol_fee_referral, fund_fee_referral)?;
+    swap.emit_event(
         pool_id,
-        input_vault_before: total_input_token_amount,
-        output_vault_before: total_output_token_amount,
-        input_amount: match u64::try_from(result.source_amount_swapped) {
-            Ok(value) => value,
-            Err(_) => return err!(GammaError::MathOverflow),
-        },
-        output_amount: match u64::try_from(result.destination_amount_swapped) {
-            Ok(value) => value,
-            Err(_) => return err!(GammaError::MathOverflow),
-        },
-        input_mint: ctx.accounts.input_vault.mint,
-        output_mint: ctx.accounts.output_vault.mint,
-        input_transfer_fee,
+        accounts.input_vault.mint,
+        accounts.output_vault.mint,
         output_transfer_fee,
-        base_input: true,
-        dynamic_fee: result.dynamic_fee
-    });
-    require_gte!(constant_after, constant_before);
+    )?;
+
     transfer_from_user_to_pool_vault(
-        ctx.accounts.payer.to_account_info(),
-        ctx.accounts.input_token_account.to_account_info(),
-        ctx.accounts.input_vault.to_account_info(),
-        ctx.accounts.input_token_mint.to_account_info(),
-        ctx.accounts.input_token_program.to_account_info(),
+        accounts.payer.to_account_info(),
+        accounts.input_token_account.to_account_info(),
+        accounts.input_vault.to_account_info(),
+        accounts.input_token_mint.to_account_info(),
+        accounts.input_token_program.to_account_info(),
         input_transfer_amount,
-        ctx.accounts.input_token_mint.decimals,
+        accounts.input_token_mint.decimals,
     )?;
     transfer_from_pool_vault_to_user(
-        ctx.accounts.authority.to_account_info(),
-        ctx.accounts.output_vault.to_account_info(),
-        ctx.accounts.output_token_account.to_account_info(),
-        ctx.accounts.output_token_mint.to_account_info(),
-        ctx.accounts.output_token_program.to_account_info(),
+        accounts.authority.to_account_info(),
+        accounts.output_vault.to_account_info(),
+        accounts.output_token_account.to_account_info(),
+        accounts.output_token_mint.to_account_info(),
+        accounts.output_token_program.to_account_info(),
         output_transfer_amount,
-        ctx.accounts.output_token_mint.decimals,
+        accounts.output_token_mint.decimals,
         &[&[crate::AUTH_SEED.as_bytes(), &[pool_state.auth_bump]]],
     )?;
 
@@ -457,23 +550,24 @@ pub fn swap_base_input<'c, 'info>(
         let info = referral_info.expect("referral_info to be non-null");
         anchor_spl::token_2022::transfer_checked(
             CpiContext::new(
-                ctx.accounts.input_token_program.to_account_info(),
+                accounts.input_token_program.to_account_info(),
                 anchor_spl::token_2022::TransferChecked {
-                    from: ctx.accounts.input_token_account.to_account_info(),
+                    from: accounts.input_token_account.to_account_info(),
                     to: info.referral_token_account.to_account_info(),
-                    authority: ctx.accounts.payer.to_account_info(),
-                    mint: ctx.accounts.input_token_mint.to_account_info(),
+                    authority: accounts.payer.to_account_info(),
+                    mint: accounts.input_token_mint.to_account_info(),
                 },
             ),
             amount,
-            ctx.accounts.input_token_mint.decimals,
+            accounts.input_token_mint.decimals,
         )?;
     }
 
     observation_state.update(
         oracle::block_timestamp()?,
-        token_0_price_x64_before_swap,
-        token_1_price_x64_before_swap,
+        u64::from(pool_state.observation_update_duration),
+        swap.token_0_price_x32,
+        swap.token_1_price_x32,
     )?;
 
     pool_state.recent_epoch = Clock::get()?.epoch;
@@ -2,7 +2,8 @@ use super::swap_base_input::Swap;
 use crate::curve::{calculator::CurveCalculator, TradeDirection};
 use crate::error::GammaError;
 use crate::external::dflow_segmenter::is_invoked_by_segmenter;
-use crate::states::{oracle, PoolStatusBitIndex, SwapEvent};
+use crate::fees::FeePath;
+use crate::states::{oracle, ObservationsMut, PoolStatusBitIndex, SwapEvent};
 use crate::utils::{swap_referral::*, token::*};
 use crate::SwapRemainingAccounts;
 use anchor_lang::prelude::*;
@@ -78,11 +79,8 @@ pub fn swap_base_output<'c, 'info>(
         } else {
             return err!(GammaError::InvalidVault);
         };
-    let constant_before = u128::from(total_input_token_amount)
-        .checked_mul(u128::from(total_output_token_amount))
-        .ok_or(GammaError::MathOverflow)?;
 
-    let mut observation_state = ctx.accounts.observation_state.load_mut()?;
+    let mut observation_state = ObservationsMut::load(&ctx.accounts.observation_state)?;
 
     let mut is_invoked_by_signed_segmenter = false;
     if swap_remaining_accounts.registered_segmenter.is_some()
@@ -94,40 +92,44 @@ pub fn swap_base_output<'c, 'info>(
                 .registered_segmenter
                 .as_ref()
                 .unwrap(),
+            &ctx.accounts.amm_config.segmenter_registry_programs(),
         );
     }
+    let fee_path = FeePath::swap(is_invoked_by_signed_segmenter);
 
     let result = match CurveCalculator::swap_base_output(
         u128::from(actual_amount_out),
         u128::from(total_input_token_amount),
         u128::from(total_output_token_amount),
+        trade_direction,
         &ctx.accounts.amm_config,
         &pool_state,
         block_timestamp,
-        &observation_state,
-        is_invoked_by_signed_segmenter,
+        &observation_state.observations(),
+        fee_path,
     ) {
         Ok(value) => value,
         Err(_) => return err!(GammaError::ZeroTradingTokens),
     };
 
-    let constant_after = u128::from(
+    CurveCalculator::check_invariant(
+        u128::from(total_input_token_amount),
+        u128::from(total_output_token_amount),
         result
             .new_swap_source_amount
             .checked_sub(result.dynamic_fee)
             .ok_or(GammaError::MathOverflow)?,
-    )
-    .checked_mul(u128::from(result.new_swap_destination_amount))
-    .ok_or(GammaError::MathOverflow)?;
+        result.new_swap_destination_amount,
+        trade_direction,
+        &pool_state,
+    )?;
 
     #[cfg(feature = "enable-log")]
     msg!(
-        "source_amount_swapped:{}, destination_amount_swapped:{}, dynamic_fee: {}, constant_before:{},constant_after:{}",
+        "source_amount_swapped:{}, destination_amount_swapped:{}, dynamic_fee: {}",
         result.source_amount_swapped,
         result.destination_amount_swapped,
-        result.dynamic_fee,
-        constant_before,
-        constant_after
+        result.dynamic_fee
     );
 
     // Re-calculate the source amount swapped based on what the curve says
@@ -160,7 +162,10 @@ pub fn swap_base_output<'c, 'info>(
 
     let mut protocol_fee = u64::try_from(result.protocol_fee).or(err!(GammaError::MathOverflow))?;
     let mut fund_fee = u64::try_from(result.fund_fee).or(err!(GammaError::MathOverflow))?;
+    let creator_fee = u64::try_from(result.creator_fee).or(err!(GammaError::MathOverflow))?;
     let dynamic_fee = u64::try_from(result.dynamic_fee).or(err!(GammaError::MathOverflow))?;
+    let lp_fee = u64::try_from(result.lp_fee().ok_or(GammaError::MathOverflow)?)
+        .or(err!(GammaError::MathOverflow))?;
 
     let mut source_amount_swapped =
         u64::try_from(result.source_amount_swapped).or(err!(GammaError::MathOverflow))?;
@@ -180,7 +185,11 @@ pub fn swap_base_output<'c, 'info>(
         )?;
 
         #[cfg(feature = "enable-log")]
-        msg!("referral_amount:{}", referral_amount, referral_transfer_fee);
+        msg!(
+            "referral_amount:{}, referral_transfer_fee:{}",
+            referral_amount,
+            referral_transfer_fee
+        );
 
         // We are aware of the fact that when referral fees are very small the referee will not get any tokens
         if referral_amount != 0 && referral_transfer_fee < referral_amount {
@@ -198,41 +207,6 @@ pub fn swap_base_output<'c, 'info>(
         }
     }
 
-    // Save fees metric for the pool partners.
-    let mut partners = pool_state.partners;
-    for partner in partners.iter_mut() {
-        // we multiply by 100000 to keep decimals.
-        let decimal_number = 100000;
-        let tvl_share = partner
-            .lp_token_linked_with_partner
-            .checked_mul(decimal_number)
-            .ok_or(GammaError::MathOverflow)?
-            .checked_div(pool_state.lp_supply)
-            .ok_or(GammaError::MathOverflow)?;
-
-        let partner_fee = protocol_fee
-            .checked_mul(tvl_share)
-            .ok_or(GammaError::MathOverflow)?
-            .checked_div(decimal_number)
-            .ok_or(GammaError::MathOverflow)?;
-
-        match trade_direction {
-            TradeDirection::ZeroForOne => {
-                partner.cumulative_fee_total_times_tvl_share_token_0 = partner
-                    .cumulative_fee_total_times_tvl_share_token_0
-                    .checked_add(partner_fee)
-                    .ok_or(GammaError::MathOverflow)?;
-            }
-            TradeDirection::OneForZero => {
-                partner.cumulative_fee_total_times_tvl_share_token_1 = partner
-                    .cumulative_fee_total_times_tvl_share_token_1
-                    .checked_add(partner_fee)
-                    .ok_or(GammaError::MathOverflow)?;
-            }
-        }
-    }
-    pool_state.partners = partners;
-
     match trade_direction {
         TradeDirection::ZeroForOne => {
             pool_state.protocol_fees_token_0 = pool_state
@@ -243,10 +217,15 @@ pub fn swap_base_output<'c, 'info>(
                 .fund_fees_token_0
                 .checked_add(fund_fee)
                 .ok_or(GammaError::MathOverflow)?;
+            pool_state.creator_fees_token_0 = pool_state
+                .creator_fees_token_0
+                .checked_add(creator_fee)
+                .ok_or(GammaError::MathOverflow)?;
             pool_state.cumulative_trade_fees_token_0 = pool_state
                 .cumulative_trade_fees_token_0
                 .checked_add(dynamic_fee as u128)
                 .ok_or(GammaError::MathOverflow)?;
+            pool_state.accrue_protocol_fees(protocol_fee, 0)?;
             pool_state.cumulative_volume_token_0 = pool_state
                 .cumulative_volume_token_0
                 .checked_add(input_transfer_amount as u128)
@@ -255,13 +234,15 @@ pub fn swap_base_output<'c, 'info>(
                 .cumulative_volume_token_1
                 .checked_add(output_transfer_amount as u128)
                 .ok_or(GammaError::MathOverflow)?;
-            
+
             pool_state.token_0_vault_amount = pool_state
                 .token_0_vault_amount
                 .checked_add(source_amount_swapped)
                 .ok_or(GammaError::MathOverflow)?
                 .checked_sub(fund_fee)
                 .ok_or(GammaError::MathOverflow)?
+                .checked_sub(creator_fee)
+                .ok_or(GammaError::MathOverflow)?
                 .checked_sub(protocol_fee)
                 .ok_or(GammaError::MathOverflow)?;
 
@@ -269,6 +250,8 @@ pub fn swap_base_output<'c, 'info>(
                 .token_1_vault_amount
                 .checked_sub(output_transfer_amount)
                 .ok_or(GammaError::MathOverflow)?;
+            // The fees of the permanent locks are set apart from the updated vault amounts
+            pool_state.accrue_lp_fees(lp_fee, 0)?;
         }
         TradeDirection::OneForZero => {
             pool_state.protocol_fees_token_1 = pool_state
@@ -279,10 +262,15 @@ pub fn swap_base_output<'c, 'info>(
                 .fund_fees_token_1
                 .checked_add(fund_fee)
                 .ok_or(GammaError::MathOverflow)?;
+            pool_state.creator_fees_token_1 = pool_state
+                .creator_fees_token_1
+                .checked_add(creator_fee)
+                .ok_or(GammaError::MathOverflow)?;
             pool_state.cumulative_trade_fees_token_1 = pool_state
                 .cumulative_trade_fees_token_1
                 .checked_add(dynamic_fee as u128)
                 .ok_or(GammaError::MathOverflow)?;
+            pool_state.accrue_protocol_fees(0, protocol_fee)?;
             pool_state.cumulative_volume_token_1 = pool_state
                 .cumulative_volume_token_1
                 .checked_add(input_transfer_amount as u128)
@@ -298,6 +286,8 @@ pub fn swap_base_output<'c, 'info>(
                 .ok_or(GammaError::MathOverflow)?
                 .checked_sub(fund_fee)
                 .ok_or(GammaError::MathOverflow)?
+                .checked_sub(creator_fee)
+                .ok_or(GammaError::MathOverflow)?
                 .checked_sub(protocol_fee)
                 .ok_or(GammaError::MathOverflow)?;
 
@@ -305,6 +295,8 @@ pub fn swap_base_output<'c, 'info>(
                 .token_0_vault_amount
                 .checked_sub(output_transfer_amount)
                 .ok_or(GammaError::MathOverflow)?;
+            // The fees of the permanent locks are set apart from the updated vault amounts
+            pool_state.accrue_lp_fees(0, lp_fee)?;
         }
     };
     pool_state.latest_dynamic_fee_rate = result.dynamic_fee_rate;
@@ -327,8 +319,8 @@ pub fn swap_base_output<'c, 'info>(
         output_transfer_fee,
         base_input: false,
         dynamic_fee: result.dynamic_fee,
+        fee_path: fee_path as u8,
     });
-    require_gte!(constant_after, constant_before);
 
     transfer_from_user_to_pool_vault(
         ctx.accounts.payer.to_account_info(),
@@ -377,6 +369,7 @@ pub fn swap_base_output<'c, 'info>(
 
     observation_state.update(
         oracle::block_timestamp()?,
+        u64::from(pool_state.observation_update_duration),
         token_0_price_x64_before_swap,
         token_1_price_x64_before_swap,
     )?;
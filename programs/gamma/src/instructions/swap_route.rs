@@ -0,0 +1,295 @@
+use super::swap_base_input::BaseInputSwap;
+use crate::curve::calculator::CurveCalculator;
+use crate::curve::TradeDirection;
+use crate::error::GammaError;
+use crate::fees::FeePath;
+use crate::states::oracle;
+use crate::states::AmmConfig;
+use crate::states::ObservationState;
+use crate::states::ObservationsMut;
+use crate::states::PoolState;
+use crate::states::PoolStatusBitIndex;
+use crate::utils::token::*;
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+/// Number of remaining accounts expected for every hop of a route, see `SwapRouteHop`.
+pub const SWAP_ROUTE_HOP_ACCOUNTS_LEN: usize = 7;
+
+#[derive(Accounts)]
+pub struct SwapRoute<'info> {
+    /// The user performing the swap
+    pub payer: Signer<'info>,
+
+    /// CHECK: pool vault authority
+    #[account(
+        seeds = [
+            crate::AUTH_SEED.as_bytes(),
+        ],
+        bump,
+    )]
+    pub authority: UncheckedAccount<'info>,
+
+    /// The user token account for the input token of the first hop
+    #[account(mut)]
+    pub input_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The user token account for the output token of the last hop
+    #[account(mut)]
+    pub output_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// SPL program for input token transfers
+    pub input_token_program: Interface<'info, TokenInterface>,
+
+    /// The mint of input token
+    #[account(
+        address = input_token_account.mint
+    )]
+    pub input_token_mint: Box<InterfaceAccount<'info, Mint>>,
+}
+
+/// Accounts of one hop of the route, passed in remaining accounts in this order.
+/// The input mint of a hop is the output mint of the previous hop (or `input_token_mint` for the first one).
+pub struct SwapRouteHop<'info> {
+    pub amm_config: Account<'info, AmmConfig>,
+    pub pool_state: AccountLoader<'info, PoolState>,
+    pub input_vault: InterfaceAccount<'info, TokenAccount>,
+    pub output_vault: InterfaceAccount<'info, TokenAccount>,
+    pub output_token_mint: InterfaceAccount<'info, Mint>,
+    pub output_token_program: Interface<'info, TokenInterface>,
+    pub observation_state: AccountLoader<'info, ObservationState>,
+}
+
+impl<'info> SwapRouteHop<'info> {
+    pub fn new(accounts: &'info [AccountInfo<'info>]) -> Result<Self> {
+        require_eq!(
+            accounts.len(),
+            SWAP_ROUTE_HOP_ACCOUNTS_LEN,
+            ErrorCode::AccountNotEnoughKeys
+        );
+        let hop = Self {
+            amm_config: Account::try_from(&accounts[0])?,
+            pool_state: AccountLoader::try_from(&accounts[1])?,
+            input_vault: InterfaceAccount::try_from(&accounts[2])?,
+            output_vault: InterfaceAccount::try_from(&accounts[3])?,
+            output_token_mint: InterfaceAccount::try_from(&accounts[4])?,
+            output_token_program: Interface::try_from(&accounts[5])?,
+            observation_state: AccountLoader::try_from(&accounts[6])?,
+        };
+
+        let pool_state = hop.pool_state.load()?;
+        require_keys_eq!(
+            hop.amm_config.key(),
+            pool_state.amm_config,
+            ErrorCode::ConstraintAddress
+        );
+        require_keys_eq!(
+            hop.observation_state.key(),
+            pool_state.observation_key,
+            ErrorCode::ConstraintAddress
+        );
+        require_keys_eq!(
+            hop.output_token_mint.key(),
+            hop.output_vault.mint,
+            ErrorCode::ConstraintAddress
+        );
+        drop(pool_state);
+
+        Ok(hop)
+    }
+}
+
+/// Swaps `amount_in` through every pool of the route, the output of a hop is sent directly
+/// from the output vault of the pool to the input vault of the next pool.
+/// Only the amount received at the end of the route is checked against `minimum_amount_out`.
+///
+/// Remaining accounts are `SWAP_ROUTE_HOP_ACCOUNTS_LEN` accounts for each hop, see `SwapRouteHop`.
+/// Segmenter and referral accounts are not supported by routes.
+pub fn swap_route<'c, 'info>(
+    ctx: Context<'_, '_, 'c, 'info, SwapRoute<'info>>,
+    amount_in: u64,
+    minimum_amount_out: u64,
+) -> Result<()>
+where
+    'c: 'info,
+{
+    let remaining_accounts = ctx.remaining_accounts;
+    require!(
+        !remaining_accounts.is_empty()
+            && remaining_accounts.len() % SWAP_ROUTE_HOP_ACCOUNTS_LEN == 0,
+        GammaError::InvalidRoute
+    );
+    let hop_count = remaining_accounts.len() / SWAP_ROUTE_HOP_ACCOUNTS_LEN;
+    let block_timestamp = solana_program::clock::Clock::get()?.unix_timestamp as u64;
+
+    let transfer_fee =
+        get_transfer_fee(&ctx.accounts.input_token_mint.to_account_info(), amount_in)?;
+    // Take transfer fees into account for actual amount transferred in
+    require_gt!(amount_in.saturating_sub(transfer_fee), 0);
+    // The input of a hop is the output of the previous one, transfer fees included
+    let mut hop_amount_in = amount_in;
+    let mut input_transfer_fee = transfer_fee;
+    let mut input_mint = ctx.accounts.input_token_mint.key();
+    let mut pool_ids: Vec<Pubkey> = Vec::with_capacity(hop_count);
+
+    for (hop_index, accounts) in remaining_accounts
+        .chunks(SWAP_ROUTE_HOP_ACCOUNTS_LEN)
+        .enumerate()
+    {
+        let hop = SwapRouteHop::new(accounts)?;
+        let pool_id = hop.pool_state.key();
+        // A pool can only be used once, its reserves would be stale otherwise.
+        require!(!pool_ids.contains(&pool_id), GammaError::InvalidRoute);
+        pool_ids.push(pool_id);
+        require_keys_eq!(hop.input_vault.mint, input_mint, GammaError::InvalidRoute);
+
+        let pool_state = &mut hop.pool_state.load_mut()?;
+        if !pool_state.get_status_by_bit(PoolStatusBitIndex::Swap)
+            || block_timestamp < pool_state.open_time
+        {
+            return err!(GammaError::NotApproved);
+        }
+
+        // Calculate the trade amounts
+        let (trade_direction, total_input_token_amount, total_output_token_amount) =
+            if hop.input_vault.key() == pool_state.token_0_vault
+                && hop.output_vault.key() == pool_state.token_1_vault
+            {
+                let (total_input_token_amount, total_output_token_amount) =
+                    pool_state.vault_amount_without_fee()?;
+
+                (
+                    TradeDirection::ZeroForOne,
+                    total_input_token_amount,
+                    total_output_token_amount,
+                )
+            } else if hop.input_vault.key() == pool_state.token_1_vault
+                && hop.output_vault.key() == pool_state.token_0_vault
+            {
+                let (total_output_token_amount, total_input_token_amount) =
+                    pool_state.vault_amount_without_fee()?;
+
+                (
+                    TradeDirection::OneForZero,
+                    total_input_token_amount,
+                    total_output_token_amount,
+                )
+            } else {
+                return err!(GammaError::InvalidVault);
+            };
+        let (token_0_price_x64_before_swap, token_1_price_x64_before_swap) =
+            pool_state.token_price_x32()?;
+        // Take transfer fees into account for actual amount transferred in
+        let actual_amount_in = hop_amount_in.saturating_sub(input_transfer_fee);
+
+        let mut observation_state = ObservationsMut::load(&hop.observation_state)?;
+
+        let result = match CurveCalculator::swap_base_input(
+            u128::from(actual_amount_in),
+            u128::from(total_input_token_amount),
+            u128::from(total_output_token_amount),
+            trade_direction,
+            &hop.amm_config,
+            &pool_state,
+            block_timestamp,
+            &observation_state.observations(),
+            FeePath::UnsignedFlow,
+        ) {
+            Ok(value) => value,
+            Err(_) => return err!(GammaError::ZeroTradingTokens),
+        };
+
+        CurveCalculator::check_invariant(
+            u128::from(total_input_token_amount),
+            u128::from(total_output_token_amount),
+            result
+                .new_swap_source_amount
+                .checked_sub(result.dynamic_fee)
+                .ok_or(GammaError::MathOverflow)?,
+            result.new_swap_destination_amount,
+            trade_direction,
+            &pool_state,
+        )?;
+        #[cfg(feature = "enable-log")]
+        msg!(
+            "hop:{} actual_amount_in:{} source_amount_swapped:{}, destination_amount_swapped:{}, dynamic_fee: {}",
+            hop_index,
+            actual_amount_in,
+            result.source_amount_swapped,
+            result.destination_amount_swapped,
+            result.dynamic_fee
+        );
+        // Only the amount received at the end of the route is checked against the slippage
+        let swap = BaseInputSwap {
+            amount_in: hop_amount_in,
+            transfer_fee: input_transfer_fee,
+            minimum_amount_out: if hop_index + 1 == hop_count {
+                minimum_amount_out
+            } else {
+                0
+            },
+            trade_direction,
+            total_input_token_amount,
+            total_output_token_amount,
+            token_0_price_x32: token_0_price_x64_before_swap,
+            token_1_price_x32: token_1_price_x64_before_swap,
+            fee_path: FeePath::UnsignedFlow,
+            result,
+        };
+        let (amount_out, output_transfer_fee) =
+            swap.amount_out(&hop.output_token_mint.to_account_info())?;
+        swap.accrue(pool_state, 0, 0)?;
+        swap.emit_event(
+            pool_id,
+            input_mint,
+            hop.output_vault.mint,
+            output_transfer_fee,
+        )?;
+
+        if hop_index == 0 {
+            transfer_from_user_to_pool_vault(
+                ctx.accounts.payer.to_account_info(),
+                ctx.accounts.input_token_account.to_account_info(),
+                hop.input_vault.to_account_info(),
+                ctx.accounts.input_token_mint.to_account_info(),
+                ctx.accounts.input_token_program.to_account_info(),
+                amount_in,
+                ctx.accounts.input_token_mint.decimals,
+            )?;
+        }
+
+        // The output of the last hop goes to the user, every other output goes to the input vault of the next hop,
+        // which is validated against its pool when the next hop is processed.
+        let output_destination = if hop_index + 1 == hop_count {
+            ctx.accounts.output_token_account.to_account_info()
+        } else {
+            remaining_accounts[(hop_index + 1) * SWAP_ROUTE_HOP_ACCOUNTS_LEN + 2].clone()
+        };
+        transfer_from_pool_vault_to_user(
+            ctx.accounts.authority.to_account_info(),
+            hop.output_vault.to_account_info(),
+            output_destination,
+            hop.output_token_mint.to_account_info(),
+            hop.output_token_program.to_account_info(),
+            amount_out,
+            hop.output_token_mint.decimals,
+            &[&[crate::AUTH_SEED.as_bytes(), &[pool_state.auth_bump]]],
+        )?;
+
+        observation_state.update(
+            oracle::block_timestamp()?,
+            u64::from(pool_state.observation_update_duration),
+            swap.token_0_price_x32,
+            swap.token_1_price_x32,
+        )?;
+
+        pool_state.recent_epoch = Clock::get()?.epoch;
+
+        hop_amount_in = amount_out;
+        input_transfer_fee = output_transfer_fee;
+        input_mint = hop.output_vault.mint;
+    }
+
+    Ok(())
+}
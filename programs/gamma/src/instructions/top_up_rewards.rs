@@ -0,0 +1,115 @@
+use crate::{
+    error::GammaError,
+    instructions::update_reward_info,
+    states::{PoolState, RewardInfo},
+    utils::transfer_from_user_to_pool_vault,
+    REWARD_VAULT_SEED,
+};
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    token::Token,
+    token_interface::{Mint, Token2022, TokenAccount},
+};
+
+#[derive(Accounts)]
+pub struct TopUpRewards<'info> {
+    /// The provider of the rewards
+    #[account(
+        mut,
+        address = reward_info.rewarded_by @ GammaError::InvalidOwner
+    )]
+    pub reward_provider: Signer<'info>,
+
+    #[account(mut)]
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    #[account(
+        mut,
+        seeds = [
+            crate::REWARD_INFO_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+            reward_info.start_at.to_le_bytes().as_ref(),
+            reward_info.mint.as_ref(),
+        ],
+        bump,
+    )]
+    pub reward_info: Account<'info, RewardInfo>,
+
+    #[account(
+        mut,
+        token::mint = reward_mint,
+        token::authority = reward_provider,
+    )]
+    pub reward_providers_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [
+            REWARD_VAULT_SEED.as_bytes(),
+            reward_info.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub reward_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(address = reward_info.mint)]
+    pub reward_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// token Program
+    pub token_program: Program<'info, Token>,
+
+    /// Token program 2022
+    pub token_program_2022: Program<'info, Token2022>,
+}
+
+/// Adds rewards to a reward that has not ended, they are released until `end_rewards_at`
+/// along with the rewards not released yet.
+pub fn top_up_rewards(ctx: Context<TopUpRewards>, reward_amount: u64) -> Result<()> {
+    let current_time = Clock::get()?.unix_timestamp as u64;
+    let pool_state = &mut ctx.accounts.pool_state.load_mut()?;
+    let reward_lp_supply = pool_state.reward_lp_supply()?;
+    let reward_info_key = ctx.accounts.reward_info.key();
+    update_reward_info(
+        &mut ctx.accounts.reward_info,
+        reward_info_key,
+        pool_state,
+        reward_lp_supply,
+        current_time,
+    )?;
+    if ctx.accounts.reward_info.is_ended() {
+        return err!(GammaError::InvalidRewardTime);
+    }
+
+    let amount_in_vault_before = ctx.accounts.reward_vault.amount;
+    transfer_from_user_to_pool_vault(
+        ctx.accounts.reward_provider.to_account_info(),
+        ctx.accounts
+            .reward_providers_token_account
+            .to_account_info(),
+        ctx.accounts.reward_vault.to_account_info(),
+        ctx.accounts.reward_mint.to_account_info(),
+        if ctx.accounts.reward_mint.to_account_info().owner == ctx.accounts.token_program.key {
+            ctx.accounts.token_program.to_account_info()
+        } else {
+            ctx.accounts.token_program_2022.to_account_info()
+        },
+        reward_amount,
+        ctx.accounts.reward_mint.decimals,
+    )?;
+    ctx.accounts.reward_vault.reload()?;
+
+    // The transfer fees of the reward mint are not distributed
+    let amount_received = ctx
+        .accounts
+        .reward_vault
+        .amount
+        .checked_sub(amount_in_vault_before)
+        .ok_or(GammaError::MathOverflow)?;
+    let reward_info = &mut ctx.accounts.reward_info;
+    reward_info.total_to_disburse = reward_info
+        .total_to_disburse
+        .checked_add(amount_received)
+        .ok_or(GammaError::MathOverflow)?;
+
+    Ok(())
+}
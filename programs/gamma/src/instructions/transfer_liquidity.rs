@@ -0,0 +1,354 @@
+use crate::{
+    error::GammaError,
+    instructions::update_active_reward_info,
+    states::{
+        update_partner_lp_tokens, PoolPartners, PoolState, RewardInfo, UserPoolLiquidity,
+        UserRewardInfo, POOL_PARTNERS_SEED, USER_POOL_LIQUIDITY_SEED,
+    },
+    USER_REWARD_INFO_SEED,
+};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct TransferLiquidity<'info> {
+    /// Owner of the liquidity provided, pays for the accounts of the receiver if needed
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// CHECK: Receiver of the liquidity, does not require any validation
+    #[account(constraint = to_user.key() != owner.key() @ GammaError::InvalidInput)]
+    pub to_user: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    /// User pool liquidity account of the owner
+    #[account(
+        mut,
+        seeds = [
+            USER_POOL_LIQUIDITY_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+            owner.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub user_pool_liquidity: Box<Account<'info, UserPoolLiquidity>>,
+
+    /// User pool liquidity account of the receiver
+    #[account(
+        init_if_needed,
+        seeds = [
+            USER_POOL_LIQUIDITY_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+            to_user.key().as_ref(),
+        ],
+        bump,
+        payer = owner,
+        space = UserPoolLiquidity::LEN,
+    )]
+    pub to_user_pool_liquidity: Box<Account<'info, UserPoolLiquidity>>,
+
+    pub system_program: Program<'info, System>,
+
+    /// The partners of the pool, required when the user is linked with a partner, see `update_partner_lp_tokens`
+    #[account(
+        mut,
+        seeds = [
+            POOL_PARTNERS_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub pool_partners: Option<Account<'info, PoolPartners>>,
+}
+
+/// Moves `lp_token_amount` of the position of the owner to the receiver.
+/// The deposited and withdrawn amounts are moved pro-rata, so the cost basis of both positions is kept.
+///
+/// Remaining accounts are triplets of (reward_info, user_reward_info of owner, user_reward_info of receiver)
+/// for each active reward of the pool, pending rewards of both users are settled before the balances change.
+pub fn transfer_liquidity<'c, 'info>(
+    ctx: Context<'_, '_, 'c, 'info, TransferLiquidity<'info>>,
+    lp_token_amount: u64,
+) -> Result<()>
+where
+    'c: 'info,
+{
+    require_gt!(lp_token_amount, 0);
+    let pool_id = ctx.accounts.pool_state.key();
+    let pool_state = &mut ctx.accounts.pool_state.load_mut()?;
+    let current_time = Clock::get()?.unix_timestamp as u64;
+
+    let from = &mut ctx.accounts.user_pool_liquidity;
+    let to = &mut ctx.accounts.to_user_pool_liquidity;
+    if to.pool_state == Pubkey::default() {
+        to.initialize(ctx.accounts.to_user.key(), pool_id, None, current_time);
+    }
+
+    require_gte!(
+        from.lp_tokens_owned,
+        u128::from(lp_token_amount),
+        GammaError::InvalidLpTokenAmount
+    );
+    from.check_unlocked_lp_tokens(lp_token_amount)?;
+
+    let from_lp_tokens_owned = from
+        .lp_tokens_owned
+        .checked_sub(u128::from(lp_token_amount))
+        .ok_or(GammaError::MathOverflow)?;
+    let to_lp_tokens_owned = to
+        .lp_tokens_owned
+        .checked_add(u128::from(lp_token_amount))
+        .ok_or(GammaError::MathOverflow)?;
+
+    let remaining_accounts = ctx.remaining_accounts;
+    let reward_accounts_len = usize::try_from(pool_state.active_reward_infos)
+        .map_err(|_| error!(GammaError::MathOverflow))?
+        .checked_mul(3)
+        .ok_or(GammaError::MathOverflow)?;
+    require_eq!(
+        remaining_accounts.len(),
+        reward_accounts_len,
+        ErrorCode::AccountNotEnoughKeys
+    );
+    let reward_lp_supply = pool_state.reward_lp_supply()?;
+    for (index, accounts) in remaining_accounts.chunks(3).enumerate() {
+        // Every active reward info must be settled once
+        require!(
+            remaining_accounts[..index * 3]
+                .iter()
+                .step_by(3)
+                .all(|settled| settled.key() != accounts[0].key()),
+            GammaError::InvalidInput
+        );
+        let mut reward_info = update_active_reward_info(
+            &accounts[0],
+            pool_id,
+            pool_state,
+            reward_lp_supply,
+            current_time,
+        )?;
+        settle_user_rewards(
+            &mut reward_info,
+            &accounts[1],
+            from,
+            from_lp_tokens_owned,
+            &ctx.accounts.owner,
+            &ctx.accounts.system_program,
+        )?;
+        settle_user_rewards(
+            &mut reward_info,
+            &accounts[2],
+            to,
+            to_lp_tokens_owned,
+            &ctx.accounts.owner,
+            &ctx.accounts.system_program,
+        )?;
+        reward_info.exit(&crate::id())?;
+    }
+
+    // token_x_moved = token_x * lp_token_amount / lp_tokens_owned
+    let pro_rata = |amount: u128| -> Result<u128> {
+        Ok(amount
+            .checked_mul(u128::from(lp_token_amount))
+            .ok_or(GammaError::MathOverflow)?
+            .checked_div(from.lp_tokens_owned)
+            .ok_or(GammaError::MathOverflow)?)
+    };
+    let token_0_deposited = pro_rata(from.token_0_deposited)?;
+    let token_1_deposited = pro_rata(from.token_1_deposited)?;
+    let token_0_withdrawn = pro_rata(from.token_0_withdrawn)?;
+    let token_1_withdrawn = pro_rata(from.token_1_withdrawn)?;
+
+    from.update_fees_earned(pool_state)?;
+    from.update_lp_tokens_changed_at()?;
+    from.lp_tokens_owned = from_lp_tokens_owned;
+    from.token_0_deposited = from
+        .token_0_deposited
+        .checked_sub(token_0_deposited)
+        .ok_or(GammaError::MathOverflow)?;
+    from.token_1_deposited = from
+        .token_1_deposited
+        .checked_sub(token_1_deposited)
+        .ok_or(GammaError::MathOverflow)?;
+    from.token_0_withdrawn = from
+        .token_0_withdrawn
+        .checked_sub(token_0_withdrawn)
+        .ok_or(GammaError::MathOverflow)?;
+    from.token_1_withdrawn = from
+        .token_1_withdrawn
+        .checked_sub(token_1_withdrawn)
+        .ok_or(GammaError::MathOverflow)?;
+
+    to.update_fees_earned(pool_state)?;
+    to.update_lp_tokens_changed_at()?;
+    to.lp_tokens_owned = to_lp_tokens_owned;
+    to.token_0_deposited = to
+        .token_0_deposited
+        .checked_add(token_0_deposited)
+        .ok_or(GammaError::MathOverflow)?;
+    to.token_1_deposited = to
+        .token_1_deposited
+        .checked_add(token_1_deposited)
+        .ok_or(GammaError::MathOverflow)?;
+    to.token_0_withdrawn = to
+        .token_0_withdrawn
+        .checked_add(token_0_withdrawn)
+        .ok_or(GammaError::MathOverflow)?;
+    to.token_1_withdrawn = to
+        .token_1_withdrawn
+        .checked_add(token_1_withdrawn)
+        .ok_or(GammaError::MathOverflow)?;
+
+    update_partner_lp_tokens(
+        pool_state,
+        ctx.accounts.pool_partners.as_deref_mut(),
+        from.partner,
+        0,
+        lp_token_amount,
+    )?;
+    update_partner_lp_tokens(
+        pool_state,
+        ctx.accounts.pool_partners.as_deref_mut(),
+        to.partner,
+        lp_token_amount,
+        0,
+    )?;
+
+    Ok(())
+}
+
+/// Settles the rewards of the user before their lp tokens change, `lp_tokens_owned` are the lp tokens after the change
+/// and the locked lp tokens of the user are boosted.
+/// The user reward info account is created if it does not exist yet.
+fn settle_user_rewards<'info>(
+    reward_info: &mut Account<'info, RewardInfo>,
+    user_reward_info: &AccountInfo<'info>,
+    user_pool_liquidity: &UserPoolLiquidity,
+    lp_tokens_owned: u128,
+    payer: &Signer<'info>,
+    system_program: &Program<'info, System>,
+) -> Result<()> {
+    let reward_info_key = reward_info.key();
+    let (expected_user_reward_info, bump) = Pubkey::find_program_address(
+        &[
+            USER_REWARD_INFO_SEED.as_bytes(),
+            reward_info_key.as_ref(),
+            user_pool_liquidity.user.as_ref(),
+        ],
+        &crate::id(),
+    );
+    require_keys_eq!(
+        user_reward_info.key(),
+        expected_user_reward_info,
+        ErrorCode::ConstraintSeeds
+    );
+
+    let mut user_reward_info_state = if user_reward_info.data_is_empty() {
+        create_pda_account(
+            user_reward_info,
+            payer,
+            system_program,
+            UserRewardInfo::LEN,
+            &[&[
+                USER_REWARD_INFO_SEED.as_bytes(),
+                reward_info_key.as_ref(),
+                user_pool_liquidity.user.as_ref(),
+                &[bump],
+            ]],
+        )?;
+        let mut user_reward_info_state = UserRewardInfo::default();
+        // Only the active rewards are settled, see `update_active_reward_info`
+        user_reward_info_state.initialize(
+            user_pool_liquidity.user,
+            reward_info_key,
+            reward_info,
+            true,
+            u64::try_from(user_pool_liquidity.reward_weight(user_pool_liquidity.lp_tokens_owned)?)
+                .map_err(|_| error!(GammaError::MathOverflow))?,
+            user_pool_liquidity.lp_tokens_changed_at,
+        );
+        reward_info.user_reward_infos = reward_info
+            .user_reward_infos
+            .checked_add(1)
+            .ok_or(GammaError::MathOverflow)?;
+        user_reward_info_state
+    } else {
+        require_keys_eq!(
+            *user_reward_info.owner,
+            crate::id(),
+            ErrorCode::AccountOwnedByWrongProgram
+        );
+        UserRewardInfo::try_deserialize(&mut &user_reward_info.try_borrow_data()?[..])?
+    };
+
+    user_reward_info_state.calculate_claimable_rewards(
+        u64::try_from(user_pool_liquidity.reward_weight(lp_tokens_owned)?)
+            .map_err(|_| error!(GammaError::MathOverflow))?,
+        reward_info,
+    )?;
+
+    user_reward_info_state.try_serialize(&mut &mut user_reward_info.try_borrow_mut_data()?[..])
+}
+
+/// Creates a program account at a PDA like the anchor `init` constraint does,
+/// the lamports already sent to the address are kept and only the missing rent is paid.
+fn create_pda_account<'info>(
+    account: &AccountInfo<'info>,
+    payer: &Signer<'info>,
+    system_program: &Program<'info, System>,
+    space: usize,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    let rent = Rent::get()?.minimum_balance(space);
+    let current_lamports = account.lamports();
+    if current_lamports == 0 {
+        return anchor_lang::system_program::create_account(
+            CpiContext::new_with_signer(
+                system_program.to_account_info(),
+                anchor_lang::system_program::CreateAccount {
+                    from: payer.to_account_info(),
+                    to: account.clone(),
+                },
+                signer_seeds,
+            ),
+            rent,
+            space as u64,
+            &crate::id(),
+        );
+    }
+
+    let missing_lamports = rent.saturating_sub(current_lamports);
+    if missing_lamports > 0 {
+        anchor_lang::system_program::transfer(
+            CpiContext::new(
+                system_program.to_account_info(),
+                anchor_lang::system_program::Transfer {
+                    from: payer.to_account_info(),
+                    to: account.clone(),
+                },
+            ),
+            missing_lamports,
+        )?;
+    }
+    anchor_lang::system_program::allocate(
+        CpiContext::new_with_signer(
+            system_program.to_account_info(),
+            anchor_lang::system_program::Allocate {
+                account_to_allocate: account.clone(),
+            },
+            signer_seeds,
+        ),
+        space as u64,
+    )?;
+    anchor_lang::system_program::assign(
+        CpiContext::new_with_signer(
+            system_program.to_account_info(),
+            anchor_lang::system_program::Assign {
+                account_to_assign: account.clone(),
+            },
+            signer_seeds,
+        ),
+        &crate::id(),
+    )
+}
@@ -0,0 +1,72 @@
+use crate::{
+    error::GammaError,
+    instructions::settle_rewards,
+    states::{PoolState, UserPoolLiquidity, USER_POOL_LIQUIDITY_SEED},
+};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct UnlockLpTokens<'info> {
+    /// Anyone can unlock the pool tokens once the lock has expired, removing their boost
+    pub signer: Signer<'info>,
+
+    #[account(mut)]
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    /// User pool liquidity account
+    #[account(
+        mut,
+        seeds = [
+            USER_POOL_LIQUIDITY_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+            user_pool_liquidity.user.as_ref(),
+        ],
+        bump,
+    )]
+    pub user_pool_liquidity: Account<'info, UserPoolLiquidity>,
+}
+
+/// Unlocks the locked pool tokens of `UserPoolLiquidity` after `lp_unlock_at`.
+/// The remaining accounts are the active rewards of the pool, see `settle_rewards`.
+pub fn unlock_lp_tokens<'c, 'info>(
+    ctx: Context<'_, '_, 'c, 'info, UnlockLpTokens<'info>>,
+) -> Result<()>
+where
+    'c: 'info,
+{
+    let current_time = Clock::get()?.unix_timestamp as u64;
+    let pool_id = ctx.accounts.pool_state.key();
+    let pool_state = &mut ctx.accounts.pool_state.load_mut()?;
+    let user_pool_liquidity = &mut ctx.accounts.user_pool_liquidity;
+    require_gt!(
+        user_pool_liquidity.locked_lp_tokens,
+        0,
+        GammaError::InvalidLpLock
+    );
+    require_gte!(
+        current_time,
+        user_pool_liquidity.lp_unlock_at,
+        GammaError::LpTokensLocked
+    );
+
+    let lp_lock_boost_weight = user_pool_liquidity.lp_lock_boost_weight()?;
+    user_pool_liquidity.locked_lp_tokens = 0;
+    user_pool_liquidity.lp_unlock_at = 0;
+    user_pool_liquidity.lp_lock_boost_rate = 0;
+    user_pool_liquidity.update_lp_tokens_changed_at()?;
+
+    // The rewards are settled with the reward weight of the pool before the unlock
+    settle_rewards(
+        ctx.remaining_accounts,
+        pool_id,
+        pool_state,
+        user_pool_liquidity,
+        user_pool_liquidity.lp_tokens_owned,
+    )?;
+    pool_state.lp_lock_boost_weight = pool_state
+        .lp_lock_boost_weight
+        .checked_sub(lp_lock_boost_weight)
+        .ok_or(GammaError::MathOverflow)?;
+
+    Ok(())
+}
@@ -0,0 +1,120 @@
+use crate::{
+    error::GammaError,
+    instructions::settle_rewards,
+    states::{
+        update_partner_lp_tokens, PoolPartners, PoolState, UserPoolLiquidity, POOL_PARTNERS_SEED,
+        USER_POOL_LIQUIDITY_SEED,
+    },
+    utils::token_burn,
+};
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    token::Token,
+    token_interface::{Mint, TokenAccount},
+};
+
+#[derive(Accounts)]
+pub struct UnwrapLpTokens<'info> {
+    /// Owner of the lp tokens, receives the liquidity
+    pub owner: Signer<'info>,
+
+    #[account(mut)]
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    /// User pool liquidity account, see `init_user_pool_liquidity`
+    #[account(
+        mut,
+        seeds = [
+            USER_POOL_LIQUIDITY_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+            owner.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub user_pool_liquidity: Account<'info, UserPoolLiquidity>,
+
+    /// Spl mint of the pool tokens
+    #[account(
+        mut,
+        address = pool_state.load()?.lp_mint @ GammaError::IncorrectLpMint
+    )]
+    pub lp_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// The token account the lp tokens are burned from
+    #[account(
+        mut,
+        token::mint = lp_mint,
+        token::authority = owner,
+    )]
+    pub lp_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+
+    /// The partners of the pool, required when the user is linked with a partner, see `update_partner_lp_tokens`
+    #[account(
+        mut,
+        seeds = [
+            POOL_PARTNERS_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub pool_partners: Option<Account<'info, PoolPartners>>,
+}
+
+/// Burns spl lp tokens and credits them back to `UserPoolLiquidity`.
+/// The remaining accounts are the active rewards of the pool, see `settle_rewards`.
+pub fn unwrap_lp_tokens<'c, 'info>(
+    ctx: Context<'_, '_, 'c, 'info, UnwrapLpTokens<'info>>,
+    lp_token_amount: u64,
+) -> Result<()>
+where
+    'c: 'info,
+{
+    require_gt!(lp_token_amount, 0);
+    let pool_id = ctx.accounts.pool_state.key();
+    let pool_state = &mut ctx.accounts.pool_state.load_mut()?;
+    require!(pool_state.has_lp_mint(), GammaError::IncorrectLpMint);
+    settle_rewards(
+        ctx.remaining_accounts,
+        pool_id,
+        pool_state,
+        &ctx.accounts.user_pool_liquidity,
+        ctx.accounts
+            .user_pool_liquidity
+            .lp_tokens_owned
+            .checked_add(u128::from(lp_token_amount))
+            .ok_or(GammaError::MathOverflow)?,
+    )?;
+
+    token_burn(
+        ctx.accounts.owner.to_account_info(),
+        ctx.accounts.token_program.to_account_info(),
+        ctx.accounts.lp_mint.to_account_info(),
+        ctx.accounts.lp_token_account.to_account_info(),
+        lp_token_amount,
+        &[],
+    )?;
+
+    pool_state.wrapped_lp_supply = pool_state
+        .wrapped_lp_supply
+        .checked_sub(lp_token_amount)
+        .ok_or(GammaError::MathOverflow)?;
+    let user_pool_liquidity = &mut ctx.accounts.user_pool_liquidity;
+    user_pool_liquidity.update_fees_earned(pool_state)?;
+    user_pool_liquidity.update_lp_tokens_changed_at()?;
+    user_pool_liquidity.lp_tokens_owned = user_pool_liquidity
+        .lp_tokens_owned
+        .checked_add(u128::from(lp_token_amount))
+        .ok_or(GammaError::MathOverflow)?;
+
+    update_partner_lp_tokens(
+        pool_state,
+        ctx.accounts.pool_partners.as_deref_mut(),
+        user_pool_liquidity.partner,
+        lp_token_amount,
+        0,
+    )?;
+
+    Ok(())
+}
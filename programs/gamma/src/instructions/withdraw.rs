@@ -5,14 +5,17 @@ use anchor_spl::token_interface::{Mint, Token2022, TokenAccount};
 use crate::curve::{CurveCalculator, RoundDirection};
 use crate::external::kamino::KaminoProgram;
 use crate::states::{
-    LpChangeEvent, PartnerType, PoolStatusBitIndex, UserPoolLiquidity, POOL_KAMINO_DEPOSITS_SEED,
-    USER_POOL_LIQUIDITY_SEED,
+    update_partner_lp_tokens, LpChangeEvent, PoolPartners, PoolStatusBitIndex, UserPoolLiquidity,
+    POOL_KAMINO_DEPOSITS_SEED, POOL_PARTNERS_SEED, USER_POOL_LIQUIDITY_SEED,
 };
-use crate::utils::{get_transfer_fee, transfer_from_pool_vault_to_user};
+use crate::utils::{get_transfer_fee, get_transfer_inverse_fee, transfer_from_pool_vault_to_user};
 use crate::{error::GammaError, states::PoolState};
 use anchor_lang::solana_program::sysvar::instructions::ID as INSTRUCTION_SYSVAR_ID;
 
-use super::calculate_amount_to_be_withdrawn_from_kamino_in_withdraw_instruction_in_liquidity_tokens;
+use super::{
+    calculate_amount_to_be_withdrawn_from_kamino_in_withdraw_instruction_in_liquidity_tokens,
+    settle_rewards,
+};
 
 #[derive(Accounts)]
 pub struct Withdraw<'info> {
@@ -106,8 +109,21 @@ pub struct Withdraw<'info> {
     #[account(address = INSTRUCTION_SYSVAR_ID )]
     /// CHECK: The native instructions sysvar
     pub instruction_sysvar_account: UncheckedAccount<'info>,
+
+    /// The partners of the pool, required when the user is linked with a partner, see `update_partner_lp_tokens`
+    #[account(
+        mut,
+        seeds = [
+            POOL_PARTNERS_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub pool_partners: Option<Account<'info, PoolPartners>>,
 }
 
+/// The remaining accounts are the kamino accounts when the vaults have to be withdrawn from kamino,
+/// followed by the active rewards of the pool, see `settle_rewards`.
 pub fn withdraw<'c, 'info>(
     ctx: Context<'_, '_, 'c, 'info, Withdraw<'info>>,
     lp_token_amount: u64,
@@ -125,8 +141,24 @@ where
     }
     require_gt!(pool_state.lp_supply, 0);
 
+    ctx.accounts
+        .user_pool_liquidity
+        .check_unlocked_lp_tokens(lp_token_amount)?;
+    settle_rewards(
+        ctx.remaining_accounts,
+        pool_id,
+        pool_state,
+        &ctx.accounts.user_pool_liquidity,
+        ctx.accounts
+            .user_pool_liquidity
+            .lp_tokens_owned
+            .checked_sub(u128::from(lp_token_amount))
+            .ok_or(GammaError::MathOverflow)?,
+    )?;
+
     let (total_token_0_amount, total_token_1_amount) = pool_state.vault_amount_without_fee()?;
     let results = CurveCalculator::lp_tokens_to_trading_tokens(
+        pool_state.curve_type()?,
         u128::from(lp_token_amount),
         u128::from(pool_state.lp_supply),
         u128::from(total_token_0_amount),
@@ -199,14 +231,28 @@ where
         change_type: 1
     });
 
-    withdraw_from_kamino_if_needed(&ctx, pool_state, token_0_amount, true)?;
-    withdraw_from_kamino_if_needed(&ctx, pool_state, token_1_amount, false)?;
+    withdraw_from_kamino_if_needed(
+        ctx.accounts,
+        ctx.remaining_accounts,
+        pool_state,
+        token_0_amount,
+        true,
+    )?;
+    withdraw_from_kamino_if_needed(
+        ctx.accounts,
+        ctx.remaining_accounts,
+        pool_state,
+        token_1_amount,
+        false,
+    )?;
 
     pool_state.lp_supply = pool_state
         .lp_supply
         .checked_sub(lp_token_amount)
         .ok_or(GammaError::MathOverflow)?;
     let user_pool_liquidity = &mut ctx.accounts.user_pool_liquidity;
+    user_pool_liquidity.update_fees_earned(pool_state)?;
+    user_pool_liquidity.update_lp_tokens_changed_at()?;
     user_pool_liquidity.lp_tokens_owned = user_pool_liquidity
         .lp_tokens_owned
         .checked_sub(u128::from(lp_token_amount))
@@ -220,19 +266,13 @@ where
         .checked_add(u128::from(receive_token_1_amount))
         .ok_or(GammaError::MathOverflow)?;
 
-    if let Some(user_pool_liquidity_partner) = user_pool_liquidity.partner {
-        let mut pool_state_partners = pool_state.partners;
-        let partner: Option<&mut crate::states::PartnerInfo> = pool_state_partners
-            .iter_mut()
-            .find(|p| PartnerType::new(p.partner_id) == user_pool_liquidity_partner);
-        if let Some(partner) = partner {
-            partner.lp_token_linked_with_partner = partner
-                .lp_token_linked_with_partner
-                .checked_sub(lp_token_amount)
-                .ok_or(GammaError::MathOverflow)?;
-        }
-        pool_state.partners = pool_state_partners;
-    }
+    update_partner_lp_tokens(
+        pool_state,
+        ctx.accounts.pool_partners.as_deref_mut(),
+        user_pool_liquidity.partner,
+        0,
+        lp_token_amount,
+    )?;
 
     transfer_from_pool_vault_to_user(
         ctx.accounts.authority.to_account_info(),
@@ -278,6 +318,70 @@ where
     Ok(())
 }
 
+/// Withdraws at least the given token amounts, burning the smallest lp token amount that pays for them.
+/// The token amounts are received after the transfer fees of the tokens.
+pub fn withdraw_exact_tokens<'c, 'info>(
+    ctx: Context<'_, '_, 'c, 'info, Withdraw<'info>>,
+    token_0_amount: u64,
+    token_1_amount: u64,
+    maximum_lp_token_amount: u64,
+) -> Result<()>
+where
+    'c: 'info,
+{
+    let lp_token_amount = {
+        let pool_state = ctx.accounts.pool_state.load()?;
+        let curve_type = pool_state.curve_type()?;
+        let (total_token_0_amount, total_token_1_amount) = pool_state.vault_amount_without_fee()?;
+
+        // The pool has to send the amounts plus the transfer fees
+        let token_0_amount_with_fee = match token_0_amount {
+            0 => 0,
+            _ => token_0_amount
+                .checked_add(get_transfer_inverse_fee(
+                    &ctx.accounts.vault_0_mint.to_account_info(),
+                    token_0_amount,
+                )?)
+                .ok_or(GammaError::MathOverflow)?,
+        };
+        let token_1_amount_with_fee = match token_1_amount {
+            0 => 0,
+            _ => token_1_amount
+                .checked_add(get_transfer_inverse_fee(
+                    &ctx.accounts.vault_1_mint.to_account_info(),
+                    token_1_amount,
+                )?)
+                .ok_or(GammaError::MathOverflow)?,
+        };
+
+        let lp_tokens_for_token_0 = CurveCalculator::token_0_to_lp_tokens(
+            curve_type,
+            u128::from(token_0_amount_with_fee),
+            u128::from(total_token_0_amount),
+            u128::from(pool_state.lp_supply),
+            RoundDirection::Ceiling,
+        )
+        .ok_or(GammaError::ZeroTradingTokens)?;
+        let lp_tokens_for_token_1 = CurveCalculator::token_1_to_lp_tokens(
+            curve_type,
+            u128::from(token_1_amount_with_fee),
+            u128::from(total_token_1_amount),
+            u128::from(pool_state.lp_supply),
+            RoundDirection::Ceiling,
+        )
+        .ok_or(GammaError::ZeroTradingTokens)?;
+        u64::try_from(lp_tokens_for_token_0.max(lp_tokens_for_token_1))
+            .map_err(|_| GammaError::MathOverflow)?
+    };
+    require_gte!(
+        maximum_lp_token_amount,
+        lp_token_amount,
+        GammaError::ExceededSlippage
+    );
+
+    withdraw(ctx, lp_token_amount, token_0_amount, token_1_amount)
+}
+
 #[derive(Accounts)]
 pub struct RemainingKaminoAccounts<'info> {
     /// Account is checked in cpi
@@ -310,7 +414,8 @@ pub struct RemainingKaminoAccounts<'info> {
 }
 
 pub fn withdraw_from_kamino_if_needed<'c, 'info>(
-    ctx: &Context<'_, '_, 'c, 'info, Withdraw<'info>>,
+    accounts: &Withdraw<'info>,
+    remaining_accounts: &'c [AccountInfo<'info>],
     pool_state: &mut PoolState,
     token_amount_being_withdrawn: u64,
     token0_or_token1: bool,
@@ -318,10 +423,9 @@ pub fn withdraw_from_kamino_if_needed<'c, 'info>(
 where
     'c: 'info,
 {
-    let remaining_accounts = &ctx.remaining_accounts;
     let token_vault = match token0_or_token1 {
-        true => &ctx.accounts.token_0_vault,
-        false => &ctx.accounts.token_1_vault,
+        true => &accounts.token_0_vault,
+        false => &accounts.token_1_vault,
     };
 
     let amount_to_withdraw_from_kamino_in_liquidity_tokens =
@@ -351,10 +455,10 @@ where
     };
 
     // Verify gamma_pool_destination_collateral seeds are correct
-    let pool_state_key = ctx.accounts.pool_state.key();
+    let pool_state_key = accounts.pool_state.key();
     let reserve_liquidity_mint = match token0_or_token1 {
-        true => ctx.accounts.vault_0_mint.to_account_info(),
-        false => ctx.accounts.vault_1_mint.to_account_info(),
+        true => accounts.vault_0_mint.to_account_info(),
+        false => accounts.vault_1_mint.to_account_info(),
     };
     let expected_seeds = [
         POOL_KAMINO_DEPOSITS_SEED.as_bytes(),
@@ -369,16 +473,16 @@ where
     let signer_seeds: &[&[&[u8]]] = &[&[crate::AUTH_SEED.as_bytes(), &[pool_state.auth_bump]]];
 
     let liquidity_token_program =
-        if token_vault.to_account_info().owner == ctx.accounts.token_program.key {
-            ctx.accounts.token_program.to_account_info()
+        if token_vault.to_account_info().owner == accounts.token_program.key {
+            accounts.token_program.to_account_info()
         } else {
-            ctx.accounts.token_program_2022.to_account_info()
+            accounts.token_program_2022.to_account_info()
         };
 
     let kamino_withdraw_cpi_ctx = CpiContext::new_with_signer(
-        ctx.accounts.kamino_program.to_account_info(),
+        accounts.kamino_program.to_account_info(),
         crate::external::kamino::kamino::cpi::accounts::RedeemReserveCollateral {
-            owner: ctx.accounts.authority.to_account_info(),
+            owner: accounts.authority.to_account_info(),
             reserve: kamino_accounts.kamino_reserve_token.to_account_info(),
             lending_market: kamino_accounts.kamino_lending_market,
             reserve_liquidity_mint,
@@ -389,9 +493,9 @@ where
                 .gamma_pool_destination_collateral
                 .to_account_info(),
             user_destination_liquidity: token_vault.to_account_info(),
-            collateral_token_program: ctx.accounts.token_program.to_account_info(),
+            collateral_token_program: accounts.token_program.to_account_info(),
             liquidity_token_program,
-            instruction_sysvar_account: ctx.accounts.instruction_sysvar_account.to_account_info(),
+            instruction_sysvar_account: accounts.instruction_sysvar_account.to_account_info(),
         },
         signer_seeds,
     );
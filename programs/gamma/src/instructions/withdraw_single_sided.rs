@@ -0,0 +1,295 @@
+use anchor_lang::prelude::*;
+
+use crate::curve::{CurveCalculator, RoundDirection, TradeDirection};
+use crate::error::GammaError;
+use crate::fees::FeePath;
+use crate::states::{
+    oracle, update_partner_lp_tokens, AmmConfig, LpChangeEvent, ObservationState, ObservationsMut,
+    PoolStatusBitIndex,
+};
+use crate::utils::{get_transfer_fee, transfer_from_pool_vault_to_user};
+
+// The composite `Withdraw` accounts need the client modules generated next to it
+use super::{settle_rewards, withdraw::*, BaseInputSwap};
+
+#[derive(Accounts)]
+pub struct WithdrawSingleSided<'info> {
+    /// Same accounts as `withdraw`, only the token account of the withdrawn token receives tokens
+    pub withdraw: Withdraw<'info>,
+
+    /// The factory state to read protocol fees
+    #[account(address = withdraw.pool_state.load()?.amm_config)]
+    pub amm_config: Box<Account<'info, AmmConfig>>,
+
+    /// The program account for the most recent oracle observation
+    #[account(mut, address = withdraw.pool_state.load()?.observation_key)]
+    pub observation_state: AccountLoader<'info, ObservationState>,
+}
+
+/// Withdraws liquidity in a single token.
+/// The share of the other token is swapped through the curve of the pool against the reserves
+/// left after the withdrawal, charging the dynamic fee, so it never leaves the pool vault.
+/// The remaining accounts are the same as the ones of `withdraw`.
+pub fn withdraw_single_sided<'c, 'info>(
+    ctx: Context<'_, '_, 'c, 'info, WithdrawSingleSided<'info>>,
+    lp_token_amount: u64,
+    minimum_amount_out: u64,
+    withdraw_token_0: bool,
+) -> Result<()>
+where
+    'c: 'info,
+{
+    require_gt!(lp_token_amount, 0);
+    let block_timestamp = Clock::get()?.unix_timestamp as u64;
+    let accounts = &mut ctx.accounts.withdraw;
+    let pool_id = accounts.pool_state.key();
+    let pool_state = &mut accounts.pool_state.load_mut()?;
+    if !pool_state.get_status_by_bit(PoolStatusBitIndex::Withdraw)
+        || !pool_state.get_status_by_bit(PoolStatusBitIndex::Swap)
+        || block_timestamp < pool_state.open_time
+    {
+        return err!(GammaError::NotApproved);
+    }
+    require_gt!(pool_state.lp_supply, lp_token_amount);
+    accounts
+        .user_pool_liquidity
+        .check_unlocked_lp_tokens(lp_token_amount)?;
+    settle_rewards(
+        ctx.remaining_accounts,
+        pool_id,
+        pool_state,
+        &accounts.user_pool_liquidity,
+        accounts
+            .user_pool_liquidity
+            .lp_tokens_owned
+            .checked_sub(u128::from(lp_token_amount))
+            .ok_or(GammaError::MathOverflow)?,
+    )?;
+
+    let (token_0_price_x64_before_swap, token_1_price_x64_before_swap) =
+        pool_state.token_price_x32()?;
+
+    let (total_token_0_amount, total_token_1_amount) = pool_state.vault_amount_without_fee()?;
+    let results = CurveCalculator::lp_tokens_to_trading_tokens(
+        pool_state.curve_type()?,
+        u128::from(lp_token_amount),
+        u128::from(pool_state.lp_supply),
+        u128::from(total_token_0_amount),
+        u128::from(total_token_1_amount),
+        RoundDirection::Floor,
+    )
+    .ok_or(GammaError::ZeroTradingTokens)?;
+    if results.token_0_amount == 0 || results.token_1_amount == 0 {
+        return err!(GammaError::ZeroTradingTokens);
+    }
+    let token_0_amount =
+        u64::try_from(results.token_0_amount).map_err(|_| GammaError::MathOverflow)?;
+    let token_1_amount =
+        u64::try_from(results.token_1_amount).map_err(|_| GammaError::MathOverflow)?;
+
+    emit!(LpChangeEvent {
+        pool_id,
+        lp_amount_before: pool_state.lp_supply,
+        token_0_vault_before: total_token_0_amount,
+        token_1_vault_before: total_token_1_amount,
+        token_0_amount,
+        token_1_amount,
+        // Nothing is transferred out for the withdrawal itself, the transfer fee is reported with the swap
+        token_0_transfer_fee: 0,
+        token_1_transfer_fee: 0,
+        change_type: 1
+    });
+
+    // The share of the other token is swapped against the reserves left after the withdrawal
+    let token_0_vault_after_withdraw = total_token_0_amount
+        .checked_sub(token_0_amount)
+        .ok_or(GammaError::MathOverflow)?;
+    let token_1_vault_after_withdraw = total_token_1_amount
+        .checked_sub(token_1_amount)
+        .ok_or(GammaError::MathOverflow)?;
+    let (
+        trade_direction,
+        amount_to_swap,
+        amount_withdrawn,
+        total_input_token_amount,
+        total_output_token_amount,
+    ) = match withdraw_token_0 {
+        true => (
+            TradeDirection::OneForZero,
+            token_1_amount,
+            token_0_amount,
+            token_1_vault_after_withdraw,
+            token_0_vault_after_withdraw,
+        ),
+        false => (
+            TradeDirection::ZeroForOne,
+            token_0_amount,
+            token_1_amount,
+            token_0_vault_after_withdraw,
+            token_1_vault_after_withdraw,
+        ),
+    };
+
+    let mut observation_state = ObservationsMut::load(&ctx.accounts.observation_state)?;
+    let result = match CurveCalculator::swap_base_input(
+        u128::from(amount_to_swap),
+        u128::from(total_input_token_amount),
+        u128::from(total_output_token_amount),
+        trade_direction,
+        &ctx.accounts.amm_config,
+        &pool_state,
+        block_timestamp,
+        &observation_state.observations(),
+        FeePath::Standard,
+    ) {
+        Ok(value) => value,
+        Err(_) => return err!(GammaError::ZeroTradingTokens),
+    };
+    CurveCalculator::check_invariant(
+        u128::from(total_input_token_amount),
+        u128::from(total_output_token_amount),
+        result
+            .new_swap_source_amount
+            .checked_sub(result.dynamic_fee)
+            .ok_or(GammaError::MathOverflow)?,
+        result.new_swap_destination_amount,
+        trade_direction,
+        &pool_state,
+    )?;
+
+    let destination_amount_swapped =
+        u64::try_from(result.destination_amount_swapped).or(err!(GammaError::MathOverflow))?;
+    let swap = BaseInputSwap {
+        amount_in: amount_to_swap,
+        // The swapped tokens never leave the pool vault
+        transfer_fee: 0,
+        // The slippage is checked on the withdrawn and swapped tokens together
+        minimum_amount_out: 0,
+        trade_direction,
+        total_input_token_amount,
+        total_output_token_amount,
+        token_0_price_x32: token_0_price_x64_before_swap,
+        token_1_price_x32: token_1_price_x64_before_swap,
+        fee_path: FeePath::Standard,
+        result,
+    };
+
+    let amount_out = amount_withdrawn
+        .checked_add(destination_amount_swapped)
+        .ok_or(GammaError::MathOverflow)?;
+    let (output_vault, output_token_account, output_mint) = match withdraw_token_0 {
+        true => (
+            &accounts.token_0_vault,
+            &accounts.token_0_account,
+            &accounts.vault_0_mint,
+        ),
+        false => (
+            &accounts.token_1_vault,
+            &accounts.token_1_account,
+            &accounts.vault_1_mint,
+        ),
+    };
+    let output_transfer_fee = get_transfer_fee(&output_mint.to_account_info(), amount_out)?;
+    let amount_received = amount_out
+        .checked_sub(output_transfer_fee)
+        .ok_or(GammaError::MathOverflow)?;
+    require_gt!(amount_received, 0);
+    require_gte!(
+        amount_received,
+        minimum_amount_out,
+        GammaError::ExceededSlippage
+    );
+
+    swap.emit_event(
+        pool_id,
+        match withdraw_token_0 {
+            true => accounts.token_1_vault.mint,
+            false => accounts.token_0_vault.mint,
+        },
+        output_vault.mint,
+        output_transfer_fee,
+    )?;
+
+    // Redeem from kamino before the vault amounts are updated, it uses the amounts before the withdrawal
+    withdraw_from_kamino_if_needed(
+        accounts,
+        ctx.remaining_accounts,
+        pool_state,
+        amount_out,
+        withdraw_token_0,
+    )?;
+
+    // The withdrawn tokens leave the reserves, then the swap adds the swapped token back, except for the fees
+    pool_state.token_0_vault_amount = token_0_vault_after_withdraw;
+    pool_state.token_1_vault_amount = token_1_vault_after_withdraw;
+    swap.accrue(pool_state, 0, 0)?;
+
+    pool_state.lp_supply = pool_state
+        .lp_supply
+        .checked_sub(lp_token_amount)
+        .ok_or(GammaError::MathOverflow)?;
+    let user_pool_liquidity = &mut accounts.user_pool_liquidity;
+    user_pool_liquidity.update_fees_earned(pool_state)?;
+    user_pool_liquidity.update_lp_tokens_changed_at()?;
+    user_pool_liquidity.lp_tokens_owned = user_pool_liquidity
+        .lp_tokens_owned
+        .checked_sub(u128::from(lp_token_amount))
+        .ok_or(GammaError::MathOverflow)?;
+    match withdraw_token_0 {
+        true => {
+            user_pool_liquidity.token_0_withdrawn = user_pool_liquidity
+                .token_0_withdrawn
+                .checked_add(u128::from(amount_received))
+                .ok_or(GammaError::MathOverflow)?;
+        }
+        false => {
+            user_pool_liquidity.token_1_withdrawn = user_pool_liquidity
+                .token_1_withdrawn
+                .checked_add(u128::from(amount_received))
+                .ok_or(GammaError::MathOverflow)?;
+        }
+    }
+
+    update_partner_lp_tokens(
+        pool_state,
+        accounts.pool_partners.as_deref_mut(),
+        user_pool_liquidity.partner,
+        0,
+        lp_token_amount,
+    )?;
+
+    #[cfg(feature = "enable-log")]
+    msg!(
+        "token_0_amount:{}, token_1_amount:{}, destination_amount_swapped:{}, amount_received:{}",
+        token_0_amount,
+        token_1_amount,
+        destination_amount_swapped,
+        amount_received
+    );
+
+    transfer_from_pool_vault_to_user(
+        accounts.authority.to_account_info(),
+        output_vault.to_account_info(),
+        output_token_account.to_account_info(),
+        output_mint.to_account_info(),
+        if output_mint.to_account_info().owner == accounts.token_program.key {
+            accounts.token_program.to_account_info()
+        } else {
+            accounts.token_program_2022.to_account_info()
+        },
+        amount_out,
+        output_mint.decimals,
+        &[&[crate::AUTH_SEED.as_bytes(), &[pool_state.auth_bump]]],
+    )?;
+
+    observation_state.update(
+        oracle::block_timestamp()?,
+        u64::from(pool_state.observation_update_duration),
+        swap.token_0_price_x32,
+        swap.token_1_price_x32,
+    )?;
+
+    pool_state.recent_epoch = Clock::get()?.epoch;
+
+    Ok(())
+}
@@ -1,6 +1,6 @@
 pub mod curve;
 pub mod error;
-mod external;
+pub mod external;
 pub mod fees;
 pub mod instructions;
 pub mod migration;
@@ -38,8 +38,6 @@ pub mod create_pool_fee_reveiver {
     declare_id!("8PhehuioLjhJ35A5eavazJSwoXcA4J7WwzgoWDBDFSuY");
 }
 
-pub const CALCULATE_REWARDS_ADMIN: Pubkey = pubkey!("5CW8MEhPPxiRmwWgigwpCKCHaZDLX62BkrneijrxoKkR");
-
 pub const AUTH_SEED: &str = "vault_and_lp_mint_auth_seed";
 pub const REWARD_VAULT_SEED: &str = "reward_vault_seed";
 pub const REWARD_INFO_SEED: &str = "reward_info_seed";
@@ -116,7 +114,16 @@ pub mod gamma {
     /// * `fund_fee_rate`- The new fund fee rate of amm config, be set when `param` is 2
     /// * `new_owner`- The config's new owner, be set when `param` is 3
     /// * `new_fund_owner`- The config's new fund owner, be set when `param` is 4
-    /// * `param`- The vaule can be 0 | 1 | 2 | 3 | 4, otherwise will report a error
+    /// * `create_pool_fee`- The new fee to create a pool, be set when `param` is 5
+    /// * `disable_create_pool`- Disables the creation of pools when not 0, be set when `param` is 6
+    /// * `max_open_time`- The new max delay of the open time of a pool, be set when `param` is 7
+    /// * `new_secondary_admin`- The config's new secondary admin, be set when `param` is 8
+    /// * `flash_loan_fee_rate`- The new flash loan fee rate of amm config, be set when `param` is 9
+    /// * `segmenter_discount_rate`- The new fee rate discount for signed segmenters, be set when `param` is 10, 0 disables the discount
+    /// * `unsigned_flow_surcharge_rate`- The new fee rate surcharge for swaps not invoked by a signed segmenter, be set when `param` is 11
+    /// * `segmenter_registry_program`- The additional segmenter registry program, be set when `param` is 12, the default pubkey disables it
+    /// * `param`- The vaule can be 0 | 1 | 2 | 3 | 4 | 5 | 6 | 7 | 8 | 9 | 10 | 11 | 12, otherwise will report a error
+    ///   The new owners, secondary admin and segmenter registry program are the first remaining account
     ///
     pub fn update_amm_config(ctx: Context<UpdateAmmConfig>, param: u16, value: u64) -> Result<()> {
         instructions::update_amm_config(ctx, param, value)
@@ -127,13 +134,74 @@ pub mod gamma {
     /// # Arguments
     ///
     /// * `ctx`- The context of accounts
-    /// * `param`- The param of pool status
-    /// * `status` - The value
+    /// * `status`- The new pool status, be set when `param` is 0
+    /// * `max_trade_fee_rate`- The new max trade fee rate of the pool, be set when `param` is 1
+    /// * `volatility_factor`- The new volatility factor of the dynamic fee, be set when `param` is 2
+    /// * `max_shared_token0`- The new max rate of the token_0 reserve shared with kamino, be set when `param` is 3
+    /// * `max_shared_token1`- The new max rate of the token_1 reserve shared with kamino, be set when `param` is 4
+    /// * `open_time`- Opens the pool now, be set when `param` is 5, the value is ignored
+    /// * `observation_update_duration`- The new min duration in seconds between two oracle observations, be set when `param` is 6
+    /// * `trade_fee_rate_override`- Overrides the trade fee rate of the amm config for the pool, be set when `param` is 7, 0 removes the override
+    /// * `protocol_fee_rate_override`- The protocol fee rate used with the overridden trade fee rate, be set when `param` is 8
+    /// * `fund_fee_rate_override`- The fund fee rate used with the overridden trade fee rate, be set when `param` is 9
+    /// * `creator_fee_rate`- The share of the trade fee accrued to the pool creator, be set when `param` is 10
+    /// * `param`- The vaule can be 0 | 1 | 2 | 3 | 4 | 5 | 6 | 7 | 8 | 9 | 10, otherwise will report a error
     ///
     pub fn update_pool(ctx: Context<UpdatePool>, param: u32, value: u64) -> Result<()> {
         instructions::update_pool(ctx, param, value)
     }
 
+    /// Registers a wallet or app partner, the users coming from it can be linked with it
+    /// Must be called by the admin
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `partner_id` - The id of the partner, 0 is AssetDash
+    /// * `name` - The name of the partner, at most `MAX_PARTNER_NAME_LEN` bytes
+    ///
+    pub fn create_partner(ctx: Context<CreatePartner>, partner_id: u8, name: String) -> Result<()> {
+        instructions::create_partner(ctx, partner_id, name)
+    }
+
+    /// Adds a registered partner to the partners of the pool
+    /// Must be called by the admin, adding AssetDash to a pool created before `PoolPartners` moves it from the pool state
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    ///
+    pub fn add_pool_partner(ctx: Context<AddPoolPartner>) -> Result<()> {
+        instructions::add_pool_partner(ctx)
+    }
+
+    /// Set the oracle price feeds used by oracle based swaps of the pool
+    /// Must be called by the admin
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `token_0_feed_id` - The pyth feed id of token_0
+    /// * `token_1_feed_id` - The pyth feed id of token_1, all zeros if the token_0 feed is quoted in token_1
+    /// * `max_price_age` - Max age in seconds of an accepted price update
+    /// * `max_confidence_rate` - Max confidence interval as a fraction of the price, denominated in hundredths of bip (10^-6)
+    ///
+    pub fn set_price_feed_config(
+        ctx: Context<SetPriceFeedConfig>,
+        token_0_feed_id: [u8; 32],
+        token_1_feed_id: [u8; 32],
+        max_price_age: u64,
+        max_confidence_rate: u64,
+    ) -> Result<()> {
+        instructions::set_price_feed_config(
+            ctx,
+            token_0_feed_id,
+            token_1_feed_id,
+            max_price_age,
+            max_confidence_rate,
+        )
+    }
+
     /// Collect the protocol fee accrued to the pool
     ///
     /// # Arguments
@@ -166,7 +234,23 @@ pub mod gamma {
         instructions::collect_fund_fee(ctx, amount_0_requested, amount_1_requested)
     }
 
-    /// Creates a pool for the given token pair and the initial price
+    /// Collect the creator fee accrued to the pool, must be signed by the pool creator
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` - The context of accounts
+    /// * `amount_0_requested` - The maximum amount of token_0 to send, can be 0 to collect fees in only token_1
+    /// * `amount_1_requested` - The maximum amount of token_1 to send, can be 0 to collect fees in only token_0
+    ///
+    pub fn collect_creator_fee(
+        ctx: Context<CollectCreatorFee>,
+        amount_0_requested: u64,
+        amount_1_requested: u64,
+    ) -> Result<()> {
+        instructions::collect_creator_fee(ctx, amount_0_requested, amount_1_requested)
+    }
+
+    /// Creates a constant product pool with the volatility fee for the given token pair and the initial price
     ///
     /// # Arguments
     ///
@@ -192,6 +276,56 @@ pub mod gamma {
             open_time,
             max_trade_fee_rate,
             volatility_factor,
+            curve::CurveType::ConstantProduct as u8,
+            0,
+            fees::FeeType::Volatility as u8,
+            0,
+            0,
+        )
+    }
+
+    /// Creates a pool for the given token pair and the initial price, with the curve and the fee type of the pool
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `init_amount_0` - the initial amount_0 to deposit
+    /// * `init_amount_1` - the initial amount_1 to deposit
+    /// * `open_time` - the timestamp allowed for swap
+    /// * `max_trade_fee_rate` - The maximum trade fee that can be charged on swaps
+    /// * `volatility_factor` - The volatility factor of the pool to determine the trade fee
+    /// * `curve_type` - The curve used for pricing swaps, 0: ConstantProduct, 1: StableSwap, 2: Weighted
+    /// * `curve_parameter` - The amplification coefficient for StableSwap, the token_0 weight out of 1_000_000 for Weighted,
+    /// 0 for ConstantProduct
+    /// * `fee_type` - The fee charged on swaps, 0: Volatility, 1: LaunchLinearDecay, 2: LaunchExponentialDecay, 3: Directional
+    /// * `launch_fee_rate` - The fee rate at `open_time` for the launch fee types, 0 otherwise
+    /// * `launch_fee_duration` - The seconds after `open_time` for the launch fee to decay to the trade fee rate, 0 otherwise
+    ///
+    pub fn initialize_with_curve(
+        ctx: Context<Initialize>,
+        init_amount_0: u64,
+        init_amount_1: u64,
+        open_time: u64,
+        max_trade_fee_rate: u64,
+        volatility_factor: u64,
+        curve_type: u8,
+        curve_parameter: u64,
+        fee_type: u8,
+        launch_fee_rate: u64,
+        launch_fee_duration: u64,
+    ) -> Result<()> {
+        instructions::initialize(
+            ctx,
+            init_amount_0,
+            init_amount_1,
+            open_time,
+            max_trade_fee_rate,
+            volatility_factor,
+            curve_type,
+            curve_parameter,
+            fee_type,
+            launch_fee_rate,
+            launch_fee_duration,
         )
     }
 
@@ -202,6 +336,19 @@ pub mod gamma {
         instructions::init_user_pool_liquidity(ctx, partner)
     }
 
+    /// Creates the user pool liquidity account of the user linked with a partner
+    /// The partner must be registered with `create_partner` and added to the pool with `add_pool_partner`
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    ///
+    pub fn init_user_pool_liquidity_with_partner(
+        ctx: Context<InitUserPoolLiquidityWithPartner>,
+    ) -> Result<()> {
+        instructions::init_user_pool_liquidity_with_partner(ctx)
+    }
+
     /// Creates a pool for the given token pair and the initial price
     ///
     /// # Arguments
@@ -211,12 +358,15 @@ pub mod gamma {
     /// * `maximum_token_0_amount` -  Maximum token 0 amount to deposit, prevents excessive slippage
     /// * `maximum_token_1_amount` - Maximum token 1 amount to deposit, prevents excessive slippage
     ///
-    pub fn deposit(
-        ctx: Context<Deposit>,
+    pub fn deposit<'c, 'info>(
+        ctx: Context<'_, '_, 'c, 'info, Deposit<'info>>,
         lp_token_amount: u64,
         maximum_token_0_amount: u64,
         maximum_token_1_amount: u64,
-    ) -> Result<()> {
+    ) -> Result<()>
+    where
+        'c: 'info,
+    {
         instructions::deposit(
             ctx,
             lp_token_amount,
@@ -225,6 +375,51 @@ pub mod gamma {
         )
     }
 
+    /// Deposit up to the given token amounts for the largest lp token amount they pay for
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `maximum_token_0_amount` -  Maximum token 0 amount to deposit, including the transfer fee
+    /// * `maximum_token_1_amount` - Maximum token 1 amount to deposit, including the transfer fee
+    /// * `minimum_lp_token_amount` - Minimum pool token amount to receive, prevents excessive slippage
+    ///
+    pub fn deposit_exact_tokens<'c, 'info>(
+        ctx: Context<'_, '_, 'c, 'info, Deposit<'info>>,
+        maximum_token_0_amount: u64,
+        maximum_token_1_amount: u64,
+        minimum_lp_token_amount: u64,
+    ) -> Result<()>
+    where
+        'c: 'info,
+    {
+        instructions::deposit_exact_tokens(
+            ctx,
+            maximum_token_0_amount,
+            maximum_token_1_amount,
+            minimum_lp_token_amount,
+        )
+    }
+
+    /// Deposit a single token, part of it is swapped through the pool so the rest can be deposited
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `amount_in` - Maximum amount of the input token to transfer, the part that can not be deposited stays with the owner
+    /// * `minimum_lp_token_amount` - Minimum pool token amount to receive, prevents excessive slippage
+    ///
+    pub fn deposit_single_sided<'c, 'info>(
+        ctx: Context<'_, '_, 'c, 'info, DepositSingleSided<'info>>,
+        amount_in: u64,
+        minimum_lp_token_amount: u64,
+    ) -> Result<()>
+    where
+        'c: 'info,
+    {
+        instructions::deposit_single_sided(ctx, amount_in, minimum_lp_token_amount)
+    }
+
     /// Withdraw lp for token0 ande token1
     ///
     /// # Arguments
@@ -251,6 +446,195 @@ pub mod gamma {
         )
     }
 
+    /// Withdraw at least the given token amounts for the smallest lp token amount that pays for them
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `token_0_amount` - Amount of token 0 to receive, after the transfer fee
+    /// * `token_1_amount` - Amount of token 1 to receive, after the transfer fee
+    /// * `maximum_lp_token_amount` - Maximum amount of pool tokens to burn, prevents excessive slippage
+    ///
+    pub fn withdraw_exact_tokens<'c, 'info>(
+        ctx: Context<'_, '_, 'c, 'info, Withdraw<'info>>,
+        token_0_amount: u64,
+        token_1_amount: u64,
+        maximum_lp_token_amount: u64,
+    ) -> Result<()>
+    where
+        'c: 'info,
+    {
+        instructions::withdraw_exact_tokens(
+            ctx,
+            token_0_amount,
+            token_1_amount,
+            maximum_lp_token_amount,
+        )
+    }
+
+    /// Withdraw lp in a single token, the share of the other token is swapped through the pool
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `lp_token_amount` - Amount of pool tokens to burn
+    /// * `minimum_amount_out` -  Minimum amount of the withdrawn token to receive, prevents excessive slippage
+    /// * `withdraw_token_0` - Withdraw everything in token 0 if true, in token 1 otherwise
+    ///
+    pub fn withdraw_single_sided<'c, 'info>(
+        ctx: Context<'_, '_, 'c, 'info, WithdrawSingleSided<'info>>,
+        lp_token_amount: u64,
+        minimum_amount_out: u64,
+        withdraw_token_0: bool,
+    ) -> Result<()>
+    where
+        'c: 'info,
+    {
+        instructions::withdraw_single_sided(
+            ctx,
+            lp_token_amount,
+            minimum_amount_out,
+            withdraw_token_0,
+        )
+    }
+
+    /// Create the spl lp mint of the pool, so liquidity can be wrapped into transferable lp tokens
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    ///
+    pub fn create_lp_mint(ctx: Context<CreateLpMint>) -> Result<()> {
+        instructions::create_lp_mint(ctx)
+    }
+
+    /// Wrap pool tokens owned in the user pool liquidity account into spl lp tokens
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `lp_token_amount` - Amount of pool tokens to wrap
+    ///
+    pub fn wrap_lp_tokens<'c, 'info>(
+        ctx: Context<'_, '_, 'c, 'info, WrapLpTokens<'info>>,
+        lp_token_amount: u64,
+    ) -> Result<()>
+    where
+        'c: 'info,
+    {
+        instructions::wrap_lp_tokens(ctx, lp_token_amount)
+    }
+
+    /// Burn spl lp tokens and credit them back to the user pool liquidity account
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `lp_token_amount` - Amount of lp tokens to unwrap
+    ///
+    pub fn unwrap_lp_tokens<'c, 'info>(
+        ctx: Context<'_, '_, 'c, 'info, UnwrapLpTokens<'info>>,
+        lp_token_amount: u64,
+    ) -> Result<()>
+    where
+        'c: 'info,
+    {
+        instructions::unwrap_lp_tokens(ctx, lp_token_amount)
+    }
+
+    /// Lock pool tokens owned in the user pool liquidity account until `unlock_at`
+    /// Locked pool tokens can not be withdrawn, wrapped or transferred and earn boosted rewards
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `lp_token_amount` - Amount of pool tokens to lock
+    /// * `unlock_at` - Time after which the pool tokens can be unlocked, longer locks earn a larger boost
+    ///
+    pub fn lock_lp_tokens<'c, 'info>(
+        ctx: Context<'_, '_, 'c, 'info, LockLpTokens<'info>>,
+        lp_token_amount: u64,
+        unlock_at: u64,
+    ) -> Result<()>
+    where
+        'c: 'info,
+    {
+        instructions::lock_lp_tokens(ctx, lp_token_amount, unlock_at)
+    }
+
+    /// Unlock the locked pool tokens of a user pool liquidity account once the lock has expired
+    /// Anyone can unlock them, which removes their reward boost
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    ///
+    pub fn unlock_lp_tokens<'c, 'info>(
+        ctx: Context<'_, '_, 'c, 'info, UnlockLpTokens<'info>>,
+    ) -> Result<()>
+    where
+        'c: 'info,
+    {
+        instructions::unlock_lp_tokens(ctx)
+    }
+
+    /// Lock pool tokens owned in the user pool liquidity account forever, like the liquidity locked at initialization
+    /// The owner of the lock keeps claiming the trading fees earned by the locked liquidity
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `lp_token_amount` - Amount of pool tokens to lock permanently
+    ///
+    pub fn lock_liquidity_permanently<'c, 'info>(
+        ctx: Context<'_, '_, 'c, 'info, LockLiquidityPermanently<'info>>,
+        lp_token_amount: u64,
+    ) -> Result<()>
+    where
+        'c: 'info,
+    {
+        instructions::lock_liquidity_permanently(ctx, lp_token_amount)
+    }
+
+    /// Claim the trading fees earned by the permanent lock of the owner without reducing its locked pool tokens
+    /// The fees are the share of the fee growth per lp token of the locked pool tokens, paid in token_0 and token_1
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    ///
+    pub fn claim_permanent_lock_fees(ctx: Context<ClaimPermanentLockFees>) -> Result<()> {
+        instructions::claim_permanent_lock_fees(ctx)
+    }
+
+    /// Transfer all or part of a liquidity position to another user
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `lp_token_amount` - Amount of pool tokens to transfer, the deposited and withdrawn amounts move pro-rata
+    ///
+    pub fn transfer_liquidity<'c, 'info>(
+        ctx: Context<'_, '_, 'c, 'info, TransferLiquidity<'info>>,
+        lp_token_amount: u64,
+    ) -> Result<()>
+    where
+        'c: 'info,
+    {
+        instructions::transfer_liquidity(ctx, lp_token_amount)
+    }
+
+    /// Close the user pool liquidity account once all the liquidity is withdrawn
+    /// The rent is returned to the owner
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    ///
+    pub fn close_user_pool_liquidity(ctx: Context<CloseUserPoolLiquidity>) -> Result<()> {
+        instructions::close_user_pool_liquidity(ctx)
+    }
+
     /// Swap the tokens in the pool base input amount
     ///
     /// # Arguments
@@ -285,7 +669,10 @@ pub mod gamma {
         instructions::swap_base_output(ctx, max_amount_in, amount_out)
     }
 
-    /// Swap the tokens in the pool base input amount, using oracle price and Curve calculator combined.
+    /// Swap the tokens in the pool base input amount, priced by the oracle price feed of the pool.
+    /// Pool reserves are only used as the capacity limit of the swap.
+    /// Remaining accounts 4, 5 and 6 are expected to be the price feed config of the pool,
+    /// the token_0 price update and the token_1 price update(optional).
     ///
     /// # Arguments
     ///
@@ -297,13 +684,139 @@ pub mod gamma {
         amount_in: u64,
         minimum_amount_out: u64,
     ) -> Result<()> {
-        // This if for demo purposes, and cpi and aggregator transaction purposes only.
-        Ok(())
+        instructions::oracle_swap_base_input(ctx, amount_in, minimum_amount_out)
+    }
+
+    /// Swap the tokens base input amount through several pools in a single instruction
+    /// Remaining accounts are 7 accounts per hop: amm_config, pool_state, input_vault, output_vault,
+    /// output_token_mint, output_token_program and observation_state of the pool.
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `amount_in` -  input amount to transfer to the first pool of the route
+    /// * `minimum_amount_out` -  Minimum amount of output token received from the last pool, prevents excessive slippage
+    pub fn swap_route<'c, 'info>(
+        ctx: Context<'_, '_, 'c, 'info, SwapRoute<'info>>,
+        amount_in: u64,
+        minimum_amount_out: u64,
+    ) -> Result<()>
+    where
+        'c: 'info,
+    {
+        instructions::swap_route(ctx, amount_in, minimum_amount_out)
+    }
+
+    /// Borrow tokens from a vault of the pool, they have to be paid back with the flash loan fee
+    /// by a `flash_repay` instruction later in the same transaction
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `amount` - Amount of tokens to borrow from the vault
+    ///
+    pub fn flash_loan(ctx: Context<FlashLoan>, amount: u64) -> Result<()> {
+        instructions::flash_loan(ctx, amount)
+    }
+
+    /// Pay back a flash loan of the same pool and vault with the flash loan fee
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `flash_loan_instruction_index` - Index in the transaction of the `flash_loan` instruction being repaid
+    ///
+    pub fn flash_repay(ctx: Context<FlashLoan>, flash_loan_instruction_index: u16) -> Result<()> {
+        instructions::flash_repay(ctx, flash_loan_instruction_index)
+    }
+
+    /// Returns the TWAP with the min and max prices of the pool over a time window as return data
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `window_seconds` - Only observations made in the last `window_seconds` are used
+    ///
+    pub fn get_twap(ctx: Context<GetTwap>, window_seconds: u64) -> Result<states::Twap> {
+        instructions::get_twap(ctx, window_seconds)
+    }
+
+    /// Returns the trade fees earned by the lp tokens of a user, in token_0 and token_1, as return data
+    /// The fees are accrued per lp token on every swap and are part of the liquidity of the user
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    ///
+    pub fn get_fees_earned(ctx: Context<GetFeesEarned>) -> Result<states::FeesEarned> {
+        instructions::get_fees_earned(ctx)
+    }
+
+    /// Expand the observation buffer of a pool so the oracle covers a longer history
+    /// Anyone can pay for the expansion, at most 256 observations can be added per call
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` - The context of accounts
+    /// * `additional_observations` - The number of observations to add to the buffer
+    ///
+    pub fn expand_observation_state(
+        ctx: Context<ExpandObservationState>,
+        additional_observations: u16,
+    ) -> Result<()> {
+        instructions::expand_observation_state(ctx, additional_observations)
+    }
+
+    /// Resize a pool created before fields were added to the pool state
+    /// The pool can not be used until it is resized, anyone can pay for the resize
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` - The context of accounts
+    ///
+    pub fn resize_pool_state(ctx: Context<ResizePoolState>) -> Result<()> {
+        instructions::resize_pool_state(ctx)
+    }
+
+    /// Resize a user pool liquidity created before fields were added to it
+    /// The position can not be used until it is resized, anyone can pay for the resize
+    /// The fees earned by the positions created before the fee growth accumulator are tracked from the resize on
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` - The context of accounts
+    ///
+    pub fn resize_user_pool_liquidity(ctx: Context<ResizeUserPoolLiquidity>) -> Result<()> {
+        instructions::resize_user_pool_liquidity(ctx)
+    }
+
+    /// Migrate a reward info created before the reward accumulator
+    /// The reward can not be used until it is migrated, anyone can pay for the migration
+    /// The rewards released before the migration are split between the lp tokens held at the migration
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` - The context of accounts
+    ///
+    pub fn migrate_reward_info(ctx: Context<MigrateRewardInfo>) -> Result<()> {
+        instructions::migrate_reward_info(ctx)
+    }
+
+    /// Migrate a user reward info created before the reward accumulator, its reward info must be migrated first
+    /// The user reward info can not be used until it is migrated, anyone can pay for the migration
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` - The context of accounts
+    ///
+    pub fn migrate_user_reward_info(ctx: Context<MigrateUserRewardInfo>) -> Result<()> {
+        instructions::migrate_user_reward_info(ctx)
     }
 
     /// Create rewards for the pool
     /// Initializes a new reward info account and a reward vault account
     /// Transfers the rewards to the reward vault
+    /// The reward must end within `MAX_REWARD_DURATION` and release `MIN_REWARD_PER_SECOND` at least
     ///
     /// # Arguments
     ///
@@ -321,6 +834,53 @@ pub mod gamma {
         instructions::create_rewards(ctx, start_time, end_time, reward_amount)
     }
 
+    /// Top up the rewards of a reward that has not ended, signed by the reward provider
+    /// The rewards not released yet and the topped up rewards are released until the end of the reward
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` - The context of accounts
+    /// * `reward_amount` - The amount of rewards to add
+    ///
+    pub fn top_up_rewards(ctx: Context<TopUpRewards>, reward_amount: u64) -> Result<()> {
+        instructions::top_up_rewards(ctx, reward_amount)
+    }
+
+    /// Extend the end time of a reward that has not ended, signed by the reward provider
+    /// The rewards not released yet are released until the new end time
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` - The context of accounts
+    /// * `end_time` - The new end time of the reward, after the current one, within `MAX_REWARD_DURATION` from now
+    ///
+    pub fn extend_rewards(ctx: Context<ExtendRewards>, end_time: u64) -> Result<()> {
+        instructions::extend_rewards(ctx, end_time)
+    }
+
+    /// Evict an active reward of the pool, must be called by the admin
+    /// The reward ends now and frees its slot of the active rewards, the rewards not released yet
+    /// can be refunded to the reward provider
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` - The context of accounts
+    ///
+    pub fn evict_reward_info(ctx: Context<EvictRewardInfo>) -> Result<()> {
+        instructions::evict_reward_info(ctx)
+    }
+
+    /// Refund the rewards released while no lp tokens were eligible to the reward provider
+    /// Can only be called by the reward provider after the reward has ended
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` - The context of accounts
+    ///
+    pub fn refund_unallocated_rewards(ctx: Context<RefundUnallocatedRewards>) -> Result<()> {
+        instructions::refund_unallocated_rewards(ctx)
+    }
+
     /// Claim rewards for the user
     /// Transfers the amount of tokens calculated for the user to their reward token account
     ///
@@ -333,6 +893,11 @@ pub mod gamma {
     }
 
     /// Calculate rewards for the user
+    /// Accrues the reward info up to now and settles the rewards of the user, anyone can calculate them
+    /// The user reward info is created if needed, it must exist before the lp tokens of the user change
+    /// while the reward is active
+    ///
+    /// # Arguments
     ///
     /// * `ctx` - The context of accounts
     ///
@@ -340,8 +905,33 @@ pub mod gamma {
         instructions::calculate_rewards(ctx)
     }
 
+    /// Close a reward info and its reward vault after the reward has ended and every user reward info is closed
+    /// The rewards left in the vault and the rent are returned to the reward provider
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` - The context of accounts
+    ///
+    pub fn close_reward_info(ctx: Context<CloseRewardInfo>) -> Result<()> {
+        instructions::close_reward_info(ctx)
+    }
+
+    /// Close the user reward info of a closed reward info, or of an ended reward once the rewards of the user
+    /// are calculated up to its end and claimed, the user pool liquidity is required then
+    /// The rent is returned to the user
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` - The context of accounts
+    ///
+    pub fn close_user_reward_info(ctx: Context<CloseUserRewardInfo>) -> Result<()> {
+        instructions::close_user_reward_info(ctx)
+    }
+
     /********************* Migration Instructions *********************/
 
+    // The last remaining accounts of the migrations are the active rewards of the gamma pool, see `settle_rewards`
+
     /// Migrate from Meteora Dlmm to Gamma
 
     pub fn migrate_meteora_dlmm_to_gamma<'a, 'b, 'c, 'info>(
@@ -349,7 +939,10 @@ pub mod gamma {
         bin_liquidity_reduction: Vec<crate::external::dlmm::lb_clmm::types::BinLiquidityReduction>,
         maximum_token_0_amount: u64,
         maximum_token_1_amount: u64,
-    ) -> Result<()> {
+    ) -> Result<()>
+    where
+        'c: 'info,
+    {
         migration::meteora::meteora_dlmm_to_gamma(
             ctx,
             bin_liquidity_reduction,
@@ -370,7 +963,10 @@ pub mod gamma {
         >,
         maximum_token_0_amount: u64,
         maximum_token_1_amount: u64,
-    ) -> Result<()> {
+    ) -> Result<()>
+    where
+        'c: 'info,
+    {
         migration::orca::orca_whirlpool_to_gamma_v2(
             ctx,
             liquidity_amount,
@@ -391,7 +987,10 @@ pub mod gamma {
         token_min_b: u64,
         maximum_token_0_amount: u64,
         maximum_token_1_amount: u64,
-    ) -> Result<()> {
+    ) -> Result<()>
+    where
+        'c: 'info,
+    {
         migration::orca::orca_whirlpool_to_gamma(
             ctx,
             liquidity_amount,
@@ -411,7 +1010,10 @@ pub mod gamma {
         amount_1_min: u64,
         maximum_token_0_amount: u64,
         maximum_token_1_amount: u64,
-    ) -> Result<()> {
+    ) -> Result<()>
+    where
+        'c: 'info,
+    {
         migration::raydium::raydium_clmm_to_gamma(
             ctx,
             liquidity,
@@ -431,7 +1033,10 @@ pub mod gamma {
         amount_1_min: u64,
         maximum_token_0_amount: u64,
         maximum_token_1_amount: u64,
-    ) -> Result<()> {
+    ) -> Result<()>
+    where
+        'c: 'info,
+    {
         migration::raydium::raydium_clmm_to_gamma_v2(
             ctx,
             liquidity,
@@ -451,7 +1056,10 @@ pub mod gamma {
         minimum_token_1_amount: u64,
         maximum_token_0_amount: u64,
         maximum_token_1_amount: u64,
-    ) -> Result<()> {
+    ) -> Result<()>
+    where
+        'c: 'info,
+    {
         migration::raydium::raydium_cp_swap_to_gamma(
             ctx,
             lp_token_amount_withdraw,
@@ -2,6 +2,7 @@ use crate::external::dlmm::lb_clmm::types::BinLiquidityReduction;
 use crate::{
     calculate_gamma_lp_tokens,
     instructions::deposit::{deposit_to_gamma_pool, Deposit},
+    settle_migration_rewards,
     states::{MigrationEvent, PoolState, UserPoolLiquidity, USER_POOL_LIQUIDITY_SEED},
 };
 use anchor_lang::prelude::*;
@@ -138,12 +139,15 @@ pub struct MeteoraDlmmToGamma<'info> {
     pub gamma_vault_1_mint: Box<InterfaceAccount<'info, Mint>>,
 }
 
-pub fn meteora_dlmm_to_gamma(
-    ctx: Context<MeteoraDlmmToGamma>,
+pub fn meteora_dlmm_to_gamma<'c, 'info>(
+    ctx: Context<'_, '_, 'c, 'info, MeteoraDlmmToGamma<'info>>,
     bin_liquidity_reduction: Vec<BinLiquidityReduction>,
     maximum_token_0_amount: u64,
     maximum_token_1_amount: u64,
-) -> Result<()> {
+) -> Result<()>
+where
+    'c: 'info,
+{
     let user_token0_balance_before = ctx.accounts.gamma_token_0_account.amount;
     let user_token1_balance_before = ctx.accounts.gamma_token_1_account.amount;
     // Withdraw from Meteora DLMM
@@ -186,11 +190,16 @@ pub fn meteora_dlmm_to_gamma(
     let token_1_amount_withdrawn = user_token1_balance_before
         .checked_sub(user_token1_balance_after)
         .unwrap();
-    let pool_state = ctx.accounts.gamma_pool_state.load()?;
     let gamma_lp_tokens = calculate_gamma_lp_tokens(
         token_0_amount_withdrawn,
         token_1_amount_withdrawn,
-        &pool_state,
+        &ctx.accounts.gamma_pool_state.load()?,
+    )?;
+    settle_migration_rewards(
+        ctx.remaining_accounts,
+        &ctx.accounts.gamma_pool_state,
+        &ctx.accounts.gamma_user_pool_liquidity,
+        gamma_lp_tokens,
     )?;
 
     let mut deposit_accounts = Deposit {
@@ -206,6 +215,7 @@ pub fn meteora_dlmm_to_gamma(
         token_program_2022: ctx.accounts.token_program_2022.clone(),
         vault_0_mint: ctx.accounts.gamma_vault_0_mint.clone(),
         vault_1_mint: ctx.accounts.gamma_vault_1_mint.clone(),
+        pool_partners: None,
     };
 
     deposit_to_gamma_pool(
@@ -6,7 +6,12 @@ pub use meteora::*;
 pub use orca::*;
 pub use raydium::*;
 
-use crate::{curve::ConstantProductCurve, error::GammaError, states::PoolState};
+use crate::{
+    curve::{CurveCalculator, RoundDirection},
+    error::GammaError,
+    instructions::settle_rewards,
+    states::{PoolState, UserPoolLiquidity},
+};
 use anchor_lang::prelude::*;
 
 pub fn calculate_gamma_lp_tokens(
@@ -16,17 +21,21 @@ pub fn calculate_gamma_lp_tokens(
 ) -> Result<u128> {
     let (total_token_0_amount, total_token_1_amount) = pool_state.vault_amount_without_fee()?;
 
-    let gamma_lp_tokens_0 = ConstantProductCurve::token_0_to_lp_tokens(
+    let gamma_lp_tokens_0 = CurveCalculator::token_0_to_lp_tokens(
+        pool_state.curve_type()?,
         u128::from(token_0_amount_withdrawn),
         u128::from(total_token_0_amount),
         u128::from(pool_state.lp_supply),
+        RoundDirection::Floor,
     )
     .ok_or(GammaError::InvalidLpTokenAmount)?;
 
-    let gamma_lp_tokens_1 = ConstantProductCurve::token_1_to_lp_tokens(
+    let gamma_lp_tokens_1 = CurveCalculator::token_1_to_lp_tokens(
+        pool_state.curve_type()?,
         u128::from(token_1_amount_withdrawn),
         u128::from(total_token_1_amount),
         u128::from(pool_state.lp_supply),
+        RoundDirection::Floor,
     )
     .ok_or(GammaError::InvalidLpTokenAmount)?;
 
@@ -34,3 +43,43 @@ pub fn calculate_gamma_lp_tokens(
 
     Ok(gamma_lp_tokens)
 }
+
+/// The remaining accounts of a migration are the accounts of the withdrawal from the other pool,
+/// followed by the active rewards of the gamma pool, see `settle_rewards`.
+pub fn withdrawal_remaining_accounts<'c, 'info>(
+    remaining_accounts: &'c [AccountInfo<'info>],
+    pool_state: &AccountLoader<'info, PoolState>,
+) -> Result<&'c [AccountInfo<'info>]> {
+    let reward_accounts_len = usize::try_from(pool_state.load()?.active_reward_infos)
+        .map_err(|_| error!(GammaError::MathOverflow))?
+        .checked_mul(2)
+        .ok_or(GammaError::MathOverflow)?;
+    let withdrawal_accounts_len = remaining_accounts
+        .len()
+        .checked_sub(reward_accounts_len)
+        .ok_or(error!(ErrorCode::AccountNotEnoughKeys))?;
+    Ok(&remaining_accounts[..withdrawal_accounts_len])
+}
+
+/// Settles the rewards of the owner before the migrated `lp_token_amount` is deposited into the gamma pool.
+pub fn settle_migration_rewards<'c, 'info>(
+    remaining_accounts: &'c [AccountInfo<'info>],
+    pool_state_loader: &AccountLoader<'info, PoolState>,
+    user_pool_liquidity: &UserPoolLiquidity,
+    lp_token_amount: u128,
+) -> Result<()>
+where
+    'c: 'info,
+{
+    let pool_state = &mut pool_state_loader.load_mut()?;
+    settle_rewards(
+        remaining_accounts,
+        pool_state_loader.key(),
+        pool_state,
+        user_pool_liquidity,
+        user_pool_liquidity
+            .lp_tokens_owned
+            .checked_add(lp_token_amount)
+            .ok_or(GammaError::MathOverflow)?,
+    )
+}
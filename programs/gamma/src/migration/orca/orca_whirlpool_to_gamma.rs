@@ -1,6 +1,7 @@
 use crate::{
     calculate_gamma_lp_tokens,
     instructions::deposit::{deposit_to_gamma_pool, Deposit},
+    settle_migration_rewards,
     states::{MigrationEvent, PoolState, UserPoolLiquidity, USER_POOL_LIQUIDITY_SEED},
 };
 use anchor_lang::prelude::*;
@@ -142,14 +143,17 @@ pub struct OrcaWhirlpoolToGamma<'info> {
     // - accounts for transfer hook program of token_mint_b
 }
 
-pub fn orca_whirlpool_to_gamma<'info>(
-    ctx: Context<'_, '_, '_, 'info, OrcaWhirlpoolToGamma<'info>>,
+pub fn orca_whirlpool_to_gamma<'c, 'info>(
+    ctx: Context<'_, '_, 'c, 'info, OrcaWhirlpoolToGamma<'info>>,
     liquidity_amount: u128,
     token_min_a: u64,
     token_min_b: u64,
     maximum_token_0_amount: u64,
     maximum_token_1_amount: u64,
-) -> Result<()> {
+) -> Result<()>
+where
+    'c: 'info,
+{
     let user_token0_balance_before = ctx.accounts.gamma_token_0_account.amount;
     let user_token1_balance_before = ctx.accounts.gamma_token_1_account.amount;
 
@@ -190,11 +194,16 @@ pub fn orca_whirlpool_to_gamma<'info>(
     let token_1_amount_withdrawn = user_token1_balance_before
         .checked_sub(user_token1_balance_after)
         .unwrap();
-    let pool_state = ctx.accounts.gamma_pool_state.load()?;
     let gamma_lp_tokens = calculate_gamma_lp_tokens(
         token_0_amount_withdrawn,
         token_1_amount_withdrawn,
-        &pool_state,
+        &ctx.accounts.gamma_pool_state.load()?,
+    )?;
+    settle_migration_rewards(
+        ctx.remaining_accounts,
+        &ctx.accounts.gamma_pool_state,
+        &ctx.accounts.gamma_user_pool_liquidity,
+        gamma_lp_tokens,
     )?;
 
     let mut deposit_accounts = Deposit {
@@ -210,6 +219,7 @@ pub fn orca_whirlpool_to_gamma<'info>(
         token_program_2022: ctx.accounts.token_program_2022.clone(),
         vault_0_mint: ctx.accounts.gamma_vault_0_mint.clone(),
         vault_1_mint: ctx.accounts.gamma_vault_1_mint.clone(),
+        pool_partners: None,
     };
 
     deposit_to_gamma_pool(
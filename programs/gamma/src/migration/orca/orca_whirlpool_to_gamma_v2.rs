@@ -2,7 +2,9 @@ use crate::external::whirlpool::whirlpool::types::RemainingAccountsInfo;
 use crate::{
     calculate_gamma_lp_tokens,
     instructions::deposit::{deposit_to_gamma_pool, Deposit},
+    settle_migration_rewards,
     states::{MigrationEvent, PoolState, UserPoolLiquidity, USER_POOL_LIQUIDITY_SEED},
+    withdrawal_remaining_accounts,
 };
 use anchor_lang::prelude::*;
 use anchor_spl::{
@@ -141,17 +143,21 @@ pub struct OrcaWhirlpoolToGammaV2<'info> {
     // remaining accounts
     // - accounts for transfer hook program of token_mint_a
     // - accounts for transfer hook program of token_mint_b
+    // - active rewards of the gamma pool, see `settle_rewards`
 }
 
-pub fn orca_whirlpool_to_gamma_v2<'info>(
-    ctx: Context<'_, '_, '_, 'info, OrcaWhirlpoolToGammaV2<'info>>,
+pub fn orca_whirlpool_to_gamma_v2<'c, 'info>(
+    ctx: Context<'_, '_, 'c, 'info, OrcaWhirlpoolToGammaV2<'info>>,
     liquidity_amount: u128,
     token_min_a: u64,
     token_min_b: u64,
     remaining_accounts_info: Option<RemainingAccountsInfo>,
     maximum_token_0_amount: u64,
     maximum_token_1_amount: u64,
-) -> Result<()> {
+) -> Result<()>
+where
+    'c: 'info,
+{
     let user_token0_balance_before = ctx.accounts.gamma_token_0_account.amount;
     let user_token1_balance_before = ctx.accounts.gamma_token_1_account.amount;
     // Withdraw from Orca Whirlpool
@@ -177,7 +183,10 @@ pub fn orca_whirlpool_to_gamma_v2<'info>(
     };
 
     let cpi_ctx = CpiContext::new(ctx.accounts.whirlpool_program.to_account_info(), accounts)
-        .with_remaining_accounts(ctx.remaining_accounts.to_vec());
+        .with_remaining_accounts(
+            withdrawal_remaining_accounts(ctx.remaining_accounts, &ctx.accounts.gamma_pool_state)?
+                .to_vec(),
+        );
     crate::external::whirlpool::whirlpool::cpi::decrease_liquidity_v2(
         cpi_ctx,
         liquidity_amount,
@@ -197,11 +206,16 @@ pub fn orca_whirlpool_to_gamma_v2<'info>(
     let token_1_amount_withdrawn = user_token1_balance_before
         .checked_sub(user_token1_balance_after)
         .unwrap();
-    let pool_state = ctx.accounts.gamma_pool_state.load()?;
     let gamma_lp_tokens = calculate_gamma_lp_tokens(
         token_0_amount_withdrawn,
         token_1_amount_withdrawn,
-        &pool_state,
+        &ctx.accounts.gamma_pool_state.load()?,
+    )?;
+    settle_migration_rewards(
+        ctx.remaining_accounts,
+        &ctx.accounts.gamma_pool_state,
+        &ctx.accounts.gamma_user_pool_liquidity,
+        gamma_lp_tokens,
     )?;
 
     let mut deposit_accounts = Deposit {
@@ -217,6 +231,7 @@ pub fn orca_whirlpool_to_gamma_v2<'info>(
         token_program_2022: ctx.accounts.token_program_2022.clone(),
         vault_0_mint: ctx.accounts.gamma_vault_0_mint.clone(),
         vault_1_mint: ctx.accounts.gamma_vault_1_mint.clone(),
+        pool_partners: None,
     };
 
     deposit_to_gamma_pool(
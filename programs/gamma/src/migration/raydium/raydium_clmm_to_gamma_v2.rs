@@ -1,7 +1,9 @@
 use crate::{
     calculate_gamma_lp_tokens,
     instructions::deposit::{deposit_to_gamma_pool, Deposit},
+    settle_migration_rewards,
     states::{MigrationEvent, PoolState, UserPoolLiquidity, USER_POOL_LIQUIDITY_SEED},
+    withdrawal_remaining_accounts,
 };
 use anchor_lang::prelude::*;
 use anchor_spl::{
@@ -147,14 +149,17 @@ pub struct RaydiumClmmToGammaV2<'info> {
     // pub tick_array_bitmap: UncheckedAccount<'info>,
 }
 
-pub fn raydium_clmm_to_gamma_v2<'a, 'b, 'c, 'info>(
-    ctx: Context<'a, 'b, 'c, 'info, RaydiumClmmToGammaV2<'info>>,
+pub fn raydium_clmm_to_gamma_v2<'c, 'info>(
+    ctx: Context<'_, '_, 'c, 'info, RaydiumClmmToGammaV2<'info>>,
     liquidity: u128,
     amount_0_min: u64,
     amount_1_min: u64,
     maximum_token_0_amount: u64,
     maximum_token_1_amount: u64,
-) -> Result<()> {
+) -> Result<()>
+where
+    'c: 'info,
+{
     let user_token0_balance_before = ctx.accounts.gamma_token_0_account.amount;
     let user_token1_balance_before = ctx.accounts.gamma_token_1_account.amount;
 
@@ -187,7 +192,10 @@ pub fn raydium_clmm_to_gamma_v2<'a, 'b, 'c, 'info>(
         ctx.accounts.raydium_clmm_program.to_account_info(),
         cpi_accounts,
     )
-    .with_remaining_accounts(ctx.remaining_accounts.to_vec());
+    .with_remaining_accounts(
+        withdrawal_remaining_accounts(ctx.remaining_accounts, &ctx.accounts.gamma_pool_state)?
+            .to_vec(),
+    );
     crate::external::raydium_clmm::amm_v3::cpi::decrease_liquidity_v2(
         cpi_context,
         liquidity,
@@ -206,11 +214,16 @@ pub fn raydium_clmm_to_gamma_v2<'a, 'b, 'c, 'info>(
     let token_1_amount_withdrawn = user_token1_balance_before
         .checked_sub(user_token1_balance_after)
         .unwrap();
-    let pool_state = ctx.accounts.gamma_pool_state.load()?;
     let gamma_lp_tokens = calculate_gamma_lp_tokens(
         token_0_amount_withdrawn,
         token_1_amount_withdrawn,
-        &pool_state,
+        &ctx.accounts.gamma_pool_state.load()?,
+    )?;
+    settle_migration_rewards(
+        ctx.remaining_accounts,
+        &ctx.accounts.gamma_pool_state,
+        &ctx.accounts.gamma_user_pool_liquidity,
+        gamma_lp_tokens,
     )?;
 
     let mut deposit_accounts = Deposit {
@@ -226,6 +239,7 @@ pub fn raydium_clmm_to_gamma_v2<'a, 'b, 'c, 'info>(
         token_program_2022: ctx.accounts.token_program_2022.clone(),
         vault_0_mint: ctx.accounts.gamma_vault_0_mint.clone(),
         vault_1_mint: ctx.accounts.gamma_vault_1_mint.clone(),
+        pool_partners: None,
     };
 
     deposit_to_gamma_pool(
@@ -1,6 +1,7 @@
 use crate::{
     calculate_gamma_lp_tokens,
     instructions::deposit::{deposit_to_gamma_pool, Deposit},
+    settle_migration_rewards,
     states::{MigrationEvent, PoolState, UserPoolLiquidity, USER_POOL_LIQUIDITY_SEED},
 };
 use anchor_lang::prelude::*;
@@ -139,14 +140,17 @@ pub struct RaydiumCpSwapToGamma<'info> {
     pub token_program_2022: Program<'info, Token2022>,
 }
 
-pub fn raydium_cp_swap_to_gamma<'a, 'b, 'c, 'info>(
-    ctx: Context<'a, 'b, 'c, 'info, RaydiumCpSwapToGamma<'info>>,
+pub fn raydium_cp_swap_to_gamma<'c, 'info>(
+    ctx: Context<'_, '_, 'c, 'info, RaydiumCpSwapToGamma<'info>>,
     lp_token_amount_withdraw: u64,
     minimum_token_0_amount: u64,
     minimum_token_1_amount: u64,
     maximum_token_0_amount: u64,
     maximum_token_1_amount: u64,
-) -> Result<()> {
+) -> Result<()>
+where
+    'c: 'info,
+{
     let user_token0_balance_before = ctx.accounts.gamma_token_0_account.amount;
     let user_token1_balance_before = ctx.accounts.gamma_token_1_account.amount;
     // Withdraw from Raydium CPMM
@@ -191,11 +195,16 @@ pub fn raydium_cp_swap_to_gamma<'a, 'b, 'c, 'info>(
     let token_1_amount_withdrawn = user_token1_balance_before
         .checked_sub(user_token1_balance_after)
         .unwrap();
-    let pool_state = ctx.accounts.gamma_pool_state.load()?;
     let gamma_lp_tokens = calculate_gamma_lp_tokens(
         token_0_amount_withdrawn,
         token_1_amount_withdrawn,
-        &pool_state,
+        &ctx.accounts.gamma_pool_state.load()?,
+    )?;
+    settle_migration_rewards(
+        ctx.remaining_accounts,
+        &ctx.accounts.gamma_pool_state,
+        &ctx.accounts.gamma_user_pool_liquidity,
+        gamma_lp_tokens,
     )?;
 
     // Prepare deposit accounts
@@ -212,6 +221,7 @@ pub fn raydium_cp_swap_to_gamma<'a, 'b, 'c, 'info>(
         token_program_2022: ctx.accounts.token_program_2022.clone(),
         vault_0_mint: ctx.accounts.gamma_vault_0_mint.clone(),
         vault_1_mint: ctx.accounts.gamma_vault_1_mint.clone(),
+        pool_partners: None,
     };
 
     // Deposit into Gamma pool
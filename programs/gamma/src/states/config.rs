@@ -1,8 +1,12 @@
 use anchor_lang::prelude::*;
 
-use crate::fees::FEE_RATE_DENOMINATOR_VALUE;
+use crate::external::dflow_segmenter::Registry;
+use crate::fees::{FEE_RATE_DENOMINATOR_VALUE, ONE_BASIS_POINT};
 
 pub const AMM_CONFIG_SEED: &str = "amm_config";
+/// `segmenter_discount_rate` of a config whose signed segmenters get no discount,
+/// zero is the `ONE_BASIS_POINT` discount of the configs created before the rate was configurable
+pub const SEGMENTER_DISCOUNT_RATE_DISABLED: u64 = u64::MAX;
 
 #[account]
 #[derive(Default, Debug)]
@@ -32,12 +36,38 @@ pub struct AmmConfig {
     pub max_open_time: u64,
     // This account is not a multisig and is allowed to update certain config values on pools
     pub secondary_admin: Pubkey,
-    /// padding
-    pub padding: [u64; 7],
+    /// The flash loan fee, denominated in hundredths of bip (10^-6)
+    /// Flash loans are disabled for the pools of the config when it is zero
+    pub flash_loan_fee_rate: u64,
+    /// Subtracted from the fee rate of swaps invoked by a signed segmenter, denominated in hundredths of bip (10^-6)
+    /// `ONE_BASIS_POINT` when it is zero, `SEGMENTER_DISCOUNT_RATE_DISABLED` when there is no discount
+    pub segmenter_discount_rate: u64,
+    /// Added to the fee rate of swaps not invoked by a signed segmenter, denominated in hundredths of bip (10^-6)
+    pub unsigned_flow_surcharge_rate: u64,
+    /// Segmenter registry program accepted besides the dflow one, disabled when it is the default pubkey
+    pub segmenter_registry_program: Pubkey,
 }
 
 impl AmmConfig {
     pub const LEN: usize = 8 + 1 + 1 + 2 + 4 * 8 + 2 * 32 + 8 * 16;
+
+    /// The programs owning the segmenter registries trusted by the pools of the config
+    pub fn segmenter_registry_programs(&self) -> Vec<Pubkey> {
+        let mut programs = vec![Registry::PROGRAM_ID];
+        if self.segmenter_registry_program != Pubkey::default() {
+            programs.push(self.segmenter_registry_program);
+        }
+        programs
+    }
+
+    /// The fee rate discount of the swaps invoked by a signed segmenter
+    pub fn segmenter_discount_rate(&self) -> u64 {
+        match self.segmenter_discount_rate {
+            0 => ONE_BASIS_POINT,
+            SEGMENTER_DISCOUNT_RATE_DISABLED => 0,
+            segmenter_discount_rate => segmenter_discount_rate,
+        }
+    }
 }
 
 // require all rates to be less than 1 (100%)
@@ -49,6 +79,15 @@ pub fn validate_config_rates(amm_config: &AmmConfig) -> Result<()> {
         FEE_RATE_DENOMINATOR_VALUE,
         amm_config.fund_fee_rate + amm_config.protocol_fee_rate
     );
+    require_gt!(FEE_RATE_DENOMINATOR_VALUE, amm_config.flash_loan_fee_rate);
+    require_gt!(
+        FEE_RATE_DENOMINATOR_VALUE,
+        amm_config.segmenter_discount_rate()
+    );
+    require_gt!(
+        FEE_RATE_DENOMINATOR_VALUE,
+        amm_config.unsigned_flow_surcharge_rate
+    );
 
     Ok(())
 }
@@ -50,6 +50,8 @@ pub struct SwapEvent {
     pub base_input: bool,
     /// dynamic_fees after this swap
     pub dynamic_fee: u128,
+    /// `FeePath` applied to the fee rate: 0 standard, 1 signed segmenter discount, 2 unsigned flow surcharge
+    pub fee_path: u8,
 }
 
 /// Emitted when migration
@@ -63,3 +65,19 @@ pub struct MigrationEvent {
     pub token_1_amount_withdrawn: u64,
     pub lp_tokens_migrated: u128,
 }
+
+/// Emitted when a flash loan is repaid
+#[event]
+#[cfg_attr(feature = "client", derive(Debug))]
+#[derive(Clone, Debug)]
+pub struct FlashLoanEvent {
+    #[index]
+    pub pool_id: Pubkey,
+    /// mint of the borrowed token
+    pub mint: Pubkey,
+    /// amount sent from the vault, without transfer fees
+    pub amount: u64,
+    /// fee paid on top of the amount, the protocol fee is part of it
+    pub fee: u64,
+    pub protocol_fee: u64,
+}
@@ -1,7 +1,10 @@
 pub mod config;
 pub mod events;
 pub mod oracle;
+pub mod partner;
+pub mod permanent_lock;
 pub mod pool;
+pub mod price_feed_config;
 pub mod reward_info;
 pub mod user_pool_liquidity;
 pub mod user_reward_info;
@@ -9,7 +12,10 @@ pub mod user_reward_info;
 pub use config::*;
 pub use events::*;
 pub use oracle::*;
+pub use partner::*;
+pub use permanent_lock::*;
 pub use pool::*;
+pub use price_feed_config::*;
 pub use reward_info::*;
 pub use user_pool_liquidity::*;
 pub use user_reward_info::*;
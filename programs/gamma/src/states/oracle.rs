@@ -2,10 +2,14 @@ use crate::error::GammaError;
 /// Oracle provides price data useful for a wide variety of system designs
 ///
 use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
+use std::cell::RefMut;
 /// Seed to derive account address and signature
 pub const OBSERVATION_SEED: &str = "observation";
-// Number of ObservationState element
+// Number of ObservationState element, accounts can be expanded to hold more
 pub const OBSERVATION_NUM: usize = 100;
+/// Max number of observations of an expanded ObservationState, every swap reads all of them
+pub const MAX_OBSERVATION_NUM: usize = 1000;
 
 /// The duration of observation update in seconds
 pub const OBSERVATION_UPDATE_DURATION_DEFAULT: u64 = 15;
@@ -26,6 +30,10 @@ impl Observation {
     pub const LEN: usize = 8 + 16 + 16;
 }
 
+// Expanded observations are cast from the account data, `Observation` is packed plain data without padding
+unsafe impl bytemuck::Zeroable for Observation {}
+unsafe impl bytemuck::Pod for Observation {}
+
 #[account(zero_copy(unsafe))]
 #[repr(packed)]
 #[cfg_attr(any(feature = "client", feature = "test-sbf"), derive(Debug))]
@@ -41,6 +49,25 @@ pub struct ObservationState {
     pub padding: [u64; 4],
 }
 
+/// Prices observed in a time window, all prices are Q32.32 and zero when there are not enough observations.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Twap {
+    /// Timestamp of the oldest observation used, can be later than the start of the window
+    pub start_timestamp: u64,
+    /// Timestamp of the newest observation used
+    pub end_timestamp: u64,
+    /// Time weighted average price of token_0 between the oldest and newest observations
+    pub token_0_twap_price_x32: u128,
+    /// Min and max average price of token_0 between two consecutive observations
+    pub token_0_min_price_x32: u128,
+    pub token_0_max_price_x32: u128,
+    /// Time weighted average price of token_1 between the oldest and newest observations
+    pub token_1_twap_price_x32: u128,
+    /// Min and max average price of token_1 between two consecutive observations
+    pub token_1_min_price_x32: u128,
+    pub token_1_max_price_x32: u128,
+}
+
 impl Default for ObservationState {
     #[inline]
     fn default() -> ObservationState {
@@ -56,15 +83,219 @@ impl Default for ObservationState {
 
 impl ObservationState {
     pub const LEN: usize = 8 + 1 + 2 + 32 + (OBSERVATION_NUM * Observation::LEN) + 4 * 8;
+}
+
+/// All the observations of an `ObservationState` account.
+/// Accounts expanded with `expand_observation_state` keep the observations after `OBSERVATION_NUM`
+/// in the account data following the `ObservationState` struct.
+#[derive(Clone, Copy)]
+pub struct Observations<'a> {
+    pub state: &'a ObservationState,
+    pub expanded_observations: &'a [Observation],
+}
+
+/// Mutable version of `Observations`, borrowed from the account data.
+pub struct ObservationsMut<'a> {
+    pub state: RefMut<'a, ObservationState>,
+    pub expanded_observations: RefMut<'a, [Observation]>,
+}
+
+/// Checks the discriminator and the length of the data of an `ObservationState` account,
+/// returns the number of expanded observations.
+fn expanded_observation_num(data: &[u8]) -> Result<usize> {
+    if data.len() < ObservationState::LEN {
+        return err!(ErrorCode::AccountDidNotDeserialize);
+    }
+    if data[..8] != ObservationState::DISCRIMINATOR {
+        return err!(ErrorCode::AccountDiscriminatorMismatch);
+    }
+    let expanded_len = data.len() - ObservationState::LEN;
+    if expanded_len % Observation::LEN != 0 {
+        return err!(ErrorCode::AccountDidNotDeserialize);
+    }
+    Ok(expanded_len / Observation::LEN)
+}
+
+impl<'a> Observations<'a> {
+    /// Reads the observations from the raw data of an `ObservationState` account, discriminator included.
+    /// Off-chain callers should decode the account with it, `AccountDeserialize` fails on expanded accounts.
+    pub fn from_account_data(data: &'a [u8]) -> Result<Self> {
+        expanded_observation_num(data)?;
+        let (state, expanded_observations) = data[8..].split_at(ObservationState::LEN - 8);
+        Ok(Self {
+            state: bytemuck::from_bytes(state),
+            expanded_observations: bytemuck::cast_slice(expanded_observations),
+        })
+    }
+
+    /// Number of observations the account can hold
+    pub fn capacity(&self) -> usize {
+        OBSERVATION_NUM + self.expanded_observations.len()
+    }
+
+    pub fn get(&self, index: usize) -> Observation {
+        if index < OBSERVATION_NUM {
+            self.state.observations[index]
+        } else {
+            self.expanded_observations[index - OBSERVATION_NUM]
+        }
+    }
+
+    /// Computes the TWAP and the min and max prices from the observations made in the last `window` seconds.
+    /// Can be used off-chain on the account data, the same way the `get_twap` instruction does.
+    ///
+    /// # Arguments
+    ///
+    /// * `current_time` - The current timestamp
+    /// * `window` - The time window to consider in seconds
+    ///
+    pub fn get_twap(&self, current_time: u64, window: u64) -> Result<Twap> {
+        // Keep valid observations within the window, sorted from newest to oldest
+        let mut descending_order_observations = (0..self.capacity())
+            .map(|index| (index, self.get(index)))
+            .filter(|(_, observation)| {
+                observation.block_timestamp != 0
+                    && observation.cumulative_token_0_price_x32 != 0
+                    && observation.cumulative_token_1_price_x32 != 0
+                    && current_time.saturating_sub(observation.block_timestamp) <= window
+            })
+            .collect::<Vec<_>>();
+        descending_order_observations
+            .sort_by(|(_, a), (_, b)| { b.block_timestamp }.cmp(&{ a.block_timestamp }));
+
+        // Need at least 2 observations to calculate prices
+        if descending_order_observations.len() < 2 {
+            return Ok(Twap::default());
+        }
+        let (_, newest_obs) = descending_order_observations[0];
+        let (_, oldest_obs) =
+            descending_order_observations[descending_order_observations.len() - 1];
+        let total_time_delta = newest_obs
+            .block_timestamp
+            .saturating_sub(oldest_obs.block_timestamp) as u128;
+        if total_time_delta == 0 {
+            return Ok(Twap::default());
+        }
+
+        let average_price = |cumulative_after: u128, cumulative_before: u128, time_delta: u128| {
+            cumulative_after
+                .checked_sub(cumulative_before)
+                .ok_or(GammaError::MathOverflow)?
+                .checked_div(time_delta)
+                .ok_or(GammaError::MathOverflow)
+        };
+        let mut twap = Twap {
+            start_timestamp: oldest_obs.block_timestamp,
+            end_timestamp: newest_obs.block_timestamp,
+            token_0_twap_price_x32: average_price(
+                newest_obs.cumulative_token_0_price_x32,
+                oldest_obs.cumulative_token_0_price_x32,
+                total_time_delta,
+            )?,
+            token_0_min_price_x32: u128::MAX,
+            token_0_max_price_x32: 0,
+            token_1_twap_price_x32: average_price(
+                newest_obs.cumulative_token_1_price_x32,
+                oldest_obs.cumulative_token_1_price_x32,
+                total_time_delta,
+            )?,
+            token_1_min_price_x32: u128::MAX,
+            token_1_max_price_x32: 0,
+        };
+
+        // Min and max of the average prices between each observation and the one before it
+        for (index, next_obs) in descending_order_observations {
+            let last_observation_index = if index == 0 {
+                self.capacity() - 1
+            } else {
+                index - 1
+            };
+            let obs = self.get(last_observation_index);
+
+            // if last observation is not valid, skip this observation
+            if obs.block_timestamp == 0 {
+                continue;
+            }
+            if obs.block_timestamp > next_obs.block_timestamp {
+                // Break if current observation is older than the last observation.
+                break;
+            }
+
+            let time_delta = next_obs.block_timestamp.saturating_sub(obs.block_timestamp) as u128;
+            if time_delta == 0 {
+                continue;
+            }
+
+            let token_0_price = average_price(
+                next_obs.cumulative_token_0_price_x32,
+                obs.cumulative_token_0_price_x32,
+                time_delta,
+            )?;
+            let token_1_price = average_price(
+                next_obs.cumulative_token_1_price_x32,
+                obs.cumulative_token_1_price_x32,
+                time_delta,
+            )?;
+            twap.token_0_min_price_x32 = twap.token_0_min_price_x32.min(token_0_price);
+            twap.token_0_max_price_x32 = twap.token_0_max_price_x32.max(token_0_price);
+            twap.token_1_min_price_x32 = twap.token_1_min_price_x32.min(token_1_price);
+            twap.token_1_max_price_x32 = twap.token_1_max_price_x32.max(token_1_price);
+        }
+        if twap.token_0_max_price_x32 == 0 && twap.token_1_max_price_x32 == 0 {
+            twap.token_0_min_price_x32 = 0;
+            twap.token_1_min_price_x32 = 0;
+        }
+
+        Ok(twap)
+    }
+}
+
+impl<'a> ObservationsMut<'a> {
+    /// Borrows the observations of the account for writing
+    pub fn load(observation_state: &'a AccountLoader<'_, ObservationState>) -> Result<Self> {
+        let account_info: &AccountInfo = observation_state.as_ref();
+        if !account_info.is_writable {
+            return err!(ErrorCode::AccountNotMutable);
+        }
+        let data = account_info.try_borrow_mut_data()?;
+        expanded_observation_num(&data)?;
+        let (state, expanded_observations) = RefMut::map_split(data, |data| {
+            let (state, expanded_observations) = data[8..].split_at_mut(ObservationState::LEN - 8);
+            (
+                bytemuck::from_bytes_mut(state),
+                bytemuck::cast_slice_mut(expanded_observations),
+            )
+        });
+        Ok(Self {
+            state,
+            expanded_observations,
+        })
+    }
+
+    pub fn observations(&self) -> Observations<'_> {
+        Observations {
+            state: &self.state,
+            expanded_observations: &self.expanded_observations,
+        }
+    }
+
+    fn get_mut(&mut self, index: usize) -> &mut Observation {
+        if index < OBSERVATION_NUM {
+            &mut self.state.observations[index]
+        } else {
+            &mut self.expanded_observations[index - OBSERVATION_NUM]
+        }
+    }
 
     // Writes an oracle observation to the account, returning the next observation_index.
-    /// Writable at most once per 15 seconds. Index represents the most recently written element.
-    /// If the index is at the end of the allowable array length (100 - 1), the next index will turn to 0.
+    /// Writable at most once per `update_duration` seconds. Index represents the most recently written element.
+    /// If the index is at the end of the allowable array length (capacity - 1), the next index will turn to 0.
     ///
     /// # Arguments
     ///
-    /// * `self` - The ObservationState account to write in
+    /// * `self` - The observations of the account to write in
     /// * `block_timestamp` - The current timestamp of to update
+    /// * `update_duration` - The min duration between two observations of the pool, `OBSERVATION_UPDATE_DURATION_DEFAULT` if zero
     /// * `token_0_price_x32` - The token_0_price_x32 at the time of the new observation
     /// * `token_1_price_x32` - The token_1_price_x32 at the time of the new observation
     ///
@@ -72,20 +303,27 @@ impl ObservationState {
     pub fn update(
         &mut self,
         block_timestamp: u64,
+        update_duration: u64,
         token_0_price_x32: u128,
         token_1_price_x32: u128,
     ) -> Result<()> {
-        let observation_index = self.observation_index;
-        if !self.initialized {
-            self.initialized = true;
-            self.observations[observation_index as usize].block_timestamp = block_timestamp;
-            self.observations[observation_index as usize].cumulative_token_0_price_x32 = 0;
-            self.observations[observation_index as usize].cumulative_token_1_price_x32 = 0;
+        let observation_index = self.state.observation_index as usize;
+        if !self.state.initialized {
+            self.state.initialized = true;
+            let observation = self.get_mut(observation_index);
+            observation.block_timestamp = block_timestamp;
+            observation.cumulative_token_0_price_x32 = 0;
+            observation.cumulative_token_1_price_x32 = 0;
             Ok(())
         } else {
-            let last_observation = self.observations[observation_index as usize];
+            let update_duration = if update_duration == 0 {
+                OBSERVATION_UPDATE_DURATION_DEFAULT
+            } else {
+                update_duration
+            };
+            let last_observation = *self.get_mut(observation_index);
             let delta_time = block_timestamp.saturating_sub(last_observation.block_timestamp);
-            if delta_time < OBSERVATION_UPDATE_DURATION_DEFAULT {
+            if delta_time < update_duration {
                 return Ok(());
             }
             let delta_token_0_price_x32 = token_0_price_x32
@@ -94,22 +332,22 @@ impl ObservationState {
             let delta_token_1_price_x32 = token_1_price_x32
                 .checked_mul(delta_time.into())
                 .ok_or(GammaError::MathOverflow)?;
-            let next_observation_index = if observation_index as usize == OBSERVATION_NUM - 1 {
+            let next_observation_index = if observation_index == self.observations().capacity() - 1
+            {
                 0
             } else {
                 observation_index + 1
             };
-            self.observations[next_observation_index as usize].block_timestamp = block_timestamp;
+            let next_observation = self.get_mut(next_observation_index);
+            next_observation.block_timestamp = block_timestamp;
             // cumulative_token_price_x32 only occupies the first 64 bits, and the remaining 64 bits are used to store overflow data
-            self.observations[next_observation_index as usize].cumulative_token_0_price_x32 =
-                last_observation
-                    .cumulative_token_0_price_x32
-                    .wrapping_add(delta_token_0_price_x32);
-            self.observations[next_observation_index as usize].cumulative_token_1_price_x32 =
-                last_observation
-                    .cumulative_token_1_price_x32
-                    .wrapping_add(delta_token_1_price_x32);
-            self.observation_index = next_observation_index;
+            next_observation.cumulative_token_0_price_x32 = last_observation
+                .cumulative_token_0_price_x32
+                .wrapping_add(delta_token_0_price_x32);
+            next_observation.cumulative_token_1_price_x32 = last_observation
+                .cumulative_token_1_price_x32
+                .wrapping_add(delta_token_1_price_x32);
+            self.state.observation_index = next_observation_index as u16;
             Ok(())
         }
     }
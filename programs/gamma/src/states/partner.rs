@@ -0,0 +1,180 @@
+use anchor_lang::prelude::*;
+
+use crate::{error::GammaError, utils::U256};
+
+use super::PoolState;
+
+pub const PARTNER_SEED: &str = "partner";
+pub const POOL_PARTNERS_SEED: &str = "pool-partners";
+
+/// Max length in bytes of the name of a partner
+pub const MAX_PARTNER_NAME_LEN: usize = 32;
+/// Max partners of a pool in `PoolPartners`, they are searched whenever the lp tokens of a linked user change
+pub const MAX_POOL_PARTNERS: usize = 32;
+/// Fractional bits of `PoolState::protocol_fee_per_lp_token_0/1`
+pub const PROTOCOL_FEE_PER_LP_FRACTIONAL_BITS: usize = 64;
+
+/// Id of a registered `Partner`
+#[derive(Default, Debug, PartialEq, Eq, Clone, Copy, AnchorDeserialize, AnchorSerialize)]
+pub struct PartnerType(pub u8);
+
+impl PartnerType {
+    /// The first partner, tracked in `PoolState::partners` of the pools created before `PoolPartners`
+    /// until it is added to their `PoolPartners`
+    pub const ASSET_DASH: PartnerType = PartnerType(0);
+
+    pub fn id(&self) -> u64 {
+        u64::from(self.0)
+    }
+}
+
+/// A wallet or app partner registered by the admin, users coming from it are linked with it
+/// in `init_user_pool_liquidity_with_partner`
+#[account]
+#[derive(Default, Debug)]
+pub struct Partner {
+    pub partner_id: u8,
+    pub name: String,
+    pub padding: [u8; 32],
+}
+
+impl Partner {
+    pub const LEN: usize = 8 + 1 + 4 + MAX_PARTNER_NAME_LEN + 32;
+
+    pub fn partner_type(&self) -> PartnerType {
+        PartnerType(self.partner_id)
+    }
+}
+
+/// Lp tokens and fees of a pool linked with a partner.
+/// The protocol fees are accrued when the linked lp tokens change, instead of on every swap like `PartnerInfo` did.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, Debug, PartialEq, Eq)]
+pub struct PoolPartnerInfo {
+    pub partner_id: u8,
+    /// Lp tokens owned by the users linked with the partner
+    pub lp_token_linked_with_partner: u64,
+    /// Protocol fees times the tvl share of the partner, up to the checkpoints
+    pub cumulative_fee_total_times_tvl_share_token_0: u64,
+    pub cumulative_fee_total_times_tvl_share_token_1: u64,
+    /// `PoolState::protocol_fee_per_lp_token_0/1` when the protocol fees were last accrued
+    pub protocol_fee_per_lp_checkpoint_token_0: u128,
+    pub protocol_fee_per_lp_checkpoint_token_1: u128,
+}
+
+impl PoolPartnerInfo {
+    pub const LEN: usize = 1 + 8 * 3 + 16 * 2;
+
+    /// Accrues the protocol fees times the tvl share of the partner since the checkpoints,
+    /// it must be called before `lp_token_linked_with_partner` changes.
+    pub fn accrue_protocol_fees(&mut self, pool_state: &PoolState) -> Result<()> {
+        self.cumulative_fee_total_times_tvl_share_token_0 = self
+            .cumulative_fee_total_times_tvl_share_token_0
+            .checked_add(self.protocol_fees_since(
+                pool_state.protocol_fee_per_lp_token_0,
+                self.protocol_fee_per_lp_checkpoint_token_0,
+            )?)
+            .ok_or(GammaError::MathOverflow)?;
+        self.cumulative_fee_total_times_tvl_share_token_1 = self
+            .cumulative_fee_total_times_tvl_share_token_1
+            .checked_add(self.protocol_fees_since(
+                pool_state.protocol_fee_per_lp_token_1,
+                self.protocol_fee_per_lp_checkpoint_token_1,
+            )?)
+            .ok_or(GammaError::MathOverflow)?;
+        self.protocol_fee_per_lp_checkpoint_token_0 = pool_state.protocol_fee_per_lp_token_0;
+        self.protocol_fee_per_lp_checkpoint_token_1 = pool_state.protocol_fee_per_lp_token_1;
+        Ok(())
+    }
+
+    /// The (token_0, token_1) protocol fees times the tvl share of the partner up to now,
+    /// `cumulative_fee_total_times_tvl_share_token_0/1` only include them up to the checkpoints.
+    pub fn cumulative_fees(&self, pool_state: &PoolState) -> Result<(u64, u64)> {
+        let mut partner_info = *self;
+        partner_info.accrue_protocol_fees(pool_state)?;
+        Ok((
+            partner_info.cumulative_fee_total_times_tvl_share_token_0,
+            partner_info.cumulative_fee_total_times_tvl_share_token_1,
+        ))
+    }
+
+    fn protocol_fees_since(
+        &self,
+        protocol_fee_per_lp: u128,
+        protocol_fee_checkpoint: u128,
+    ) -> Result<u64> {
+        let protocol_fee_growth = protocol_fee_per_lp
+            .checked_sub(protocol_fee_checkpoint)
+            .ok_or(GammaError::MathOverflow)?;
+        let protocol_fees = (U256::from(self.lp_token_linked_with_partner)
+            * U256::from(protocol_fee_growth))
+            >> PROTOCOL_FEE_PER_LP_FRACTIONAL_BITS;
+        u64::try_from(protocol_fees).map_err(|_| error!(GammaError::MathOverflow))
+    }
+}
+
+/// Partners of a pool added by the admin, the account grows with every partner added
+#[account]
+#[derive(Default, Debug)]
+pub struct PoolPartners {
+    pub pool_state: Pubkey,
+    pub partners: Vec<PoolPartnerInfo>,
+}
+
+impl PoolPartners {
+    pub fn space(partners: usize) -> usize {
+        8 + 32 + 4 + partners * PoolPartnerInfo::LEN
+    }
+
+    pub fn contains(&self, partner: PartnerType) -> bool {
+        self.partners.iter().any(|p| p.partner_id == partner.0)
+    }
+}
+
+/// Whether the partner is still tracked in `PoolState::partners`, only AssetDash in the pools created
+/// before `PoolPartners` until it is added to their `PoolPartners`
+pub fn is_pool_state_partner(pool_state: &PoolState, partner: PartnerType) -> bool {
+    let pool_state_partners = pool_state.partners;
+    partner == PartnerType::ASSET_DASH && pool_state_partners[0].partner_id == partner.id()
+}
+
+/// Links `lp_tokens_added` and unlinks `lp_tokens_removed` from the partner of a user whose lp tokens change.
+/// The partners are tracked in `pool_partners` which are required for their users, except AssetDash
+/// while it is still tracked in `PoolState::partners`, see `is_pool_state_partner`.
+pub fn update_partner_lp_tokens(
+    pool_state: &mut PoolState,
+    pool_partners: Option<&mut PoolPartners>,
+    partner: Option<PartnerType>,
+    lp_tokens_added: u64,
+    lp_tokens_removed: u64,
+) -> Result<()> {
+    let Some(partner) = partner else {
+        return Ok(());
+    };
+    if is_pool_state_partner(pool_state, partner) {
+        // Only the lp tokens are linked, the fees accrue once it is moved to `PoolPartners`
+        let mut pool_state_partners = pool_state.partners;
+        pool_state_partners[0].lp_token_linked_with_partner = pool_state_partners[0]
+            .lp_token_linked_with_partner
+            .checked_add(lp_tokens_added)
+            .ok_or(GammaError::MathOverflow)?
+            .checked_sub(lp_tokens_removed)
+            .ok_or(GammaError::MathOverflow)?;
+        pool_state.partners = pool_state_partners;
+        return Ok(());
+    }
+
+    let pool_partners = pool_partners.ok_or(GammaError::InvalidPartner)?;
+    let partner_info = pool_partners
+        .partners
+        .iter_mut()
+        .find(|p| p.partner_id == partner.0)
+        .ok_or(GammaError::InvalidPartner)?;
+    partner_info.accrue_protocol_fees(pool_state)?;
+    partner_info.lp_token_linked_with_partner = partner_info
+        .lp_token_linked_with_partner
+        .checked_add(lp_tokens_added)
+        .ok_or(GammaError::MathOverflow)?
+        .checked_sub(lp_tokens_removed)
+        .ok_or(GammaError::MathOverflow)?;
+    Ok(())
+}
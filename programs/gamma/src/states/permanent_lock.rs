@@ -0,0 +1,100 @@
+use anchor_lang::prelude::*;
+
+use crate::{error::GammaError, utils::U256};
+
+use super::{PoolState, FEE_GROWTH_PER_LP_FRACTIONAL_BITS};
+
+pub const PERMANENT_LOCK_SEED: &str = "permanent-lock";
+
+/// Pool tokens locked forever by their owner, like the `LOCK_LP_AMOUNT` of the pool.
+/// The locked pool tokens never decrease, the trading fees they earn through
+/// `PoolState::fee_growth_per_lp_token_0/1` are set apart in `PoolState::permanent_lock_fees_token_0/1`
+/// and can be claimed by the owner.
+#[account]
+#[derive(Default, Debug)]
+pub struct PermanentLock {
+    pub owner: Pubkey,
+    pub pool_state: Pubkey,
+    /// Pool tokens that can never be withdrawn
+    pub locked_lp_tokens: u64,
+    /// `PoolState::fee_growth_per_lp_token_0/1` when the fees owed were last updated
+    pub fee_growth_checkpoint_token_0: u128,
+    pub fee_growth_checkpoint_token_1: u128,
+    /// Fees earned by the locked pool tokens and not claimed yet
+    pub fees_owed_token_0: u64,
+    pub fees_owed_token_1: u64,
+    /// Fees claimed by the owner
+    pub total_fees_claimed_token_0: u64,
+    pub total_fees_claimed_token_1: u64,
+    pub padding: [u8; 32],
+}
+
+impl PermanentLock {
+    pub const LEN: usize = 8 + 32 * 2 + 8 + 16 * 2 + 8 * 4 + 32;
+
+    pub fn initialize(&mut self, owner: Pubkey, pool_state_key: Pubkey, pool_state: &PoolState) {
+        self.owner = owner;
+        self.pool_state = pool_state_key;
+        self.locked_lp_tokens = 0;
+        self.fee_growth_checkpoint_token_0 = pool_state.fee_growth_per_lp_token_0;
+        self.fee_growth_checkpoint_token_1 = pool_state.fee_growth_per_lp_token_1;
+        self.fees_owed_token_0 = 0;
+        self.fees_owed_token_1 = 0;
+        self.total_fees_claimed_token_0 = 0;
+        self.total_fees_claimed_token_1 = 0;
+        self.padding = [0u8; 32];
+    }
+
+    /// Accrues the fees earned since the checkpoints to the fees owed and moves them to the current
+    /// fee growth of the pool, it must be called before `locked_lp_tokens` changes.
+    pub fn update_fees_owed(&mut self, pool_state: &PoolState) -> Result<()> {
+        self.fees_owed_token_0 = self
+            .fees_owed_token_0
+            .checked_add(self.fees_earned_since(
+                pool_state.fee_growth_per_lp_token_0,
+                self.fee_growth_checkpoint_token_0,
+            )?)
+            .ok_or(GammaError::MathOverflow)?;
+        self.fees_owed_token_1 = self
+            .fees_owed_token_1
+            .checked_add(self.fees_earned_since(
+                pool_state.fee_growth_per_lp_token_1,
+                self.fee_growth_checkpoint_token_1,
+            )?)
+            .ok_or(GammaError::MathOverflow)?;
+        self.fee_growth_checkpoint_token_0 = pool_state.fee_growth_per_lp_token_0;
+        self.fee_growth_checkpoint_token_1 = pool_state.fee_growth_per_lp_token_1;
+        Ok(())
+    }
+
+    /// Takes the fees owed up to the current fee growth of the pool, the locked pool tokens are unchanged.
+    /// Returns the (token_0, token_1) fees claimed.
+    pub fn claim_fees(&mut self, pool_state: &PoolState) -> Result<(u64, u64)> {
+        self.update_fees_owed(pool_state)?;
+        let fees = (self.fees_owed_token_0, self.fees_owed_token_1);
+        self.fees_owed_token_0 = 0;
+        self.fees_owed_token_1 = 0;
+        self.total_fees_claimed_token_0 = self
+            .total_fees_claimed_token_0
+            .checked_add(fees.0)
+            .ok_or(GammaError::MathOverflow)?;
+        self.total_fees_claimed_token_1 = self
+            .total_fees_claimed_token_1
+            .checked_add(fees.1)
+            .ok_or(GammaError::MathOverflow)?;
+        Ok(fees)
+    }
+
+    fn fees_earned_since(
+        &self,
+        fee_growth_per_lp: u128,
+        fee_growth_checkpoint: u128,
+    ) -> Result<u64> {
+        let fee_growth = fee_growth_per_lp
+            .checked_sub(fee_growth_checkpoint)
+            .ok_or(GammaError::MathOverflow)?;
+        let fees_earned = (U256::from(self.locked_lp_tokens) * U256::from(fee_growth))
+            >> FEE_GROWTH_PER_LP_FRACTIONAL_BITS;
+        u64::try_from(fees_earned).map_err(|_| error!(GammaError::MathOverflow))
+    }
+}
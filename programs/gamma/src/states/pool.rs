@@ -1,4 +1,11 @@
+use crate::curve::{CurveType, StableSwapCurve, WeightedCurve};
 use crate::error::GammaError;
+use crate::fees::FeeType;
+use crate::states::{
+    AmmConfig, OBSERVATION_UPDATE_DURATION_DEFAULT, PROTOCOL_FEE_PER_LP_FRACTIONAL_BITS,
+};
+use crate::utils::U256;
+use crate::LOCK_LP_AMOUNT;
 use anchor_lang::prelude::*;
 use anchor_spl::token_interface::Mint;
 use std::ops::{BitAnd, BitOr, BitXor};
@@ -6,12 +13,19 @@ use std::ops::{BitAnd, BitOr, BitXor};
 // Seed to derive account address and signature
 pub const POOL_SEED: &str = "pool";
 pub const POOL_LP_MINT_SEED: &str = "pool_lp_mint";
+pub const LP_MINT_DECIMALS: u8 = 9;
 pub const POOL_VAULT_SEED: &str = "pool_vault";
 // This is for deriving the token account where kamino collateral is deposited
 pub const POOL_KAMINO_DEPOSITS_SEED: &str = "pool_kamino_deposits";
 
 pub const Q32: u128 = (u32::MAX as u128) + 1; // 2^32
 
+/// Max reward infos of a pool that can be active at the same time
+pub const MAX_ACTIVE_REWARD_INFOS: usize = 4;
+
+/// Fractional bits of `PoolState::fee_growth_per_lp_token_0/1`
+pub const FEE_GROWTH_PER_LP_FRACTIONAL_BITS: usize = 64;
+
 pub enum PoolStatusBitIndex {
     Deposit,
     Withdraw,
@@ -24,22 +38,6 @@ pub enum PoolStatusBitFlag {
     Disable,
 }
 
-#[derive(Default, Debug, PartialEq, Eq, Clone, Copy, AnchorDeserialize, AnchorSerialize)]
-#[repr(u64)]
-pub enum PartnerType {
-    #[default]
-    AssetDash = 0,
-}
-
-impl PartnerType {
-    pub fn new(value: u64) -> Self {
-        match value {
-            0 => PartnerType::AssetDash,
-            _ => PartnerType::AssetDash,
-        }
-    }
-}
-
 #[zero_copy(unsafe)]
 #[repr(packed)]
 #[derive(Default, Debug)]
@@ -55,6 +53,12 @@ pub struct PartnerInfo {
     pub cumulative_fee_total_times_tvl_share_token_1: u64,
 }
 
+impl PartnerInfo {
+    /// `partner_id` once AssetDash was moved to `PoolPartners` by `add_pool_partner`,
+    /// the pools created since track all their partners in `PoolPartners`
+    pub const MOVED_TO_POOL_PARTNERS: u64 = u64::MAX;
+}
+
 #[account(zero_copy(unsafe))]
 #[repr(packed)]
 #[derive(Default, Debug)]
@@ -68,10 +72,9 @@ pub struct PoolState {
     /// Vault to store Token B of the pool
     pub token_1_vault: Pubkey,
 
-    /// Pool tokens are issued when Token A or Token B are deposited
-    /// Pool tokens can be withdrawn back to the original Token A or Token B
-    // pub lp_mint: Pubkey,
-    pub _padding1: [u8; 32],
+    /// Optional spl mint of the pool tokens, `UserPoolLiquidity` balances can be wrapped into it.
+    /// Default pubkey if the lp mint of the pool is not created.
+    pub lp_mint: Pubkey,
     /// Mint info of Token A
     pub token_0_mint: Pubkey,
     /// Mint info of Token B
@@ -94,8 +97,7 @@ pub struct PoolState {
     pub status: u8,
 
     /// lp_mint decimals
-    // pub lp_mint_decimals: u8,
-    pub _padding2: u8,
+    pub lp_mint_decimals: u8,
     /// mint0 and mint1 decimals
     pub mint_0_decimals: u8,
     pub mint_1_decimals: u8,
@@ -150,12 +152,69 @@ pub struct PoolState {
     // To keep track of the profit we made from kamino, in terms of the token0 or token1.
     pub withdrawn_kamino_profit_token_0: u64,
     pub withdrawn_kamino_profit_token_1: u64,
-    /// padding
-    pub padding: [u64; 8],
+
+    /// The curve used for pricing swaps, see `CurveType`
+    pub curve_type: u8,
+    /// The fee charged on swaps, see `FeeType`
+    pub fee_type: u8,
+    pub _padding3: [u8; 2],
+    /// Min duration in seconds between two observations of the pool
+    /// if zero then default of `OBSERVATION_UPDATE_DURATION_DEFAULT` is used
+    pub observation_update_duration: u32,
+    /// Parameter of the curve, the amplification coefficient for StableSwap
+    /// or the token_0 weight for Weighted
+    pub curve_parameter: u64,
+    /// Pool tokens wrapped into `lp_mint`, they are part of `lp_supply` but not owned by any `UserPoolLiquidity`
+    pub wrapped_lp_supply: u64,
+    /// Fee rate charged at `open_time` by the launch fee types, decaying to the base fee
+    pub launch_fee_rate: u32,
+    /// Duration in seconds after `open_time` over which the launch fee decays
+    pub launch_fee_duration: u32,
+    /// Trade fee rate of the pool used instead of the one of the amm config when it is not zero
+    pub trade_fee_rate_override: u32,
+    /// Protocol fee rate of the pool, used instead of the one of the amm config with `trade_fee_rate_override`
+    pub protocol_fee_rate_override: u32,
+    /// Fund fee rate of the pool, used instead of the one of the amm config with `trade_fee_rate_override`
+    pub fund_fee_rate_override: u32,
+    /// Share of the trade fee accrued to the pool creator, denominated in hundredths of bip (10^-6)
+    pub creator_fee_rate: u32,
+    /// Creator fees accrued to the pool, claimable by `pool_creator`
+    pub creator_fees_token_0: u64,
+    pub creator_fees_token_1: u64,
+    /// Reward infos of the pool that have not ended yet, they are settled whenever the lp tokens change
+    pub active_reward_infos: u64,
+    /// Index of the `active_reward_infos`, the unused slots are the default pubkey
+    pub active_reward_info_keys: [Pubkey; MAX_ACTIVE_REWARD_INFOS],
+    /// Extra reward weight of the locked lp tokens of all the users, see `UserPoolLiquidity::lp_lock_boost_weight`
+    pub lp_lock_boost_weight: u64,
+    /// Pool tokens locked forever in `PermanentLock` accounts, like `LOCK_LP_AMOUNT` they are not owned by any user
+    pub permanent_lp_supply: u64,
+    /// Trade fees kept in the vaults for the liquidity providers per lp token of `lp_supply`, in Q64.64
+    pub fee_growth_per_lp_token_0: u128,
+    pub fee_growth_per_lp_token_1: u128,
+    /// Protocol fees per lp token of `lp_supply`, in Q64.64, the fees of the partners in `PoolPartners` accrue from it
+    pub protocol_fee_per_lp_token_0: u128,
+    pub protocol_fee_per_lp_token_1: u128,
+    /// Share of the trade fees of the `permanent_lp_supply`, set apart from the vaults amounts
+    /// and claimable by the `PermanentLock` accounts
+    pub permanent_lock_fees_token_0: u64,
+    pub permanent_lock_fees_token_1: u64,
 }
 
 impl PoolState {
-    pub const LEN: usize = 8 + 10 * 32 + 5 * 1 + 7 * 8 + 16 * 4 + 23 * 8;
+    pub const LEN: usize = 8
+        + 10 * 32
+        + 5 * 1
+        + 7 * 8
+        + 16 * 4
+        + 23 * 8
+        + 8
+        + MAX_ACTIVE_REWARD_INFOS * 32
+        + 8
+        + 8
+        + 16 * 2
+        + 16 * 2
+        + 2 * 8;
 
     pub fn initialize(
         &mut self,
@@ -166,6 +225,11 @@ impl PoolState {
         open_time: u64,
         max_trade_fee_rate: u64,
         volatility_factor: u64,
+        curve_type: CurveType,
+        curve_parameter: u64,
+        fee_type: FeeType,
+        launch_fee_rate: u32,
+        launch_fee_duration: u32,
         pool_creator: Pubkey,
         amm_config: Pubkey,
         token_0_vault: Pubkey,
@@ -211,9 +275,36 @@ impl PoolState {
         self.token_0_amount_in_kamino = 0;
         self.token_1_amount_in_kamino = 0;
 
-        self.partners = [PartnerInfo::default(); 1];
-
-        self.padding = [0u64; 8];
+        self.partners = [PartnerInfo {
+            partner_id: PartnerInfo::MOVED_TO_POOL_PARTNERS,
+            ..Default::default()
+        }; 1];
+
+        self.curve_type = curve_type as u8;
+        self.curve_parameter = curve_parameter;
+        self.fee_type = fee_type as u8;
+        self.launch_fee_rate = launch_fee_rate;
+        self.launch_fee_duration = launch_fee_duration;
+        self.lp_mint = Pubkey::default();
+        self.lp_mint_decimals = 0;
+        self.wrapped_lp_supply = 0;
+        self.observation_update_duration = OBSERVATION_UPDATE_DURATION_DEFAULT as u32;
+        self.trade_fee_rate_override = 0;
+        self.protocol_fee_rate_override = 0;
+        self.fund_fee_rate_override = 0;
+        self.creator_fee_rate = 0;
+        self.creator_fees_token_0 = 0;
+        self.creator_fees_token_1 = 0;
+        self.active_reward_infos = 0;
+        self.active_reward_info_keys = [Pubkey::default(); MAX_ACTIVE_REWARD_INFOS];
+        self.lp_lock_boost_weight = 0;
+        self.permanent_lp_supply = 0;
+        self.fee_growth_per_lp_token_0 = 0;
+        self.fee_growth_per_lp_token_1 = 0;
+        self.protocol_fee_per_lp_token_0 = 0;
+        self.protocol_fee_per_lp_token_1 = 0;
+        self.permanent_lock_fees_token_0 = 0;
+        self.permanent_lock_fees_token_1 = 0;
         Ok(())
     }
 
@@ -237,15 +328,219 @@ impl PoolState {
         self.status.bitand(status) == 0
     }
 
+    pub fn curve_type(&self) -> Result<CurveType> {
+        CurveType::new(self.curve_type)
+    }
+
+    pub fn fee_type(&self) -> Result<FeeType> {
+        FeeType::new(self.fee_type)
+    }
+
+    /// The base fee rate of the swaps, the trade fee rate of the amm config unless it is overridden
+    pub fn trade_fee_rate(&self, amm_config: &AmmConfig) -> u64 {
+        if self.trade_fee_rate_override == 0 {
+            amm_config.trade_fee_rate
+        } else {
+            u64::from(self.trade_fee_rate_override)
+        }
+    }
+
+    /// The protocol fee rate within the trade fee
+    pub fn protocol_fee_rate(&self, amm_config: &AmmConfig) -> u64 {
+        if self.trade_fee_rate_override == 0 {
+            amm_config.protocol_fee_rate
+        } else {
+            u64::from(self.protocol_fee_rate_override)
+        }
+    }
+
+    /// The fund fee rate within the trade fee
+    pub fn fund_fee_rate(&self, amm_config: &AmmConfig) -> u64 {
+        if self.trade_fee_rate_override == 0 {
+            amm_config.fund_fee_rate
+        } else {
+            u64::from(self.fund_fee_rate_override)
+        }
+    }
+
+    pub fn has_lp_mint(&self) -> bool {
+        self.lp_mint != Pubkey::default()
+    }
+
+    /// Pool tokens owned through `UserPoolLiquidity` accounts, including the locked liquidity
+    pub fn unwrapped_lp_supply(&self) -> Result<u64> {
+        Ok(self
+            .lp_supply
+            .checked_sub(self.wrapped_lp_supply)
+            .ok_or(GammaError::MathOverflow)?)
+    }
+
+    /// Reward weight of the pool tokens eligible for rewards, the locked liquidity is not owned by any user
+    /// and the lp tokens locked by the users are boosted
+    pub fn reward_lp_supply(&self) -> Result<u64> {
+        Ok(self
+            .unwrapped_lp_supply()?
+            .checked_sub(LOCK_LP_AMOUNT)
+            .ok_or(GammaError::MathOverflow)?
+            .checked_sub(self.permanent_lp_supply)
+            .ok_or(GammaError::MathOverflow)?
+            .checked_add(self.lp_lock_boost_weight)
+            .ok_or(GammaError::MathOverflow)?)
+    }
+
+    pub fn is_active_reward_info(&self, reward_info: Pubkey) -> bool {
+        reward_info != Pubkey::default() && self.active_reward_info_keys.contains(&reward_info)
+    }
+
+    pub fn add_active_reward_info(&mut self, reward_info: Pubkey) -> Result<()> {
+        let slot = self
+            .active_reward_info_keys
+            .iter()
+            .position(|key| *key == Pubkey::default())
+            .ok_or(GammaError::TooManyActiveRewardInfos)?;
+        self.active_reward_info_keys[slot] = reward_info;
+        self.active_reward_infos = self
+            .active_reward_infos
+            .checked_add(1)
+            .ok_or(GammaError::MathOverflow)?;
+        Ok(())
+    }
+
+    pub fn remove_active_reward_info(&mut self, reward_info: Pubkey) -> Result<()> {
+        let slot = self
+            .active_reward_info_keys
+            .iter()
+            .position(|key| *key == reward_info)
+            .ok_or(GammaError::InvalidInput)?;
+        self.active_reward_info_keys[slot] = Pubkey::default();
+        self.active_reward_infos = self
+            .active_reward_infos
+            .checked_sub(1)
+            .ok_or(GammaError::MathOverflow)?;
+        Ok(())
+    }
+
+    /// Accrues the trade fees kept in the vaults for the liquidity providers to the fee growth per lp token,
+    /// rounded down so the fees earned by the users never exceed the fees accrued.
+    /// The share of the `permanent_lp_supply` is set apart from the vaults amounts, rounded up
+    /// so it always covers the fees earned by the permanent locks.
+    pub fn accrue_lp_fees(&mut self, lp_fees_token_0: u64, lp_fees_token_1: u64) -> Result<()> {
+        if self.lp_supply == 0 {
+            return Ok(());
+        }
+        let fee_growth_token_0 = (u128::from(lp_fees_token_0) << FEE_GROWTH_PER_LP_FRACTIONAL_BITS)
+            / u128::from(self.lp_supply);
+        let fee_growth_token_1 = (u128::from(lp_fees_token_1) << FEE_GROWTH_PER_LP_FRACTIONAL_BITS)
+            / u128::from(self.lp_supply);
+        let permanent_lock_fees_token_0 = self.permanent_lock_fees(fee_growth_token_0)?;
+        let permanent_lock_fees_token_1 = self.permanent_lock_fees(fee_growth_token_1)?;
+        self.token_0_vault_amount = self
+            .token_0_vault_amount
+            .checked_sub(permanent_lock_fees_token_0)
+            .ok_or(GammaError::MathOverflow)?;
+        self.token_1_vault_amount = self
+            .token_1_vault_amount
+            .checked_sub(permanent_lock_fees_token_1)
+            .ok_or(GammaError::MathOverflow)?;
+        self.permanent_lock_fees_token_0 = self
+            .permanent_lock_fees_token_0
+            .checked_add(permanent_lock_fees_token_0)
+            .ok_or(GammaError::MathOverflow)?;
+        self.permanent_lock_fees_token_1 = self
+            .permanent_lock_fees_token_1
+            .checked_add(permanent_lock_fees_token_1)
+            .ok_or(GammaError::MathOverflow)?;
+        self.fee_growth_per_lp_token_0 = self
+            .fee_growth_per_lp_token_0
+            .checked_add(fee_growth_token_0)
+            .ok_or(GammaError::MathOverflow)?;
+        self.fee_growth_per_lp_token_1 = self
+            .fee_growth_per_lp_token_1
+            .checked_add(fee_growth_token_1)
+            .ok_or(GammaError::MathOverflow)?;
+        Ok(())
+    }
+
+    /// Fees of the `permanent_lp_supply` for a fee growth per lp token, rounded up
+    fn permanent_lock_fees(&self, fee_growth: u128) -> Result<u64> {
+        let fees = (U256::from(self.permanent_lp_supply) * U256::from(fee_growth)
+            + (U256::one() << FEE_GROWTH_PER_LP_FRACTIONAL_BITS)
+            - 1)
+            >> FEE_GROWTH_PER_LP_FRACTIONAL_BITS;
+        u64::try_from(fees).map_err(|_| error!(GammaError::MathOverflow))
+    }
+
+    /// Accrues the protocol fees to the protocol fee per lp token, see `PoolPartnerInfo::accrue_protocol_fees`
+    pub fn accrue_protocol_fees(
+        &mut self,
+        protocol_fees_token_0: u64,
+        protocol_fees_token_1: u64,
+    ) -> Result<()> {
+        if self.lp_supply == 0 {
+            return Ok(());
+        }
+        let protocol_fee_token_0 = (u128::from(protocol_fees_token_0)
+            << PROTOCOL_FEE_PER_LP_FRACTIONAL_BITS)
+            / u128::from(self.lp_supply);
+        let protocol_fee_token_1 = (u128::from(protocol_fees_token_1)
+            << PROTOCOL_FEE_PER_LP_FRACTIONAL_BITS)
+            / u128::from(self.lp_supply);
+        self.protocol_fee_per_lp_token_0 = self
+            .protocol_fee_per_lp_token_0
+            .checked_add(protocol_fee_token_0)
+            .ok_or(GammaError::MathOverflow)?;
+        self.protocol_fee_per_lp_token_1 = self
+            .protocol_fee_per_lp_token_1
+            .checked_add(protocol_fee_token_1)
+            .ok_or(GammaError::MathOverflow)?;
+        Ok(())
+    }
+
     pub fn vault_amount_without_fee(&self) -> Result<(u64, u64)> {
         Ok((self.token_0_vault_amount, self.token_1_vault_amount))
     }
 
+    /// The (token_0, token_1) spot prices of the pool, Q32.32
     pub fn token_price_x32(&self) -> Result<(u128, u128)> {
         let (token_0_amount, token_1_amount) = self.vault_amount_without_fee()?;
+        let (token_0_value, token_1_value) =
+            self.price_values(u128::from(token_0_amount), u128::from(token_1_amount))?;
+        Ok((
+            token_1_value
+                .checked_mul(Q32)
+                .ok_or(GammaError::MathOverflow)?
+                .checked_div(token_0_value)
+                .ok_or(GammaError::MathOverflow)?,
+            token_0_value
+                .checked_mul(Q32)
+                .ok_or(GammaError::MathOverflow)?
+                .checked_div(token_1_value)
+                .ok_or(GammaError::MathOverflow)?,
+        ))
+    }
+
+    /// The amounts of the tokens scaled for pricing, a token price is the ratio of the values.
+    /// The amounts of weighted pools are divided by their weights, the token_0 price is (y / w_y) / (x / w_x).
+    /// StableSwap pools are priced by their marginal price at the invariant, see `StableSwapCurve::price_values`.
+    pub fn price_values(&self, token_0_amount: u128, token_1_amount: u128) -> Result<(u128, u128)> {
+        let (token_0_weight, token_1_weight) = match self.curve_type()? {
+            CurveType::Weighted => WeightedCurve::weights(self.curve_parameter)?,
+            CurveType::StableSwap => {
+                return StableSwapCurve::price_values(
+                    self.curve_parameter,
+                    token_0_amount,
+                    token_1_amount,
+                )
+            }
+            CurveType::ConstantProduct => (1, 1),
+        };
         Ok((
-            token_1_amount as u128 * Q32 as u128 / token_0_amount as u128,
-            token_0_amount as u128 * Q32 as u128 / token_1_amount as u128,
+            token_0_amount
+                .checked_mul(u128::from(token_1_weight))
+                .ok_or(GammaError::MathOverflow)?,
+            token_1_amount
+                .checked_mul(u128::from(token_0_weight))
+                .ok_or(GammaError::MathOverflow)?,
         ))
     }
 }
@@ -0,0 +1,102 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    curve::OraclePrice, error::GammaError, external::pyth::PriceUpdateV2,
+    external::pyth::VerificationLevel, fees::FEE_RATE_DENOMINATOR_VALUE,
+};
+
+pub const PRICE_FEED_CONFIG_SEED: &str = "price_feed_config";
+
+/// Oracle configuration used by oracle based swaps of a pool.
+#[account]
+#[derive(Default, Debug)]
+pub struct PriceFeedConfig {
+    pub pool_state: Pubkey,
+    /// Pyth feed id of token_0, quoted in a reference asset(e.g. USD)
+    pub token_0_feed_id: [u8; 32],
+    /// Pyth feed id of token_1, quoted in the same reference asset as token_0.
+    /// If all zeros, the token_0 feed is expected to be quoted in token_1 directly.
+    pub token_1_feed_id: [u8; 32],
+    /// Max age in seconds of a price update that is accepted
+    pub max_price_age: u64,
+    /// Max confidence interval as a fraction of the price, denominated in hundredths of bip (10^-6)
+    pub max_confidence_rate: u64,
+    /// padding
+    pub padding: [u64; 4],
+}
+
+impl PriceFeedConfig {
+    pub const LEN: usize = 8 + 32 * 3 + 2 * 8 + 4 * 8;
+
+    pub fn has_token_1_feed(&self) -> bool {
+        self.token_1_feed_id != [0u8; 32]
+    }
+
+    /// Reads and validates the price of a single raw unit of the token from the price update account.
+    ///
+    /// # Arguments
+    ///
+    /// * `price_update` - The pyth price update account
+    /// * `feed_id` - The feed id that is expected for the token
+    /// * `mint_decimals` - The decimals of the token priced by the feed
+    /// * `block_timestamp` - The current block timestamp
+    /// * `round_up` - Whether to add(true) or subtract(false) the confidence interval from the price
+    ///
+    pub fn get_oracle_price(
+        &self,
+        price_update: &AccountInfo<'_>,
+        feed_id: &[u8; 32],
+        mint_decimals: u8,
+        block_timestamp: u64,
+        round_up: bool,
+    ) -> Result<OraclePrice> {
+        let price_update = PriceUpdateV2::try_from_account_info(price_update)
+            .ok_or(GammaError::InvalidPriceFeed)?;
+        if price_update.verification_level != VerificationLevel::Full {
+            return err!(GammaError::InvalidPriceFeed);
+        }
+
+        let message = price_update.price_message;
+        if message.feed_id != *feed_id || message.price <= 0 {
+            return err!(GammaError::InvalidPriceFeed);
+        }
+
+        let publish_time =
+            u64::try_from(message.publish_time).or(err!(GammaError::InvalidPriceFeed))?;
+        if publish_time
+            .checked_add(self.max_price_age)
+            .ok_or(GammaError::MathOverflow)?
+            < block_timestamp
+        {
+            return err!(GammaError::StalePrice);
+        }
+
+        let price = message.price as u64;
+        // conf / price <= max_confidence_rate / FEE_RATE_DENOMINATOR_VALUE
+        if u128::from(message.conf)
+            .checked_mul(u128::from(FEE_RATE_DENOMINATOR_VALUE))
+            .ok_or(GammaError::MathOverflow)?
+            > u128::from(price)
+                .checked_mul(u128::from(self.max_confidence_rate))
+                .ok_or(GammaError::MathOverflow)?
+        {
+            return err!(GammaError::PriceConfidenceTooWide);
+        }
+
+        let price = if round_up {
+            price.checked_add(message.conf)
+        } else {
+            price.checked_sub(message.conf)
+        }
+        .ok_or(GammaError::MathOverflow)?;
+        require_gt!(price, 0, GammaError::PriceConfidenceTooWide);
+
+        Ok(OraclePrice {
+            price,
+            exponent: message
+                .exponent
+                .checked_sub(i32::from(mint_decimals))
+                .ok_or(GammaError::MathOverflow)?,
+        })
+    }
+}
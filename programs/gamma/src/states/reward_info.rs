@@ -1,8 +1,13 @@
 use anchor_lang::prelude::*;
-use rust_decimal::Decimal;
 
-use crate::error::GammaError;
-use rust_decimal::prelude::FromPrimitive;
+use crate::{error::GammaError, utils::U256};
+
+/// Fractional bits of `RewardInfo::reward_per_lp_share`
+pub const REWARD_PER_LP_SHARE_FRACTIONAL_BITS: usize = 64;
+/// Longest time a reward can take from now until its end, a reward holds a slot of the active rewards of the pool until it ends
+pub const MAX_REWARD_DURATION: u64 = 365 * 24 * 60 * 60;
+/// Smallest reward released per second, so the slots of the active rewards can not be held with dust rewards
+pub const MIN_REWARD_PER_SECOND: u64 = 1;
 
 #[account]
 pub struct RewardInfo {
@@ -12,15 +17,137 @@ pub struct RewardInfo {
     pub mint: Pubkey,
     pub total_to_disburse: u64, // Total rewards to distribute in this unix timestamp.
     pub rewarded_by: Pubkey,    // The reward given by
+    pub reward_per_lp_share: u128, // Rewards accrued per eligible lp token, in Q64.64.
+    pub last_updated_at: u64,   // Last time the rewards were accrued to `reward_per_lp_share`.
+    pub total_released: u64,    // Rewards released up to `last_updated_at`.
+    pub unallocated_rewards: u64, // Rewards released while no lp tokens were eligible, refundable to `rewarded_by`.
+    pub legacy_reward_per_lp_share: u128, // `reward_per_lp_share` when a reward created before the accumulator was migrated.
+    pub legacy_rewards_until: u64, // Time a reward created before the accumulator was migrated, 0 for the other rewards.
+    pub user_reward_infos: u64, // User reward infos of the reward not closed yet, the reward can only be closed without them.
 }
 
 impl RewardInfo {
-    pub fn get_time_diff(&self) -> Result<Decimal> {
-        let time_diff = self
+    pub const LEN: usize = 8 + std::mem::size_of::<RewardInfo>();
+    /// Size of the reward infos created before the accumulator, up to `rewarded_by`
+    pub const LEGACY_LEN: usize = 8 + 32 * 3 + 8 * 3;
+
+    /// The reward has been accrued up to its end, `reward_per_lp_share` does not change anymore.
+    pub fn is_ended(&self) -> bool {
+        self.last_updated_at >= self.end_rewards_at
+    }
+
+    /// Checks that the reward ends within `MAX_REWARD_DURATION` from now and that the rewards not released yet
+    /// are released at `MIN_REWARD_PER_SECOND` at least.
+    pub fn validate_reward_rate(&self, current_time: u64) -> Result<()> {
+        require_gte!(
+            current_time
+                .checked_add(MAX_REWARD_DURATION)
+                .ok_or(GammaError::MathOverflow)?,
+            self.end_rewards_at,
+            GammaError::InvalidRewardTime
+        );
+        let remaining_duration = self
             .end_rewards_at
-            .checked_sub(self.start_at)
-            .ok_or(error!(GammaError::MathOverflow))?;
+            .checked_sub(self.last_updated_at.max(self.start_at))
+            .ok_or(GammaError::MathOverflow)?;
+        require_gte!(
+            self.total_to_disburse
+                .checked_sub(self.total_released)
+                .ok_or(GammaError::MathOverflow)?,
+            remaining_duration
+                .checked_mul(MIN_REWARD_PER_SECOND)
+                .ok_or(GammaError::MathOverflow)?,
+            GammaError::InvalidRewardAmount
+        );
+        Ok(())
+    }
+
+    /// Ends the reward now, the rewards not released yet become unallocated and refundable to `rewarded_by`.
+    /// The reward must have been accrued up to `current_time`.
+    pub fn end_now(&mut self, current_time: u64) -> Result<()> {
+        let unreleased = self
+            .total_to_disburse
+            .checked_sub(self.total_released)
+            .ok_or(GammaError::MathOverflow)?;
+        self.unallocated_rewards = self
+            .unallocated_rewards
+            .checked_add(unreleased)
+            .ok_or(GammaError::MathOverflow)?;
+        self.total_released = self.total_to_disburse;
+        self.end_rewards_at = current_time;
+        self.last_updated_at = current_time;
+        Ok(())
+    }
+
+    /// Accrues the rewards released since the last update to `reward_per_lp_share`.
+    /// The rewards not released yet are released linearly until `end_rewards_at`, so the rewards topped up
+    /// and the extensions only change the rewards released from now on. They are split between the
+    /// `reward_lp_supply` lp tokens held meanwhile, the rewards released while it is zero are unallocated.
+    /// The accrued rewards and the rewards of the users are rounded down, so the users can never claim
+    /// more than the rewards released.
+    ///
+    /// Returns true when the reward ended with this update.
+    pub fn update_reward_per_lp_share(
+        &mut self,
+        reward_lp_supply: u64,
+        current_time: u64,
+    ) -> Result<bool> {
+        if self.is_ended() || current_time <= self.last_updated_at {
+            return Ok(false);
+        }
+
+        let accrued_from = self.last_updated_at.max(self.start_at);
+        let accrued_to = current_time.min(self.end_rewards_at);
+        if accrued_to > accrued_from {
+            // (total_to_disburse - total_released) * (accrued_to - accrued_from) / (end_rewards_at - accrued_from)
+            let released = u128::from(
+                self.total_to_disburse
+                    .checked_sub(self.total_released)
+                    .ok_or(GammaError::MathOverflow)?,
+            )
+            .checked_mul(u128::from(accrued_to - accrued_from))
+            .ok_or(GammaError::MathOverflow)?
+            .checked_div(u128::from(self.end_rewards_at - accrued_from))
+            .ok_or(GammaError::MathOverflow)?;
+            let released = u64::try_from(released).map_err(|_| error!(GammaError::MathOverflow))?;
+            self.total_released = self
+                .total_released
+                .checked_add(released)
+                .ok_or(GammaError::MathOverflow)?;
+
+            if reward_lp_supply == 0 {
+                self.unallocated_rewards = self
+                    .unallocated_rewards
+                    .checked_add(released)
+                    .ok_or(GammaError::MathOverflow)?;
+            } else {
+                let accrued = (U256::from(released) << REWARD_PER_LP_SHARE_FRACTIONAL_BITS)
+                    / U256::from(reward_lp_supply);
+                self.reward_per_lp_share = self
+                    .reward_per_lp_share
+                    .checked_add(
+                        u128::try_from(accrued).map_err(|_| error!(GammaError::MathOverflow))?,
+                    )
+                    .ok_or(GammaError::MathOverflow)?;
+            }
+        }
+
+        self.last_updated_at = current_time;
+        Ok(self.is_ended())
+    }
 
-        Decimal::from_u64(time_diff).ok_or(error!(GammaError::MathOverflow))
+    /// Starts the accumulator of a reward created before it. The rewards released since the start are accrued
+    /// at once, split between the `reward_lp_supply` lp tokens held now like the time-weighted calculation did.
+    ///
+    /// Returns true when the reward already ended.
+    pub fn migrate(&mut self, reward_lp_supply: u64, current_time: u64) -> Result<bool> {
+        self.reward_per_lp_share = 0;
+        self.last_updated_at = self.start_at;
+        self.total_released = 0;
+        self.unallocated_rewards = 0;
+        self.update_reward_per_lp_share(reward_lp_supply, current_time)?;
+        self.legacy_reward_per_lp_share = self.reward_per_lp_share;
+        self.legacy_rewards_until = current_time.min(self.end_rewards_at);
+        Ok(self.is_ended())
     }
 }
@@ -1,9 +1,18 @@
 use anchor_lang::prelude::*;
 
-use super::PartnerType;
+use crate::{error::GammaError, utils::U256};
+
+use super::{PartnerType, PoolState, FEE_GROWTH_PER_LP_FRACTIONAL_BITS};
 
 pub const USER_POOL_LIQUIDITY_SEED: &str = "user-pool-liquidity";
 
+/// Lock duration giving the max boost, longer locks do not increase it
+pub const MAX_LP_LOCK_DURATION: u64 = 365 * 24 * 60 * 60;
+/// Extra reward weight of the lp tokens locked for `MAX_LP_LOCK_DURATION`, denominated in hundredths of bip (10^-6)
+pub const MAX_LP_LOCK_BOOST_RATE: u32 = 1_000_000;
+/// Denominator of `UserPoolLiquidity::lp_lock_boost_rate`
+pub const LP_LOCK_BOOST_RATE_DENOMINATOR: u64 = 1_000_000;
+
 #[account]
 #[derive(Default, Debug)]
 pub struct UserPoolLiquidity {
@@ -16,11 +25,35 @@ pub struct UserPoolLiquidity {
     pub lp_tokens_owned: u128,
     pub partner: Option<PartnerType>,
     pub first_investment_at: u64,
-    pub padding: [u8; 15],
+    /// Lp tokens that can not be withdrawn, wrapped or transferred until they are unlocked
+    pub locked_lp_tokens: u64,
+    /// Time after which the locked lp tokens can be unlocked
+    pub lp_unlock_at: u64,
+    /// Extra reward weight of the locked lp tokens, denominated in hundredths of bip (10^-6)
+    pub lp_lock_boost_rate: u32,
+    pub padding: [u8; 2],
+    /// `PoolState::fee_growth_per_lp_token_0/1` when the fees earned were last updated
+    pub fee_growth_checkpoint_token_0: u128,
+    pub fee_growth_checkpoint_token_1: u128,
+    /// Trade fees earned by the lp tokens owned up to the checkpoints
+    pub fees_earned_token_0: u128,
+    pub fees_earned_token_1: u128,
+    /// Last time the lp tokens owned or their lock changed, see `UserRewardInfo::initialize`.
+    /// Closing the user reward info of a reward that has not been closed moves it after the start of the reward.
+    pub lp_tokens_changed_at: u64,
+}
+
+/// Trade fees earned by the lp tokens of a user
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FeesEarned {
+    pub token_0: u128,
+    pub token_1: u128,
 }
 
 impl UserPoolLiquidity {
-    pub const LEN: usize = 8 + 32 * 2 + 16 * 5 + 32;
+    pub const LEN: usize = 8 + 32 * 2 + 16 * 5 + 32 + 16 * 4 + 8;
+    /// Size of the user pool liquidities created before the fee growth accumulator
+    pub const LEGACY_LEN: usize = 8 + 32 * 2 + 16 * 5 + 32;
 
     pub fn initialize(
         &mut self,
@@ -38,6 +71,99 @@ impl UserPoolLiquidity {
         self.lp_tokens_owned = 0;
         self.partner = partner;
         self.first_investment_at = current_time;
-        self.padding = [0u8; 15];
+        self.locked_lp_tokens = 0;
+        self.lp_unlock_at = 0;
+        self.lp_lock_boost_rate = 0;
+        self.padding = [0u8; 2];
+        self.fee_growth_checkpoint_token_0 = 0;
+        self.fee_growth_checkpoint_token_1 = 0;
+        self.fees_earned_token_0 = 0;
+        self.fees_earned_token_1 = 0;
+        self.lp_tokens_changed_at = current_time;
+    }
+
+    /// The boost rate grows linearly with the lock duration up to `MAX_LP_LOCK_DURATION`
+    pub fn get_lp_lock_boost_rate(lock_duration: u64) -> u32 {
+        let lock_duration = lock_duration.min(MAX_LP_LOCK_DURATION);
+        // lock_duration <= MAX_LP_LOCK_DURATION so it fits in u32
+        (u64::from(MAX_LP_LOCK_BOOST_RATE) * lock_duration / MAX_LP_LOCK_DURATION) as u32
+    }
+
+    /// Extra reward weight of the locked lp tokens, it is counted in `PoolState::lp_lock_boost_weight`
+    pub fn lp_lock_boost_weight(&self) -> Result<u64> {
+        let boost_weight = u128::from(self.locked_lp_tokens)
+            .checked_mul(u128::from(self.lp_lock_boost_rate))
+            .ok_or(GammaError::MathOverflow)?
+            / u128::from(LP_LOCK_BOOST_RATE_DENOMINATOR);
+        u64::try_from(boost_weight).map_err(|_| error!(GammaError::MathOverflow))
+    }
+
+    /// Reward weight of the user owning `lp_tokens_owned`, their locked lp tokens are boosted
+    pub fn reward_weight(&self, lp_tokens_owned: u128) -> Result<u128> {
+        Ok(lp_tokens_owned
+            .checked_add(u128::from(self.lp_lock_boost_weight()?))
+            .ok_or(GammaError::MathOverflow)?)
+    }
+
+    /// Records that the lp tokens owned or their lock change now, the reward weight of the user changes with them
+    pub fn update_lp_tokens_changed_at(&mut self) -> Result<()> {
+        self.lp_tokens_changed_at = Clock::get()?.unix_timestamp as u64;
+        Ok(())
+    }
+
+    /// Fails when taking `lp_token_amount` out of the position would take locked lp tokens
+    pub fn check_unlocked_lp_tokens(&self, lp_token_amount: u64) -> Result<()> {
+        let unlocked_lp_tokens = self
+            .lp_tokens_owned
+            .saturating_sub(u128::from(self.locked_lp_tokens));
+        require!(
+            self.locked_lp_tokens == 0 || u128::from(lp_token_amount) <= unlocked_lp_tokens,
+            GammaError::LpTokensLocked
+        );
+        Ok(())
+    }
+
+    /// Trade fees earned by the lp tokens owned up to the current fee growth of the pool
+    pub fn get_fees_earned(&self, pool_state: &PoolState) -> Result<FeesEarned> {
+        Ok(FeesEarned {
+            token_0: self
+                .fees_earned_token_0
+                .checked_add(self.fees_earned_since(
+                    pool_state.fee_growth_per_lp_token_0,
+                    self.fee_growth_checkpoint_token_0,
+                )?)
+                .ok_or(GammaError::MathOverflow)?,
+            token_1: self
+                .fees_earned_token_1
+                .checked_add(self.fees_earned_since(
+                    pool_state.fee_growth_per_lp_token_1,
+                    self.fee_growth_checkpoint_token_1,
+                )?)
+                .ok_or(GammaError::MathOverflow)?,
+        })
+    }
+
+    /// Accrues the fees earned since the checkpoints and moves them to the current fee growth of the pool,
+    /// it must be called before `lp_tokens_owned` changes.
+    pub fn update_fees_earned(&mut self, pool_state: &PoolState) -> Result<()> {
+        let fees_earned = self.get_fees_earned(pool_state)?;
+        self.fees_earned_token_0 = fees_earned.token_0;
+        self.fees_earned_token_1 = fees_earned.token_1;
+        self.fee_growth_checkpoint_token_0 = pool_state.fee_growth_per_lp_token_0;
+        self.fee_growth_checkpoint_token_1 = pool_state.fee_growth_per_lp_token_1;
+        Ok(())
+    }
+
+    fn fees_earned_since(
+        &self,
+        fee_growth_per_lp: u128,
+        fee_growth_checkpoint: u128,
+    ) -> Result<u128> {
+        let fee_growth = fee_growth_per_lp
+            .checked_sub(fee_growth_checkpoint)
+            .ok_or(GammaError::MathOverflow)?;
+        let fees_earned = (U256::from(self.lp_tokens_owned) * U256::from(fee_growth))
+            >> FEE_GROWTH_PER_LP_FRACTIONAL_BITS;
+        u128::try_from(fees_earned).map_err(|_| error!(GammaError::MathOverflow))
     }
 }
@@ -1,79 +1,123 @@
 use anchor_lang::prelude::*;
-use rust_decimal::Decimal;
 
-use crate::{error::GammaError, LOCK_LP_AMOUNT};
-use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
+use crate::{error::GammaError, utils::U256};
 
-use super::RewardInfo;
+use super::{RewardInfo, REWARD_PER_LP_SHARE_FRACTIONAL_BITS};
 
 #[account]
+#[derive(Default)]
 pub struct UserRewardInfo {
-    pub user: Pubkey,                    // The user that is claiming the rewards.
-    pub reward_info: Pubkey,             // The account that holds the rewards for the user.
+    pub user: Pubkey,                         // The user that is claiming the rewards.
+    pub reward_info: Pubkey,                  // The account that holds the rewards for the user.
     pub pool_state: Pubkey, // The pool state that the user is claiming the rewards from.
     pub total_claimed: u64, // Total rewards claimed by the user.
     pub total_rewards: u64, // Total rewards calculated for the user.
     pub rewards_last_calculated_at: u64, // Last time the rewards were calculated.
+    pub reward_per_lp_share_checkpoint: u128, // `RewardInfo::reward_per_lp_share` when the rewards were calculated.
+    pub lp_tokens_checkpoint: u64, // Reward weight of the user since the checkpoint, their lp tokens with the lock boost.
 }
 
 impl UserRewardInfo {
+    pub const LEN: usize = 8 + std::mem::size_of::<UserRewardInfo>();
+    /// Size of the user reward infos created before the accumulator, up to `rewards_last_calculated_at`
+    pub const LEGACY_LEN: usize = 8 + 32 * 3 + 8 * 3;
+
+    /// While a reward is active the lp tokens of a user only change once their user reward info exists, see `settle_rewards`,
+    /// so when the reward was still active before its last update the rewards are calculated from the start of the reward.
+    /// Once the reward is no longer active they are also calculated from the start when the lp tokens did not change
+    /// since it started, `lp_tokens_changed_at` being `UserPoolLiquidity::lp_tokens_changed_at`.
+    /// Otherwise the lp tokens changed after it ended and the rewards are calculated from the current `reward_per_lp_share`.
+    /// The lp tokens of the user are their reward weight, see `UserPoolLiquidity::reward_weight`.
+    pub fn initialize(
+        &mut self,
+        user: Pubkey,
+        reward_info_key: Pubkey,
+        reward_info: &RewardInfo,
+        is_active_reward: bool,
+        lp_tokens_owned: u64,
+        lp_tokens_changed_at: u64,
+    ) {
+        self.user = user;
+        self.reward_info = reward_info_key;
+        self.pool_state = reward_info.pool;
+        self.total_claimed = 0;
+        self.total_rewards = 0;
+        self.rewards_last_calculated_at = 0;
+        self.reward_per_lp_share_checkpoint =
+            if is_active_reward || lp_tokens_changed_at <= reward_info.start_at {
+                0
+            } else {
+                reward_info.reward_per_lp_share
+            };
+        self.lp_tokens_checkpoint = lp_tokens_owned;
+    }
+
     pub fn get_total_claimable_rewards(&self) -> u64 {
         self.total_rewards.saturating_sub(self.total_claimed)
     }
 
-    pub fn calculate_claimable_rewards<'info>(
+    /// Adds the rewards earned by `lp_tokens_checkpoint` since the checkpoint, the reward info must be updated before.
+    /// `lp_tokens_owned` are the lp tokens of the user earning rewards from now on.
+    pub fn calculate_claimable_rewards(
         &mut self,
-        lp_owned_by_user: u64,
-        current_lp_supply: u64,
-        reward_info: &Account<'info, RewardInfo>,
+        lp_tokens_owned: u64,
+        reward_info: &RewardInfo,
     ) -> Result<()> {
-        let time_now = Clock::get()?.unix_timestamp as u64;
-        if time_now < reward_info.start_at {
-            return Ok(());
-        }
-
-        let last_disbursed_till = reward_info.start_at.max(self.rewards_last_calculated_at);
-
-        let end_time = time_now.min(reward_info.end_rewards_at);
-        let duration = end_time
-            .checked_sub(last_disbursed_till)
-            .ok_or(GammaError::MathOverflow)?;
-
-        let total_to_disburse =
-            Decimal::from_u64(reward_info.total_to_disburse).ok_or(GammaError::MathOverflow)?;
-
-        let max_duration = reward_info.get_time_diff()?;
-        let duration_decimal = Decimal::from_u64(duration).ok_or(GammaError::MathOverflow)?;
-        let lp_owned_by_user_decimal =
-            Decimal::from_u64(lp_owned_by_user).ok_or(GammaError::MathOverflow)?;
-
-        let current_lp_supply_decimal = Decimal::from_u64(current_lp_supply)
-            .ok_or(GammaError::MathOverflow)?
-            // The locked liquidity is not eligible for rewards
-            .checked_sub(LOCK_LP_AMOUNT.into())
-            .ok_or(GammaError::MathOverflow)?;
-
-        let lp_ratio = lp_owned_by_user_decimal
-            .checked_div(current_lp_supply_decimal)
-            .ok_or(GammaError::MathOverflow)?;
-
-        let time_ratio = duration_decimal
-            .checked_div(max_duration)
-            .ok_or(GammaError::MathOverflow)?;
-
-        let rewards_to_add = total_to_disburse
-            .checked_mul(time_ratio)
-            .ok_or(GammaError::MathOverflow)?
-            .checked_mul(lp_ratio)
+        let reward_per_lp_share = reward_info
+            .reward_per_lp_share
+            .checked_sub(self.reward_per_lp_share_checkpoint)
             .ok_or(GammaError::MathOverflow)?;
+        let rewards_to_add = U256::from(self.lp_tokens_checkpoint)
+            * U256::from(reward_per_lp_share)
+            >> REWARD_PER_LP_SHARE_FRACTIONAL_BITS;
 
         self.total_rewards = self
             .total_rewards
-            .checked_add(rewards_to_add.to_u64().ok_or(GammaError::MathOverflow)?)
+            .checked_add(
+                u64::try_from(rewards_to_add).map_err(|_| error!(GammaError::MathOverflow))?,
+            )
             .ok_or(GammaError::MathOverflow)?;
 
-        self.rewards_last_calculated_at = end_time;
+        self.reward_per_lp_share_checkpoint = reward_info.reward_per_lp_share;
+        self.lp_tokens_checkpoint = lp_tokens_owned;
+        self.rewards_last_calculated_at = reward_info.last_updated_at;
 
         Ok(())
     }
+
+    /// Moves a user reward info created before the accumulator to it, the reward info must be migrated.
+    /// The rewards were calculated by the time-weighted calculation up to `rewards_last_calculated_at`,
+    /// the legacy rewards after it are earned from `legacy_reward_per_lp_share` pro rata of the time.
+    ///
+    /// While the reward is active the lp tokens of the user can not change before the user reward info is migrated,
+    /// see `initialize`. Once it is no longer active, the rewards are only calculated from the current `reward_per_lp_share`.
+    pub fn migrate(
+        &mut self,
+        reward_info: &RewardInfo,
+        is_active_reward: bool,
+        lp_tokens_owned: u64,
+    ) -> Result<()> {
+        self.reward_per_lp_share_checkpoint = if is_active_reward {
+            let legacy_duration = reward_info
+                .legacy_rewards_until
+                .saturating_sub(reward_info.start_at);
+            if legacy_duration == 0 {
+                0
+            } else {
+                // legacy_reward_per_lp_share * (rewards_last_calculated_at - start_at) / (legacy_rewards_until - start_at)
+                let calculated_duration = self
+                    .rewards_last_calculated_at
+                    .clamp(reward_info.start_at, reward_info.legacy_rewards_until)
+                    - reward_info.start_at;
+                let checkpoint = U256::from(reward_info.legacy_reward_per_lp_share)
+                    * U256::from(calculated_duration)
+                    / U256::from(legacy_duration);
+                u128::try_from(checkpoint).map_err(|_| error!(GammaError::MathOverflow))?
+            }
+        } else {
+            reward_info.reward_per_lp_share
+        };
+        self.lp_tokens_checkpoint = lp_tokens_owned;
+        Ok(())
+    }
 }
@@ -1,6 +1,7 @@
 ///! 128 and 256 bit numbers
 ///! U128 is more efficient that u128
 ///! https://github.com/solana-labs/solana/issues/19549
+use fixed::types::U64F64;
 use uint::construct_uint;
 construct_uint! {
     pub struct U128(2);
@@ -46,3 +47,34 @@ impl CheckedCeilDiv for u128 {
     }
 }
 
+/// Fractional bits of the mantissa while computing `checked_log2`, it is squared in a u128
+const LOG2_MANTISSA_FRACTIONAL_BITS: u32 = 63;
+
+/// Base 2 logarithm of `x` in Q64.64, `None` if `x` is zero.
+/// Only integer operations are used, the result is the same on-chain and off-chain.
+/// The fractional bits are computed one by one by squaring the mantissa, the result is floored.
+pub fn checked_log2(x: u128) -> Option<U64F64> {
+    if x == 0 {
+        return None;
+    }
+    let integer_part = 127 - x.leading_zeros();
+    // Normalize x / 2^integer_part in [1, 2) to Q1.63
+    let mut mantissa = if integer_part >= LOG2_MANTISSA_FRACTIONAL_BITS {
+        x >> (integer_part - LOG2_MANTISSA_FRACTIONAL_BITS)
+    } else {
+        x << (LOG2_MANTISSA_FRACTIONAL_BITS - integer_part)
+    };
+
+    let mut result = u128::from(integer_part) << U64F64::FRAC_NBITS;
+    let mut bit = 1u128 << (U64F64::FRAC_NBITS - 1);
+    while bit > 0 {
+        // mantissa < 2^64 so the square can not overflow
+        mantissa = mantissa.checked_mul(mantissa)? >> LOG2_MANTISSA_FRACTIONAL_BITS;
+        if mantissa >= 2u128 << LOG2_MANTISSA_FRACTIONAL_BITS {
+            mantissa >>= 1;
+            result |= bit;
+        }
+        bit >>= 1;
+    }
+    Some(U64F64::from_bits(result))
+}
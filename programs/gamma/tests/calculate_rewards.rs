@@ -134,8 +134,9 @@ async fn test_calculate_rewards() {
         .calculate_rewards(&user, pool_id, reward_info_key)
         .await;
     let user_reward_info: UserRewardInfo = test_env.fetch_account(user_reward_info_key).await;
-    // Some rounding issues cause the last reward to be less than 1
-    assert_eq!(user_reward_info.total_rewards, 1000000000 - 1);
+    // The rewards of each calculation are rounded down
+    assert!(user_reward_info.total_rewards <= 1000000000);
+    assert!(user_reward_info.total_rewards >= 1000000000 - 2);
 }
 
 #[tokio::test]
@@ -313,8 +314,9 @@ async fn should_split_rewards_between_users() {
         .calculate_rewards(&user2, pool_id, reward_info_key)
         .await;
     let user2_reward_info: UserRewardInfo = test_env.fetch_account(user2_reward_info_key).await;
-    assert_eq!(user2_reward_info.total_rewards, 1000000000 / 2);
-    assert_eq!(user_reward_info.total_rewards, 1000000000 / 2); // 999240121?
+    // The rewards are rounded down
+    assert_eq!(user2_reward_info.total_rewards, 1000000000 / 2 - 1);
+    assert_eq!(user_reward_info.total_rewards, 1000000000 / 2 - 1);
 
     assert_eq!(user_reward_info.reward_info, reward_info_key);
     assert_eq!(user2_reward_info.reward_info, reward_info_key);
@@ -583,9 +585,22 @@ async fn should_only_calculate_rewards_from_first_investment() {
         &gamma::id(),
     );
     test_env.init_user_pool_liquidity(&user2, pool_id).await;
+    // The reward info of user2 is needed to settle the active reward when depositing
     test_env
-        .deposit(&user2, pool_id, amm_index, 1315, 20000000000, 400000000000)
+        .calculate_rewards(&user2, pool_id, reward_info_key)
         .await;
+    test_env
+        .deposit_with_rewards(
+            &user2,
+            pool_id,
+            amm_index,
+            1315,
+            20000000000,
+            400000000000,
+            vec![reward_info_key],
+        )
+        .await
+        .unwrap();
 
     let user_pool_liquidity2: UserPoolLiquidity =
         test_env.fetch_account(user_pool_liquidity2).await;
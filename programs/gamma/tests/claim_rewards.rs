@@ -124,7 +124,8 @@ async fn test_claim_rewards() {
         .calculate_rewards(&user, pool_id, reward_info_key)
         .await;
     let user_reward_info: UserRewardInfo = test_env.fetch_account(user_reward_info_key).await;
-    assert_eq!(user_reward_info.total_rewards, 1000000000);
+    // The rewards are rounded down
+    assert_eq!(user_reward_info.total_rewards, 1000000000 - 1);
 
     test_env
         .claim_rewards(&user, pool_id, reward_info_key, reward_mint.pubkey())
@@ -0,0 +1,270 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anchor_spl::token::TokenAccount;
+use gamma::{
+    states::{PoolState, UserPoolLiquidity, UserRewardInfo, USER_POOL_LIQUIDITY_SEED},
+    REWARD_INFO_SEED, REWARD_VAULT_SEED, USER_REWARD_INFO_SEED,
+};
+use solana_program_test::tokio;
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer};
+mod utils;
+
+use utils::*;
+
+#[tokio::test]
+async fn should_close_reward_accounts_after_rewards_are_claimed() {
+    let user = Keypair::new();
+    let reward_provider = Keypair::new();
+    let admin = get_admin();
+    let amm_index = 0;
+    let mut test_env = TestEnv::new(vec![
+        user.pubkey(),
+        admin.pubkey(),
+        reward_provider.pubkey(),
+    ])
+    .await;
+    test_env
+        .create_config(&admin, amm_index, 100, 20, 5, 0)
+        .await;
+
+    let user_token_0_account = test_env
+        .get_or_create_associated_token_account(user.pubkey(), test_env.token_0_mint, &user)
+        .await;
+    test_env
+        .mint_base_tokens(user_token_0_account, 100000, test_env.token_0_mint)
+        .await;
+    let user_token_1_account = test_env
+        .get_or_create_associated_token_account(user.pubkey(), test_env.token_1_mint, &user)
+        .await;
+    test_env
+        .mint_base_tokens(user_token_1_account, 100000, test_env.token_1_mint)
+        .await;
+
+    let pool_id = test_env
+        .initialize_pool(
+            &user,
+            amm_index,
+            1000,
+            2000,
+            0,
+            gamma::create_pool_fee_reveiver::id(),
+        )
+        .await;
+
+    let reward_mint = Keypair::new();
+    test_env
+        .create_token_mint(&reward_mint, &test_env.mint_authority.pubkey(), 9)
+        .await;
+    let reward_provider_token_account = test_env
+        .get_or_create_associated_token_account(
+            reward_provider.pubkey(),
+            reward_mint.pubkey(),
+            &reward_provider,
+        )
+        .await;
+    let reward_amount = 1000000000;
+    test_env
+        .mint_base_tokens(
+            reward_provider_token_account,
+            reward_amount,
+            reward_mint.pubkey(),
+        )
+        .await;
+
+    let timestamp_now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    let start_time = timestamp_now + 10;
+    let end_time = timestamp_now + 3000;
+    test_env
+        .create_rewards(
+            &reward_provider,
+            pool_id,
+            start_time,
+            end_time,
+            reward_mint.pubkey(),
+            reward_amount,
+        )
+        .await;
+    let (reward_info_key, _) = Pubkey::find_program_address(
+        &[
+            REWARD_INFO_SEED.as_bytes(),
+            pool_id.to_bytes().as_ref(),
+            &start_time.to_le_bytes(),
+            reward_mint.pubkey().to_bytes().as_ref(),
+        ],
+        &gamma::id(),
+    );
+    let (reward_vault_key, _) = Pubkey::find_program_address(
+        &[
+            REWARD_VAULT_SEED.as_bytes(),
+            reward_info_key.to_bytes().as_ref(),
+        ],
+        &gamma::id(),
+    );
+    let (user_reward_info_key, _) = Pubkey::find_program_address(
+        &[
+            USER_REWARD_INFO_SEED.as_bytes(),
+            reward_info_key.to_bytes().as_ref(),
+            user.pubkey().to_bytes().as_ref(),
+        ],
+        &gamma::id(),
+    );
+
+    test_env
+        .calculate_rewards(&user, pool_id, reward_info_key)
+        .await;
+
+    // The reward can not be closed before it ends
+    assert!(test_env
+        .close_reward_info(
+            &reward_provider,
+            pool_id,
+            reward_info_key,
+            reward_mint.pubkey()
+        )
+        .await
+        .is_err());
+
+    test_env.jump_seconds(500000).await;
+    test_env
+        .calculate_rewards(&user, pool_id, reward_info_key)
+        .await;
+
+    // The user reward info can not be closed before the rewards are claimed
+    assert!(test_env
+        .close_user_reward_info(&user, pool_id, reward_info_key)
+        .await
+        .is_err());
+
+    test_env
+        .claim_rewards(&user, pool_id, reward_info_key, reward_mint.pubkey())
+        .await;
+
+    // The reward can not be closed while the user reward info is open
+    assert!(test_env
+        .close_reward_info(
+            &reward_provider,
+            pool_id,
+            reward_info_key,
+            reward_mint.pubkey()
+        )
+        .await
+        .is_err());
+
+    test_env
+        .close_user_reward_info(&user, pool_id, reward_info_key)
+        .await
+        .unwrap();
+    assert!(test_env
+        .get_account_info(user_reward_info_key)
+        .await
+        .unwrap()
+        .is_none());
+
+    // A user reward info created again does not earn the rewards twice
+    test_env
+        .calculate_rewards(&user, pool_id, reward_info_key)
+        .await;
+    let user_reward_info: UserRewardInfo = test_env.fetch_account(user_reward_info_key).await;
+    assert_eq!(user_reward_info.total_rewards, 0);
+    test_env
+        .close_user_reward_info(&user, pool_id, reward_info_key)
+        .await
+        .unwrap();
+
+    // The rounding dust left in the vault is swept to the reward provider
+    let reward_providers_token_account = test_env
+        .get_or_create_associated_token_account(
+            reward_provider.pubkey(),
+            reward_mint.pubkey(),
+            &reward_provider,
+        )
+        .await;
+    let vault: TokenAccount = test_env.fetch_account(reward_vault_key).await;
+    let balance_before: TokenAccount = test_env.fetch_account(reward_providers_token_account).await;
+    test_env
+        .close_reward_info(
+            &reward_provider,
+            pool_id,
+            reward_info_key,
+            reward_mint.pubkey(),
+        )
+        .await
+        .unwrap();
+    let balance_after: TokenAccount = test_env.fetch_account(reward_providers_token_account).await;
+    assert_eq!(balance_after.amount - balance_before.amount, vault.amount);
+    assert!(test_env
+        .get_account_info(reward_info_key)
+        .await
+        .unwrap()
+        .is_none());
+    assert!(test_env
+        .get_account_info(reward_vault_key)
+        .await
+        .unwrap()
+        .is_none());
+    // The closed reward is no longer active
+    let pool_state: PoolState = test_env.fetch_account(pool_id).await;
+    assert_eq_with_copy!(pool_state.active_reward_infos, 0);
+    assert!(!pool_state.is_active_reward_info(reward_info_key));
+}
+
+#[tokio::test]
+async fn should_close_user_pool_liquidity_after_full_withdraw() {
+    let user = Keypair::new();
+    let admin = get_admin();
+    let amm_index = 0;
+    let mut test_env = TestEnv::new(vec![user.pubkey(), admin.pubkey()]).await;
+    test_env
+        .create_config(&admin, amm_index, 100, 20, 5, 0)
+        .await;
+
+    let user_token_0_account = test_env
+        .get_or_create_associated_token_account(user.pubkey(), test_env.token_0_mint, &user)
+        .await;
+    test_env
+        .mint_base_tokens(user_token_0_account, 10000000000, test_env.token_0_mint)
+        .await;
+    let user_token_1_account = test_env
+        .get_or_create_associated_token_account(user.pubkey(), test_env.token_1_mint, &user)
+        .await;
+    test_env
+        .mint_base_tokens(user_token_1_account, 10000000000, test_env.token_1_mint)
+        .await;
+
+    let pool_id = test_env
+        .initialize_pool(
+            &user,
+            amm_index,
+            1000000000,
+            1000000000,
+            0,
+            gamma::create_pool_fee_reveiver::id(),
+        )
+        .await;
+
+    let (user_pool_liquidity_address, _) = Pubkey::find_program_address(
+        &[
+            USER_POOL_LIQUIDITY_SEED.as_bytes(),
+            pool_id.as_ref(),
+            user.pubkey().as_ref(),
+        ],
+        &gamma::ID,
+    );
+    let user_pool_liquidity: UserPoolLiquidity =
+        test_env.fetch_account(user_pool_liquidity_address).await;
+    let lp_tokens_owned = user_pool_liquidity.lp_tokens_owned as u64;
+
+    test_env
+        .withdraw(&user, pool_id, amm_index, lp_tokens_owned, 0, 0)
+        .await;
+    test_env.close_user_pool_liquidity(&user, pool_id).await;
+
+    assert!(test_env
+        .get_account_info(user_pool_liquidity_address)
+        .await
+        .unwrap()
+        .is_none());
+}
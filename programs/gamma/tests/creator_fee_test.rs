@@ -0,0 +1,109 @@
+use anchor_spl::token::TokenAccount;
+use gamma::{curve::TradeDirection, states::PoolState};
+use solana_program_test::tokio;
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer};
+mod utils;
+
+use utils::*;
+
+const TRADE_FEE_RATE: u64 = 10_000;
+const CREATOR_FEE_RATE: u64 = 100_000;
+
+async fn setup(other_user: &Keypair) -> (TestEnv, Keypair, Pubkey) {
+    let user = Keypair::new();
+    let admin = get_admin();
+    let mut test_env = TestEnv::new(vec![user.pubkey(), admin.pubkey(), other_user.pubkey()]).await;
+    test_env
+        .create_config(&admin, 0, TRADE_FEE_RATE, 20, 5, 0)
+        .await;
+
+    let user_token_0_account = test_env
+        .get_or_create_associated_token_account(user.pubkey(), test_env.token_0_mint, &user)
+        .await;
+    test_env
+        .mint_base_tokens(user_token_0_account, 10000000000, test_env.token_0_mint)
+        .await;
+    let user_token_1_account = test_env
+        .get_or_create_associated_token_account(user.pubkey(), test_env.token_1_mint, &user)
+        .await;
+    test_env
+        .mint_base_tokens(user_token_1_account, 10000000000, test_env.token_1_mint)
+        .await;
+
+    let pool_id = test_env
+        .initialize_pool(
+            &user,
+            0,
+            1000000000,
+            1000000000,
+            0,
+            gamma::create_pool_fee_reveiver::id(),
+        )
+        .await;
+    test_env.jump_seconds(2).await;
+    (test_env, user, pool_id)
+}
+
+#[tokio::test]
+async fn creator_should_collect_creator_fee() {
+    let other_user = Keypair::new();
+    let (mut test_env, user, pool_id) = setup(&other_user).await;
+    let admin = get_admin();
+    test_env
+        .update_pool(&admin, pool_id, 0, 10, CREATOR_FEE_RATE)
+        .await;
+
+    test_env
+        .swap_base_input(&user, pool_id, 0, 1000000, 0, TradeDirection::ZeroForOne)
+        .await;
+    let pool_state: PoolState = test_env.fetch_account(pool_id).await;
+    // 1% of the input is charged and 10% of it goes to the creator
+    assert_eq_with_copy!(pool_state.creator_fees_token_0, 1000);
+    assert_eq_with_copy!(pool_state.creator_fees_token_1, 0);
+
+    let result = test_env
+        .collect_creator_fee(&other_user, pool_id, u64::MAX, u64::MAX)
+        .await;
+    assert!(result.is_err());
+
+    let user_token_0_account = test_env
+        .get_or_create_associated_token_account(user.pubkey(), test_env.token_0_mint, &user)
+        .await;
+    let balance_before: TokenAccount = test_env.fetch_account(user_token_0_account).await;
+    test_env
+        .collect_creator_fee(&user, pool_id, u64::MAX, u64::MAX)
+        .await
+        .unwrap();
+    let balance_after: TokenAccount = test_env.fetch_account(user_token_0_account).await;
+    assert_eq!(balance_after.amount - balance_before.amount, 1000);
+
+    let pool_state: PoolState = test_env.fetch_account(pool_id).await;
+    assert_eq_with_copy!(pool_state.creator_fees_token_0, 0);
+}
+
+#[tokio::test]
+async fn pool_created_before_creator_fee_should_be_resized() {
+    let (mut test_env, user, pool_id) = setup(&Keypair::new()).await;
+
+    // A pool of the original size, the creator fee fields fit in its padding
+    let original_len = 8 + 10 * 32 + 5 * 1 + 7 * 8 + 16 * 4 + 23 * 8;
+    let mut account = test_env.get_account_info(pool_id).await.unwrap().unwrap();
+    account.data.truncate(original_len);
+    test_env
+        .program_test_context
+        .set_account(&pool_id, &account.into());
+
+    test_env.resize_pool_state(&user, pool_id).await.unwrap();
+    let account = test_env.get_account_info(pool_id).await.unwrap().unwrap();
+    assert_eq!(account.data.len(), PoolState::LEN);
+
+    test_env
+        .swap_base_input(&user, pool_id, 0, 1000000, 0, TradeDirection::ZeroForOne)
+        .await;
+    let pool_state: PoolState = test_env.fetch_account(pool_id).await;
+    assert_eq_with_copy!(pool_state.creator_fee_rate, 0);
+    assert_eq_with_copy!(pool_state.creator_fees_token_0, 0);
+
+    // Resizing a pool of the current size does nothing
+    test_env.resize_pool_state(&user, pool_id).await.unwrap();
+}
@@ -0,0 +1,200 @@
+use anchor_spl::token::TokenAccount;
+use gamma::{
+    curve::TradeDirection,
+    states::{PoolState, UserPoolLiquidity, POOL_VAULT_SEED, USER_POOL_LIQUIDITY_SEED},
+};
+use solana_program_test::tokio;
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer};
+mod utils;
+
+use utils::*;
+
+/// Compute units an instruction gets without a compute budget instruction
+const DEFAULT_COMPUTE_UNIT_LIMIT: u64 = 200_000;
+
+#[tokio::test]
+async fn should_deposit_single_token() {
+    let user = Keypair::new();
+    let depositor = Keypair::new();
+    let admin = get_admin();
+    let amm_index = 0;
+    let mut test_env = TestEnv::new(vec![user.pubkey(), depositor.pubkey(), admin.pubkey()]).await;
+    test_env
+        .create_config(&admin, amm_index, 100, 20, 5, 0)
+        .await;
+
+    let user_token_0_account = test_env
+        .get_or_create_associated_token_account(user.pubkey(), test_env.token_0_mint, &user)
+        .await;
+    test_env
+        .mint_base_tokens(user_token_0_account, 10000000000, test_env.token_0_mint)
+        .await;
+    let user_token_1_account = test_env
+        .get_or_create_associated_token_account(user.pubkey(), test_env.token_1_mint, &user)
+        .await;
+    test_env
+        .mint_base_tokens(user_token_1_account, 10000000000, test_env.token_1_mint)
+        .await;
+
+    let pool_id = test_env
+        .initialize_pool(
+            &user,
+            amm_index,
+            1000000000,
+            1000000000,
+            0,
+            gamma::create_pool_fee_reveiver::id(),
+        )
+        .await;
+
+    // The depositor only holds token_0
+    let depositor_token_0_account = test_env
+        .get_or_create_associated_token_account(
+            depositor.pubkey(),
+            test_env.token_0_mint,
+            &depositor,
+        )
+        .await;
+    let amount_in = 100000000;
+    test_env
+        .mint_base_tokens(depositor_token_0_account, amount_in, test_env.token_0_mint)
+        .await;
+    test_env.init_user_pool_liquidity(&depositor, pool_id).await;
+
+    let (token_1_vault, _) = Pubkey::find_program_address(
+        &[
+            POOL_VAULT_SEED.as_bytes(),
+            pool_id.as_ref(),
+            test_env.token_1_mint.as_ref(),
+        ],
+        &gamma::ID,
+    );
+    let token_1_vault_before: TokenAccount = test_env.fetch_account(token_1_vault).await;
+    let pool_state_before: PoolState = test_env.fetch_account(pool_id).await;
+
+    // Swapping about half of the deposit into token_1 gives roughly
+    // lp_supply * (sqrt(1 + amount_in / token_0_vault) - 1) = 48808848 lp tokens without fees
+    let minimum_lp_token_amount = 48500000;
+    test_env
+        .deposit_single_sided(
+            &depositor,
+            pool_id,
+            amm_index,
+            amount_in,
+            minimum_lp_token_amount,
+            TradeDirection::ZeroForOne,
+        )
+        .await;
+
+    let (depositor_pool_liquidity_address, _) = Pubkey::find_program_address(
+        &[
+            USER_POOL_LIQUIDITY_SEED.as_bytes(),
+            pool_id.as_ref(),
+            depositor.pubkey().as_ref(),
+        ],
+        &gamma::ID,
+    );
+    let depositor_pool_liquidity: UserPoolLiquidity = test_env
+        .fetch_account(depositor_pool_liquidity_address)
+        .await;
+    let lp_token_amount = depositor_pool_liquidity.lp_tokens_owned as u64;
+    assert!(lp_token_amount >= minimum_lp_token_amount);
+    assert!(lp_token_amount < 48808848);
+
+    let pool_state: PoolState = test_env.fetch_account(pool_id).await;
+    assert_eq_with_copy!(
+        pool_state.lp_supply,
+        pool_state_before.lp_supply + lp_token_amount
+    );
+    // Fees collected on the swap are charged on token_0
+    assert!({ pool_state.cumulative_trade_fees_token_0 } > 0);
+    // The swapped token_1 never leaves the vault
+    let token_1_vault_after: TokenAccount = test_env.fetch_account(token_1_vault).await;
+    assert_eq!(token_1_vault_after.amount, token_1_vault_before.amount);
+    assert_eq_with_copy!(
+        pool_state.token_1_vault_amount,
+        pool_state_before.token_1_vault_amount
+    );
+
+    // Only dust of the input is left with the depositor
+    let depositor_token_0: TokenAccount = test_env.fetch_account(depositor_token_0_account).await;
+    assert!(depositor_token_0.amount < 10);
+}
+
+/// Creates a pool with the curve and zaps 10% of its token_0 reserve into it
+async fn zap_compute_units(
+    curve_type: u8,
+    curve_parameter: u64,
+    init_amount_0: u64,
+    init_amount_1: u64,
+) -> u64 {
+    let user = Keypair::new();
+    let depositor = Keypair::new();
+    let admin = get_admin();
+    let amm_index = 0;
+    let mut test_env = TestEnv::new(vec![user.pubkey(), depositor.pubkey(), admin.pubkey()]).await;
+    test_env
+        .create_config(&admin, amm_index, 100, 20, 5, 0)
+        .await;
+
+    let user_token_0_account = test_env
+        .get_or_create_associated_token_account(user.pubkey(), test_env.token_0_mint, &user)
+        .await;
+    test_env
+        .mint_base_tokens(user_token_0_account, init_amount_0, test_env.token_0_mint)
+        .await;
+    let user_token_1_account = test_env
+        .get_or_create_associated_token_account(user.pubkey(), test_env.token_1_mint, &user)
+        .await;
+    test_env
+        .mint_base_tokens(user_token_1_account, init_amount_1, test_env.token_1_mint)
+        .await;
+    let pool_id = test_env
+        .initialize_pool_with_curve(
+            &user,
+            amm_index,
+            init_amount_0,
+            init_amount_1,
+            0,
+            gamma::create_pool_fee_reveiver::id(),
+            curve_type,
+            curve_parameter,
+        )
+        .await;
+
+    let depositor_token_0_account = test_env
+        .get_or_create_associated_token_account(
+            depositor.pubkey(),
+            test_env.token_0_mint,
+            &depositor,
+        )
+        .await;
+    let amount_in = init_amount_0 / 10;
+    test_env
+        .mint_base_tokens(depositor_token_0_account, amount_in, test_env.token_0_mint)
+        .await;
+    test_env.init_user_pool_liquidity(&depositor, pool_id).await;
+
+    test_env
+        .deposit_single_sided(
+            &depositor,
+            pool_id,
+            amm_index,
+            amount_in,
+            1,
+            TradeDirection::ZeroForOne,
+        )
+        .await
+}
+
+#[tokio::test]
+async fn stable_swap_zap_should_fit_in_the_default_compute_budget() {
+    let compute_units = zap_compute_units(1, 100, 1000000000000, 1000000000000).await;
+    assert!(compute_units < DEFAULT_COMPUTE_UNIT_LIMIT);
+}
+
+#[tokio::test]
+async fn weighted_zap_should_fit_in_the_default_compute_budget() {
+    let compute_units = zap_compute_units(2, 800000, 4000000000000, 1000000000000).await;
+    assert!(compute_units < DEFAULT_COMPUTE_UNIT_LIMIT);
+}
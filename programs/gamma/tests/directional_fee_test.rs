@@ -0,0 +1,86 @@
+use gamma::{curve::TradeDirection, fees::FeeType, states::PoolState};
+use solana_program_test::tokio;
+use solana_sdk::{signature::Keypair, signer::Signer};
+mod utils;
+
+use utils::*;
+
+const TRADE_FEE_RATE: u64 = 100;
+
+#[tokio::test]
+async fn directional_fee_should_only_charge_swaps_moving_price_from_twap() {
+    let user = Keypair::new();
+    let admin = get_admin();
+    let amm_index = 0;
+    let mut test_env = TestEnv::new(vec![user.pubkey(), admin.pubkey()]).await;
+    test_env
+        .create_config(&admin, amm_index, TRADE_FEE_RATE, 20, 5, 0)
+        .await;
+
+    let user_token_0_account = test_env
+        .get_or_create_associated_token_account(user.pubkey(), test_env.token_0_mint, &user)
+        .await;
+    test_env
+        .mint_base_tokens(user_token_0_account, 10000000000, test_env.token_0_mint)
+        .await;
+    let user_token_1_account = test_env
+        .get_or_create_associated_token_account(user.pubkey(), test_env.token_1_mint, &user)
+        .await;
+    test_env
+        .mint_base_tokens(user_token_1_account, 10000000000, test_env.token_1_mint)
+        .await;
+
+    let pool_id = test_env
+        .initialize_pool_with_fee_type(
+            &user,
+            amm_index,
+            1000000000,
+            1000000000,
+            0,
+            gamma::create_pool_fee_reveiver::id(),
+            0,
+            0,
+            FeeType::Directional as u8,
+            0,
+            0,
+        )
+        .await;
+
+    // Two observations for a TWAP around the initial price
+    for trade_direction in [TradeDirection::ZeroForOne, TradeDirection::OneForZero] {
+        test_env.jump_seconds(20).await;
+        test_env
+            .swap_base_input(&user, pool_id, amm_index, 1000000, 0, trade_direction)
+            .await;
+    }
+
+    // Selling 10% of the reserves pushes the token_0 price away from the TWAP
+    test_env.jump_seconds(1).await;
+    test_env
+        .swap_base_input(
+            &user,
+            pool_id,
+            amm_index,
+            100000000,
+            0,
+            TradeDirection::ZeroForOne,
+        )
+        .await;
+    let pool_state: PoolState = test_env.fetch_account(pool_id).await;
+    let away_fee_rate = pool_state.latest_dynamic_fee_rate;
+    assert!(away_fee_rate > 10 * TRADE_FEE_RATE);
+
+    // Buying back part of it moves the price toward the TWAP
+    test_env
+        .swap_base_input(
+            &user,
+            pool_id,
+            amm_index,
+            50000000,
+            0,
+            TradeDirection::OneForZero,
+        )
+        .await;
+    let pool_state: PoolState = test_env.fetch_account(pool_id).await;
+    assert_eq_with_copy!(pool_state.latest_dynamic_fee_rate, TRADE_FEE_RATE);
+}
@@ -0,0 +1,97 @@
+use anchor_spl::token::TokenAccount;
+use gamma::states::{UserPoolLiquidity, USER_POOL_LIQUIDITY_SEED};
+use solana_program_test::tokio;
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer};
+mod utils;
+
+use utils::*;
+
+#[tokio::test]
+async fn should_deposit_and_withdraw_exact_token_amounts() {
+    let user = Keypair::new();
+    let admin = get_admin();
+    let amm_index = 0;
+    let mut test_env = TestEnv::new(vec![user.pubkey(), admin.pubkey()]).await;
+    test_env
+        .create_config(&admin, amm_index, 100, 20, 5, 0)
+        .await;
+
+    let user_token_0_account = test_env
+        .get_or_create_associated_token_account(user.pubkey(), test_env.token_0_mint, &user)
+        .await;
+    test_env
+        .mint_base_tokens(user_token_0_account, 10000000000, test_env.token_0_mint)
+        .await;
+    let user_token_1_account = test_env
+        .get_or_create_associated_token_account(user.pubkey(), test_env.token_1_mint, &user)
+        .await;
+    test_env
+        .mint_base_tokens(user_token_1_account, 10000000000, test_env.token_1_mint)
+        .await;
+
+    let pool_id = test_env
+        .initialize_pool(
+            &user,
+            amm_index,
+            1000000000,
+            1000000000,
+            0,
+            gamma::create_pool_fee_reveiver::id(),
+        )
+        .await;
+
+    let (user_pool_liquidity_address, _) = Pubkey::find_program_address(
+        &[
+            USER_POOL_LIQUIDITY_SEED.as_bytes(),
+            pool_id.as_ref(),
+            user.pubkey().as_ref(),
+        ],
+        &gamma::ID,
+    );
+    let user_pool_liquidity_before: UserPoolLiquidity =
+        test_env.fetch_account(user_pool_liquidity_address).await;
+
+    // Asking for more lp tokens than the maximum amounts are worth fails
+    let result = test_env
+        .deposit_exact_tokens(&user, pool_id, 100000000, 100000000, 100000001)
+        .await;
+    assert!(result.is_err());
+
+    // The smaller of the two amounts bounds the deposit
+    test_env
+        .deposit_exact_tokens(&user, pool_id, 100000000, 200000000, 100000000)
+        .await
+        .unwrap();
+
+    let user_pool_liquidity: UserPoolLiquidity =
+        test_env.fetch_account(user_pool_liquidity_address).await;
+    assert_eq!(
+        user_pool_liquidity.lp_tokens_owned,
+        user_pool_liquidity_before.lp_tokens_owned + 100000000
+    );
+    let user_token_0: TokenAccount = test_env.fetch_account(user_token_0_account).await;
+    let user_token_1: TokenAccount = test_env.fetch_account(user_token_1_account).await;
+    assert!(user_token_0.amount >= 10000000000 - 1000000000 - 100000000);
+    assert!(user_token_1.amount >= 10000000000 - 1000000000 - 100000000);
+
+    // Burning fewer lp tokens than needed for the requested amounts fails
+    let result = test_env
+        .withdraw_exact_tokens(&user, pool_id, 50000000, 50000000, 49999999)
+        .await;
+    assert!(result.is_err());
+
+    test_env
+        .withdraw_exact_tokens(&user, pool_id, 50000000, 50000000, 50000000)
+        .await
+        .unwrap();
+
+    let user_pool_liquidity_after: UserPoolLiquidity =
+        test_env.fetch_account(user_pool_liquidity_address).await;
+    assert!(
+        user_pool_liquidity.lp_tokens_owned - user_pool_liquidity_after.lp_tokens_owned <= 50000000
+    );
+    let user_token_0_after: TokenAccount = test_env.fetch_account(user_token_0_account).await;
+    let user_token_1_after: TokenAccount = test_env.fetch_account(user_token_1_account).await;
+    assert!(user_token_0_after.amount - user_token_0.amount >= 50000000);
+    assert!(user_token_1_after.amount - user_token_1.amount >= 50000000);
+}
@@ -0,0 +1,215 @@
+use gamma::{
+    curve::TradeDirection,
+    states::{PoolState, UserPoolLiquidity, USER_POOL_LIQUIDITY_SEED},
+};
+use solana_program_test::tokio;
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer};
+mod utils;
+
+use utils::*;
+
+const TRADE_FEE_RATE: u64 = 10_000;
+
+async fn setup(user: &Keypair, other_user: &Keypair) -> (TestEnv, Pubkey) {
+    let admin = get_admin();
+    let mut test_env = TestEnv::new(vec![user.pubkey(), admin.pubkey(), other_user.pubkey()]).await;
+    test_env
+        .create_config(&admin, 0, TRADE_FEE_RATE, 20, 5, 0)
+        .await;
+
+    for owner in [user, other_user] {
+        let token_0_account = test_env
+            .get_or_create_associated_token_account(owner.pubkey(), test_env.token_0_mint, owner)
+            .await;
+        test_env
+            .mint_base_tokens(token_0_account, 10000000000, test_env.token_0_mint)
+            .await;
+        let token_1_account = test_env
+            .get_or_create_associated_token_account(owner.pubkey(), test_env.token_1_mint, owner)
+            .await;
+        test_env
+            .mint_base_tokens(token_1_account, 10000000000, test_env.token_1_mint)
+            .await;
+    }
+
+    let pool_id = test_env
+        .initialize_pool(
+            user,
+            0,
+            1000000000,
+            1000000000,
+            0,
+            gamma::create_pool_fee_reveiver::id(),
+        )
+        .await;
+    test_env.jump_seconds(2).await;
+    (test_env, pool_id)
+}
+
+fn user_pool_liquidity_key(pool_id: Pubkey, user: Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            USER_POOL_LIQUIDITY_SEED.as_bytes(),
+            pool_id.as_ref(),
+            user.as_ref(),
+        ],
+        &gamma::ID,
+    )
+    .0
+}
+
+#[tokio::test]
+async fn should_track_fees_earned_by_each_lp() {
+    let user = Keypair::new();
+    let other_user = Keypair::new();
+    let (mut test_env, pool_id) = setup(&user, &other_user).await;
+
+    test_env
+        .swap_base_input(&user, pool_id, 0, 100000000, 0, TradeDirection::ZeroForOne)
+        .await;
+    let pool_state: PoolState = test_env.fetch_account(pool_id).await;
+    let fees_earned = test_env
+        .get_fees_earned(&user, pool_id, user.pubkey())
+        .await;
+    // The user owns all the lp tokens except the locked liquidity
+    assert!(fees_earned.token_0 > 0);
+    assert!(fees_earned.token_0 < pool_state.cumulative_trade_fees_token_0);
+    assert_eq!(fees_earned.token_1, 0);
+
+    // The fees accrued before the deposit are not earned by the other user
+    test_env
+        .init_user_pool_liquidity(&other_user, pool_id)
+        .await;
+    test_env
+        .deposit(&other_user, pool_id, 0, 500000000, u64::MAX, u64::MAX)
+        .await;
+    let other_fees_earned = test_env
+        .get_fees_earned(&other_user, pool_id, other_user.pubkey())
+        .await;
+    assert_eq!(other_fees_earned.token_0, 0);
+    assert_eq!(other_fees_earned.token_1, 0);
+
+    test_env
+        .swap_base_input(&user, pool_id, 0, 100000000, 0, TradeDirection::OneForZero)
+        .await;
+    let user_pool_liquidity: UserPoolLiquidity = test_env
+        .fetch_account(user_pool_liquidity_key(pool_id, user.pubkey()))
+        .await;
+    let other_user_pool_liquidity: UserPoolLiquidity = test_env
+        .fetch_account(user_pool_liquidity_key(pool_id, other_user.pubkey()))
+        .await;
+    let pool_state: PoolState = test_env.fetch_account(pool_id).await;
+    test_env.jump_seconds(1).await;
+    let fees_earned_after = test_env
+        .get_fees_earned(&user, pool_id, user.pubkey())
+        .await;
+    let other_fees_earned = test_env
+        .get_fees_earned(&other_user, pool_id, other_user.pubkey())
+        .await;
+    assert_eq!(fees_earned_after.token_0, fees_earned.token_0);
+    assert!(fees_earned_after.token_1 > 0);
+    assert_eq!(other_fees_earned.token_0, 0);
+    assert!(other_fees_earned.token_1 > 0);
+    assert!(
+        fees_earned_after.token_1 + other_fees_earned.token_1
+            < pool_state.cumulative_trade_fees_token_1
+    );
+    // The fees are shared pro rata of the lp tokens owned
+    let lp_tokens_owned = user_pool_liquidity.lp_tokens_owned;
+    let other_lp_tokens_owned = other_user_pool_liquidity.lp_tokens_owned;
+    assert!(
+        (fees_earned_after.token_1 * other_lp_tokens_owned)
+            .abs_diff(other_fees_earned.token_1 * lp_tokens_owned)
+            <= lp_tokens_owned.max(other_lp_tokens_owned)
+    );
+
+    // The fees earned are kept after withdrawing and do not grow without lp tokens
+    test_env
+        .withdraw(&other_user, pool_id, 0, other_lp_tokens_owned as u64, 0, 0)
+        .await;
+    let other_user_pool_liquidity: UserPoolLiquidity = test_env
+        .fetch_account(user_pool_liquidity_key(pool_id, other_user.pubkey()))
+        .await;
+    assert_eq!(other_user_pool_liquidity.lp_tokens_owned, 0);
+    assert_eq!(
+        other_user_pool_liquidity.fees_earned_token_1,
+        other_fees_earned.token_1
+    );
+    assert_eq_with_copy!(
+        other_user_pool_liquidity.fee_growth_checkpoint_token_1,
+        pool_state.fee_growth_per_lp_token_1
+    );
+
+    test_env
+        .swap_base_input(&user, pool_id, 0, 50000000, 0, TradeDirection::OneForZero)
+        .await;
+    test_env.jump_seconds(1).await;
+    assert_eq!(
+        test_env
+            .get_fees_earned(&other_user, pool_id, other_user.pubkey())
+            .await,
+        other_fees_earned
+    );
+}
+
+#[tokio::test]
+async fn user_pool_liquidity_created_before_fee_tracking_should_be_resized() {
+    let user = Keypair::new();
+    let other_user = Keypair::new();
+    let (mut test_env, pool_id) = setup(&user, &other_user).await;
+    test_env
+        .swap_base_input(&user, pool_id, 0, 100000000, 0, TradeDirection::ZeroForOne)
+        .await;
+
+    // A user pool liquidity of the size before the fee tracking fields were added
+    let user_pool_liquidity_address = user_pool_liquidity_key(pool_id, user.pubkey());
+    let mut account = test_env
+        .get_account_info(user_pool_liquidity_address)
+        .await
+        .unwrap()
+        .unwrap();
+    account.data.truncate(UserPoolLiquidity::LEGACY_LEN);
+    test_env
+        .program_test_context
+        .set_account(&user_pool_liquidity_address, &account.into());
+
+    test_env
+        .resize_user_pool_liquidity(&other_user, pool_id, user.pubkey())
+        .await
+        .unwrap();
+    let account = test_env
+        .get_account_info(user_pool_liquidity_address)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(account.data.len(), UserPoolLiquidity::LEN);
+
+    // The fees accrued before the resize are not tracked
+    let pool_state: PoolState = test_env.fetch_account(pool_id).await;
+    let user_pool_liquidity: UserPoolLiquidity =
+        test_env.fetch_account(user_pool_liquidity_address).await;
+    assert_eq_with_copy!(
+        user_pool_liquidity.fee_growth_checkpoint_token_0,
+        pool_state.fee_growth_per_lp_token_0
+    );
+    assert_eq!(user_pool_liquidity.fees_earned_token_0, 0);
+    let fees_earned = test_env
+        .get_fees_earned(&user, pool_id, user.pubkey())
+        .await;
+    assert_eq!(fees_earned.token_0, 0);
+
+    test_env
+        .swap_base_input(&user, pool_id, 0, 50000000, 0, TradeDirection::ZeroForOne)
+        .await;
+    test_env.jump_seconds(1).await;
+    let fees_earned = test_env
+        .get_fees_earned(&user, pool_id, user.pubkey())
+        .await;
+    assert!(fees_earned.token_0 > 0);
+
+    // Resizing a user pool liquidity of the current size does nothing
+    test_env
+        .resize_user_pool_liquidity(&other_user, pool_id, user.pubkey())
+        .await
+        .unwrap();
+}
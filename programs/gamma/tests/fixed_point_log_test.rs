@@ -0,0 +1,69 @@
+use fixed::types::U64F64;
+use gamma::{fees::DynamicFee, utils::checked_log2};
+use proptest::prelude::*;
+
+/// The float implementation the volatility fee used before fixed point logarithms
+fn float_volatility_component(
+    min_price: u128,
+    max_price: u128,
+    twap_price: u128,
+    volatility_factor: u64,
+) -> u64 {
+    let volatility_numerator = ((max_price as f64).ln() - (min_price as f64).ln()).abs();
+    let volatility_denominator = (twap_price as f64).ln().abs();
+    (volatility_factor as f64 * (volatility_numerator / volatility_denominator)) as u64
+}
+
+#[test]
+fn checked_log2_of_powers_of_two_is_exact() {
+    assert_eq!(checked_log2(0), None);
+    for power in 0..128 {
+        let log = checked_log2(1u128 << power).unwrap();
+        assert_eq!(log.frac(), U64F64::ZERO);
+        assert_eq!(log.to_num::<u32>(), power);
+    }
+    assert_eq!(checked_log2(u128::MAX).unwrap().to_num::<u32>(), 127);
+}
+
+proptest! {
+    #[test]
+    fn checked_log2_matches_float(x in 1u128..) {
+        let log = checked_log2(x).unwrap().to_num::<f64>();
+        prop_assert!((log - (x as f64).log2()).abs() < 1e-12);
+    }
+
+    #[test]
+    fn volatility_component_matches_float(
+        twap_price in 2u128..(1u128 << 64),
+        min_price_percent in 1u128..=100,
+        max_price_percent in 100u128..=200,
+        volatility_factor in 0u64..=1_000_000,
+    ) {
+        let min_price = (twap_price * min_price_percent / 100).max(1);
+        let max_price = twap_price * max_price_percent / 100;
+        let volatility_component =
+            DynamicFee::volatility_component(min_price, max_price, twap_price, volatility_factor)
+                .unwrap()
+                .unwrap();
+        let float_volatility_component =
+            float_volatility_component(min_price, max_price, twap_price, volatility_factor);
+        // Both are rounded down, they can only differ when the float result is close to an integer
+        prop_assert!(volatility_component.abs_diff(float_volatility_component) <= 1);
+    }
+}
+
+#[test]
+fn volatility_component_needs_observations() {
+    assert_eq!(
+        DynamicFee::volatility_component(0, 0, 0, 300_000).unwrap(),
+        None
+    );
+    assert_eq!(
+        DynamicFee::volatility_component(1, 1, 1, 300_000).unwrap(),
+        None
+    );
+    assert_eq!(
+        DynamicFee::volatility_component(1 << 32, 1 << 32, 1 << 32, 300_000).unwrap(),
+        Some(0)
+    );
+}
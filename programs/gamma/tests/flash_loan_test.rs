@@ -0,0 +1,90 @@
+use anchor_spl::token::TokenAccount;
+use gamma::states::PoolState;
+use solana_program_test::tokio;
+use solana_sdk::{signature::Keypair, signer::Signer};
+mod utils;
+
+use utils::*;
+
+#[tokio::test]
+async fn should_lend_and_repay_with_fee_in_same_transaction() {
+    let user = Keypair::new();
+    let admin = get_admin();
+    let amm_index = 0;
+    let mut test_env = TestEnv::new(vec![user.pubkey(), admin.pubkey()]).await;
+    test_env
+        .create_config(&admin, amm_index, 100, 200000, 5, 0)
+        .await;
+
+    let user_token_0_account = test_env
+        .get_or_create_associated_token_account(user.pubkey(), test_env.token_0_mint, &user)
+        .await;
+    test_env
+        .mint_base_tokens(user_token_0_account, 10000000000, test_env.token_0_mint)
+        .await;
+    let user_token_1_account = test_env
+        .get_or_create_associated_token_account(user.pubkey(), test_env.token_1_mint, &user)
+        .await;
+    test_env
+        .mint_base_tokens(user_token_1_account, 10000000000, test_env.token_1_mint)
+        .await;
+
+    let pool_id = test_env
+        .initialize_pool(
+            &user,
+            amm_index,
+            1000000000,
+            1000000000,
+            0,
+            gamma::create_pool_fee_reveiver::id(),
+        )
+        .await;
+
+    let amount = 100000000;
+    // Flash loans are disabled until a fee is configured
+    let result = test_env
+        .flash_loan(&user, pool_id, amm_index, amount, true, true)
+        .await;
+    assert!(result.is_err());
+
+    // 0.1% flash loan fee
+    test_env.update_amm_config(&admin, amm_index, 9, 1000).await;
+
+    // A loan without a repayment in the same transaction fails
+    let result = test_env
+        .flash_loan(&user, pool_id, amm_index, amount, true, false)
+        .await;
+    assert!(result.is_err());
+
+    let user_token_0_before: TokenAccount = test_env.fetch_account(user_token_0_account).await;
+    let pool_state_before: PoolState = test_env.fetch_account(pool_id).await;
+
+    test_env
+        .flash_loan(&user, pool_id, amm_index, amount, true, true)
+        .await
+        .unwrap();
+
+    let user_token_0_after: TokenAccount = test_env.fetch_account(user_token_0_account).await;
+    let pool_state_after: PoolState = test_env.fetch_account(pool_id).await;
+
+    // fee = 100000000 * 0.1% = 100000, 20% of it goes to the protocol
+    assert_eq!(
+        user_token_0_before.amount - user_token_0_after.amount,
+        100000
+    );
+    let protocol_fees_before = pool_state_before.protocol_fees_token_0;
+    let protocol_fees_after = pool_state_after.protocol_fees_token_0;
+    assert_eq!(protocol_fees_after - protocol_fees_before, 20000);
+    // The protocol fee accrues to the partners of the pool like the one of a swap
+    let protocol_fee_per_lp_before = pool_state_before.protocol_fee_per_lp_token_0;
+    let protocol_fee_per_lp_after = pool_state_after.protocol_fee_per_lp_token_0;
+    assert!(protocol_fee_per_lp_after > protocol_fee_per_lp_before);
+    let vault_amount_before = pool_state_before.token_0_vault_amount;
+    let vault_amount_after = pool_state_after.token_0_vault_amount;
+    assert_eq!(vault_amount_after - vault_amount_before, 80000);
+    // The other side of the pool is untouched
+    assert_eq_with_copy!(
+        pool_state_after.token_1_vault_amount,
+        pool_state_before.token_1_vault_amount
+    );
+}
@@ -0,0 +1,93 @@
+use gamma::{curve::TradeDirection, fees::FeeType, states::PoolState};
+use solana_program_test::tokio;
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer};
+mod utils;
+
+use utils::*;
+
+const TRADE_FEE_RATE: u64 = 100;
+const LAUNCH_FEE_RATE: u64 = 500_000;
+const LAUNCH_FEE_DURATION: u64 = 1000;
+
+async fn setup(fee_type: FeeType) -> (TestEnv, Keypair, Pubkey) {
+    let user = Keypair::new();
+    let admin = get_admin();
+    let mut test_env = TestEnv::new(vec![user.pubkey(), admin.pubkey()]).await;
+    test_env
+        .create_config(&admin, 0, TRADE_FEE_RATE, 20, 5, 0)
+        .await;
+
+    let user_token_0_account = test_env
+        .get_or_create_associated_token_account(user.pubkey(), test_env.token_0_mint, &user)
+        .await;
+    test_env
+        .mint_base_tokens(user_token_0_account, 10000000000, test_env.token_0_mint)
+        .await;
+    let user_token_1_account = test_env
+        .get_or_create_associated_token_account(user.pubkey(), test_env.token_1_mint, &user)
+        .await;
+    test_env
+        .mint_base_tokens(user_token_1_account, 10000000000, test_env.token_1_mint)
+        .await;
+
+    let pool_id = test_env
+        .initialize_pool_with_fee_type(
+            &user,
+            0,
+            1000000000,
+            1000000000,
+            0,
+            gamma::create_pool_fee_reveiver::id(),
+            0,
+            0,
+            fee_type as u8,
+            LAUNCH_FEE_RATE,
+            LAUNCH_FEE_DURATION,
+        )
+        .await;
+    (test_env, user, pool_id)
+}
+
+async fn swap_and_get_fee_rate(test_env: &mut TestEnv, user: &Keypair, pool_id: Pubkey) -> u64 {
+    test_env
+        .swap_base_input(user, pool_id, 0, 1000000, 0, TradeDirection::ZeroForOne)
+        .await;
+    let pool_state: PoolState = test_env.fetch_account(pool_id).await;
+    pool_state.latest_dynamic_fee_rate
+}
+
+#[tokio::test]
+async fn launch_fee_should_decay_linearly_to_trade_fee() {
+    let (mut test_env, user, pool_id) = setup(FeeType::LaunchLinearDecay).await;
+
+    test_env.jump_seconds(2).await;
+    let fee_rate = swap_and_get_fee_rate(&mut test_env, &user, pool_id).await;
+    assert!(fee_rate > 499_000 && fee_rate <= LAUNCH_FEE_RATE);
+
+    // Half of the launch fee above the trade fee is left half way through
+    test_env.jump_seconds(LAUNCH_FEE_DURATION / 2).await;
+    let fee_rate = swap_and_get_fee_rate(&mut test_env, &user, pool_id).await;
+    assert!(fee_rate > 248_000 && fee_rate < 251_000);
+
+    test_env.jump_seconds(LAUNCH_FEE_DURATION / 2).await;
+    let fee_rate = swap_and_get_fee_rate(&mut test_env, &user, pool_id).await;
+    assert_eq!(fee_rate, TRADE_FEE_RATE);
+}
+
+#[tokio::test]
+async fn launch_fee_should_decay_exponentially_to_trade_fee() {
+    let (mut test_env, user, pool_id) = setup(FeeType::LaunchExponentialDecay).await;
+
+    test_env.jump_seconds(2).await;
+    let fee_rate = swap_and_get_fee_rate(&mut test_env, &user, pool_id).await;
+    assert!(fee_rate > 495_000 && fee_rate <= LAUNCH_FEE_RATE);
+
+    // Halved 5 times half way through
+    test_env.jump_seconds(LAUNCH_FEE_DURATION / 2).await;
+    let fee_rate = swap_and_get_fee_rate(&mut test_env, &user, pool_id).await;
+    assert!(fee_rate > 15_000 && fee_rate < 16_000);
+
+    test_env.jump_seconds(LAUNCH_FEE_DURATION / 2).await;
+    let fee_rate = swap_and_get_fee_rate(&mut test_env, &user, pool_id).await;
+    assert_eq!(fee_rate, TRADE_FEE_RATE);
+}
@@ -0,0 +1,288 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use gamma::{
+    states::{
+        PoolState, UserPoolLiquidity, UserRewardInfo, MAX_LP_LOCK_DURATION,
+        USER_POOL_LIQUIDITY_SEED,
+    },
+    REWARD_INFO_SEED, USER_REWARD_INFO_SEED,
+};
+use solana_program_test::tokio;
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer};
+mod utils;
+
+use utils::*;
+
+#[tokio::test]
+async fn should_refuse_to_withdraw_locked_lp_tokens() {
+    let user = Keypair::new();
+    let other_user = Keypair::new();
+    let admin = get_admin();
+    let amm_index = 0;
+    let mut test_env = TestEnv::new(vec![user.pubkey(), other_user.pubkey(), admin.pubkey()]).await;
+    test_env
+        .create_config(&admin, amm_index, 100, 20, 5, 0)
+        .await;
+
+    let user_token_0_account = test_env
+        .get_or_create_associated_token_account(user.pubkey(), test_env.token_0_mint, &user)
+        .await;
+    test_env
+        .mint_base_tokens(user_token_0_account, 10000000000, test_env.token_0_mint)
+        .await;
+    let user_token_1_account = test_env
+        .get_or_create_associated_token_account(user.pubkey(), test_env.token_1_mint, &user)
+        .await;
+    test_env
+        .mint_base_tokens(user_token_1_account, 10000000000, test_env.token_1_mint)
+        .await;
+
+    let pool_id = test_env
+        .initialize_pool(
+            &user,
+            amm_index,
+            1000000000,
+            1000000000,
+            0,
+            gamma::create_pool_fee_reveiver::id(),
+        )
+        .await;
+    let user_pool_liquidity_address = user_pool_liquidity_key(pool_id, user.pubkey());
+    let user_pool_liquidity: UserPoolLiquidity =
+        test_env.fetch_account(user_pool_liquidity_address).await;
+    let lp_tokens_owned = user_pool_liquidity.lp_tokens_owned as u64;
+    let locked_lp_tokens = lp_tokens_owned / 2;
+
+    let unlock_at = test_env.timestamp_now().await as u64 + 1000;
+    test_env
+        .lock_lp_tokens(&user, pool_id, locked_lp_tokens, unlock_at, vec![])
+        .await
+        .unwrap();
+    // A position has at most one lock
+    let result = test_env
+        .lock_lp_tokens(&user, pool_id, 1, unlock_at + 1000, vec![])
+        .await;
+    assert!(result.is_err());
+
+    let user_pool_liquidity: UserPoolLiquidity =
+        test_env.fetch_account(user_pool_liquidity_address).await;
+    assert_eq!(user_pool_liquidity.locked_lp_tokens, locked_lp_tokens);
+    assert_eq!(user_pool_liquidity.lp_unlock_at, unlock_at);
+    let pool_state: PoolState = test_env.fetch_account(pool_id).await;
+    assert_eq_with_copy!(
+        pool_state.lp_lock_boost_weight,
+        user_pool_liquidity.lp_lock_boost_weight().unwrap()
+    );
+    assert!(user_pool_liquidity.lp_lock_boost_weight().unwrap() > 0);
+
+    // Only the unlocked lp tokens can be withdrawn
+    let result = test_env
+        .withdraw_with_rewards(&user, pool_id, amm_index, lp_tokens_owned, 0, 0, vec![])
+        .await;
+    assert!(result.is_err());
+    test_env
+        .withdraw(
+            &user,
+            pool_id,
+            amm_index,
+            lp_tokens_owned - locked_lp_tokens,
+            0,
+            0,
+        )
+        .await;
+
+    let result = test_env
+        .unlock_lp_tokens(&other_user, user.pubkey(), pool_id, vec![])
+        .await;
+    assert!(result.is_err());
+
+    // Anyone can unlock the lp tokens once the lock has expired
+    test_env.jump_seconds(1000).await;
+    test_env
+        .unlock_lp_tokens(&other_user, user.pubkey(), pool_id, vec![])
+        .await
+        .unwrap();
+    let pool_state: PoolState = test_env.fetch_account(pool_id).await;
+    assert_eq_with_copy!(pool_state.lp_lock_boost_weight, 0);
+
+    test_env
+        .withdraw(&user, pool_id, amm_index, locked_lp_tokens, 0, 0)
+        .await;
+    let user_pool_liquidity: UserPoolLiquidity =
+        test_env.fetch_account(user_pool_liquidity_address).await;
+    assert_eq!(user_pool_liquidity.lp_tokens_owned, 0);
+    assert_eq!(user_pool_liquidity.locked_lp_tokens, 0);
+}
+
+#[tokio::test]
+async fn should_boost_rewards_of_locked_lp_tokens() {
+    let user = Keypair::new();
+    let user2 = Keypair::new();
+    let reward_provider = Keypair::new();
+    let admin = get_admin();
+    let amm_index = 0;
+    let mut test_env = TestEnv::new(vec![
+        user.pubkey(),
+        user2.pubkey(),
+        admin.pubkey(),
+        reward_provider.pubkey(),
+    ])
+    .await;
+    test_env
+        .create_config(&admin, amm_index, 100, 20, 5, 0)
+        .await;
+
+    let user_token_0_account = test_env
+        .get_or_create_associated_token_account(user.pubkey(), test_env.token_0_mint, &user)
+        .await;
+    test_env
+        .mint_base_tokens(user_token_0_account, 100000, test_env.token_0_mint)
+        .await;
+    let user_token_1_account = test_env
+        .get_or_create_associated_token_account(user.pubkey(), test_env.token_1_mint, &user)
+        .await;
+    test_env
+        .mint_base_tokens(user_token_1_account, 100000, test_env.token_1_mint)
+        .await;
+    let user2_token_0_account = test_env
+        .get_or_create_associated_token_account(user2.pubkey(), test_env.token_0_mint, &user2)
+        .await;
+    test_env
+        .mint_base_tokens(user2_token_0_account, 20000000000, test_env.token_0_mint)
+        .await;
+    let user2_token_1_account = test_env
+        .get_or_create_associated_token_account(user2.pubkey(), test_env.token_1_mint, &user2)
+        .await;
+    test_env
+        .mint_base_tokens(user2_token_1_account, 400000000000, test_env.token_1_mint)
+        .await;
+
+    let pool_id = test_env
+        .initialize_pool(
+            &user,
+            amm_index,
+            1000,
+            2000,
+            0,
+            gamma::create_pool_fee_reveiver::id(),
+        )
+        .await;
+    test_env
+        .deposit(&user, pool_id, amm_index, 1, 1000, 2000)
+        .await;
+    test_env.init_user_pool_liquidity(&user2, pool_id).await;
+    test_env
+        .deposit(&user2, pool_id, amm_index, 1315, 20000000000, 400000000000)
+        .await;
+
+    // Locking for the max duration doubles the reward weight
+    let timestamp_now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    test_env
+        .lock_lp_tokens(
+            &user2,
+            pool_id,
+            1315,
+            timestamp_now + 2 * MAX_LP_LOCK_DURATION,
+            vec![],
+        )
+        .await
+        .unwrap();
+
+    let reward_mint = Keypair::new();
+    test_env
+        .create_token_mint(&reward_mint, &test_env.mint_authority.pubkey(), 9)
+        .await;
+    let reward_provider_token_account = test_env
+        .get_or_create_associated_token_account(
+            reward_provider.pubkey(),
+            reward_mint.pubkey(),
+            &reward_provider,
+        )
+        .await;
+    let reward_amount = 1000000000;
+    test_env
+        .mint_base_tokens(
+            reward_provider_token_account,
+            reward_amount,
+            reward_mint.pubkey(),
+        )
+        .await;
+
+    let start_time = timestamp_now + 10;
+    let end_time = timestamp_now + 3000;
+    test_env
+        .create_rewards(
+            &reward_provider,
+            pool_id,
+            start_time,
+            end_time,
+            reward_mint.pubkey(),
+            reward_amount,
+        )
+        .await;
+    let (reward_info_key, _) = Pubkey::find_program_address(
+        &[
+            REWARD_INFO_SEED.as_bytes(),
+            pool_id.to_bytes().as_ref(),
+            &start_time.to_le_bytes(),
+            reward_mint.pubkey().to_bytes().as_ref(),
+        ],
+        &gamma::id(),
+    );
+    test_env
+        .calculate_rewards(&user, pool_id, reward_info_key)
+        .await;
+    test_env
+        .calculate_rewards(&user2, pool_id, reward_info_key)
+        .await;
+
+    // The locked lp tokens can not be unlocked before the lock expires, even after the reward
+    test_env.jump_seconds(500000).await;
+    let result = test_env
+        .unlock_lp_tokens(&user2, user2.pubkey(), pool_id, vec![])
+        .await;
+    assert!(result.is_err());
+
+    test_env
+        .calculate_rewards(&user, pool_id, reward_info_key)
+        .await;
+    test_env
+        .calculate_rewards(&user2, pool_id, reward_info_key)
+        .await;
+    let user_reward_info: UserRewardInfo = test_env
+        .fetch_account(user_reward_info_key(reward_info_key, user.pubkey()))
+        .await;
+    let user2_reward_info: UserRewardInfo = test_env
+        .fetch_account(user_reward_info_key(reward_info_key, user2.pubkey()))
+        .await;
+    // 1315 lp tokens against 1315 lp tokens boosted to 2630
+    assert_eq!(user_reward_info.total_rewards, 333333333);
+    assert_eq!(user2_reward_info.total_rewards, 666666666);
+}
+
+fn user_pool_liquidity_key(pool_id: Pubkey, user: Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            USER_POOL_LIQUIDITY_SEED.as_bytes(),
+            pool_id.as_ref(),
+            user.as_ref(),
+        ],
+        &gamma::ID,
+    )
+    .0
+}
+
+fn user_reward_info_key(reward_info: Pubkey, user: Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            USER_REWARD_INFO_SEED.as_bytes(),
+            reward_info.as_ref(),
+            user.as_ref(),
+        ],
+        &gamma::ID,
+    )
+    .0
+}
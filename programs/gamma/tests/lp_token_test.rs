@@ -0,0 +1,133 @@
+use anchor_spl::token::TokenAccount;
+use gamma::states::{
+    PartnerType, PoolPartners, PoolState, UserPoolLiquidity, USER_POOL_LIQUIDITY_SEED,
+};
+use solana_program_test::tokio;
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer};
+mod utils;
+
+use utils::*;
+
+#[tokio::test]
+async fn wrap_and_unwrap_lp_tokens() {
+    let user = Keypair::new();
+    let receiver = Keypair::new();
+    let admin = get_admin();
+    let amm_index = 0;
+    let mut test_env = TestEnv::new(vec![user.pubkey(), receiver.pubkey(), admin.pubkey()]).await;
+
+    test_env
+        .create_config(&admin, amm_index, 100, 20, 5, 0)
+        .await;
+
+    let user_token_0_account = test_env
+        .get_or_create_associated_token_account(user.pubkey(), test_env.token_0_mint, &user)
+        .await;
+    test_env
+        .mint_base_tokens(user_token_0_account, 10000000000000, test_env.token_0_mint)
+        .await;
+    let user_token_1_account = test_env
+        .get_or_create_associated_token_account(user.pubkey(), test_env.token_1_mint, &user)
+        .await;
+    test_env
+        .mint_base_tokens(user_token_1_account, 10000000000000, test_env.token_1_mint)
+        .await;
+    test_env
+        .get_or_create_associated_token_account(receiver.pubkey(), test_env.token_0_mint, &receiver)
+        .await;
+    test_env
+        .get_or_create_associated_token_account(receiver.pubkey(), test_env.token_1_mint, &receiver)
+        .await;
+
+    let pool_id = test_env
+        .initialize_pool(
+            &user,
+            amm_index,
+            1000000000,
+            1000000000,
+            0,
+            gamma::create_pool_fee_reveiver::id(),
+        )
+        .await;
+
+    let lp_mint = test_env.create_lp_mint(&user, pool_id).await;
+    let pool_state: PoolState = test_env.fetch_account(pool_id).await;
+    assert_eq_with_copy!(pool_state.lp_mint, lp_mint);
+
+    let receiver_lp_token_account = test_env
+        .get_or_create_associated_token_account(receiver.pubkey(), lp_mint, &receiver)
+        .await;
+
+    // Wrap to the lp token account of another user
+    let wrap_amount = 400000000;
+    test_env
+        .wrap_lp_tokens(&user, pool_id, receiver_lp_token_account, wrap_amount)
+        .await;
+
+    let user_pool_liquidity_address = user_pool_liquidity_key(pool_id, user.pubkey());
+    let user_pool_liquidity: UserPoolLiquidity =
+        test_env.fetch_account(user_pool_liquidity_address).await;
+    assert_eq!(
+        user_pool_liquidity.lp_tokens_owned,
+        1000000000 - 100 - u128::from(wrap_amount)
+    );
+    let pool_state: PoolState = test_env.fetch_account(pool_id).await;
+    assert_eq_with_copy!(pool_state.wrapped_lp_supply, wrap_amount);
+    assert_eq_with_copy!(pool_state.lp_supply, 1000000000);
+    let receiver_lp_tokens: TokenAccount = test_env.fetch_account(receiver_lp_token_account).await;
+    assert_eq!(receiver_lp_tokens.amount, wrap_amount);
+
+    // Unwrapping links the liquidity with the partner of the receiver
+    test_env
+        .create_partner(&admin, PartnerType::ASSET_DASH.0, "AssetDash")
+        .await
+        .unwrap();
+    test_env
+        .add_pool_partner(&admin, pool_id, PartnerType::ASSET_DASH.0)
+        .await
+        .unwrap();
+    test_env
+        .init_user_pool_liquidity_with_partner(&receiver, pool_id, PartnerType::ASSET_DASH.0)
+        .await;
+    let unwrap_amount = 300000000;
+    test_env
+        .unwrap_lp_tokens(&receiver, pool_id, receiver_lp_token_account, unwrap_amount)
+        .await;
+
+    let receiver_pool_liquidity: UserPoolLiquidity = test_env
+        .fetch_account(user_pool_liquidity_key(pool_id, receiver.pubkey()))
+        .await;
+    assert_eq!(
+        receiver_pool_liquidity.lp_tokens_owned,
+        u128::from(unwrap_amount)
+    );
+    let pool_state: PoolState = test_env.fetch_account(pool_id).await;
+    assert_eq_with_copy!(pool_state.wrapped_lp_supply, wrap_amount - unwrap_amount);
+    let pool_partners: PoolPartners = test_env.fetch_account(pool_partners_key(pool_id)).await;
+    let partner = pool_partners.partners[0];
+    assert_eq!(partner.partner_id, PartnerType::ASSET_DASH.0);
+    assert_eq!(partner.lp_token_linked_with_partner, unwrap_amount);
+    let receiver_lp_tokens: TokenAccount = test_env.fetch_account(receiver_lp_token_account).await;
+    assert_eq!(receiver_lp_tokens.amount, wrap_amount - unwrap_amount);
+
+    // Unwrapped liquidity can be withdrawn
+    test_env
+        .withdraw(&receiver, pool_id, amm_index, unwrap_amount, 0, 0)
+        .await;
+    let pool_state: PoolState = test_env.fetch_account(pool_id).await;
+    assert_eq_with_copy!(pool_state.lp_supply, 1000000000 - unwrap_amount);
+    let pool_partners: PoolPartners = test_env.fetch_account(pool_partners_key(pool_id)).await;
+    assert_eq!(pool_partners.partners[0].lp_token_linked_with_partner, 0);
+}
+
+fn user_pool_liquidity_key(pool_id: Pubkey, user: Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            USER_POOL_LIQUIDITY_SEED.as_bytes(),
+            pool_id.as_ref(),
+            user.as_ref(),
+        ],
+        &gamma::ID,
+    )
+    .0
+}
@@ -0,0 +1,101 @@
+use gamma::{
+    curve::TradeDirection,
+    states::{Observations, MAX_OBSERVATION_NUM, OBSERVATION_NUM},
+};
+use solana_program_test::tokio;
+use solana_sdk::{signature::Keypair, signer::Signer};
+mod utils;
+
+use utils::*;
+
+#[tokio::test]
+async fn should_expand_observation_state_and_wrap_around_new_capacity() {
+    let user = Keypair::new();
+    let admin = get_admin();
+    let amm_index = 0;
+    let mut test_env = TestEnv::new(vec![user.pubkey(), admin.pubkey()]).await;
+    test_env
+        .create_config(&admin, amm_index, 100, 20, 5, 0)
+        .await;
+
+    let user_token_0_account = test_env
+        .get_or_create_associated_token_account(user.pubkey(), test_env.token_0_mint, &user)
+        .await;
+    test_env
+        .mint_base_tokens(user_token_0_account, 10000000000, test_env.token_0_mint)
+        .await;
+    let user_token_1_account = test_env
+        .get_or_create_associated_token_account(user.pubkey(), test_env.token_1_mint, &user)
+        .await;
+    test_env
+        .mint_base_tokens(user_token_1_account, 10000000000, test_env.token_1_mint)
+        .await;
+
+    let pool_id = test_env
+        .initialize_pool(
+            &user,
+            amm_index,
+            1000000000,
+            1000000000,
+            0,
+            gamma::create_pool_fee_reveiver::id(),
+        )
+        .await;
+
+    let data = test_env.fetch_observation_data(pool_id).await;
+    let observations = Observations::from_account_data(&data).unwrap();
+    assert_eq!(observations.capacity(), OBSERVATION_NUM);
+
+    // Anyone can pay for the expansion
+    test_env
+        .expand_observation_state(&user, pool_id, 20)
+        .await
+        .unwrap();
+    let data = test_env.fetch_observation_data(pool_id).await;
+    let observations = Observations::from_account_data(&data).unwrap();
+    assert_eq!(observations.capacity(), OBSERVATION_NUM + 20);
+
+    let result = test_env
+        .expand_observation_state(
+            &user,
+            pool_id,
+            (MAX_OBSERVATION_NUM - OBSERVATION_NUM) as u16,
+        )
+        .await;
+    // Expansions are capped to keep the swaps within the compute budget
+    assert!(result.is_err());
+
+    // One observation per swap, the buffer goes past `OBSERVATION_NUM` before wrapping around
+    test_env
+        .update_pool(&admin, pool_id, amm_index, 6, 30)
+        .await;
+    let observation_count = OBSERVATION_NUM + 25;
+    for index in 0..observation_count {
+        test_env.jump_seconds(30).await;
+        let trade_direction = if index % 2 == 0 {
+            TradeDirection::ZeroForOne
+        } else {
+            TradeDirection::OneForZero
+        };
+        test_env
+            .swap_base_input(&user, pool_id, amm_index, 1000000, 0, trade_direction)
+            .await;
+    }
+
+    let data = test_env.fetch_observation_data(pool_id).await;
+    let observations = Observations::from_account_data(&data).unwrap();
+    // The first swap initializes the buffer at index 0
+    let observation_index = observations.state.observation_index;
+    assert_eq!(
+        usize::from(observation_index),
+        (observation_count - 1) % observations.capacity()
+    );
+    let last_timestamp = observations.get(OBSERVATION_NUM + 19).block_timestamp;
+    let first_timestamp = observations.get(0).block_timestamp;
+    assert_eq!(first_timestamp, last_timestamp + 30);
+
+    // The 30 seconds interval covers the whole hour of the volatility window
+    let twap = test_env.get_twap(&user, pool_id, 3600).await;
+    assert_eq!(twap.end_timestamp - twap.start_timestamp, 3570);
+    assert!(twap.token_0_twap_price_x32 > 0);
+}
@@ -0,0 +1,175 @@
+use gamma::{curve::TradeDirection, error::GammaError, states::PoolState};
+use solana_program_test::tokio;
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer};
+mod utils;
+
+use utils::*;
+
+const TOKEN_0_FEED_ID: [u8; 32] = [1u8; 32];
+const TOKEN_1_FEED_ID: [u8; 32] = [2u8; 32];
+
+async fn setup_pool(test_env: &mut TestEnv, user: &Keypair, admin: &Keypair) -> Pubkey {
+    test_env.create_config(admin, 0, 100, 20, 5, 0).await;
+
+    let user_token_0_account = test_env
+        .get_or_create_associated_token_account(user.pubkey(), test_env.token_0_mint, user)
+        .await;
+    test_env
+        .mint_base_tokens(user_token_0_account, 100000000000000, test_env.token_0_mint)
+        .await;
+
+    let user_token_1_account = test_env
+        .get_or_create_associated_token_account(user.pubkey(), test_env.token_1_mint, user)
+        .await;
+    test_env
+        .mint_base_tokens(user_token_1_account, 100000000000000, test_env.token_1_mint)
+        .await;
+
+    // Pool price is 0.5 token_1 for 1 token_0
+    let pool_id = test_env
+        .initialize_pool(
+            user,
+            0,
+            20000000000000,
+            10000000000000,
+            0,
+            gamma::create_pool_fee_reveiver::id(),
+        )
+        .await;
+    test_env.jump_seconds(100).await;
+
+    pool_id
+}
+
+#[tokio::test]
+async fn oracle_swap_uses_oracle_price() {
+    let user = Keypair::new();
+    let admin = get_admin();
+    let mut test_env = TestEnv::new(vec![user.pubkey(), admin.pubkey()]).await;
+    let pool_id = setup_pool(&mut test_env, &user, &admin).await;
+
+    let price_feed_config = test_env
+        .set_price_feed_config(
+            &admin,
+            pool_id,
+            TOKEN_0_FEED_ID,
+            TOKEN_1_FEED_ID,
+            60,
+            10_000,
+        )
+        .await;
+
+    // Oracle price is 0.25 token_1 for 1 token_0: token_0 = $1, token_1 = $4
+    let now = test_env.timestamp_now().await;
+    let token_0_price_update = Pubkey::new_unique();
+    let token_1_price_update = Pubkey::new_unique();
+    test_env.set_price_update(token_0_price_update, TOKEN_0_FEED_ID, 100000000, 0, -8, now);
+    test_env.set_price_update(token_1_price_update, TOKEN_1_FEED_ID, 400000000, 0, -8, now);
+
+    let pool_state_before: PoolState = test_env.fetch_account(pool_id).await;
+    let amount_in = 1000000000;
+    test_env
+        .oracle_swap_base_input(
+            &user,
+            pool_id,
+            0,
+            amount_in,
+            0,
+            TradeDirection::ZeroForOne,
+            price_feed_config,
+            vec![token_0_price_update, token_1_price_update],
+        )
+        .await
+        .unwrap_transaction();
+    let pool_state_after: PoolState = test_env.fetch_account(pool_id).await;
+
+    let fee = pool_state_after.cumulative_trade_fees_token_0 as u64;
+    let amount_out = pool_state_before.token_1_vault_amount - pool_state_after.token_1_vault_amount;
+    assert!(fee > 0);
+    assert_eq!(amount_out, (amount_in - fee) / 4);
+}
+
+#[tokio::test]
+async fn oracle_swap_rejects_stale_and_uncertain_prices() {
+    let user = Keypair::new();
+    let admin = get_admin();
+    let mut test_env = TestEnv::new(vec![user.pubkey(), admin.pubkey()]).await;
+    let pool_id = setup_pool(&mut test_env, &user, &admin).await;
+
+    let price_feed_config = test_env
+        .set_price_feed_config(
+            &admin,
+            pool_id,
+            TOKEN_0_FEED_ID,
+            TOKEN_1_FEED_ID,
+            60,
+            10_000,
+        )
+        .await;
+
+    let now = test_env.timestamp_now().await;
+    let token_0_price_update = Pubkey::new_unique();
+    let token_1_price_update = Pubkey::new_unique();
+    test_env.set_price_update(
+        token_0_price_update,
+        TOKEN_0_FEED_ID,
+        100000000,
+        0,
+        -8,
+        now - 61,
+    );
+    test_env.set_price_update(token_1_price_update, TOKEN_1_FEED_ID, 400000000, 0, -8, now);
+
+    let result = test_env
+        .oracle_swap_base_input(
+            &user,
+            pool_id,
+            0,
+            1000000000,
+            0,
+            TradeDirection::ZeroForOne,
+            price_feed_config,
+            vec![token_0_price_update, token_1_price_update],
+        )
+        .await;
+    assert_error!(result, GammaError::StalePrice);
+
+    // 2% confidence interval, while 1% is allowed
+    test_env.set_price_update(
+        token_0_price_update,
+        TOKEN_0_FEED_ID,
+        100000000,
+        2000000,
+        -8,
+        now,
+    );
+    let result = test_env
+        .oracle_swap_base_input(
+            &user,
+            pool_id,
+            0,
+            1000000000,
+            0,
+            TradeDirection::ZeroForOne,
+            price_feed_config,
+            vec![token_0_price_update, token_1_price_update],
+        )
+        .await;
+    assert_error!(result, GammaError::PriceConfidenceTooWide);
+
+    // feed of another asset
+    test_env.set_price_update(token_0_price_update, [3u8; 32], 100000000, 0, -8, now);
+    let result = test_env
+        .oracle_swap_base_input(
+            &user,
+            pool_id,
+            0,
+            1000000000,
+            0,
+            TradeDirection::ZeroForOne,
+            price_feed_config,
+            vec![token_0_price_update, token_1_price_update],
+        )
+        .await;
+    assert_error!(result, GammaError::InvalidPriceFeed);
+}
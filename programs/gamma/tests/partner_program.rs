@@ -1,10 +1,9 @@
 use std::u64;
 
 use gamma::curve::TradeDirection;
-use gamma::states::PartnerType;
-use gamma::states::PoolState;
+use gamma::states::{PartnerInfo, PartnerType, PoolPartnerInfo, PoolPartners, PoolState};
 use solana_program_test::tokio;
-use solana_sdk::{signature::Keypair, signer::Signer};
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer};
 mod utils;
 
 use utils::*;
@@ -91,33 +90,39 @@ async fn should_track_cumulative_rates_correctly() {
     // we jump 100 seconds in time to make sure current blockTime is more than pool.open_time
     test_env.jump_seconds(100).await;
 
+    // AssetDash is tracked in the partners of the pools created since `PoolPartners`
     let pool_state: PoolState = test_env.fetch_account(pool_id).await;
-    dbg!(1, pool_state.token_0_vault_amount, pool_state.token_1_vault_amount);
-
-    assert_eq_with_copy!(pool_state.cumulative_trade_fees_token_0, 0);
-    assert_eq_with_copy!(pool_state.cumulative_trade_fees_token_1, 0);
-
     assert_eq_with_copy!(
-        pool_state.partners[0].cumulative_fee_total_times_tvl_share_token_0,
-        0
+        pool_state.partners[0].partner_id,
+        PartnerInfo::MOVED_TO_POOL_PARTNERS
     );
-    assert_eq_with_copy!(
-        pool_state.partners[0].cumulative_fee_total_times_tvl_share_token_1,
-        0
+    test_env
+        .create_partner(&admin, PartnerType::ASSET_DASH.0, "AssetDash")
+        .await
+        .unwrap();
+    test_env
+        .add_pool_partner(&admin, pool_id, PartnerType::ASSET_DASH.0)
+        .await
+        .unwrap();
+
+    let (pool_state, partner, cumulative_fees) = asset_dash_partner(&mut test_env, pool_id).await;
+    dbg!(
+        1,
+        pool_state.token_0_vault_amount,
+        pool_state.token_1_vault_amount
     );
 
-    assert_eq_with_copy!(pool_state.partners[0].lp_token_linked_with_partner, 0);
-    assert_eq_with_copy!(
-        PartnerType::new(pool_state.partners[0].partner_id),
-        PartnerType::AssetDash
-    );
-    assert_eq_with_copy!(pool_state.partners[0].partner_id, 0);
+    assert_eq_with_copy!(pool_state.cumulative_trade_fees_token_0, 0);
+    assert_eq_with_copy!(pool_state.cumulative_trade_fees_token_1, 0);
+    assert_eq!(cumulative_fees, (0, 0));
+    assert_eq!(partner.lp_token_linked_with_partner, 0);
+    assert_eq!(partner.partner_id, PartnerType::ASSET_DASH.0);
 
     test_env
         .init_user_pool_liquidity_with_partner(
             &lp_depositor_asset_dash,
             pool_id,
-            Some("AssetDash".to_string()),
+            PartnerType::ASSET_DASH.0,
         )
         .await;
 
@@ -133,23 +138,16 @@ async fn should_track_cumulative_rates_correctly() {
         )
         .await;
 
-    let pool_state: PoolState = test_env.fetch_account(pool_id).await;
-    dbg!(2, pool_state.token_0_vault_amount, pool_state.token_1_vault_amount);
-
-
-    assert_eq_with_copy!(
-        pool_state.partners[0].lp_token_linked_with_partner,
-        lp_deposit_amount
-    );
-    assert_eq_with_copy!(
-        pool_state.partners[0].cumulative_fee_total_times_tvl_share_token_0,
-        0
-    );
-    assert_eq_with_copy!(
-        pool_state.partners[0].cumulative_fee_total_times_tvl_share_token_1,
-        0
+    let (pool_state, partner, cumulative_fees) = asset_dash_partner(&mut test_env, pool_id).await;
+    dbg!(
+        2,
+        pool_state.token_0_vault_amount,
+        pool_state.token_1_vault_amount
     );
 
+    assert_eq!(partner.lp_token_linked_with_partner, lp_deposit_amount);
+    assert_eq!(cumulative_fees, (0, 0));
+
     let withdraw_amount = 100000000;
     test_env
         .withdraw(
@@ -162,21 +160,18 @@ async fn should_track_cumulative_rates_correctly() {
         )
         .await;
 
-    let pool_state: PoolState = test_env.fetch_account(pool_id).await;
-    dbg!(3, pool_state.token_0_vault_amount, pool_state.token_1_vault_amount);
+    let (pool_state, partner, cumulative_fees) = asset_dash_partner(&mut test_env, pool_id).await;
+    dbg!(
+        3,
+        pool_state.token_0_vault_amount,
+        pool_state.token_1_vault_amount
+    );
 
-    assert_eq_with_copy!(
-        pool_state.partners[0].lp_token_linked_with_partner,
+    assert_eq!(
+        partner.lp_token_linked_with_partner,
         lp_deposit_amount - withdraw_amount
     );
-    assert_eq_with_copy!(
-        pool_state.partners[0].cumulative_fee_total_times_tvl_share_token_0,
-        0
-    );
-    assert_eq_with_copy!(
-        pool_state.partners[0].cumulative_fee_total_times_tvl_share_token_1,
-        0
-    );
+    assert_eq!(cumulative_fees, (0, 0));
 
     test_env
         .swap_base_input(
@@ -189,22 +184,19 @@ async fn should_track_cumulative_rates_correctly() {
         )
         .await;
 
-    let pool_state: PoolState = test_env.fetch_account(pool_id).await;
-    dbg!(4, pool_state.token_0_vault_amount, pool_state.token_1_vault_amount);
+    let (pool_state, partner, cumulative_fees) = asset_dash_partner(&mut test_env, pool_id).await;
+    dbg!(
+        4,
+        pool_state.token_0_vault_amount,
+        pool_state.token_1_vault_amount
+    );
 
-    assert_eq_with_copy!(
-        pool_state.partners[0].lp_token_linked_with_partner,
+    assert_eq!(
+        partner.lp_token_linked_with_partner,
         lp_deposit_amount - withdraw_amount
     );
-    assert_eq_with_copy!(
-        pool_state.partners[0].cumulative_fee_total_times_tvl_share_token_0,
-        0
-    );
-    assert_eq_with_copy!(
-        pool_state.partners[0].cumulative_fee_total_times_tvl_share_token_1,
-        (pool_state.partners[0].lp_token_linked_with_partner * pool_state.protocol_fees_token_1)
-            / pool_state.lp_supply
-    );
+    assert_eq!(cumulative_fees.0, 0);
+    assert_partner_fees(&pool_state, &partner, cumulative_fees);
 
     test_env
         .swap_base_input(
@@ -217,23 +209,18 @@ async fn should_track_cumulative_rates_correctly() {
         )
         .await;
 
-    let pool_state: PoolState = test_env.fetch_account(pool_id).await;
-    dbg!(5, pool_state.token_0_vault_amount, pool_state.token_1_vault_amount);
+    let (pool_state, partner, cumulative_fees) = asset_dash_partner(&mut test_env, pool_id).await;
+    dbg!(
+        5,
+        pool_state.token_0_vault_amount,
+        pool_state.token_1_vault_amount
+    );
 
-    assert_eq_with_copy!(
-        pool_state.partners[0].lp_token_linked_with_partner,
+    assert_eq!(
+        partner.lp_token_linked_with_partner,
         lp_deposit_amount - withdraw_amount
     );
-    assert_eq_with_copy!(
-        pool_state.partners[0].cumulative_fee_total_times_tvl_share_token_0,
-        (pool_state.partners[0].lp_token_linked_with_partner * pool_state.protocol_fees_token_0)
-            / pool_state.lp_supply
-    );
-    assert_eq_with_copy!(
-        pool_state.partners[0].cumulative_fee_total_times_tvl_share_token_1,
-        (pool_state.partners[0].lp_token_linked_with_partner * pool_state.protocol_fees_token_1)
-            / pool_state.lp_supply
-    );
+    assert_partner_fees(&pool_state, &partner, cumulative_fees);
 
     // swap base output
 
@@ -248,23 +235,18 @@ async fn should_track_cumulative_rates_correctly() {
         )
         .await;
 
-    let pool_state: PoolState = test_env.fetch_account(pool_id).await;
-    dbg!(6, pool_state.token_0_vault_amount, pool_state.token_1_vault_amount);
+    let (pool_state, partner, cumulative_fees) = asset_dash_partner(&mut test_env, pool_id).await;
+    dbg!(
+        6,
+        pool_state.token_0_vault_amount,
+        pool_state.token_1_vault_amount
+    );
 
-    assert_eq_with_copy!(
-        pool_state.partners[0].lp_token_linked_with_partner,
+    assert_eq!(
+        partner.lp_token_linked_with_partner,
         lp_deposit_amount - withdraw_amount
     );
-    assert_eq_with_copy!(
-        pool_state.partners[0].cumulative_fee_total_times_tvl_share_token_0,
-        (pool_state.partners[0].lp_token_linked_with_partner * pool_state.protocol_fees_token_0)
-            / pool_state.lp_supply
-    );
-    assert_eq_with_copy!(
-        pool_state.partners[0].cumulative_fee_total_times_tvl_share_token_1,
-        (pool_state.partners[0].lp_token_linked_with_partner * pool_state.protocol_fees_token_1)
-            / pool_state.lp_supply
-    );
+    assert_partner_fees(&pool_state, &partner, cumulative_fees);
 
     test_env
         .swap_base_output(
@@ -277,21 +259,46 @@ async fn should_track_cumulative_rates_correctly() {
         )
         .await;
 
-    let pool_state: PoolState = test_env.fetch_account(pool_id).await;
-    dbg!(7, pool_state.token_0_vault_amount, pool_state.token_1_vault_amount);
+    let (pool_state, partner, cumulative_fees) = asset_dash_partner(&mut test_env, pool_id).await;
+    dbg!(
+        7,
+        pool_state.token_0_vault_amount,
+        pool_state.token_1_vault_amount
+    );
 
-    assert_eq_with_copy!(
-        pool_state.partners[0].lp_token_linked_with_partner,
+    assert_eq!(
+        partner.lp_token_linked_with_partner,
         lp_deposit_amount - withdraw_amount
     );
-    assert_eq_with_copy!(
-        pool_state.partners[0].cumulative_fee_total_times_tvl_share_token_0,
-        (pool_state.partners[0].lp_token_linked_with_partner * pool_state.protocol_fees_token_0)
-            / pool_state.lp_supply
-    );
-    assert_eq_with_copy!(
-        pool_state.partners[0].cumulative_fee_total_times_tvl_share_token_1,
-        (pool_state.partners[0].lp_token_linked_with_partner * pool_state.protocol_fees_token_1)
-            / pool_state.lp_supply
-    );
+    assert_partner_fees(&pool_state, &partner, cumulative_fees);
+}
+
+/// The pool state, the AssetDash partner of the pool and its cumulative fees up to now
+async fn asset_dash_partner(
+    test_env: &mut TestEnv,
+    pool_id: Pubkey,
+) -> (PoolState, PoolPartnerInfo, (u64, u64)) {
+    let pool_state: PoolState = test_env.fetch_account(pool_id).await;
+    let pool_partners: PoolPartners = test_env.fetch_account(pool_partners_key(pool_id)).await;
+    let partner = *pool_partners
+        .partners
+        .iter()
+        .find(|p| p.partner_id == PartnerType::ASSET_DASH.0)
+        .unwrap();
+    let cumulative_fees = partner.cumulative_fees(&pool_state).unwrap();
+    (pool_state, partner, cumulative_fees)
+}
+
+/// The cumulative fees are the protocol fees times the tvl share of the partner, the fee accumulator rounds down
+fn assert_partner_fees(
+    pool_state: &PoolState,
+    partner: &PoolPartnerInfo,
+    cumulative_fees: (u64, u64),
+) {
+    let lp_supply = u128::from(pool_state.lp_supply);
+    let linked = u128::from(partner.lp_token_linked_with_partner);
+    let expected_token_0 = linked * u128::from(pool_state.protocol_fees_token_0) / lp_supply;
+    let expected_token_1 = linked * u128::from(pool_state.protocol_fees_token_1) / lp_supply;
+    assert!(expected_token_0 - u128::from(cumulative_fees.0) <= 1);
+    assert!(expected_token_1 - u128::from(cumulative_fees.1) <= 1);
 }
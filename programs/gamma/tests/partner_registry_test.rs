@@ -0,0 +1,287 @@
+use gamma::{
+    curve::TradeDirection,
+    states::{Partner, PartnerInfo, PartnerType, PoolPartners, PoolState},
+};
+use solana_program_test::tokio;
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer};
+mod utils;
+
+use utils::*;
+
+const PARTNER_ID: u8 = 1;
+
+async fn setup(user: &Keypair, lp_depositor: &Keypair) -> (TestEnv, Pubkey) {
+    let admin = get_admin();
+    let mut test_env =
+        TestEnv::new(vec![user.pubkey(), admin.pubkey(), lp_depositor.pubkey()]).await;
+    test_env.create_config(&admin, 0, 3000, 2000, 50, 0).await;
+
+    for owner in [user, lp_depositor] {
+        let token_0_account = test_env
+            .get_or_create_associated_token_account(owner.pubkey(), test_env.token_0_mint, owner)
+            .await;
+        test_env
+            .mint_base_tokens(token_0_account, 10000000000, test_env.token_0_mint)
+            .await;
+        let token_1_account = test_env
+            .get_or_create_associated_token_account(owner.pubkey(), test_env.token_1_mint, owner)
+            .await;
+        test_env
+            .mint_base_tokens(token_1_account, 10000000000, test_env.token_1_mint)
+            .await;
+    }
+
+    let pool_id = test_env
+        .initialize_pool(
+            user,
+            0,
+            1000000000,
+            1000000000,
+            0,
+            gamma::create_pool_fee_reveiver::id(),
+        )
+        .await;
+    test_env.jump_seconds(2).await;
+    (test_env, pool_id)
+}
+
+#[tokio::test]
+async fn should_register_partners_and_add_them_to_pools() {
+    let user = Keypair::new();
+    let lp_depositor = Keypair::new();
+    let admin = get_admin();
+    let (mut test_env, pool_id) = setup(&user, &lp_depositor).await;
+
+    // Only the admin can register a partner
+    assert!(test_env
+        .create_partner(&user, PARTNER_ID, "Phantom")
+        .await
+        .is_err());
+    test_env
+        .create_partner(&admin, PARTNER_ID, "Phantom")
+        .await
+        .unwrap();
+    let partner: Partner = test_env.fetch_account(partner_key(PARTNER_ID)).await;
+    assert_eq!(partner.partner_type(), PartnerType(PARTNER_ID));
+    assert_eq!(partner.name, "Phantom");
+
+    // Users can not be linked with a partner not added to the pool
+    assert!(test_env
+        .try_init_user_pool_liquidity_with_partner(&lp_depositor, pool_id, PARTNER_ID)
+        .await
+        .is_err());
+    assert!(test_env.pool_partners(pool_id).await.is_none());
+
+    assert!(test_env
+        .add_pool_partner(&user, pool_id, PARTNER_ID)
+        .await
+        .is_err());
+    test_env
+        .add_pool_partner(&admin, pool_id, PARTNER_ID)
+        .await
+        .unwrap();
+    let pool_partners: PoolPartners = test_env.fetch_account(pool_partners_key(pool_id)).await;
+    assert_eq!(pool_partners.pool_state, pool_id);
+    assert_eq!(pool_partners.partners.len(), 1);
+    assert_eq!(pool_partners.partners[0].partner_id, PARTNER_ID);
+    assert_eq!(pool_partners.partners[0].lp_token_linked_with_partner, 0);
+    let account = test_env
+        .get_account_info(pool_partners_key(pool_id))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(account.data.len(), PoolPartners::space(1));
+
+    // A partner is added to a pool once
+    test_env.jump_seconds(1).await;
+    assert!(test_env
+        .add_pool_partner(&admin, pool_id, PARTNER_ID)
+        .await
+        .is_err());
+
+    test_env
+        .create_partner(&admin, PARTNER_ID + 1, "Backpack")
+        .await
+        .unwrap();
+    test_env
+        .add_pool_partner(&admin, pool_id, PARTNER_ID + 1)
+        .await
+        .unwrap();
+    let pool_partners: PoolPartners = test_env.fetch_account(pool_partners_key(pool_id)).await;
+    assert_eq!(pool_partners.partners.len(), 2);
+    assert_eq!(pool_partners.partners[1].partner_id, PARTNER_ID + 1);
+    let account = test_env
+        .get_account_info(pool_partners_key(pool_id))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(account.data.len(), PoolPartners::space(2));
+}
+
+#[tokio::test]
+async fn should_accrue_protocol_fees_of_pool_partners() {
+    let user = Keypair::new();
+    let lp_depositor = Keypair::new();
+    let admin = get_admin();
+    let (mut test_env, pool_id) = setup(&user, &lp_depositor).await;
+
+    test_env
+        .create_partner(&admin, PARTNER_ID, "Phantom")
+        .await
+        .unwrap();
+    test_env
+        .add_pool_partner(&admin, pool_id, PARTNER_ID)
+        .await
+        .unwrap();
+    test_env
+        .init_user_pool_liquidity_with_partner(&lp_depositor, pool_id, PARTNER_ID)
+        .await;
+
+    let lp_deposit_amount = 200000000;
+    test_env
+        .deposit(
+            &lp_depositor,
+            pool_id,
+            0,
+            lp_deposit_amount,
+            u64::MAX,
+            u64::MAX,
+        )
+        .await;
+    let pool_partners: PoolPartners = test_env.fetch_account(pool_partners_key(pool_id)).await;
+    let partner_info = pool_partners.partners[0];
+    assert_eq!(partner_info.lp_token_linked_with_partner, lp_deposit_amount);
+    assert_eq!(partner_info.cumulative_fee_total_times_tvl_share_token_0, 0);
+    assert_eq!(partner_info.cumulative_fee_total_times_tvl_share_token_1, 0);
+
+    let pool_state_before: PoolState = test_env.fetch_account(pool_id).await;
+    test_env
+        .swap_base_input(&user, pool_id, 0, 100000000, 0, TradeDirection::ZeroForOne)
+        .await;
+    let pool_state: PoolState = test_env.fetch_account(pool_id).await;
+    let protocol_fees = pool_state.protocol_fees_token_0 - pool_state_before.protocol_fees_token_0;
+    assert!(protocol_fees > 0);
+
+    // The protocol fees are accrued to the partner when its linked lp tokens change
+    let pool_partners: PoolPartners = test_env.fetch_account(pool_partners_key(pool_id)).await;
+    assert_eq!(
+        pool_partners.partners[0].cumulative_fee_total_times_tvl_share_token_0,
+        0
+    );
+    let lp_withdraw_amount = lp_deposit_amount / 2;
+    test_env
+        .withdraw(&lp_depositor, pool_id, 0, lp_withdraw_amount, 0, 0)
+        .await;
+    let pool_partners: PoolPartners = test_env.fetch_account(pool_partners_key(pool_id)).await;
+    let partner_info = pool_partners.partners[0];
+    assert_eq!(
+        partner_info.lp_token_linked_with_partner,
+        lp_deposit_amount - lp_withdraw_amount
+    );
+    let expected_fees = u128::from(lp_deposit_amount) * u128::from(protocol_fees)
+        / u128::from(pool_state.lp_supply);
+    assert!(
+        u128::from(partner_info.cumulative_fee_total_times_tvl_share_token_0)
+            .abs_diff(expected_fees)
+            <= 1
+    );
+    assert_eq!(partner_info.cumulative_fee_total_times_tvl_share_token_1, 0);
+    assert_eq_with_copy!(
+        partner_info.protocol_fee_per_lp_checkpoint_token_0,
+        pool_state.protocol_fee_per_lp_token_0
+    );
+
+    // The partners of the pool state are not used by the pools created since `PoolPartners`
+    assert_eq_with_copy!(
+        pool_state.partners[0].partner_id,
+        PartnerInfo::MOVED_TO_POOL_PARTNERS
+    );
+    assert_eq_with_copy!(pool_state.partners[0].lp_token_linked_with_partner, 0);
+}
+
+#[tokio::test]
+async fn should_move_asset_dash_of_pools_created_before_pool_partners() {
+    let user = Keypair::new();
+    let lp_depositor = Keypair::new();
+    let admin = get_admin();
+    let (mut test_env, pool_id) = setup(&user, &lp_depositor).await;
+
+    // A pool tracking AssetDash in its pool state, like the pools created before `PoolPartners`
+    let mut account = test_env.get_account_info(pool_id).await.unwrap().unwrap();
+    let partner_id_offset = 8 + std::mem::offset_of!(PoolState, partners);
+    account.data[partner_id_offset..partner_id_offset + 8]
+        .copy_from_slice(&PartnerType::ASSET_DASH.id().to_le_bytes());
+    test_env
+        .program_test_context
+        .set_account(&pool_id, &account.into());
+
+    test_env
+        .init_user_pool_liquidity_with_partner_name(
+            &lp_depositor,
+            pool_id,
+            Some("AssetDash".to_string()),
+        )
+        .await;
+    let lp_deposit_amount = 200000000;
+    test_env
+        .deposit(
+            &lp_depositor,
+            pool_id,
+            0,
+            lp_deposit_amount,
+            u64::MAX,
+            u64::MAX,
+        )
+        .await;
+    let pool_state: PoolState = test_env.fetch_account(pool_id).await;
+    assert_eq_with_copy!(
+        pool_state.partners[0].lp_token_linked_with_partner,
+        lp_deposit_amount
+    );
+
+    // Only the lp tokens are linked until AssetDash is moved to the pool partners
+    test_env
+        .swap_base_input(&user, pool_id, 0, 100000000, 0, TradeDirection::ZeroForOne)
+        .await;
+    let pool_state: PoolState = test_env.fetch_account(pool_id).await;
+    assert!(pool_state.protocol_fees_token_0 > 0);
+    assert_eq_with_copy!(
+        pool_state.partners[0].cumulative_fee_total_times_tvl_share_token_0,
+        0
+    );
+
+    test_env
+        .create_partner(&admin, PartnerType::ASSET_DASH.0, "AssetDash")
+        .await
+        .unwrap();
+    test_env
+        .add_pool_partner(&admin, pool_id, PartnerType::ASSET_DASH.0)
+        .await
+        .unwrap();
+    let pool_state: PoolState = test_env.fetch_account(pool_id).await;
+    assert_eq_with_copy!(
+        pool_state.partners[0].partner_id,
+        PartnerInfo::MOVED_TO_POOL_PARTNERS
+    );
+    assert_eq_with_copy!(pool_state.partners[0].lp_token_linked_with_partner, 0);
+    let pool_partners: PoolPartners = test_env.fetch_account(pool_partners_key(pool_id)).await;
+    let partner_info = pool_partners.partners[0];
+    assert_eq!(partner_info.partner_id, PartnerType::ASSET_DASH.0);
+    assert_eq!(partner_info.lp_token_linked_with_partner, lp_deposit_amount);
+    assert_eq!(partner_info.cumulative_fees(&pool_state).unwrap(), (0, 0));
+
+    // The fees accrue to AssetDash from then on
+    test_env
+        .swap_base_input(&user, pool_id, 0, 100000000, 0, TradeDirection::ZeroForOne)
+        .await;
+    test_env
+        .withdraw(&lp_depositor, pool_id, 0, lp_deposit_amount / 2, 0, 0)
+        .await;
+    let pool_partners: PoolPartners = test_env.fetch_account(pool_partners_key(pool_id)).await;
+    let partner_info = pool_partners.partners[0];
+    assert_eq!(
+        partner_info.lp_token_linked_with_partner,
+        lp_deposit_amount / 2
+    );
+    assert!(partner_info.cumulative_fee_total_times_tvl_share_token_0 > 0);
+}
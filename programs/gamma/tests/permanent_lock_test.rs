@@ -0,0 +1,206 @@
+use anchor_spl::token::TokenAccount;
+use gamma::{
+    curve::TradeDirection,
+    states::{
+        PermanentLock, PoolState, UserPoolLiquidity, PERMANENT_LOCK_SEED, USER_POOL_LIQUIDITY_SEED,
+    },
+};
+use solana_program_test::tokio;
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer};
+mod utils;
+
+use utils::*;
+
+const TRADE_FEE_RATE: u64 = 10_000;
+
+#[tokio::test]
+async fn should_claim_fees_of_permanently_locked_liquidity() {
+    let user = Keypair::new();
+    let admin = get_admin();
+    let amm_index = 0;
+    let mut test_env = TestEnv::new(vec![user.pubkey(), admin.pubkey()]).await;
+    test_env
+        .create_config(&admin, amm_index, TRADE_FEE_RATE, 20, 5, 0)
+        .await;
+
+    let user_token_0_account = test_env
+        .get_or_create_associated_token_account(user.pubkey(), test_env.token_0_mint, &user)
+        .await;
+    test_env
+        .mint_base_tokens(user_token_0_account, 10000000000, test_env.token_0_mint)
+        .await;
+    let user_token_1_account = test_env
+        .get_or_create_associated_token_account(user.pubkey(), test_env.token_1_mint, &user)
+        .await;
+    test_env
+        .mint_base_tokens(user_token_1_account, 10000000000, test_env.token_1_mint)
+        .await;
+
+    let pool_id = test_env
+        .initialize_pool(
+            &user,
+            amm_index,
+            1000000000,
+            1000000000,
+            0,
+            gamma::create_pool_fee_reveiver::id(),
+        )
+        .await;
+    test_env.jump_seconds(2).await;
+
+    let user_pool_liquidity_address = Pubkey::find_program_address(
+        &[
+            USER_POOL_LIQUIDITY_SEED.as_bytes(),
+            pool_id.as_ref(),
+            user.pubkey().as_ref(),
+        ],
+        &gamma::ID,
+    )
+    .0;
+    let permanent_lock_address = Pubkey::find_program_address(
+        &[
+            PERMANENT_LOCK_SEED.as_bytes(),
+            pool_id.as_ref(),
+            user.pubkey().as_ref(),
+        ],
+        &gamma::ID,
+    )
+    .0;
+    let user_pool_liquidity: UserPoolLiquidity =
+        test_env.fetch_account(user_pool_liquidity_address).await;
+    let lp_tokens_owned = user_pool_liquidity.lp_tokens_owned as u64;
+    let locked_lp_tokens = lp_tokens_owned / 2;
+
+    test_env
+        .lock_liquidity_permanently(&user, pool_id, locked_lp_tokens, vec![])
+        .await
+        .unwrap();
+    let user_pool_liquidity: UserPoolLiquidity =
+        test_env.fetch_account(user_pool_liquidity_address).await;
+    assert_eq!(
+        user_pool_liquidity.lp_tokens_owned,
+        u128::from(lp_tokens_owned - locked_lp_tokens)
+    );
+    let permanent_lock: PermanentLock = test_env.fetch_account(permanent_lock_address).await;
+    assert_eq!(permanent_lock.owner, user.pubkey());
+    assert_eq!(permanent_lock.locked_lp_tokens, locked_lp_tokens);
+    let pool_state: PoolState = test_env.fetch_account(pool_id).await;
+    assert_eq_with_copy!(pool_state.permanent_lp_supply, locked_lp_tokens);
+
+    // Only the liquidity left in the user pool liquidity account can be withdrawn
+    let result = test_env
+        .withdraw_with_rewards(&user, pool_id, amm_index, lp_tokens_owned, 0, 0, vec![])
+        .await;
+    assert!(result.is_err());
+
+    // The swaps grow the liquidity of the pool with the trading fees
+    for i in 0..5 {
+        test_env
+            .swap_base_input(
+                &user,
+                pool_id,
+                amm_index,
+                100000000 + i,
+                0,
+                TradeDirection::ZeroForOne,
+            )
+            .await;
+        test_env
+            .swap_base_input(
+                &user,
+                pool_id,
+                amm_index,
+                100000000 + i,
+                0,
+                TradeDirection::OneForZero,
+            )
+            .await;
+    }
+
+    let token_0_balance_before: TokenAccount = test_env.fetch_account(user_token_0_account).await;
+    let token_1_balance_before: TokenAccount = test_env.fetch_account(user_token_1_account).await;
+    let pool_state_before: PoolState = test_env.fetch_account(pool_id).await;
+    test_env
+        .claim_permanent_lock_fees(&user, pool_id)
+        .await
+        .unwrap();
+    let token_0_balance_after: TokenAccount = test_env.fetch_account(user_token_0_account).await;
+    let token_1_balance_after: TokenAccount = test_env.fetch_account(user_token_1_account).await;
+    let fees_claimed_token_0 = token_0_balance_after.amount - token_0_balance_before.amount;
+    let fees_claimed_token_1 = token_1_balance_after.amount - token_1_balance_before.amount;
+    assert!(fees_claimed_token_0 > 0);
+    assert!(fees_claimed_token_1 > 0);
+
+    // The locked pool tokens are not reduced by the claim
+    let permanent_lock: PermanentLock = test_env.fetch_account(permanent_lock_address).await;
+    assert_eq!(permanent_lock.locked_lp_tokens, locked_lp_tokens);
+    assert_eq!(permanent_lock.fees_owed_token_0, 0);
+    assert_eq!(permanent_lock.fees_owed_token_1, 0);
+    assert_eq!(
+        permanent_lock.total_fees_claimed_token_0,
+        fees_claimed_token_0
+    );
+    assert_eq!(
+        permanent_lock.total_fees_claimed_token_1,
+        fees_claimed_token_1
+    );
+    let pool_state: PoolState = test_env.fetch_account(pool_id).await;
+    assert_eq_with_copy!(pool_state.permanent_lp_supply, locked_lp_tokens);
+    assert_eq_with_copy!(
+        permanent_lock.fee_growth_checkpoint_token_0,
+        pool_state.fee_growth_per_lp_token_0
+    );
+    assert_eq_with_copy!(
+        permanent_lock.fee_growth_checkpoint_token_1,
+        pool_state.fee_growth_per_lp_token_1
+    );
+
+    // The fees are paid from the fees set apart for the permanent locks, not from the pool reserves
+    let token_0_vault_amount_before = pool_state_before.token_0_vault_amount;
+    let token_1_vault_amount_before = pool_state_before.token_1_vault_amount;
+    assert_eq_with_copy!(pool_state.token_0_vault_amount, token_0_vault_amount_before);
+    assert_eq_with_copy!(pool_state.token_1_vault_amount, token_1_vault_amount_before);
+    let permanent_lock_fees_token_0_before = pool_state_before.permanent_lock_fees_token_0;
+    let permanent_lock_fees_token_1_before = pool_state_before.permanent_lock_fees_token_1;
+    assert_eq_with_copy!(
+        pool_state.permanent_lock_fees_token_0,
+        permanent_lock_fees_token_0_before - fees_claimed_token_0
+    );
+    assert_eq_with_copy!(
+        pool_state.permanent_lock_fees_token_1,
+        permanent_lock_fees_token_1_before - fees_claimed_token_1
+    );
+
+    // Nothing more to claim without new trading fees
+    test_env.jump_seconds(1).await;
+    test_env
+        .claim_permanent_lock_fees(&user, pool_id)
+        .await
+        .unwrap();
+    let token_0_balance_after_second_claim: TokenAccount =
+        test_env.fetch_account(user_token_0_account).await;
+    let token_1_balance_after_second_claim: TokenAccount =
+        test_env.fetch_account(user_token_1_account).await;
+    assert_eq!(
+        token_0_balance_after_second_claim.amount,
+        token_0_balance_after.amount
+    );
+    assert_eq!(
+        token_1_balance_after_second_claim.amount,
+        token_1_balance_after.amount
+    );
+    let permanent_lock_after: PermanentLock = test_env.fetch_account(permanent_lock_address).await;
+    assert_eq!(permanent_lock_after.locked_lp_tokens, locked_lp_tokens);
+
+    // The liquidity left in the user pool liquidity account can still be withdrawn
+    test_env
+        .withdraw(
+            &user,
+            pool_id,
+            amm_index,
+            lp_tokens_owned - locked_lp_tokens,
+            0,
+            0,
+        )
+        .await;
+}
@@ -0,0 +1,94 @@
+use gamma::{curve::TradeDirection, states::PoolState};
+use solana_program_test::tokio;
+use solana_sdk::{signature::Keypair, signer::Signer};
+mod utils;
+
+use utils::*;
+
+const TRADE_FEE_RATE: u64 = 500;
+const POOL_TRADE_FEE_RATE: u64 = 10_000;
+const POOL_PROTOCOL_FEE_RATE: u64 = 200_000;
+
+#[tokio::test]
+async fn pool_fee_override_should_replace_config_fees() {
+    let user = Keypair::new();
+    let admin = get_admin();
+    let amm_index = 0;
+    let mut test_env = TestEnv::new(vec![user.pubkey(), admin.pubkey()]).await;
+    test_env
+        .create_config(&admin, amm_index, TRADE_FEE_RATE, 20, 5, 0)
+        .await;
+
+    let user_token_0_account = test_env
+        .get_or_create_associated_token_account(user.pubkey(), test_env.token_0_mint, &user)
+        .await;
+    test_env
+        .mint_base_tokens(user_token_0_account, 10000000000, test_env.token_0_mint)
+        .await;
+    let user_token_1_account = test_env
+        .get_or_create_associated_token_account(user.pubkey(), test_env.token_1_mint, &user)
+        .await;
+    test_env
+        .mint_base_tokens(user_token_1_account, 10000000000, test_env.token_1_mint)
+        .await;
+
+    let pool_id = test_env
+        .initialize_pool(
+            &user,
+            amm_index,
+            1000000000,
+            1000000000,
+            0,
+            gamma::create_pool_fee_reveiver::id(),
+        )
+        .await;
+
+    test_env
+        .update_pool(&admin, pool_id, amm_index, 7, POOL_TRADE_FEE_RATE)
+        .await;
+    let pool_state: PoolState = test_env.fetch_account(pool_id).await;
+    // The protocol and fund fee rates of the config are copied when the override is set
+    assert_eq!(
+        u64::from(pool_state.trade_fee_rate_override),
+        POOL_TRADE_FEE_RATE
+    );
+    assert_eq_with_copy!(pool_state.protocol_fee_rate_override, 20);
+    assert_eq_with_copy!(pool_state.fund_fee_rate_override, 5);
+
+    test_env
+        .update_pool(&admin, pool_id, amm_index, 8, POOL_PROTOCOL_FEE_RATE)
+        .await;
+
+    test_env.jump_seconds(2).await;
+    test_env
+        .swap_base_input(
+            &user,
+            pool_id,
+            amm_index,
+            1000000,
+            0,
+            TradeDirection::ZeroForOne,
+        )
+        .await;
+    let pool_state: PoolState = test_env.fetch_account(pool_id).await;
+    assert_eq_with_copy!(pool_state.latest_dynamic_fee_rate, POOL_TRADE_FEE_RATE);
+    // 1% of the input is charged and 20% of it goes to the protocol
+    assert_eq_with_copy!(pool_state.protocol_fees_token_0, 2000);
+    assert_eq_with_copy!(pool_state.fund_fees_token_0, 0);
+
+    // Removing the override restores the fees of the config
+    test_env.update_pool(&admin, pool_id, amm_index, 7, 0).await;
+    test_env
+        .swap_base_input(
+            &user,
+            pool_id,
+            amm_index,
+            1000000,
+            0,
+            TradeDirection::ZeroForOne,
+        )
+        .await;
+    let pool_state: PoolState = test_env.fetch_account(pool_id).await;
+    assert_eq_with_copy!(pool_state.protocol_fee_rate_override, 0);
+    assert_eq_with_copy!(pool_state.latest_dynamic_fee_rate, TRADE_FEE_RATE);
+}
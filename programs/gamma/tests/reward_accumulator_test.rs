@@ -0,0 +1,455 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use gamma::{
+    states::{PoolState, RewardInfo, UserPoolLiquidity, UserRewardInfo, USER_POOL_LIQUIDITY_SEED},
+    REWARD_INFO_SEED, USER_REWARD_INFO_SEED,
+};
+use solana_program_test::tokio;
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer};
+mod utils;
+
+use utils::*;
+
+#[tokio::test]
+async fn should_settle_active_rewards_when_lp_tokens_change() {
+    let user = Keypair::new();
+    let reward_provider = Keypair::new();
+    let admin = get_admin();
+    let amm_index = 0;
+    let mut test_env = TestEnv::new(vec![
+        user.pubkey(),
+        admin.pubkey(),
+        reward_provider.pubkey(),
+    ])
+    .await;
+    test_env
+        .create_config(&admin, amm_index, 100, 20, 5, 0)
+        .await;
+
+    let user_token_0_account = test_env
+        .get_or_create_associated_token_account(user.pubkey(), test_env.token_0_mint, &user)
+        .await;
+    test_env
+        .mint_base_tokens(user_token_0_account, 10000000000, test_env.token_0_mint)
+        .await;
+    let user_token_1_account = test_env
+        .get_or_create_associated_token_account(user.pubkey(), test_env.token_1_mint, &user)
+        .await;
+    test_env
+        .mint_base_tokens(user_token_1_account, 10000000000, test_env.token_1_mint)
+        .await;
+
+    let pool_id = test_env
+        .initialize_pool(
+            &user,
+            amm_index,
+            1000000000,
+            1000000000,
+            0,
+            gamma::create_pool_fee_reveiver::id(),
+        )
+        .await;
+
+    let reward_mint = Keypair::new();
+    test_env
+        .create_token_mint(&reward_mint, &test_env.mint_authority.pubkey(), 9)
+        .await;
+    let reward_provider_token_account = test_env
+        .get_or_create_associated_token_account(
+            reward_provider.pubkey(),
+            reward_mint.pubkey(),
+            &reward_provider,
+        )
+        .await;
+    let reward_amount = 1000000000;
+    test_env
+        .mint_base_tokens(
+            reward_provider_token_account,
+            reward_amount,
+            reward_mint.pubkey(),
+        )
+        .await;
+
+    let timestamp_now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    let start_time = timestamp_now + 10;
+    let end_time = timestamp_now + 3000;
+    test_env
+        .create_rewards(
+            &reward_provider,
+            pool_id,
+            start_time,
+            end_time,
+            reward_mint.pubkey(),
+            reward_amount,
+        )
+        .await;
+    let (reward_info_key, _) = Pubkey::find_program_address(
+        &[
+            REWARD_INFO_SEED.as_bytes(),
+            pool_id.to_bytes().as_ref(),
+            &start_time.to_le_bytes(),
+            reward_mint.pubkey().to_bytes().as_ref(),
+        ],
+        &gamma::id(),
+    );
+    let pool_state: PoolState = test_env.fetch_account(pool_id).await;
+    assert_eq_with_copy!(pool_state.active_reward_infos, 1);
+
+    test_env
+        .calculate_rewards(&user, pool_id, reward_info_key)
+        .await;
+    test_env.jump_seconds(100).await;
+
+    // The active reward must be settled when the lp tokens change
+    let result = test_env
+        .deposit_with_rewards(&user, pool_id, amm_index, 1000, u64::MAX, u64::MAX, vec![])
+        .await;
+    assert!(result.is_err());
+
+    let user_pool_liquidity_before: UserPoolLiquidity = test_env
+        .fetch_account(user_pool_liquidity_key(pool_id, user.pubkey()))
+        .await;
+    let withdraw_amount = 400000000;
+    test_env
+        .withdraw_with_rewards(
+            &user,
+            pool_id,
+            amm_index,
+            withdraw_amount,
+            0,
+            0,
+            vec![reward_info_key],
+        )
+        .await
+        .unwrap();
+
+    let user_reward_info_address = user_reward_info_key(reward_info_key, user.pubkey());
+    let user_reward_info: UserRewardInfo = test_env.fetch_account(user_reward_info_address).await;
+    assert!(user_reward_info.total_rewards > 0);
+    assert_eq!(
+        u128::from(user_reward_info.lp_tokens_checkpoint),
+        user_pool_liquidity_before.lp_tokens_owned - u128::from(withdraw_amount)
+    );
+
+    // The user kept earning all the rewards with less lp tokens
+    test_env.jump_seconds(500000).await;
+    test_env
+        .calculate_rewards(&user, pool_id, reward_info_key)
+        .await;
+    let user_reward_info: UserRewardInfo = test_env.fetch_account(user_reward_info_address).await;
+    // The rewards of each settlement are rounded down
+    assert!(user_reward_info.total_rewards <= reward_amount);
+    assert!(user_reward_info.total_rewards >= reward_amount - 2);
+
+    // Nothing has to be settled after the reward ended
+    let pool_state: PoolState = test_env.fetch_account(pool_id).await;
+    assert_eq_with_copy!(pool_state.active_reward_infos, 0);
+    test_env
+        .deposit(&user, pool_id, amm_index, 1000, u64::MAX, u64::MAX)
+        .await;
+}
+
+#[tokio::test]
+async fn should_not_earn_rewards_for_lp_tokens_deposited_after_the_reward_ended() {
+    let user = Keypair::new();
+    let late_user = Keypair::new();
+    let reward_provider = Keypair::new();
+    let admin = get_admin();
+    let amm_index = 0;
+    let mut test_env = TestEnv::new(vec![
+        user.pubkey(),
+        late_user.pubkey(),
+        admin.pubkey(),
+        reward_provider.pubkey(),
+    ])
+    .await;
+    test_env
+        .create_config(&admin, amm_index, 100, 20, 5, 0)
+        .await;
+
+    for owner in [&user, &late_user] {
+        let token_0_account = test_env
+            .get_or_create_associated_token_account(owner.pubkey(), test_env.token_0_mint, owner)
+            .await;
+        test_env
+            .mint_base_tokens(token_0_account, 100000000000, test_env.token_0_mint)
+            .await;
+        let token_1_account = test_env
+            .get_or_create_associated_token_account(owner.pubkey(), test_env.token_1_mint, owner)
+            .await;
+        test_env
+            .mint_base_tokens(token_1_account, 100000000000, test_env.token_1_mint)
+            .await;
+    }
+
+    let pool_id = test_env
+        .initialize_pool(
+            &user,
+            amm_index,
+            1000000000,
+            1000000000,
+            0,
+            gamma::create_pool_fee_reveiver::id(),
+        )
+        .await;
+
+    let reward_mint = Keypair::new();
+    test_env
+        .create_token_mint(&reward_mint, &test_env.mint_authority.pubkey(), 9)
+        .await;
+    let reward_provider_token_account = test_env
+        .get_or_create_associated_token_account(
+            reward_provider.pubkey(),
+            reward_mint.pubkey(),
+            &reward_provider,
+        )
+        .await;
+    let reward_amount = 1000000000;
+    test_env
+        .mint_base_tokens(
+            reward_provider_token_account,
+            reward_amount,
+            reward_mint.pubkey(),
+        )
+        .await;
+
+    let timestamp_now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    let start_time = timestamp_now + 10;
+    let end_time = timestamp_now + 3000;
+    test_env
+        .create_rewards(
+            &reward_provider,
+            pool_id,
+            start_time,
+            end_time,
+            reward_mint.pubkey(),
+            reward_amount,
+        )
+        .await;
+    let (reward_info_key, _) = Pubkey::find_program_address(
+        &[
+            REWARD_INFO_SEED.as_bytes(),
+            pool_id.to_bytes().as_ref(),
+            &start_time.to_le_bytes(),
+            reward_mint.pubkey().to_bytes().as_ref(),
+        ],
+        &gamma::id(),
+    );
+
+    // The reward ends and is no longer settled when the lp tokens change
+    test_env.jump_seconds(500000).await;
+    test_env
+        .calculate_rewards(&user, pool_id, reward_info_key)
+        .await;
+    let pool_state: PoolState = test_env.fetch_account(pool_id).await;
+    assert_eq_with_copy!(pool_state.active_reward_infos, 0);
+
+    test_env.init_user_pool_liquidity(&late_user, pool_id).await;
+    test_env
+        .deposit(
+            &late_user,
+            pool_id,
+            amm_index,
+            50000000000,
+            u64::MAX,
+            u64::MAX,
+        )
+        .await;
+    test_env
+        .calculate_rewards(&late_user, pool_id, reward_info_key)
+        .await;
+
+    let user_reward_info: UserRewardInfo = test_env
+        .fetch_account(user_reward_info_key(reward_info_key, late_user.pubkey()))
+        .await;
+    assert_eq!(user_reward_info.total_rewards, 0);
+    assert_eq!(user_reward_info.get_total_claimable_rewards(), 0);
+}
+
+#[tokio::test]
+async fn reward_infos_created_before_the_accumulator_should_be_migrated() {
+    let user = Keypair::new();
+    let reward_provider = Keypair::new();
+    let admin = get_admin();
+    let amm_index = 0;
+    let mut test_env = TestEnv::new(vec![
+        user.pubkey(),
+        admin.pubkey(),
+        reward_provider.pubkey(),
+    ])
+    .await;
+    test_env
+        .create_config(&admin, amm_index, 100, 20, 5, 0)
+        .await;
+
+    let user_token_0_account = test_env
+        .get_or_create_associated_token_account(user.pubkey(), test_env.token_0_mint, &user)
+        .await;
+    test_env
+        .mint_base_tokens(user_token_0_account, 10000000000, test_env.token_0_mint)
+        .await;
+    let user_token_1_account = test_env
+        .get_or_create_associated_token_account(user.pubkey(), test_env.token_1_mint, &user)
+        .await;
+    test_env
+        .mint_base_tokens(user_token_1_account, 10000000000, test_env.token_1_mint)
+        .await;
+
+    let pool_id = test_env
+        .initialize_pool(
+            &user,
+            amm_index,
+            1000000000,
+            1000000000,
+            0,
+            gamma::create_pool_fee_reveiver::id(),
+        )
+        .await;
+
+    let reward_mint = Keypair::new();
+    test_env
+        .create_token_mint(&reward_mint, &test_env.mint_authority.pubkey(), 9)
+        .await;
+    let reward_provider_token_account = test_env
+        .get_or_create_associated_token_account(
+            reward_provider.pubkey(),
+            reward_mint.pubkey(),
+            &reward_provider,
+        )
+        .await;
+    let reward_amount = 1000000000;
+    test_env
+        .mint_base_tokens(
+            reward_provider_token_account,
+            reward_amount,
+            reward_mint.pubkey(),
+        )
+        .await;
+
+    let timestamp_now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    let start_time = timestamp_now + 10;
+    let end_time = timestamp_now + 3000;
+    test_env
+        .create_rewards(
+            &reward_provider,
+            pool_id,
+            start_time,
+            end_time,
+            reward_mint.pubkey(),
+            reward_amount,
+        )
+        .await;
+    let (reward_info_key, _) = Pubkey::find_program_address(
+        &[
+            REWARD_INFO_SEED.as_bytes(),
+            pool_id.to_bytes().as_ref(),
+            &start_time.to_le_bytes(),
+            reward_mint.pubkey().to_bytes().as_ref(),
+        ],
+        &gamma::id(),
+    );
+    test_env.jump_seconds(1000).await;
+    test_env
+        .calculate_rewards(&user, pool_id, reward_info_key)
+        .await;
+    let user_reward_info_address = user_reward_info_key(reward_info_key, user.pubkey());
+    let rewards_before: UserRewardInfo = test_env.fetch_account(user_reward_info_address).await;
+    assert!(rewards_before.total_rewards > 0);
+
+    // Reward infos of the size before the accumulator fields were added
+    for (address, legacy_len) in [
+        (reward_info_key, RewardInfo::LEGACY_LEN),
+        (user_reward_info_address, UserRewardInfo::LEGACY_LEN),
+    ] {
+        let mut account = test_env.get_account_info(address).await.unwrap().unwrap();
+        account.data.truncate(legacy_len);
+        test_env
+            .program_test_context
+            .set_account(&address, &account.into());
+    }
+
+    // The user reward info can only be migrated once its reward info is migrated
+    let result = test_env
+        .migrate_user_reward_info(&reward_provider, pool_id, reward_info_key, user.pubkey())
+        .await;
+    assert!(result.is_err());
+
+    test_env
+        .migrate_reward_info(&reward_provider, pool_id, reward_info_key)
+        .await
+        .unwrap();
+    test_env
+        .migrate_user_reward_info(&reward_provider, pool_id, reward_info_key, user.pubkey())
+        .await
+        .unwrap();
+    for (address, len) in [
+        (reward_info_key, RewardInfo::LEN),
+        (user_reward_info_address, UserRewardInfo::LEN),
+    ] {
+        let account = test_env.get_account_info(address).await.unwrap().unwrap();
+        assert_eq!(account.data.len(), len);
+    }
+    let pool_state: PoolState = test_env.fetch_account(pool_id).await;
+    assert_eq_with_copy!(pool_state.active_reward_infos, 1);
+
+    // The rewards calculated before the migration are kept and the user earns the whole reward
+    test_env
+        .calculate_rewards(&user, pool_id, reward_info_key)
+        .await;
+    let user_reward_info: UserRewardInfo = test_env.fetch_account(user_reward_info_address).await;
+    assert!(
+        user_reward_info
+            .total_rewards
+            .abs_diff(rewards_before.total_rewards)
+            <= 1
+    );
+    test_env.jump_seconds(5000).await;
+    test_env
+        .calculate_rewards(&user, pool_id, reward_info_key)
+        .await;
+    let user_reward_info: UserRewardInfo = test_env.fetch_account(user_reward_info_address).await;
+    assert!(reward_amount - user_reward_info.total_rewards <= 2);
+
+    // Migrating accounts of the current size does nothing
+    test_env
+        .migrate_reward_info(&reward_provider, pool_id, reward_info_key)
+        .await
+        .unwrap();
+    test_env
+        .migrate_user_reward_info(&reward_provider, pool_id, reward_info_key, user.pubkey())
+        .await
+        .unwrap();
+}
+
+fn user_pool_liquidity_key(pool_id: Pubkey, user: Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            USER_POOL_LIQUIDITY_SEED.as_bytes(),
+            pool_id.as_ref(),
+            user.as_ref(),
+        ],
+        &gamma::ID,
+    )
+    .0
+}
+
+fn user_reward_info_key(reward_info: Pubkey, user: Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            USER_REWARD_INFO_SEED.as_bytes(),
+            reward_info.as_ref(),
+            user.as_ref(),
+        ],
+        &gamma::ID,
+    )
+    .0
+}
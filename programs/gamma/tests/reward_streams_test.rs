@@ -0,0 +1,358 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anchor_spl::token::TokenAccount;
+use gamma::{
+    states::{
+        PoolState, RewardInfo, UserPoolLiquidity, UserRewardInfo, MAX_REWARD_DURATION,
+        USER_POOL_LIQUIDITY_SEED,
+    },
+    REWARD_INFO_SEED, USER_REWARD_INFO_SEED,
+};
+use solana_program_test::tokio;
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer};
+mod utils;
+
+use utils::*;
+
+const REWARD_AMOUNT: u64 = 1000000000;
+
+struct RewardSetup {
+    test_env: TestEnv,
+    user: Keypair,
+    reward_provider: Keypair,
+    pool_id: Pubkey,
+    reward_mint: Pubkey,
+    reward_info_key: Pubkey,
+    end_time: u64,
+}
+
+/// Creates a pool and a reward of `REWARD_AMOUNT`, the provider holds another `REWARD_AMOUNT` to top up.
+/// The user holds all the lp tokens of the pool unless `withdraw_all` is set.
+async fn setup(withdraw_all: bool) -> RewardSetup {
+    let user = Keypair::new();
+    let reward_provider = Keypair::new();
+    let admin = get_admin();
+    let amm_index = 0;
+    let mut test_env = TestEnv::new(vec![
+        user.pubkey(),
+        admin.pubkey(),
+        reward_provider.pubkey(),
+    ])
+    .await;
+    test_env
+        .create_config(&admin, amm_index, 100, 20, 5, 0)
+        .await;
+
+    let user_token_0_account = test_env
+        .get_or_create_associated_token_account(user.pubkey(), test_env.token_0_mint, &user)
+        .await;
+    test_env
+        .mint_base_tokens(user_token_0_account, 10000000000, test_env.token_0_mint)
+        .await;
+    let user_token_1_account = test_env
+        .get_or_create_associated_token_account(user.pubkey(), test_env.token_1_mint, &user)
+        .await;
+    test_env
+        .mint_base_tokens(user_token_1_account, 10000000000, test_env.token_1_mint)
+        .await;
+
+    let pool_id = test_env
+        .initialize_pool(
+            &user,
+            amm_index,
+            1000000000,
+            1000000000,
+            0,
+            gamma::create_pool_fee_reveiver::id(),
+        )
+        .await;
+
+    if withdraw_all {
+        let user_pool_liquidity: UserPoolLiquidity = test_env
+            .fetch_account(
+                Pubkey::find_program_address(
+                    &[
+                        USER_POOL_LIQUIDITY_SEED.as_bytes(),
+                        pool_id.as_ref(),
+                        user.pubkey().as_ref(),
+                    ],
+                    &gamma::ID,
+                )
+                .0,
+            )
+            .await;
+        test_env
+            .withdraw(
+                &user,
+                pool_id,
+                amm_index,
+                user_pool_liquidity.lp_tokens_owned as u64,
+                0,
+                0,
+            )
+            .await;
+    }
+
+    let reward_mint = Keypair::new();
+    test_env
+        .create_token_mint(&reward_mint, &test_env.mint_authority.pubkey(), 9)
+        .await;
+    let reward_provider_token_account = test_env
+        .get_or_create_associated_token_account(
+            reward_provider.pubkey(),
+            reward_mint.pubkey(),
+            &reward_provider,
+        )
+        .await;
+    test_env
+        .mint_base_tokens(
+            reward_provider_token_account,
+            2 * REWARD_AMOUNT,
+            reward_mint.pubkey(),
+        )
+        .await;
+
+    let timestamp_now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    let start_time = timestamp_now + 10;
+    let end_time = timestamp_now + 3000;
+    test_env
+        .create_rewards(
+            &reward_provider,
+            pool_id,
+            start_time,
+            end_time,
+            reward_mint.pubkey(),
+            REWARD_AMOUNT,
+        )
+        .await;
+    let (reward_info_key, _) = Pubkey::find_program_address(
+        &[
+            REWARD_INFO_SEED.as_bytes(),
+            pool_id.to_bytes().as_ref(),
+            &start_time.to_le_bytes(),
+            reward_mint.pubkey().to_bytes().as_ref(),
+        ],
+        &gamma::id(),
+    );
+
+    RewardSetup {
+        test_env,
+        user,
+        reward_provider,
+        pool_id,
+        reward_mint: reward_mint.pubkey(),
+        reward_info_key,
+        end_time,
+    }
+}
+
+#[tokio::test]
+async fn should_top_up_and_extend_rewards() {
+    let RewardSetup {
+        mut test_env,
+        user,
+        reward_provider,
+        pool_id,
+        reward_mint,
+        reward_info_key,
+        end_time,
+    } = setup(false).await;
+
+    // Active reward infos are indexed in the pool
+    let pool_state: PoolState = test_env.fetch_account(pool_id).await;
+    assert_eq_with_copy!(pool_state.active_reward_infos, 1);
+    assert_eq_with_copy!(pool_state.active_reward_info_keys[0], reward_info_key);
+
+    test_env
+        .calculate_rewards(&user, pool_id, reward_info_key)
+        .await;
+    test_env.jump_seconds(100).await;
+
+    // Only the reward provider can top up or extend the rewards
+    let result = test_env
+        .extend_rewards(&user, pool_id, reward_info_key, end_time + 3000)
+        .await;
+    assert!(result.is_err());
+    let result = test_env
+        .extend_rewards(&reward_provider, pool_id, reward_info_key, end_time)
+        .await;
+    assert!(result.is_err());
+
+    test_env
+        .top_up_rewards(
+            &reward_provider,
+            pool_id,
+            reward_info_key,
+            reward_mint,
+            REWARD_AMOUNT,
+        )
+        .await
+        .unwrap();
+    test_env
+        .extend_rewards(&reward_provider, pool_id, reward_info_key, end_time + 3000)
+        .await
+        .unwrap();
+    let reward_info: RewardInfo = test_env.fetch_account(reward_info_key).await;
+    assert_eq!(reward_info.total_to_disburse, 2 * REWARD_AMOUNT);
+    assert_eq!(reward_info.end_rewards_at, end_time + 3000);
+
+    // The user holding all the lp tokens earns all the rewards
+    test_env.jump_seconds(500000).await;
+    test_env
+        .calculate_rewards(&user, pool_id, reward_info_key)
+        .await;
+    let (user_reward_info_key, _) = Pubkey::find_program_address(
+        &[
+            USER_REWARD_INFO_SEED.as_bytes(),
+            reward_info_key.to_bytes().as_ref(),
+            user.pubkey().to_bytes().as_ref(),
+        ],
+        &gamma::id(),
+    );
+    let user_reward_info: UserRewardInfo = test_env.fetch_account(user_reward_info_key).await;
+    // Less the rounding down of the rewards
+    assert_eq!(user_reward_info.total_rewards, 2 * REWARD_AMOUNT - 1);
+
+    let pool_state: PoolState = test_env.fetch_account(pool_id).await;
+    assert_eq_with_copy!(pool_state.active_reward_infos, 0);
+    assert_eq_with_copy!(pool_state.active_reward_info_keys[0], Pubkey::default());
+
+    // An ended reward can not be topped up or extended
+    let result = test_env
+        .extend_rewards(
+            &reward_provider,
+            pool_id,
+            reward_info_key,
+            end_time + 1000000,
+        )
+        .await;
+    assert!(result.is_err());
+    let result = test_env
+        .top_up_rewards(&reward_provider, pool_id, reward_info_key, reward_mint, 1)
+        .await;
+    assert!(result.is_err());
+}
+
+#[tokio::test]
+async fn should_refund_unallocated_rewards_after_the_end() {
+    let RewardSetup {
+        mut test_env,
+        user,
+        reward_provider,
+        pool_id,
+        reward_mint,
+        reward_info_key,
+        ..
+    } = setup(true).await;
+
+    let result = test_env
+        .refund_unallocated_rewards(&reward_provider, pool_id, reward_info_key, reward_mint)
+        .await;
+    assert!(result.is_err());
+
+    test_env.jump_seconds(500000).await;
+    let result = test_env
+        .refund_unallocated_rewards(&user, pool_id, reward_info_key, reward_mint)
+        .await;
+    assert!(result.is_err());
+
+    // No lp tokens were eligible for the rewards during the whole reward
+    let reward_provider_token_account = test_env
+        .get_or_create_associated_token_account(
+            reward_provider.pubkey(),
+            reward_mint,
+            &reward_provider,
+        )
+        .await;
+    let balance_before: TokenAccount = test_env.fetch_account(reward_provider_token_account).await;
+    test_env
+        .refund_unallocated_rewards(&reward_provider, pool_id, reward_info_key, reward_mint)
+        .await
+        .unwrap();
+    let balance_after: TokenAccount = test_env.fetch_account(reward_provider_token_account).await;
+    assert_eq!(balance_after.amount - balance_before.amount, REWARD_AMOUNT);
+
+    let reward_info: RewardInfo = test_env.fetch_account(reward_info_key).await;
+    assert_eq!(reward_info.unallocated_rewards, 0);
+    let pool_state: PoolState = test_env.fetch_account(pool_id).await;
+    assert_eq_with_copy!(pool_state.active_reward_infos, 0);
+
+    // The vault is empty so the reward can be closed
+    test_env
+        .close_reward_info(&reward_provider, pool_id, reward_info_key, reward_mint)
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn should_let_the_admin_evict_a_reward() {
+    let RewardSetup {
+        mut test_env,
+        user,
+        reward_provider,
+        pool_id,
+        reward_mint,
+        reward_info_key,
+        end_time,
+    } = setup(false).await;
+
+    // A reward can not be extended beyond the max duration
+    let result = test_env
+        .extend_rewards(
+            &reward_provider,
+            pool_id,
+            reward_info_key,
+            end_time + MAX_REWARD_DURATION,
+        )
+        .await;
+    assert!(result.is_err());
+
+    test_env
+        .calculate_rewards(&user, pool_id, reward_info_key)
+        .await;
+    test_env.jump_seconds(1000).await;
+
+    // Only the admin can evict a reward
+    let result = test_env
+        .evict_reward_info(&reward_provider, pool_id, reward_info_key)
+        .await;
+    assert!(result.is_err());
+    test_env
+        .evict_reward_info(&get_admin(), pool_id, reward_info_key)
+        .await
+        .unwrap();
+
+    let pool_state: PoolState = test_env.fetch_account(pool_id).await;
+    assert_eq_with_copy!(pool_state.active_reward_infos, 0);
+    assert_eq_with_copy!(pool_state.active_reward_info_keys[0], Pubkey::default());
+    let reward_info: RewardInfo = test_env.fetch_account(reward_info_key).await;
+    assert!(reward_info.end_rewards_at < end_time);
+    assert_eq!(reward_info.total_released, REWARD_AMOUNT);
+    assert!(reward_info.unallocated_rewards > 0);
+
+    // The rewards released until the eviction stay with the user, the others are refunded
+    test_env
+        .calculate_rewards(&user, pool_id, reward_info_key)
+        .await;
+    let (user_reward_info_key, _) = Pubkey::find_program_address(
+        &[
+            USER_REWARD_INFO_SEED.as_bytes(),
+            reward_info_key.to_bytes().as_ref(),
+            user.pubkey().to_bytes().as_ref(),
+        ],
+        &gamma::id(),
+    );
+    let user_reward_info: UserRewardInfo = test_env.fetch_account(user_reward_info_key).await;
+    assert!(user_reward_info.total_rewards > 0);
+    test_env
+        .refund_unallocated_rewards(&reward_provider, pool_id, reward_info_key, reward_mint)
+        .await
+        .unwrap();
+    // Less the rounding down of the rewards
+    assert!(
+        REWARD_AMOUNT - (user_reward_info.total_rewards + reward_info.unallocated_rewards) <= 1
+    );
+}
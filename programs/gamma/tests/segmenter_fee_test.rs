@@ -0,0 +1,202 @@
+use gamma::{
+    curve::TradeDirection,
+    external::dflow_segmenter::Registry,
+    fees::{FeeType, ONE_BASIS_POINT},
+    states::PoolState,
+};
+use solana_program_test::tokio;
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer};
+mod utils;
+
+use utils::*;
+
+const TRADE_FEE_RATE: u64 = 100;
+const LAUNCH_FEE_RATE: u64 = 500_000;
+const LAUNCH_FEE_DURATION: u64 = 1_000_000_000;
+
+async fn setup(
+    fee_type: FeeType,
+    launch_fee_rate: u64,
+    launch_fee_duration: u64,
+) -> (TestEnv, Keypair, Pubkey) {
+    let user = Keypair::new();
+    let admin = get_admin();
+    let mut test_env = TestEnv::new(vec![user.pubkey(), admin.pubkey()]).await;
+    test_env
+        .create_config(&admin, 0, TRADE_FEE_RATE, 20, 5, 0)
+        .await;
+
+    let user_token_0_account = test_env
+        .get_or_create_associated_token_account(user.pubkey(), test_env.token_0_mint, &user)
+        .await;
+    test_env
+        .mint_base_tokens(user_token_0_account, 10000000000, test_env.token_0_mint)
+        .await;
+    let user_token_1_account = test_env
+        .get_or_create_associated_token_account(user.pubkey(), test_env.token_1_mint, &user)
+        .await;
+    test_env
+        .mint_base_tokens(user_token_1_account, 10000000000, test_env.token_1_mint)
+        .await;
+
+    let pool_id = test_env
+        .initialize_pool_with_fee_type(
+            &user,
+            0,
+            1000000000,
+            1000000000,
+            0,
+            gamma::create_pool_fee_reveiver::id(),
+            0,
+            0,
+            fee_type as u8,
+            launch_fee_rate,
+            launch_fee_duration,
+        )
+        .await;
+    (test_env, user, pool_id)
+}
+
+async fn latest_fee_rate(test_env: &mut TestEnv, pool_id: Pubkey) -> u64 {
+    let pool_state: PoolState = test_env.fetch_account(pool_id).await;
+    pool_state.latest_dynamic_fee_rate
+}
+
+#[tokio::test]
+async fn unsigned_flow_should_pay_surcharge() {
+    let (mut test_env, user, pool_id) = setup(FeeType::Volatility, 0, 0).await;
+    let admin = get_admin();
+    test_env.update_amm_config(&admin, 0, 10, 50).await;
+    test_env.update_amm_config(&admin, 0, 11, 300).await;
+
+    test_env.jump_seconds(2).await;
+    test_env
+        .swap_base_input(&user, pool_id, 0, 1000000, 0, TradeDirection::ZeroForOne)
+        .await;
+    assert_eq!(latest_fee_rate(&mut test_env, pool_id).await, 400);
+
+    // The discount can not take the fee below the trade fee
+    let registry = test_env.create_segmenter_registry(Registry::PROGRAM_ID, vec![user.pubkey()]);
+    test_env
+        .swap_base_input_with_segmenter(
+            &user,
+            pool_id,
+            0,
+            1000000,
+            0,
+            TradeDirection::OneForZero,
+            registry,
+        )
+        .await;
+    assert_eq!(
+        latest_fee_rate(&mut test_env, pool_id).await,
+        TRADE_FEE_RATE
+    );
+}
+
+#[tokio::test]
+async fn signed_segmenter_should_get_discount_from_configured_registry_program() {
+    let (mut test_env, user, pool_id) = setup(
+        FeeType::LaunchLinearDecay,
+        LAUNCH_FEE_RATE,
+        LAUNCH_FEE_DURATION,
+    )
+    .await;
+    let admin = get_admin();
+    test_env.update_amm_config(&admin, 0, 10, 1000).await;
+
+    // The launch fee barely decays over the long duration, one second in it is 1 below the launch fee
+    test_env.jump_seconds(2).await;
+    test_env
+        .swap_base_input(&user, pool_id, 0, 1000000, 0, TradeDirection::ZeroForOne)
+        .await;
+    assert_eq!(
+        latest_fee_rate(&mut test_env, pool_id).await,
+        LAUNCH_FEE_RATE - 1
+    );
+
+    // Registries of other programs are not trusted until the program is set in the config
+    let registry_program = Pubkey::new_unique();
+    let registry = test_env.create_segmenter_registry(registry_program, vec![user.pubkey()]);
+    test_env
+        .swap_base_input_with_segmenter(
+            &user,
+            pool_id,
+            0,
+            1000000,
+            0,
+            TradeDirection::OneForZero,
+            registry,
+        )
+        .await;
+    assert_eq!(
+        latest_fee_rate(&mut test_env, pool_id).await,
+        LAUNCH_FEE_RATE - 1
+    );
+
+    test_env
+        .update_amm_config_with_account(&admin, 0, 12, registry_program)
+        .await;
+    test_env
+        .swap_base_input_with_segmenter(
+            &user,
+            pool_id,
+            0,
+            1000000,
+            0,
+            TradeDirection::ZeroForOne,
+            registry,
+        )
+        .await;
+    assert_eq!(
+        latest_fee_rate(&mut test_env, pool_id).await,
+        LAUNCH_FEE_RATE - 1 - 1000
+    );
+}
+
+#[tokio::test]
+async fn signed_segmenter_should_get_one_basis_point_discount_by_default() {
+    let (mut test_env, user, pool_id) = setup(
+        FeeType::LaunchLinearDecay,
+        LAUNCH_FEE_RATE,
+        LAUNCH_FEE_DURATION,
+    )
+    .await;
+    let admin = get_admin();
+
+    test_env.jump_seconds(2).await;
+    let registry = test_env.create_segmenter_registry(Registry::PROGRAM_ID, vec![user.pubkey()]);
+    test_env
+        .swap_base_input_with_segmenter(
+            &user,
+            pool_id,
+            0,
+            1000000,
+            0,
+            TradeDirection::ZeroForOne,
+            registry,
+        )
+        .await;
+    assert_eq!(
+        latest_fee_rate(&mut test_env, pool_id).await,
+        LAUNCH_FEE_RATE - 1 - ONE_BASIS_POINT
+    );
+
+    // Setting the discount to zero disables it
+    test_env.update_amm_config(&admin, 0, 10, 0).await;
+    test_env
+        .swap_base_input_with_segmenter(
+            &user,
+            pool_id,
+            0,
+            1000000,
+            0,
+            TradeDirection::OneForZero,
+            registry,
+        )
+        .await;
+    assert_eq!(
+        latest_fee_rate(&mut test_env, pool_id).await,
+        LAUNCH_FEE_RATE - 1
+    );
+}
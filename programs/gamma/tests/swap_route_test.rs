@@ -0,0 +1,134 @@
+use anchor_spl::token::TokenAccount;
+use gamma::{
+    curve::TradeDirection,
+    states::{PoolState, POOL_VAULT_SEED},
+};
+use solana_program_test::tokio;
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer};
+mod utils;
+
+use utils::*;
+
+#[tokio::test]
+async fn should_swap_through_route_of_pools() {
+    let user = Keypair::new();
+    let admin = get_admin();
+    let mut test_env = TestEnv::new(vec![user.pubkey(), admin.pubkey()]).await;
+    test_env.create_config(&admin, 0, 100, 20, 5, 0).await;
+    test_env.create_config(&admin, 1, 100, 20, 5, 0).await;
+
+    let user_token_0_account = test_env
+        .get_or_create_associated_token_account(user.pubkey(), test_env.token_0_mint, &user)
+        .await;
+    test_env
+        .mint_base_tokens(user_token_0_account, 10000000000, test_env.token_0_mint)
+        .await;
+    let user_token_1_account = test_env
+        .get_or_create_associated_token_account(user.pubkey(), test_env.token_1_mint, &user)
+        .await;
+    test_env
+        .mint_base_tokens(user_token_1_account, 10000000000, test_env.token_1_mint)
+        .await;
+
+    // token_1 is twice as expensive in the second pool
+    let first_pool_id = test_env
+        .initialize_pool(
+            &user,
+            0,
+            1000000000,
+            1000000000,
+            0,
+            gamma::create_pool_fee_reveiver::id(),
+        )
+        .await;
+    let second_pool_id = test_env
+        .initialize_pool(
+            &user,
+            1,
+            2000000000,
+            1000000000,
+            0,
+            gamma::create_pool_fee_reveiver::id(),
+        )
+        .await;
+
+    let token_1_vault = |pool_id: Pubkey| {
+        Pubkey::find_program_address(
+            &[
+                POOL_VAULT_SEED.as_bytes(),
+                pool_id.as_ref(),
+                test_env.token_1_mint.as_ref(),
+            ],
+            &gamma::ID,
+        )
+        .0
+    };
+    let first_pool_token_1_vault = token_1_vault(first_pool_id);
+    let second_pool_token_1_vault = token_1_vault(second_pool_id);
+
+    let route = vec![
+        (first_pool_id, 0, TradeDirection::ZeroForOne),
+        (second_pool_id, 1, TradeDirection::OneForZero),
+    ];
+    let amount_in = 10000000;
+
+    // A pool can not be used twice in the same route
+    let result = test_env
+        .swap_route(
+            &user,
+            vec![
+                (first_pool_id, 0, TradeDirection::ZeroForOne),
+                (first_pool_id, 0, TradeDirection::OneForZero),
+            ],
+            amount_in,
+            0,
+        )
+        .await;
+    assert!(result.is_err());
+
+    // The minimum amount out is checked against the output of the last hop
+    let result = test_env
+        .swap_route(&user, route.clone(), amount_in, 2 * amount_in)
+        .await;
+    assert!(result.is_err());
+
+    let user_token_0_before: TokenAccount = test_env.fetch_account(user_token_0_account).await;
+    let user_token_1_before: TokenAccount = test_env.fetch_account(user_token_1_account).await;
+    let first_pool_token_1_vault_before: TokenAccount =
+        test_env.fetch_account(first_pool_token_1_vault).await;
+    let second_pool_token_1_vault_before: TokenAccount =
+        test_env.fetch_account(second_pool_token_1_vault).await;
+    let second_pool_state_before: PoolState = test_env.fetch_account(second_pool_id).await;
+
+    // Buying token_1 in the first pool and selling it in the second one returns about twice the input
+    let minimum_amount_out = 19000000;
+    test_env
+        .swap_route(&user, route, amount_in, minimum_amount_out)
+        .await
+        .unwrap();
+
+    let user_token_0_after: TokenAccount = test_env.fetch_account(user_token_0_account).await;
+    let user_token_1_after: TokenAccount = test_env.fetch_account(user_token_1_account).await;
+    assert!(
+        user_token_0_after.amount - (user_token_0_before.amount - amount_in) >= minimum_amount_out
+    );
+    // The intermediate token never goes through the user
+    assert_eq!(user_token_1_after.amount, user_token_1_before.amount);
+
+    let first_pool_token_1_vault_after: TokenAccount =
+        test_env.fetch_account(first_pool_token_1_vault).await;
+    let second_pool_token_1_vault_after: TokenAccount =
+        test_env.fetch_account(second_pool_token_1_vault).await;
+    let intermediate_amount =
+        first_pool_token_1_vault_before.amount - first_pool_token_1_vault_after.amount;
+    assert!(intermediate_amount > 0);
+    assert_eq!(
+        second_pool_token_1_vault_after.amount - second_pool_token_1_vault_before.amount,
+        intermediate_amount
+    );
+
+    let second_pool_state_after: PoolState = test_env.fetch_account(second_pool_id).await;
+    let token_1_vault_amount_before = second_pool_state_before.token_1_vault_amount;
+    let token_1_vault_amount_after = second_pool_state_after.token_1_vault_amount;
+    assert!(token_1_vault_amount_after > token_1_vault_amount_before);
+}
@@ -1,6 +1,6 @@
 use gamma::{
     curve::TradeDirection,
-    states::{ObservationState, PoolState},
+    states::{ObservationState, PoolState, Q32},
 };
 use solana_program_test::tokio;
 use solana_sdk::{signature::Keypair, signer::Signer};
@@ -718,3 +718,133 @@ async fn swap_change_with_swaps() {
     //     )
     //     .await;
 }
+
+#[tokio::test]
+async fn stable_swap_pool_test() {
+    let user = Keypair::new();
+    let admin = get_admin();
+    let amm_index = 0;
+    let mut test_env = TestEnv::new(vec![user.pubkey(), admin.pubkey()]).await;
+
+    test_env
+        .create_config(&admin, amm_index, 100, 20, 5, 0)
+        .await;
+
+    let user_token_0_account = test_env
+        .get_or_create_associated_token_account(user.pubkey(), test_env.token_0_mint, &user)
+        .await;
+    test_env
+        .mint_base_tokens(user_token_0_account, 10000000000000, test_env.token_0_mint)
+        .await;
+
+    let user_token_1_account = test_env
+        .get_or_create_associated_token_account(user.pubkey(), test_env.token_1_mint, &user)
+        .await;
+    test_env
+        .mint_base_tokens(user_token_1_account, 10000000000000, test_env.token_1_mint)
+        .await;
+
+    let pool_id = test_env
+        .initialize_pool_with_curve(
+            &user,
+            amm_index,
+            1000000000000,
+            1000000000000,
+            0,
+            gamma::create_pool_fee_reveiver::id(),
+            1,
+            100,
+        )
+        .await;
+    test_env.jump_seconds(100).await;
+
+    let pool_state_before: PoolState = test_env.fetch_account(pool_id).await;
+    // Initial lp tokens are the StableSwap invariant, equal to the sum of balanced reserves
+    assert_eq_with_copy!(pool_state_before.lp_supply, 2000000000000);
+
+    // Trade of 10% of the reserves, constant product would give back ~90.9% of the input
+    let amount_in = 100000000000;
+    test_env
+        .swap_base_input(
+            &user,
+            pool_id,
+            amm_index,
+            amount_in,
+            0,
+            TradeDirection::ZeroForOne,
+        )
+        .await;
+
+    let pool_state_after: PoolState = test_env.fetch_account(pool_id).await;
+    let amount_out = pool_state_before.token_1_vault_amount - pool_state_after.token_1_vault_amount;
+    assert!(amount_out > amount_in * 99 / 100);
+    assert!(amount_out < amount_in);
+
+    // The pool is priced at its marginal price, close to 1:1 while the reserve ratio is ~0.82
+    let (token_0_price_x32, _) = pool_state_after.token_price_x32().unwrap();
+    assert!(token_0_price_x32 > Q32 * 99 / 100);
+    assert!(token_0_price_x32 < Q32);
+}
+
+#[tokio::test]
+async fn weighted_pool_test() {
+    let user = Keypair::new();
+    let admin = get_admin();
+    let amm_index = 0;
+    let mut test_env = TestEnv::new(vec![user.pubkey(), admin.pubkey()]).await;
+
+    test_env
+        .create_config(&admin, amm_index, 100, 20, 5, 0)
+        .await;
+
+    let user_token_0_account = test_env
+        .get_or_create_associated_token_account(user.pubkey(), test_env.token_0_mint, &user)
+        .await;
+    test_env
+        .mint_base_tokens(user_token_0_account, 10000000000000, test_env.token_0_mint)
+        .await;
+
+    let user_token_1_account = test_env
+        .get_or_create_associated_token_account(user.pubkey(), test_env.token_1_mint, &user)
+        .await;
+    test_env
+        .mint_base_tokens(user_token_1_account, 10000000000000, test_env.token_1_mint)
+        .await;
+
+    // 80/20 pool, with 4 times more token_0 the price is 1 token_1 for 1 token_0
+    let pool_id = test_env
+        .initialize_pool_with_curve(
+            &user,
+            amm_index,
+            4000000000000,
+            1000000000000,
+            0,
+            gamma::create_pool_fee_reveiver::id(),
+            2,
+            800000,
+        )
+        .await;
+    test_env.jump_seconds(100).await;
+
+    let pool_state_before: PoolState = test_env.fetch_account(pool_id).await;
+    // Initial lp tokens are the weighted geometric mean, 4e12^0.8 * 1e12^0.2
+    assert_eq_with_copy!(pool_state_before.lp_supply, 3031433133020);
+
+    // Constant product would give back ~25% of the input at this balance
+    let amount_in = 1000000000;
+    test_env
+        .swap_base_input(
+            &user,
+            pool_id,
+            amm_index,
+            amount_in,
+            0,
+            TradeDirection::ZeroForOne,
+        )
+        .await;
+
+    let pool_state_after: PoolState = test_env.fetch_account(pool_id).await;
+    let amount_out = pool_state_before.token_1_vault_amount - pool_state_after.token_1_vault_amount;
+    assert!(amount_out > amount_in * 99 / 100);
+    assert!(amount_out < amount_in);
+}
@@ -0,0 +1,188 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use gamma::{
+    states::{UserPoolLiquidity, UserRewardInfo, USER_POOL_LIQUIDITY_SEED},
+    REWARD_INFO_SEED, USER_REWARD_INFO_SEED,
+};
+use solana_program_test::tokio;
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer};
+mod utils;
+
+use utils::*;
+
+#[tokio::test]
+async fn should_transfer_liquidity_and_settle_rewards() {
+    let user = Keypair::new();
+    let receiver = Keypair::new();
+    let reward_provider = Keypair::new();
+    let admin = get_admin();
+    let amm_index = 0;
+    let mut test_env = TestEnv::new(vec![
+        user.pubkey(),
+        receiver.pubkey(),
+        admin.pubkey(),
+        reward_provider.pubkey(),
+    ])
+    .await;
+    test_env
+        .create_config(&admin, amm_index, 100, 20, 5, 0)
+        .await;
+
+    let user_token_0_account = test_env
+        .get_or_create_associated_token_account(user.pubkey(), test_env.token_0_mint, &user)
+        .await;
+    test_env
+        .mint_base_tokens(user_token_0_account, 10000000000, test_env.token_0_mint)
+        .await;
+    let user_token_1_account = test_env
+        .get_or_create_associated_token_account(user.pubkey(), test_env.token_1_mint, &user)
+        .await;
+    test_env
+        .mint_base_tokens(user_token_1_account, 10000000000, test_env.token_1_mint)
+        .await;
+
+    let pool_id = test_env
+        .initialize_pool(
+            &user,
+            amm_index,
+            1000000000,
+            1000000000,
+            0,
+            gamma::create_pool_fee_reveiver::id(),
+        )
+        .await;
+
+    let reward_mint = Keypair::new();
+    test_env
+        .create_token_mint(&reward_mint, &test_env.mint_authority.pubkey(), 9)
+        .await;
+    let reward_provider_token_account = test_env
+        .get_or_create_associated_token_account(
+            reward_provider.pubkey(),
+            reward_mint.pubkey(),
+            &reward_provider,
+        )
+        .await;
+    let reward_amount = 1000000000;
+    test_env
+        .mint_base_tokens(
+            reward_provider_token_account,
+            reward_amount,
+            reward_mint.pubkey(),
+        )
+        .await;
+
+    let timestamp_now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    let start_time = timestamp_now + 10;
+    let end_time = timestamp_now + 3000;
+    test_env
+        .create_rewards(
+            &reward_provider,
+            pool_id,
+            start_time,
+            end_time,
+            reward_mint.pubkey(),
+            reward_amount,
+        )
+        .await;
+    let (reward_info_key, _) = Pubkey::find_program_address(
+        &[
+            REWARD_INFO_SEED.as_bytes(),
+            pool_id.to_bytes().as_ref(),
+            &start_time.to_le_bytes(),
+            reward_mint.pubkey().to_bytes().as_ref(),
+        ],
+        &gamma::id(),
+    );
+
+    test_env
+        .calculate_rewards(&user, pool_id, reward_info_key)
+        .await;
+    test_env.jump_seconds(100).await;
+
+    // Lamports sent to the user reward info address of the receiver do not block the transfer
+    test_env
+        .transfer_lamports(
+            &user,
+            user_reward_info_key(reward_info_key, receiver.pubkey()),
+            1,
+        )
+        .await;
+
+    let user_pool_liquidity_address = user_pool_liquidity_key(pool_id, user.pubkey());
+    let user_pool_liquidity_before: UserPoolLiquidity =
+        test_env.fetch_account(user_pool_liquidity_address).await;
+    let transfer_amount = 400000000;
+    test_env
+        .transfer_liquidity(
+            &user,
+            receiver.pubkey(),
+            pool_id,
+            transfer_amount,
+            vec![reward_info_key],
+        )
+        .await;
+
+    let user_pool_liquidity: UserPoolLiquidity =
+        test_env.fetch_account(user_pool_liquidity_address).await;
+    let receiver_pool_liquidity: UserPoolLiquidity = test_env
+        .fetch_account(user_pool_liquidity_key(pool_id, receiver.pubkey()))
+        .await;
+    assert_eq!(
+        user_pool_liquidity.lp_tokens_owned,
+        user_pool_liquidity_before.lp_tokens_owned - u128::from(transfer_amount)
+    );
+    assert_eq!(
+        receiver_pool_liquidity.lp_tokens_owned,
+        u128::from(transfer_amount)
+    );
+    // Cost basis moves pro-rata
+    let moved_token_0 = user_pool_liquidity_before.token_0_deposited * u128::from(transfer_amount)
+        / user_pool_liquidity_before.lp_tokens_owned;
+    assert_eq!(receiver_pool_liquidity.token_0_deposited, moved_token_0);
+    assert_eq!(
+        user_pool_liquidity.token_0_deposited,
+        user_pool_liquidity_before.token_0_deposited - moved_token_0
+    );
+
+    // Rewards of the owner are settled up to the transfer, the receiver starts from now
+    let user_reward_info: UserRewardInfo = test_env
+        .fetch_account(user_reward_info_key(reward_info_key, user.pubkey()))
+        .await;
+    assert!(user_reward_info.total_rewards > 0);
+    let receiver_reward_info: UserRewardInfo = test_env
+        .fetch_account(user_reward_info_key(reward_info_key, receiver.pubkey()))
+        .await;
+    assert_eq!(receiver_reward_info.total_rewards, 0);
+    assert_eq!(
+        receiver_reward_info.rewards_last_calculated_at,
+        user_reward_info.rewards_last_calculated_at
+    );
+}
+
+fn user_pool_liquidity_key(pool_id: Pubkey, user: Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            USER_POOL_LIQUIDITY_SEED.as_bytes(),
+            pool_id.as_ref(),
+            user.as_ref(),
+        ],
+        &gamma::ID,
+    )
+    .0
+}
+
+fn user_reward_info_key(reward_info: Pubkey, user: Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            USER_REWARD_INFO_SEED.as_bytes(),
+            reward_info.as_ref(),
+            user.as_ref(),
+        ],
+        &gamma::ID,
+    )
+    .0
+}
@@ -0,0 +1,77 @@
+use gamma::{curve::TradeDirection, states::Twap};
+use solana_program_test::tokio;
+use solana_sdk::{signature::Keypair, signer::Signer};
+mod utils;
+
+use utils::*;
+
+#[tokio::test]
+async fn should_return_twap_of_observations_in_window() {
+    let user = Keypair::new();
+    let admin = get_admin();
+    let amm_index = 0;
+    let mut test_env = TestEnv::new(vec![user.pubkey(), admin.pubkey()]).await;
+    test_env
+        .create_config(&admin, amm_index, 100, 20, 5, 0)
+        .await;
+
+    let user_token_0_account = test_env
+        .get_or_create_associated_token_account(user.pubkey(), test_env.token_0_mint, &user)
+        .await;
+    test_env
+        .mint_base_tokens(user_token_0_account, 10000000000, test_env.token_0_mint)
+        .await;
+    let user_token_1_account = test_env
+        .get_or_create_associated_token_account(user.pubkey(), test_env.token_1_mint, &user)
+        .await;
+    test_env
+        .mint_base_tokens(user_token_1_account, 10000000000, test_env.token_1_mint)
+        .await;
+
+    let pool_id = test_env
+        .initialize_pool(
+            &user,
+            amm_index,
+            1000000000,
+            1000000000,
+            0,
+            gamma::create_pool_fee_reveiver::id(),
+        )
+        .await;
+
+    // Not enough observations yet
+    let twap = test_env.get_twap(&user, pool_id, 3600).await;
+    assert_eq!(twap, Twap::default());
+
+    // Every swap sells token_0 and records the price before it
+    for _ in 0..5 {
+        test_env.jump_seconds(20).await;
+        test_env
+            .swap_base_input(
+                &user,
+                pool_id,
+                amm_index,
+                50000000,
+                0,
+                TradeDirection::ZeroForOne,
+            )
+            .await;
+    }
+
+    let twap = test_env.get_twap(&user, pool_id, 3600).await;
+    assert!(twap.end_timestamp > twap.start_timestamp);
+    assert!(twap.token_0_twap_price_x32 > 0);
+    assert!(twap.token_0_twap_price_x32 <= 1 << 32);
+    assert!(twap.token_0_min_price_x32 < twap.token_0_max_price_x32);
+    assert!(twap.token_0_min_price_x32 <= twap.token_0_twap_price_x32);
+    assert!(twap.token_0_twap_price_x32 <= twap.token_0_max_price_x32);
+    // token_1 gets more expensive as token_0 is sold
+    assert!(twap.token_1_twap_price_x32 >= 1 << 32);
+    assert!(twap.token_1_min_price_x32 <= twap.token_1_twap_price_x32);
+    assert!(twap.token_1_twap_price_x32 <= twap.token_1_max_price_x32);
+
+    // Observations older than the window are ignored
+    test_env.jump_seconds(3600).await;
+    let twap = test_env.get_twap(&user, pool_id, 60).await;
+    assert_eq!(twap, Twap::default());
+}
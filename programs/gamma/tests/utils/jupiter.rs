@@ -1,6 +1,6 @@
 use anchor_lang::AccountDeserialize;
 use anyhow::{anyhow, Context, Result};
-use gamma::states::{ObservationState, PoolStatusBitIndex};
+use gamma::states::{Observations, PoolStatusBitIndex};
 use jupiter_amm_interface::{
     try_get_account_data, AccountMap, Amm, AmmContext, KeyedAccount, Quote, QuoteParams,
     SwapAndAccountMetas, SwapParams,
@@ -41,7 +41,8 @@ pub struct Gamma {
     token_mints_and_token_programs: Option<TokenMints>,
     epoch: Arc<AtomicU64>,
     timestamp: Arc<AtomicI64>,
-    observation_state: Option<ObservationState>,
+    // Raw account data, expanded observation accounts can not be deserialized as `ObservationState`
+    observation_state: Option<Vec<u8>>,
 }
 
 impl Gamma {
@@ -151,9 +152,8 @@ impl Amm for Gamma {
 
         let observation_state =
             try_get_account_data(account_map, &self.pool_state.observation_key)?;
-        self.observation_state = Some(ObservationState::try_deserialize(
-            &mut observation_state.as_ref(),
-        )?);
+        Observations::from_account_data(observation_state)?;
+        self.observation_state = Some(observation_state.to_vec());
 
         self.vault_0_amount = get_unfrozen_token_amount(&self.pool_state.token_0_vault);
         self.vault_1_amount = get_unfrozen_token_amount(&self.pool_state.token_1_vault);
@@ -236,13 +236,20 @@ impl Amm for Gamma {
             } else {
                 total_token_0_amount.into()
             },
+            if zero_for_one {
+                gamma::curve::TradeDirection::ZeroForOne
+            } else {
+                gamma::curve::TradeDirection::OneForZero
+            },
             &amm_config,
             &self.pool_state,
             self.timestamp.load(std::sync::atomic::Ordering::Relaxed) as u64,
-            self.observation_state
-                .as_ref()
-                .context("Missing observation state")?,
-            false,
+            &Observations::from_account_data(
+                self.observation_state
+                    .as_ref()
+                    .context("Missing observation state")?,
+            )?,
+            gamma::fees::FeePath::UnsignedFlow,
         )
         .context("swap failed")?;
 
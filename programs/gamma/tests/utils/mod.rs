@@ -5,18 +5,23 @@ use anchor_spl::associated_token::get_associated_token_address;
 use anchor_spl::token::TokenAccount;
 use anchor_spl::token_2022::spl_token_2022;
 use gamma::curve::TradeDirection;
+use gamma::external::dflow_segmenter::Registry;
 use gamma::states::{
-    ObservationState, AMM_CONFIG_SEED, OBSERVATION_NUM, OBSERVATION_SEED, POOL_LP_MINT_SEED,
-    POOL_SEED, POOL_VAULT_SEED, USER_POOL_LIQUIDITY_SEED,
+    FeesEarned, ObservationState, Twap, AMM_CONFIG_SEED, OBSERVATION_NUM, OBSERVATION_SEED,
+    PARTNER_SEED, PERMANENT_LOCK_SEED, POOL_LP_MINT_SEED, POOL_PARTNERS_SEED, POOL_SEED,
+    POOL_VAULT_SEED, PRICE_FEED_CONFIG_SEED, USER_POOL_LIQUIDITY_SEED,
 };
 use gamma::{AUTH_SEED, REWARD_INFO_SEED, REWARD_VAULT_SEED, USER_REWARD_INFO_SEED};
 use solana_program_runtime::invoke_context::BuiltinFunctionWithContext;
-use solana_sdk::instruction::Instruction;
+use solana_sdk::compute_budget::ComputeBudgetInstruction;
+use solana_sdk::instruction::{AccountMeta, Instruction};
 use solana_sdk::program_option::COption;
 use solana_sdk::program_pack::Pack;
 
 use anchor_lang::prelude::{Clock, Pubkey, Rent};
-use anchor_lang::{system_program, AccountDeserialize, InstructionData, ToAccountMetas};
+use anchor_lang::{
+    system_program, AccountDeserialize, AnchorDeserialize, InstructionData, ToAccountMetas,
+};
 use solana_program_test::{
     BanksClientError, BanksTransactionResultWithMetadata, ProgramTest, ProgramTestBanksClientExt,
     ProgramTestContext,
@@ -210,6 +215,36 @@ pub fn get_current_price_token_0_price(observation: ObservationState) -> u128 {
         .unwrap()
 }
 
+pub fn partner_key(partner_id: u8) -> Pubkey {
+    Pubkey::find_program_address(&[PARTNER_SEED.as_bytes(), &[partner_id]], &gamma::id()).0
+}
+
+pub fn pool_partners_key(pool_id: Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[POOL_PARTNERS_SEED.as_bytes(), pool_id.as_ref()],
+        &gamma::id(),
+    )
+    .0
+}
+
+/// The (reward_info, user_reward_info) remaining accounts settling the rewards of the user
+pub fn get_reward_account_metas(reward_infos: &[Pubkey], user: Pubkey) -> Vec<AccountMeta> {
+    let mut account_metas = Vec::with_capacity(reward_infos.len() * 2);
+    for reward_info in reward_infos {
+        let (user_reward_info, _) = Pubkey::find_program_address(
+            &[
+                USER_REWARD_INFO_SEED.as_bytes(),
+                reward_info.as_ref(),
+                user.as_ref(),
+            ],
+            &gamma::id(),
+        );
+        account_metas.push(AccountMeta::new(*reward_info, false));
+        account_metas.push(AccountMeta::new(user_reward_info, false));
+    }
+    account_metas
+}
+
 pub struct ProgramInfo {
     pub program_name: String,
     pub program_id: Pubkey,
@@ -633,6 +668,25 @@ impl TestEnv {
             .expect("Failed to mint base tokens");
     }
 
+    pub async fn transfer_lamports(&mut self, from: &Keypair, to: Pubkey, lamports: u64) {
+        let latest_blockhash = self
+            .program_test_context
+            .banks_client
+            .get_latest_blockhash()
+            .await
+            .unwrap();
+        self.program_test_context
+            .banks_client
+            .process_transaction(system_transaction::transfer(
+                from,
+                &to,
+                lamports,
+                latest_blockhash,
+            ))
+            .await
+            .unwrap();
+    }
+
     pub async fn jump_seconds<T: Into<i64>>(&mut self, jump_seconds: T) {
         // we add 1 seconds so we are in the new time(computers are very fast).
         let timestamp = self.timestamp_now().await + jump_seconds.into() + 1;
@@ -743,6 +797,60 @@ impl TestEnv {
         init_amount_1: u64,
         open_time: u64,
         create_pool_fee: Pubkey,
+    ) -> Pubkey {
+        self.initialize_pool_with_curve(
+            user,
+            amm_config_index,
+            init_amount_0,
+            init_amount_1,
+            open_time,
+            create_pool_fee,
+            0,
+            0,
+        )
+        .await
+    }
+
+    pub async fn initialize_pool_with_curve(
+        &mut self,
+        user: &Keypair,
+        amm_config_index: u16,
+        init_amount_0: u64,
+        init_amount_1: u64,
+        open_time: u64,
+        create_pool_fee: Pubkey,
+        curve_type: u8,
+        curve_parameter: u64,
+    ) -> Pubkey {
+        self.initialize_pool_with_fee_type(
+            user,
+            amm_config_index,
+            init_amount_0,
+            init_amount_1,
+            open_time,
+            create_pool_fee,
+            curve_type,
+            curve_parameter,
+            0,
+            0,
+            0,
+        )
+        .await
+    }
+
+    pub async fn initialize_pool_with_fee_type(
+        &mut self,
+        user: &Keypair,
+        amm_config_index: u16,
+        init_amount_0: u64,
+        init_amount_1: u64,
+        open_time: u64,
+        create_pool_fee: Pubkey,
+        curve_type: u8,
+        curve_parameter: u64,
+        fee_type: u8,
+        launch_fee_rate: u64,
+        launch_fee_duration: u64,
     ) -> Pubkey {
         let (amm_config_key, __bump) = Pubkey::find_program_address(
             &[AMM_CONFIG_SEED.as_bytes(), &amm_config_index.to_be_bytes()],
@@ -829,18 +937,34 @@ impl TestEnv {
             rent: sysvar::rent::id(),
         };
 
-        let data = gamma::instruction::Initialize {
-            init_amount_0,
-            init_amount_1,
-            open_time,
-            max_trade_fee_rate: 0,
-            volatility_factor: 0,
+        // Constant product pools with the volatility fee are created with the original instruction
+        let transaction = if curve_type == 0 && fee_type == 0 {
+            let data = gamma::instruction::Initialize {
+                init_amount_0,
+                init_amount_1,
+                open_time,
+                max_trade_fee_rate: 0,
+                volatility_factor: 0,
+            };
+            self.encode_instruction_and_sign_transaction(data, accounts, user)
+                .await
+        } else {
+            let data = gamma::instruction::InitializeWithCurve {
+                init_amount_0,
+                init_amount_1,
+                open_time,
+                max_trade_fee_rate: 0,
+                volatility_factor: 0,
+                curve_type,
+                curve_parameter,
+                fee_type,
+                launch_fee_rate,
+                launch_fee_duration,
+            };
+            self.encode_instruction_and_sign_transaction(data, accounts, user)
+                .await
         };
 
-        let transaction = self
-            .encode_instruction_and_sign_transaction(data, accounts, user)
-            .await;
-
         self.program_test_context
             .banks_client
             .process_transaction(transaction)
@@ -859,6 +983,30 @@ impl TestEnv {
         maximum_token_0_amount: u64,
         maximum_token_1_amount: u64,
     ) {
+        self.deposit_with_rewards(
+            user,
+            pool_id,
+            amm_config_index,
+            lp_token_amount,
+            maximum_token_0_amount,
+            maximum_token_1_amount,
+            vec![],
+        )
+        .await
+        .unwrap();
+    }
+
+    /// Deposits settling the rewards of the user for the given active reward infos of the pool
+    pub async fn deposit_with_rewards(
+        &mut self,
+        user: &Keypair,
+        pool_id: Pubkey,
+        amm_config_index: u16,
+        lp_token_amount: u64,
+        maximum_token_0_amount: u64,
+        maximum_token_1_amount: u64,
+        reward_infos: Vec<Pubkey>,
+    ) -> Result<(), BanksClientError> {
         let (amm_config_key, __bump) = Pubkey::find_program_address(
             &[AMM_CONFIG_SEED.as_bytes(), &amm_config_index.to_be_bytes()],
             &gamma::ID,
@@ -926,6 +1074,7 @@ impl TestEnv {
             token_program_2022: spl_token_2022::id(),
             vault_0_mint: self.token_0_mint,
             vault_1_mint: self.token_1_mint,
+            pool_partners: self.pool_partners(pool_id).await,
         };
 
         let data = gamma::instruction::Deposit {
@@ -934,15 +1083,17 @@ impl TestEnv {
             maximum_token_1_amount,
         };
 
-        let transaction = self
-            .encode_instruction_and_sign_transaction(data, accounts, user)
-            .await;
+        let mut instruction = get_instruction(data, accounts);
+        instruction
+            .accounts
+            .extend(get_reward_account_metas(&reward_infos, user.pubkey()));
+        let transaction =
+            get_signed_transaction(&mut self.program_test_context, &[instruction], user).await;
 
         self.program_test_context
             .banks_client
             .process_transaction(transaction)
             .await
-            .unwrap();
     }
 
     pub async fn withdraw(
@@ -954,6 +1105,30 @@ impl TestEnv {
         minimum_token_0_amount: u64,
         minimum_token_1_amount: u64,
     ) {
+        self.withdraw_with_rewards(
+            user,
+            pool_id,
+            amm_config_index,
+            lp_token_amount,
+            minimum_token_0_amount,
+            minimum_token_1_amount,
+            vec![],
+        )
+        .await
+        .unwrap();
+    }
+
+    /// Withdraws settling the rewards of the user for the given active reward infos of the pool
+    pub async fn withdraw_with_rewards(
+        &mut self,
+        user: &Keypair,
+        pool_id: Pubkey,
+        amm_config_index: u16,
+        lp_token_amount: u64,
+        minimum_token_0_amount: u64,
+        minimum_token_1_amount: u64,
+        reward_infos: Vec<Pubkey>,
+    ) -> Result<(), BanksClientError> {
         let (amm_config_key, __bump) = Pubkey::find_program_address(
             &[AMM_CONFIG_SEED.as_bytes(), &amm_config_index.to_be_bytes()],
             &gamma::ID,
@@ -1021,6 +1196,7 @@ impl TestEnv {
             memo_program: spl_memo::id(),
             instruction_sysvar_account: sysvar::instructions::id(),
             kamino_program: solana_sdk::pubkey!("KLend2g3cP87fffoy8q1mQqGKjrxjC8boSyAYavgmjD"),
+            pool_partners: self.pool_partners(pool_id).await,
         };
 
         let data = gamma::instruction::Withdraw {
@@ -1029,83 +1205,42 @@ impl TestEnv {
             minimum_token_1_amount,
         };
 
-        let transaction = self
-            .encode_instruction_and_sign_transaction(data, accounts, user)
-            .await;
-
-        self.program_test_context
-            .banks_client
-            .process_transaction(transaction)
-            .await
-            .unwrap();
-    }
-
-    pub async fn init_user_pool_liquidity(&mut self, user: &Keypair, pool_id: Pubkey) {
-        self.init_user_pool_liquidity_with_partner(user, pool_id, None)
-            .await;
-    }
-
-    pub async fn init_user_pool_liquidity_with_partner(
-        &mut self,
-        user: &Keypair,
-        pool_id: Pubkey,
-        partner: Option<String>,
-    ) {
-        let user_pool_liquidity = Pubkey::find_program_address(
-            &[
-                USER_POOL_LIQUIDITY_SEED.as_bytes(),
-                pool_id.to_bytes().as_ref(),
-                user.pubkey().to_bytes().as_ref(),
-            ],
-            &gamma::id(),
-        )
-        .0;
-
-        let accounts: gamma::accounts::InitUserPoolLiquidity =
-            gamma::accounts::InitUserPoolLiquidity {
-                user: user.pubkey(),
-                pool_state: pool_id,
-                user_pool_liquidity,
-                system_program: system_program::ID,
-            };
-
-        let data = gamma::instruction::InitUserPoolLiquidity { partner };
-
-        let transaction = self
-            .encode_instruction_and_sign_transaction(data, accounts, user)
-            .await;
+        let mut instruction = get_instruction(data, accounts);
+        instruction
+            .accounts
+            .extend(get_reward_account_metas(&reward_infos, user.pubkey()));
+        let transaction =
+            get_signed_transaction(&mut self.program_test_context, &[instruction], user).await;
 
         self.program_test_context
             .banks_client
             .process_transaction(transaction)
             .await
-            .unwrap();
     }
 
-    pub async fn swap_base_input(
+    pub async fn deposit_single_sided(
         &mut self,
         user: &Keypair,
         pool_id: Pubkey,
         amm_config_index: u16,
         amount_in: u64,
-        minimum_amount_out: u64,
+        minimum_lp_token_amount: u64,
         trade_direction: TradeDirection,
-    ) {
+    ) -> u64 {
         let (authority, __bump) =
             Pubkey::find_program_address(&[AUTH_SEED.as_bytes()], &gamma::id());
         let (amm_config_key, __bump) = Pubkey::find_program_address(
             &[AMM_CONFIG_SEED.as_bytes(), &amm_config_index.to_be_bytes()],
             &gamma::ID,
         );
-
-        let user_token_0_account: Pubkey = self
-            .get_or_create_associated_token_account(user.pubkey(), self.token_0_mint.clone(), &user)
-            .await;
-
-        let user_token_1_account = self
-            .get_or_create_associated_token_account(user.pubkey(), self.token_1_mint.clone(), &user)
-            .await;
-
+        let (user_pool_liquidity, __bump) = Pubkey::find_program_address(
+            &[
+                USER_POOL_LIQUIDITY_SEED.as_bytes(),
+                pool_id.to_bytes().as_ref(),
+                user.pubkey().to_bytes().as_ref(),
+            ],
+            &gamma::ID,
+        );
         let (token_0_vault, __bump) = Pubkey::find_program_address(
             &[
                 POOL_VAULT_SEED.as_bytes(),
@@ -1127,124 +1262,1629 @@ impl TestEnv {
             &gamma::ID,
         );
 
-        let (
-            input_token_account,
-            output_token_account,
-            input_token_mint,
-            output_token_mint,
-            input_vault,
-            output_vault,
-            input_token_program,
-            output_token_program,
-        ) = match trade_direction {
-            TradeDirection::ZeroForOne => (
-                user_token_0_account,
-                user_token_1_account,
-                self.token_0_mint,
-                self.token_1_mint,
-                token_0_vault,
-                token_1_vault,
-                spl_token::id(),
-                spl_token::id(),
-            ),
-            TradeDirection::OneForZero => (
-                user_token_1_account,
-                user_token_0_account,
-                self.token_1_mint,
-                self.token_0_mint,
-                token_1_vault,
-                token_0_vault,
-                spl_token::id(),
-                spl_token::id(),
-            ),
+        let (input_token_mint, input_vault, output_vault) = match trade_direction {
+            TradeDirection::ZeroForOne => (self.token_0_mint, token_0_vault, token_1_vault),
+            TradeDirection::OneForZero => (self.token_1_mint, token_1_vault, token_0_vault),
         };
+        let input_token_account = self
+            .get_or_create_associated_token_account(user.pubkey(), input_token_mint, &user)
+            .await;
 
-        let accounts = gamma::accounts::Swap {
-            payer: user.pubkey(),
+        let accounts = gamma::accounts::DepositSingleSided {
+            owner: user.pubkey(),
             authority,
             amm_config: amm_config_key,
             pool_state: pool_id,
-            observation_state: observation_key,
+            user_pool_liquidity,
             input_token_account,
-            output_token_account,
             input_vault,
             output_vault,
-            input_token_program,
-            output_token_program,
+            input_token_program: spl_token::id(),
             input_token_mint,
-            output_token_mint,
+            observation_state: observation_key,
+            pool_partners: self.pool_partners(pool_id).await,
         };
 
-        let data = gamma::instruction::SwapBaseInput {
+        let data = gamma::instruction::DepositSingleSided {
             amount_in,
-            minimum_amount_out,
+            minimum_lp_token_amount,
         };
 
-        let transaction = self
-            .encode_instruction_and_sign_transaction(data, accounts, user)
-            .await;
+        // The compute units of the zaps are checked by the tests, they are not limited here
+        let instructions = [
+            ComputeBudgetInstruction::set_compute_unit_limit(1_400_000),
+            get_instruction(data, accounts),
+        ];
+        let transaction =
+            get_signed_transaction(&mut self.program_test_context, &instructions, user).await;
 
-        self.program_test_context
+        let result = self
+            .program_test_context
             .banks_client
-            .process_transaction(transaction)
+            .process_transaction_with_metadata(transaction)
             .await
             .unwrap();
+        result.result.unwrap();
+        result.metadata.unwrap().compute_units_consumed
     }
 
-    pub async fn swap_base_output(
+    pub async fn withdraw_single_sided(
         &mut self,
         user: &Keypair,
         pool_id: Pubkey,
         amm_config_index: u16,
-        amount_out: u64,
-        max_amount_in: u64,
-        trade_direction: TradeDirection,
+        lp_token_amount: u64,
+        minimum_amount_out: u64,
+        withdraw_token_0: bool,
     ) {
-        let (authority, __bump) =
-            Pubkey::find_program_address(&[AUTH_SEED.as_bytes()], &gamma::id());
         let (amm_config_key, __bump) = Pubkey::find_program_address(
             &[AMM_CONFIG_SEED.as_bytes(), &amm_config_index.to_be_bytes()],
             &gamma::ID,
         );
-
-        let user_token_0_account: Pubkey = self
-            .get_or_create_associated_token_account(user.pubkey(), self.token_0_mint.clone(), &user)
-            .await;
-
-        let user_token_1_account = self
-            .get_or_create_associated_token_account(user.pubkey(), self.token_1_mint.clone(), &user)
-            .await;
-
-        let (token_0_vault, __bump) = Pubkey::find_program_address(
-            &[
-                POOL_VAULT_SEED.as_bytes(),
-                pool_id.to_bytes().as_ref(),
-                self.token_0_mint.to_bytes().as_ref(),
-            ],
-            &gamma::ID,
-        );
-        let (token_1_vault, __bump) = Pubkey::find_program_address(
-            &[
-                POOL_VAULT_SEED.as_bytes(),
-                pool_id.to_bytes().as_ref(),
-                self.token_1_mint.to_bytes().as_ref(),
-            ],
-            &gamma::ID,
-        );
         let (observation_key, __bump) = Pubkey::find_program_address(
             &[OBSERVATION_SEED.as_bytes(), pool_id.to_bytes().as_ref()],
             &gamma::ID,
         );
 
-        let (
-            input_token_account,
-            output_token_account,
+        let accounts = gamma::accounts::WithdrawSingleSided {
+            withdraw: self.withdraw_accounts(user, pool_id).await,
+            amm_config: amm_config_key,
+            observation_state: observation_key,
+        };
+
+        let data = gamma::instruction::WithdrawSingleSided {
+            lp_token_amount,
+            minimum_amount_out,
+            withdraw_token_0,
+        };
+
+        let transaction = self
+            .encode_instruction_and_sign_transaction(data, accounts, user)
+            .await;
+
+        self.program_test_context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap();
+    }
+
+    pub async fn deposit_exact_tokens(
+        &mut self,
+        user: &Keypair,
+        pool_id: Pubkey,
+        maximum_token_0_amount: u64,
+        maximum_token_1_amount: u64,
+        minimum_lp_token_amount: u64,
+    ) -> Result<(), BanksClientError> {
+        let accounts = self.deposit_accounts(user, pool_id).await;
+
+        let data = gamma::instruction::DepositExactTokens {
+            maximum_token_0_amount,
+            maximum_token_1_amount,
+            minimum_lp_token_amount,
+        };
+
+        let transaction = self
+            .encode_instruction_and_sign_transaction(data, accounts, user)
+            .await;
+
+        self.program_test_context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+    }
+
+    pub async fn withdraw_exact_tokens(
+        &mut self,
+        user: &Keypair,
+        pool_id: Pubkey,
+        token_0_amount: u64,
+        token_1_amount: u64,
+        maximum_lp_token_amount: u64,
+    ) -> Result<(), BanksClientError> {
+        let accounts = self.withdraw_accounts(user, pool_id).await;
+
+        let data = gamma::instruction::WithdrawExactTokens {
+            token_0_amount,
+            token_1_amount,
+            maximum_lp_token_amount,
+        };
+
+        let transaction = self
+            .encode_instruction_and_sign_transaction(data, accounts, user)
+            .await;
+
+        self.program_test_context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+    }
+
+    async fn deposit_accounts(
+        &mut self,
+        user: &Keypair,
+        pool_id: Pubkey,
+    ) -> gamma::accounts::Deposit {
+        let (token_0_vault, token_1_vault) = self.pool_vaults(pool_id);
+        let (authority, __bump) =
+            Pubkey::find_program_address(&[AUTH_SEED.as_bytes()], &gamma::id());
+        let user_pool_liquidity = Pubkey::find_program_address(
+            &[
+                USER_POOL_LIQUIDITY_SEED.as_bytes(),
+                pool_id.to_bytes().as_ref(),
+                user.pubkey().to_bytes().as_ref(),
+            ],
+            &gamma::id(),
+        )
+        .0;
+        let user_token_0_account = self
+            .get_or_create_associated_token_account(user.pubkey(), self.token_0_mint.clone(), &user)
+            .await;
+        let user_token_1_account = self
+            .get_or_create_associated_token_account(user.pubkey(), self.token_1_mint.clone(), &user)
+            .await;
+
+        gamma::accounts::Deposit {
+            owner: user.pubkey(),
+            authority,
+            pool_state: pool_id,
+            user_pool_liquidity,
+            token_0_account: user_token_0_account,
+            token_1_account: user_token_1_account,
+            token_0_vault,
+            token_1_vault,
+            token_program: spl_token::id(),
+            token_program_2022: spl_token_2022::id(),
+            vault_0_mint: self.token_0_mint,
+            vault_1_mint: self.token_1_mint,
+            pool_partners: self.pool_partners(pool_id).await,
+        }
+    }
+
+    async fn withdraw_accounts(
+        &mut self,
+        user: &Keypair,
+        pool_id: Pubkey,
+    ) -> gamma::accounts::Withdraw {
+        let (token_0_vault, token_1_vault) = self.pool_vaults(pool_id);
+        let (authority, __bump) =
+            Pubkey::find_program_address(&[AUTH_SEED.as_bytes()], &gamma::id());
+        let user_pool_liquidity = Pubkey::find_program_address(
+            &[
+                USER_POOL_LIQUIDITY_SEED.as_bytes(),
+                pool_id.to_bytes().as_ref(),
+                user.pubkey().to_bytes().as_ref(),
+            ],
+            &gamma::id(),
+        )
+        .0;
+        let user_token_0_account = self
+            .get_or_create_associated_token_account(user.pubkey(), self.token_0_mint.clone(), &user)
+            .await;
+        let user_token_1_account = self
+            .get_or_create_associated_token_account(user.pubkey(), self.token_1_mint.clone(), &user)
+            .await;
+
+        gamma::accounts::Withdraw {
+            owner: user.pubkey(),
+            authority,
+            pool_state: pool_id,
+            user_pool_liquidity,
+            token_0_account: user_token_0_account,
+            token_1_account: user_token_1_account,
+            token_0_vault,
+            token_1_vault,
+            token_program: spl_token::id(),
+            token_program_2022: spl_token_2022::id(),
+            vault_0_mint: self.token_0_mint,
+            vault_1_mint: self.token_1_mint,
+            memo_program: spl_memo::id(),
+            instruction_sysvar_account: sysvar::instructions::id(),
+            kamino_program: solana_sdk::pubkey!("KLend2g3cP87fffoy8q1mQqGKjrxjC8boSyAYavgmjD"),
+            pool_partners: self.pool_partners(pool_id).await,
+        }
+    }
+
+    fn pool_vaults(&self, pool_id: Pubkey) -> (Pubkey, Pubkey) {
+        let (token_0_vault, __bump) = Pubkey::find_program_address(
+            &[
+                POOL_VAULT_SEED.as_bytes(),
+                pool_id.to_bytes().as_ref(),
+                self.token_0_mint.to_bytes().as_ref(),
+            ],
+            &gamma::ID,
+        );
+        let (token_1_vault, __bump) = Pubkey::find_program_address(
+            &[
+                POOL_VAULT_SEED.as_bytes(),
+                pool_id.to_bytes().as_ref(),
+                self.token_1_mint.to_bytes().as_ref(),
+            ],
+            &gamma::ID,
+        );
+        (token_0_vault, token_1_vault)
+    }
+
+    pub async fn init_user_pool_liquidity(&mut self, user: &Keypair, pool_id: Pubkey) {
+        self.init_user_pool_liquidity_with_partner_name(user, pool_id, None)
+            .await;
+    }
+
+    pub async fn init_user_pool_liquidity_with_partner_name(
+        &mut self,
+        user: &Keypair,
+        pool_id: Pubkey,
+        partner: Option<String>,
+    ) {
+        let user_pool_liquidity = Pubkey::find_program_address(
+            &[
+                USER_POOL_LIQUIDITY_SEED.as_bytes(),
+                pool_id.to_bytes().as_ref(),
+                user.pubkey().to_bytes().as_ref(),
+            ],
+            &gamma::id(),
+        )
+        .0;
+
+        let accounts: gamma::accounts::InitUserPoolLiquidity =
+            gamma::accounts::InitUserPoolLiquidity {
+                user: user.pubkey(),
+                pool_state: pool_id,
+                user_pool_liquidity,
+                system_program: system_program::ID,
+            };
+
+        let data = gamma::instruction::InitUserPoolLiquidity { partner };
+
+        let transaction = self
+            .encode_instruction_and_sign_transaction(data, accounts, user)
+            .await;
+
+        self.program_test_context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap();
+    }
+
+    pub async fn init_user_pool_liquidity_with_partner(
+        &mut self,
+        user: &Keypair,
+        pool_id: Pubkey,
+        partner_id: u8,
+    ) {
+        self.try_init_user_pool_liquidity_with_partner(user, pool_id, partner_id)
+            .await
+            .unwrap();
+    }
+
+    pub async fn try_init_user_pool_liquidity_with_partner(
+        &mut self,
+        user: &Keypair,
+        pool_id: Pubkey,
+        partner_id: u8,
+    ) -> Result<(), BanksClientError> {
+        let user_pool_liquidity = Pubkey::find_program_address(
+            &[
+                USER_POOL_LIQUIDITY_SEED.as_bytes(),
+                pool_id.to_bytes().as_ref(),
+                user.pubkey().to_bytes().as_ref(),
+            ],
+            &gamma::id(),
+        )
+        .0;
+
+        let accounts: gamma::accounts::InitUserPoolLiquidityWithPartner =
+            gamma::accounts::InitUserPoolLiquidityWithPartner {
+                user: user.pubkey(),
+                pool_state: pool_id,
+                user_pool_liquidity,
+                system_program: system_program::ID,
+                partner: partner_key(partner_id),
+                pool_partners: self.pool_partners(pool_id).await,
+            };
+
+        let data = gamma::instruction::InitUserPoolLiquidityWithPartner {};
+
+        let transaction = self
+            .encode_instruction_and_sign_transaction(data, accounts, user)
+            .await;
+
+        self.program_test_context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+    }
+
+    /// The partners of the pool if any were added with `add_pool_partner`
+    pub async fn pool_partners(&mut self, pool_id: Pubkey) -> Option<Pubkey> {
+        let pool_partners = pool_partners_key(pool_id);
+        self.get_account_info(pool_partners)
+            .await
+            .unwrap()
+            .map(|_| pool_partners)
+    }
+
+    pub async fn create_partner(
+        &mut self,
+        admin: &Keypair,
+        partner_id: u8,
+        name: &str,
+    ) -> Result<(), BanksClientError> {
+        let accounts = gamma::accounts::CreatePartner {
+            owner: admin.pubkey(),
+            partner: partner_key(partner_id),
+            system_program: system_program::ID,
+        };
+
+        let data = gamma::instruction::CreatePartner {
+            partner_id,
+            name: name.to_string(),
+        };
+
+        let transaction = self
+            .encode_instruction_and_sign_transaction(data, accounts, admin)
+            .await;
+
+        self.program_test_context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+    }
+
+    pub async fn add_pool_partner(
+        &mut self,
+        admin: &Keypair,
+        pool_id: Pubkey,
+        partner_id: u8,
+    ) -> Result<(), BanksClientError> {
+        let accounts = gamma::accounts::AddPoolPartner {
+            owner: admin.pubkey(),
+            pool_state: pool_id,
+            partner: partner_key(partner_id),
+            pool_partners: pool_partners_key(pool_id),
+            system_program: system_program::ID,
+        };
+
+        let data = gamma::instruction::AddPoolPartner {};
+
+        let transaction = self
+            .encode_instruction_and_sign_transaction(data, accounts, admin)
+            .await;
+
+        self.program_test_context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+    }
+
+    pub async fn create_lp_mint(&mut self, payer: &Keypair, pool_id: Pubkey) -> Pubkey {
+        let (authority, __bump) = Pubkey::find_program_address(&[AUTH_SEED.as_bytes()], &gamma::ID);
+        let (lp_mint, __bump) = Pubkey::find_program_address(
+            &[POOL_LP_MINT_SEED.as_bytes(), pool_id.to_bytes().as_ref()],
+            &gamma::ID,
+        );
+
+        let accounts = gamma::accounts::CreateLpMint {
+            payer: payer.pubkey(),
+            authority,
+            pool_state: pool_id,
+            lp_mint,
+            token_program: spl_token::id(),
+            system_program: system_program::ID,
+        };
+
+        let data = gamma::instruction::CreateLpMint {};
+
+        let transaction = self
+            .encode_instruction_and_sign_transaction(data, accounts, payer)
+            .await;
+
+        self.program_test_context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap();
+
+        lp_mint
+    }
+
+    pub async fn wrap_lp_tokens(
+        &mut self,
+        user: &Keypair,
+        pool_id: Pubkey,
+        lp_token_account: Pubkey,
+        lp_token_amount: u64,
+    ) {
+        let (authority, __bump) = Pubkey::find_program_address(&[AUTH_SEED.as_bytes()], &gamma::ID);
+        let (lp_mint, __bump) = Pubkey::find_program_address(
+            &[POOL_LP_MINT_SEED.as_bytes(), pool_id.to_bytes().as_ref()],
+            &gamma::ID,
+        );
+        let (user_pool_liquidity, __bump) = Pubkey::find_program_address(
+            &[
+                USER_POOL_LIQUIDITY_SEED.as_bytes(),
+                pool_id.to_bytes().as_ref(),
+                user.pubkey().to_bytes().as_ref(),
+            ],
+            &gamma::ID,
+        );
+
+        let accounts = gamma::accounts::WrapLpTokens {
+            owner: user.pubkey(),
+            authority,
+            pool_state: pool_id,
+            user_pool_liquidity,
+            lp_mint,
+            lp_token_account,
+            token_program: spl_token::id(),
+            pool_partners: self.pool_partners(pool_id).await,
+        };
+
+        let data = gamma::instruction::WrapLpTokens { lp_token_amount };
+
+        let transaction = self
+            .encode_instruction_and_sign_transaction(data, accounts, user)
+            .await;
+
+        self.program_test_context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap();
+    }
+
+    pub async fn lock_lp_tokens(
+        &mut self,
+        user: &Keypair,
+        pool_id: Pubkey,
+        lp_token_amount: u64,
+        unlock_at: u64,
+        reward_infos: Vec<Pubkey>,
+    ) -> Result<(), BanksClientError> {
+        let (user_pool_liquidity, __bump) = Pubkey::find_program_address(
+            &[
+                USER_POOL_LIQUIDITY_SEED.as_bytes(),
+                pool_id.to_bytes().as_ref(),
+                user.pubkey().to_bytes().as_ref(),
+            ],
+            &gamma::ID,
+        );
+
+        let accounts = gamma::accounts::LockLpTokens {
+            owner: user.pubkey(),
+            pool_state: pool_id,
+            user_pool_liquidity,
+        };
+
+        let data = gamma::instruction::LockLpTokens {
+            lp_token_amount,
+            unlock_at,
+        };
+
+        let mut instruction = get_instruction(data, accounts);
+        instruction
+            .accounts
+            .extend(get_reward_account_metas(&reward_infos, user.pubkey()));
+        let transaction =
+            get_signed_transaction(&mut self.program_test_context, &[instruction], user).await;
+
+        self.program_test_context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+    }
+
+    pub async fn unlock_lp_tokens(
+        &mut self,
+        signer: &Keypair,
+        user: Pubkey,
+        pool_id: Pubkey,
+        reward_infos: Vec<Pubkey>,
+    ) -> Result<(), BanksClientError> {
+        let (user_pool_liquidity, __bump) = Pubkey::find_program_address(
+            &[
+                USER_POOL_LIQUIDITY_SEED.as_bytes(),
+                pool_id.to_bytes().as_ref(),
+                user.to_bytes().as_ref(),
+            ],
+            &gamma::ID,
+        );
+
+        let accounts = gamma::accounts::UnlockLpTokens {
+            signer: signer.pubkey(),
+            pool_state: pool_id,
+            user_pool_liquidity,
+        };
+
+        let data = gamma::instruction::UnlockLpTokens {};
+
+        let mut instruction = get_instruction(data, accounts);
+        instruction
+            .accounts
+            .extend(get_reward_account_metas(&reward_infos, user));
+        let transaction =
+            get_signed_transaction(&mut self.program_test_context, &[instruction], signer).await;
+
+        self.program_test_context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+    }
+
+    pub async fn lock_liquidity_permanently(
+        &mut self,
+        user: &Keypair,
+        pool_id: Pubkey,
+        lp_token_amount: u64,
+        reward_infos: Vec<Pubkey>,
+    ) -> Result<(), BanksClientError> {
+        let (user_pool_liquidity, __bump) = Pubkey::find_program_address(
+            &[
+                USER_POOL_LIQUIDITY_SEED.as_bytes(),
+                pool_id.to_bytes().as_ref(),
+                user.pubkey().to_bytes().as_ref(),
+            ],
+            &gamma::ID,
+        );
+        let (permanent_lock, __bump) = Pubkey::find_program_address(
+            &[
+                PERMANENT_LOCK_SEED.as_bytes(),
+                pool_id.to_bytes().as_ref(),
+                user.pubkey().to_bytes().as_ref(),
+            ],
+            &gamma::ID,
+        );
+
+        let accounts = gamma::accounts::LockLiquidityPermanently {
+            owner: user.pubkey(),
+            pool_state: pool_id,
+            user_pool_liquidity,
+            permanent_lock,
+            system_program: system_program::ID,
+            pool_partners: self.pool_partners(pool_id).await,
+        };
+
+        let data = gamma::instruction::LockLiquidityPermanently { lp_token_amount };
+
+        let mut instruction = get_instruction(data, accounts);
+        instruction
+            .accounts
+            .extend(get_reward_account_metas(&reward_infos, user.pubkey()));
+        let transaction =
+            get_signed_transaction(&mut self.program_test_context, &[instruction], user).await;
+
+        self.program_test_context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+    }
+
+    pub async fn claim_permanent_lock_fees(
+        &mut self,
+        user: &Keypair,
+        pool_id: Pubkey,
+    ) -> Result<(), BanksClientError> {
+        let (authority, __bump) =
+            Pubkey::find_program_address(&[AUTH_SEED.as_bytes()], &gamma::id());
+        let (permanent_lock, __bump) = Pubkey::find_program_address(
+            &[
+                PERMANENT_LOCK_SEED.as_bytes(),
+                pool_id.to_bytes().as_ref(),
+                user.pubkey().to_bytes().as_ref(),
+            ],
+            &gamma::ID,
+        );
+        let (token_0_vault, __bump) = Pubkey::find_program_address(
+            &[
+                POOL_VAULT_SEED.as_bytes(),
+                pool_id.to_bytes().as_ref(),
+                self.token_0_mint.to_bytes().as_ref(),
+            ],
+            &gamma::ID,
+        );
+        let (token_1_vault, __bump) = Pubkey::find_program_address(
+            &[
+                POOL_VAULT_SEED.as_bytes(),
+                pool_id.to_bytes().as_ref(),
+                self.token_1_mint.to_bytes().as_ref(),
+            ],
+            &gamma::ID,
+        );
+        let recipient_token_0_account = self
+            .get_or_create_associated_token_account(user.pubkey(), self.token_0_mint, user)
+            .await;
+        let recipient_token_1_account = self
+            .get_or_create_associated_token_account(user.pubkey(), self.token_1_mint, user)
+            .await;
+
+        let accounts = gamma::accounts::ClaimPermanentLockFees {
+            owner: user.pubkey(),
+            authority,
+            pool_state: pool_id,
+            permanent_lock,
+            token_0_vault,
+            token_1_vault,
+            vault_0_mint: self.token_0_mint,
+            vault_1_mint: self.token_1_mint,
+            recipient_token_0_account,
+            recipient_token_1_account,
+            token_program: spl_token::id(),
+            token_program_2022: spl_token_2022::id(),
+        };
+        let data = gamma::instruction::ClaimPermanentLockFees {};
+
+        let transaction = self
+            .encode_instruction_and_sign_transaction(data, accounts, user)
+            .await;
+
+        self.program_test_context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+    }
+
+    pub async fn unwrap_lp_tokens(
+        &mut self,
+        user: &Keypair,
+        pool_id: Pubkey,
+        lp_token_account: Pubkey,
+        lp_token_amount: u64,
+    ) {
+        let (lp_mint, __bump) = Pubkey::find_program_address(
+            &[POOL_LP_MINT_SEED.as_bytes(), pool_id.to_bytes().as_ref()],
+            &gamma::ID,
+        );
+        let (user_pool_liquidity, __bump) = Pubkey::find_program_address(
+            &[
+                USER_POOL_LIQUIDITY_SEED.as_bytes(),
+                pool_id.to_bytes().as_ref(),
+                user.pubkey().to_bytes().as_ref(),
+            ],
+            &gamma::ID,
+        );
+
+        let accounts = gamma::accounts::UnwrapLpTokens {
+            owner: user.pubkey(),
+            pool_state: pool_id,
+            user_pool_liquidity,
+            lp_mint,
+            lp_token_account,
+            token_program: spl_token::id(),
+            pool_partners: self.pool_partners(pool_id).await,
+        };
+
+        let data = gamma::instruction::UnwrapLpTokens { lp_token_amount };
+
+        let transaction = self
+            .encode_instruction_and_sign_transaction(data, accounts, user)
+            .await;
+
+        self.program_test_context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap();
+    }
+
+    pub async fn transfer_liquidity(
+        &mut self,
+        user: &Keypair,
+        to_user: Pubkey,
+        pool_id: Pubkey,
+        lp_token_amount: u64,
+        reward_infos: Vec<Pubkey>,
+    ) {
+        let user_pool_liquidity_key = |user: Pubkey| {
+            Pubkey::find_program_address(
+                &[
+                    USER_POOL_LIQUIDITY_SEED.as_bytes(),
+                    pool_id.to_bytes().as_ref(),
+                    user.to_bytes().as_ref(),
+                ],
+                &gamma::ID,
+            )
+            .0
+        };
+        let user_reward_info_key = |reward_info: Pubkey, user: Pubkey| {
+            Pubkey::find_program_address(
+                &[
+                    USER_REWARD_INFO_SEED.as_bytes(),
+                    reward_info.to_bytes().as_ref(),
+                    user.to_bytes().as_ref(),
+                ],
+                &gamma::ID,
+            )
+            .0
+        };
+
+        let accounts = gamma::accounts::TransferLiquidity {
+            owner: user.pubkey(),
+            to_user,
+            pool_state: pool_id,
+            user_pool_liquidity: user_pool_liquidity_key(user.pubkey()),
+            to_user_pool_liquidity: user_pool_liquidity_key(to_user),
+            system_program: system_program::ID,
+            pool_partners: self.pool_partners(pool_id).await,
+        };
+        let mut account_metas = accounts.to_account_metas(None);
+        for reward_info in reward_infos {
+            account_metas.push(AccountMeta::new(reward_info, false));
+            account_metas.push(AccountMeta::new(
+                user_reward_info_key(reward_info, user.pubkey()),
+                false,
+            ));
+            account_metas.push(AccountMeta::new(
+                user_reward_info_key(reward_info, to_user),
+                false,
+            ));
+        }
+
+        let data = gamma::instruction::TransferLiquidity { lp_token_amount };
+        let instruction = Instruction {
+            program_id: gamma::ID,
+            accounts: account_metas,
+            data: data.data(),
+        };
+
+        let transaction =
+            get_signed_transaction(&mut self.program_test_context, &[instruction], user).await;
+
+        self.program_test_context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap();
+    }
+
+    pub async fn close_user_pool_liquidity(&mut self, user: &Keypair, pool_id: Pubkey) {
+        let (user_pool_liquidity, __bump) = Pubkey::find_program_address(
+            &[
+                USER_POOL_LIQUIDITY_SEED.as_bytes(),
+                pool_id.to_bytes().as_ref(),
+                user.pubkey().to_bytes().as_ref(),
+            ],
+            &gamma::ID,
+        );
+
+        let accounts = gamma::accounts::CloseUserPoolLiquidity {
+            owner: user.pubkey(),
+            pool_state: pool_id,
+            user_pool_liquidity,
+        };
+
+        let data = gamma::instruction::CloseUserPoolLiquidity {};
+
+        let transaction = self
+            .encode_instruction_and_sign_transaction(data, accounts, user)
+            .await;
+
+        self.program_test_context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap();
+    }
+
+    pub async fn swap_base_input(
+        &mut self,
+        user: &Keypair,
+        pool_id: Pubkey,
+        amm_config_index: u16,
+        amount_in: u64,
+        minimum_amount_out: u64,
+        trade_direction: TradeDirection,
+    ) {
+        self.swap_base_input_with_remaining_accounts(
+            user,
+            pool_id,
+            amm_config_index,
+            amount_in,
+            minimum_amount_out,
+            trade_direction,
+            vec![],
+        )
+        .await;
+    }
+
+    /// Swaps with `user` signing as a segmenter registered in `registry`
+    pub async fn swap_base_input_with_segmenter(
+        &mut self,
+        user: &Keypair,
+        pool_id: Pubkey,
+        amm_config_index: u16,
+        amount_in: u64,
+        minimum_amount_out: u64,
+        trade_direction: TradeDirection,
+        registry: Pubkey,
+    ) {
+        self.swap_base_input_with_remaining_accounts(
+            user,
+            pool_id,
+            amm_config_index,
+            amount_in,
+            minimum_amount_out,
+            trade_direction,
+            vec![
+                AccountMeta::new_readonly(user.pubkey(), true),
+                AccountMeta::new_readonly(registry, false),
+            ],
+        )
+        .await;
+    }
+
+    pub async fn swap_base_input_with_remaining_accounts(
+        &mut self,
+        user: &Keypair,
+        pool_id: Pubkey,
+        amm_config_index: u16,
+        amount_in: u64,
+        minimum_amount_out: u64,
+        trade_direction: TradeDirection,
+        remaining_accounts: Vec<AccountMeta>,
+    ) {
+        let (authority, __bump) =
+            Pubkey::find_program_address(&[AUTH_SEED.as_bytes()], &gamma::id());
+        let (amm_config_key, __bump) = Pubkey::find_program_address(
+            &[AMM_CONFIG_SEED.as_bytes(), &amm_config_index.to_be_bytes()],
+            &gamma::ID,
+        );
+
+        let user_token_0_account: Pubkey = self
+            .get_or_create_associated_token_account(user.pubkey(), self.token_0_mint.clone(), &user)
+            .await;
+
+        let user_token_1_account = self
+            .get_or_create_associated_token_account(user.pubkey(), self.token_1_mint.clone(), &user)
+            .await;
+
+        let (token_0_vault, __bump) = Pubkey::find_program_address(
+            &[
+                POOL_VAULT_SEED.as_bytes(),
+                pool_id.to_bytes().as_ref(),
+                self.token_0_mint.to_bytes().as_ref(),
+            ],
+            &gamma::ID,
+        );
+        let (token_1_vault, __bump) = Pubkey::find_program_address(
+            &[
+                POOL_VAULT_SEED.as_bytes(),
+                pool_id.to_bytes().as_ref(),
+                self.token_1_mint.to_bytes().as_ref(),
+            ],
+            &gamma::ID,
+        );
+        let (observation_key, __bump) = Pubkey::find_program_address(
+            &[OBSERVATION_SEED.as_bytes(), pool_id.to_bytes().as_ref()],
+            &gamma::ID,
+        );
+
+        let (
+            input_token_account,
+            output_token_account,
+            input_token_mint,
+            output_token_mint,
+            input_vault,
+            output_vault,
+            input_token_program,
+            output_token_program,
+        ) = match trade_direction {
+            TradeDirection::ZeroForOne => (
+                user_token_0_account,
+                user_token_1_account,
+                self.token_0_mint,
+                self.token_1_mint,
+                token_0_vault,
+                token_1_vault,
+                spl_token::id(),
+                spl_token::id(),
+            ),
+            TradeDirection::OneForZero => (
+                user_token_1_account,
+                user_token_0_account,
+                self.token_1_mint,
+                self.token_0_mint,
+                token_1_vault,
+                token_0_vault,
+                spl_token::id(),
+                spl_token::id(),
+            ),
+        };
+
+        let accounts = gamma::accounts::Swap {
+            payer: user.pubkey(),
+            authority,
+            amm_config: amm_config_key,
+            pool_state: pool_id,
+            observation_state: observation_key,
+            input_token_account,
+            output_token_account,
+            input_vault,
+            output_vault,
+            input_token_program,
+            output_token_program,
+            input_token_mint,
+            output_token_mint,
+        };
+
+        let data = gamma::instruction::SwapBaseInput {
+            amount_in,
+            minimum_amount_out,
+        };
+
+        let mut instruction = get_instruction(data, accounts);
+        instruction.accounts.extend(remaining_accounts);
+        let transaction =
+            get_signed_transaction(&mut self.program_test_context, &[instruction], user).await;
+
+        self.program_test_context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap();
+    }
+
+    pub async fn swap_base_output(
+        &mut self,
+        user: &Keypair,
+        pool_id: Pubkey,
+        amm_config_index: u16,
+        amount_out: u64,
+        max_amount_in: u64,
+        trade_direction: TradeDirection,
+    ) {
+        let (authority, __bump) =
+            Pubkey::find_program_address(&[AUTH_SEED.as_bytes()], &gamma::id());
+        let (amm_config_key, __bump) = Pubkey::find_program_address(
+            &[AMM_CONFIG_SEED.as_bytes(), &amm_config_index.to_be_bytes()],
+            &gamma::ID,
+        );
+
+        let user_token_0_account: Pubkey = self
+            .get_or_create_associated_token_account(user.pubkey(), self.token_0_mint.clone(), &user)
+            .await;
+
+        let user_token_1_account = self
+            .get_or_create_associated_token_account(user.pubkey(), self.token_1_mint.clone(), &user)
+            .await;
+
+        let (token_0_vault, __bump) = Pubkey::find_program_address(
+            &[
+                POOL_VAULT_SEED.as_bytes(),
+                pool_id.to_bytes().as_ref(),
+                self.token_0_mint.to_bytes().as_ref(),
+            ],
+            &gamma::ID,
+        );
+        let (token_1_vault, __bump) = Pubkey::find_program_address(
+            &[
+                POOL_VAULT_SEED.as_bytes(),
+                pool_id.to_bytes().as_ref(),
+                self.token_1_mint.to_bytes().as_ref(),
+            ],
+            &gamma::ID,
+        );
+        let (observation_key, __bump) = Pubkey::find_program_address(
+            &[OBSERVATION_SEED.as_bytes(), pool_id.to_bytes().as_ref()],
+            &gamma::ID,
+        );
+
+        let (
+            input_token_account,
+            output_token_account,
+            input_token_mint,
+            output_token_mint,
+            input_vault,
+            output_vault,
+            input_token_program,
+            output_token_program,
+        ) = match trade_direction {
+            TradeDirection::ZeroForOne => (
+                user_token_0_account,
+                user_token_1_account,
+                self.token_0_mint,
+                self.token_1_mint,
+                token_0_vault,
+                token_1_vault,
+                spl_token::id(),
+                spl_token::id(),
+            ),
+            TradeDirection::OneForZero => (
+                user_token_1_account,
+                user_token_0_account,
+                self.token_1_mint,
+                self.token_0_mint,
+                token_1_vault,
+                token_0_vault,
+                spl_token::id(),
+                spl_token::id(),
+            ),
+        };
+
+        let accounts = gamma::accounts::Swap {
+            payer: user.pubkey(),
+            authority,
+            amm_config: amm_config_key,
+            pool_state: pool_id,
+            observation_state: observation_key,
+            input_token_account,
+            output_token_account,
+            input_vault,
+            output_vault,
+            input_token_program,
+            output_token_program,
+            input_token_mint,
+            output_token_mint,
+        };
+
+        let data = gamma::instruction::SwapBaseOutput {
+            amount_out,
+            max_amount_in,
+        };
+
+        let transaction = self
+            .encode_instruction_and_sign_transaction(data, accounts, user)
+            .await;
+
+        self.program_test_context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap();
+    }
+
+    pub async fn create_rewards(
+        &mut self,
+        user: &Keypair,
+        pool_id: Pubkey,
+        start_time: u64,
+        end_time: u64,
+        reward_mint: Pubkey,
+        reward_amount: u64,
+    ) {
+        let (authority, __bump) =
+            Pubkey::find_program_address(&[AUTH_SEED.as_bytes()], &gamma::id());
+
+        let (reward_info_key, _) = Pubkey::find_program_address(
+            &[
+                REWARD_INFO_SEED.as_bytes(),
+                pool_id.to_bytes().as_ref(),
+                &start_time.to_le_bytes(),
+                reward_mint.to_bytes().as_ref(),
+            ],
+            &gamma::id(),
+        );
+
+        let (reward_vault_key, _) = Pubkey::find_program_address(
+            &[
+                REWARD_VAULT_SEED.as_bytes(),
+                reward_info_key.to_bytes().as_ref(),
+            ],
+            &gamma::id(),
+        );
+
+        let reward_providers_token_account = self
+            .get_or_create_associated_token_account(user.pubkey(), reward_mint, user)
+            .await;
+
+        let accounts = gamma::accounts::CreateRewards {
+            reward_provider: user.pubkey(),
+            authority,
+            pool_state: pool_id,
+            reward_info: reward_info_key,
+            reward_vault: reward_vault_key,
+            reward_providers_token_account,
+            reward_mint,
+            token_program: spl_token::id(),
+            token_program_2022: spl_token_2022::id(),
+            system_program: system_program::ID,
+        };
+
+        let data = gamma::instruction::CreateRewards {
+            start_time,
+            end_time,
+            reward_amount,
+        };
+
+        let transaction = self
+            .encode_instruction_and_sign_transaction(data, accounts, user)
+            .await;
+
+        self.program_test_context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap();
+    }
+
+    pub async fn calculate_rewards(
+        &mut self,
+        user: &Keypair,
+        pool_id: Pubkey,
+        reward_info_key: Pubkey,
+    ) {
+        self.calculate_rewards_with_signer(user.pubkey(), pool_id, reward_info_key, user)
+            .await;
+    }
+
+    pub async fn calculate_rewards_with_signer(
+        &mut self,
+        user: Pubkey,
+        pool_id: Pubkey,
+        reward_info_key: Pubkey,
+        signer: &Keypair,
+    ) {
+        let user_pool_liquidity = Pubkey::find_program_address(
+            &[
+                USER_POOL_LIQUIDITY_SEED.as_bytes(),
+                pool_id.to_bytes().as_ref(),
+                user.to_bytes().as_ref(),
+            ],
+            &gamma::ID,
+        )
+        .0;
+
+        let (user_reward_info_key, _) = Pubkey::find_program_address(
+            &[
+                USER_REWARD_INFO_SEED.as_bytes(),
+                reward_info_key.to_bytes().as_ref(),
+                user.to_bytes().as_ref(),
+            ],
+            &gamma::id(),
+        );
+
+        let accounts = gamma::accounts::CalculateRewards {
+            signer: signer.pubkey(),
+            user,
+            user_reward_info: user_reward_info_key,
+            user_pool_liquidity,
+            pool_state: pool_id,
+            reward_info: reward_info_key,
+            system_program: system_program::ID,
+        };
+
+        let data = gamma::instruction::CalculateRewards {};
+
+        let transaction = self
+            .encode_instruction_and_sign_transaction(data, accounts, signer)
+            .await;
+
+        self.program_test_context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap();
+    }
+
+    pub async fn claim_rewards(
+        &mut self,
+        user: &Keypair,
+        pool_id: Pubkey,
+        reward_info_key: Pubkey,
+        reward_mint: Pubkey,
+    ) {
+        let (authority, __bump) =
+            Pubkey::find_program_address(&[AUTH_SEED.as_bytes()], &gamma::id());
+
+        let (user_reward_info_key, _) = Pubkey::find_program_address(
+            &[
+                USER_REWARD_INFO_SEED.as_bytes(),
+                reward_info_key.to_bytes().as_ref(),
+                user.pubkey().to_bytes().as_ref(),
+            ],
+            &gamma::id(),
+        );
+
+        let (reward_vault_key, _) = Pubkey::find_program_address(
+            &[
+                REWARD_VAULT_SEED.as_bytes(),
+                reward_info_key.to_bytes().as_ref(),
+            ],
+            &gamma::id(),
+        );
+
+        let user_token_account = self
+            .get_or_create_associated_token_account(user.pubkey(), reward_mint, user)
+            .await;
+
+        let accounts = gamma::accounts::ClaimRewards {
+            user: user.pubkey(),
+            user_reward_info: user_reward_info_key,
+            authority,
+            pool_state: pool_id,
+            reward_info: reward_info_key,
+            reward_vault: reward_vault_key,
+            user_token_account,
+            reward_mint,
+            token_program: spl_token::id(),
+            token_program_2022: spl_token_2022::id(),
+            system_program: system_program::ID,
+        };
+
+        let data = gamma::instruction::ClaimRewards {};
+
+        let transaction = self
+            .encode_instruction_and_sign_transaction(data, accounts, user)
+            .await;
+
+        self.program_test_context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap();
+    }
+
+    pub async fn close_reward_info(
+        &mut self,
+        reward_provider: &Keypair,
+        pool_id: Pubkey,
+        reward_info_key: Pubkey,
+        reward_mint: Pubkey,
+    ) -> Result<(), BanksClientError> {
+        let (authority, __bump) =
+            Pubkey::find_program_address(&[AUTH_SEED.as_bytes()], &gamma::id());
+
+        let (reward_vault_key, _) = Pubkey::find_program_address(
+            &[
+                REWARD_VAULT_SEED.as_bytes(),
+                reward_info_key.to_bytes().as_ref(),
+            ],
+            &gamma::id(),
+        );
+
+        let reward_providers_token_account = self
+            .get_or_create_associated_token_account(
+                reward_provider.pubkey(),
+                reward_mint,
+                reward_provider,
+            )
+            .await;
+
+        let accounts = gamma::accounts::CloseRewardInfo {
+            reward_provider: reward_provider.pubkey(),
+            authority,
+            pool_state: pool_id,
+            reward_info: reward_info_key,
+            reward_providers_token_account,
+            reward_vault: reward_vault_key,
+            reward_mint,
+            token_program: spl_token::id(),
+            token_program_2022: spl_token_2022::id(),
+        };
+
+        let data = gamma::instruction::CloseRewardInfo {};
+
+        let transaction = self
+            .encode_instruction_and_sign_transaction(data, accounts, reward_provider)
+            .await;
+
+        self.program_test_context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+    }
+
+    pub async fn top_up_rewards(
+        &mut self,
+        reward_provider: &Keypair,
+        pool_id: Pubkey,
+        reward_info_key: Pubkey,
+        reward_mint: Pubkey,
+        reward_amount: u64,
+    ) -> Result<(), BanksClientError> {
+        let (reward_vault_key, _) = Pubkey::find_program_address(
+            &[
+                REWARD_VAULT_SEED.as_bytes(),
+                reward_info_key.to_bytes().as_ref(),
+            ],
+            &gamma::id(),
+        );
+
+        let reward_providers_token_account = self
+            .get_or_create_associated_token_account(
+                reward_provider.pubkey(),
+                reward_mint,
+                reward_provider,
+            )
+            .await;
+
+        let accounts = gamma::accounts::TopUpRewards {
+            reward_provider: reward_provider.pubkey(),
+            pool_state: pool_id,
+            reward_info: reward_info_key,
+            reward_providers_token_account,
+            reward_vault: reward_vault_key,
+            reward_mint,
+            token_program: spl_token::id(),
+            token_program_2022: spl_token_2022::id(),
+        };
+
+        let data = gamma::instruction::TopUpRewards { reward_amount };
+
+        let transaction = self
+            .encode_instruction_and_sign_transaction(data, accounts, reward_provider)
+            .await;
+
+        self.program_test_context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+    }
+
+    pub async fn extend_rewards(
+        &mut self,
+        reward_provider: &Keypair,
+        pool_id: Pubkey,
+        reward_info_key: Pubkey,
+        end_time: u64,
+    ) -> Result<(), BanksClientError> {
+        let accounts = gamma::accounts::ExtendRewards {
+            reward_provider: reward_provider.pubkey(),
+            pool_state: pool_id,
+            reward_info: reward_info_key,
+        };
+
+        let data = gamma::instruction::ExtendRewards { end_time };
+
+        let transaction = self
+            .encode_instruction_and_sign_transaction(data, accounts, reward_provider)
+            .await;
+
+        self.program_test_context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+    }
+
+    pub async fn evict_reward_info(
+        &mut self,
+        owner: &Keypair,
+        pool_id: Pubkey,
+        reward_info_key: Pubkey,
+    ) -> Result<(), BanksClientError> {
+        let accounts = gamma::accounts::EvictRewardInfo {
+            owner: owner.pubkey(),
+            pool_state: pool_id,
+            reward_info: reward_info_key,
+        };
+
+        let data = gamma::instruction::EvictRewardInfo {};
+
+        let transaction = self
+            .encode_instruction_and_sign_transaction(data, accounts, owner)
+            .await;
+
+        self.program_test_context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+    }
+
+    pub async fn refund_unallocated_rewards(
+        &mut self,
+        reward_provider: &Keypair,
+        pool_id: Pubkey,
+        reward_info_key: Pubkey,
+        reward_mint: Pubkey,
+    ) -> Result<(), BanksClientError> {
+        let (authority, __bump) =
+            Pubkey::find_program_address(&[AUTH_SEED.as_bytes()], &gamma::id());
+
+        let (reward_vault_key, _) = Pubkey::find_program_address(
+            &[
+                REWARD_VAULT_SEED.as_bytes(),
+                reward_info_key.to_bytes().as_ref(),
+            ],
+            &gamma::id(),
+        );
+
+        let reward_providers_token_account = self
+            .get_or_create_associated_token_account(
+                reward_provider.pubkey(),
+                reward_mint,
+                reward_provider,
+            )
+            .await;
+
+        let accounts = gamma::accounts::RefundUnallocatedRewards {
+            reward_provider: reward_provider.pubkey(),
+            authority,
+            pool_state: pool_id,
+            reward_info: reward_info_key,
+            reward_providers_token_account,
+            reward_vault: reward_vault_key,
+            reward_mint,
+            token_program: spl_token::id(),
+            token_program_2022: spl_token_2022::id(),
+        };
+
+        let data = gamma::instruction::RefundUnallocatedRewards {};
+
+        let transaction = self
+            .encode_instruction_and_sign_transaction(data, accounts, reward_provider)
+            .await;
+
+        self.program_test_context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+    }
+
+    pub async fn close_user_reward_info(
+        &mut self,
+        user: &Keypair,
+        pool_id: Pubkey,
+        reward_info_key: Pubkey,
+    ) -> Result<(), BanksClientError> {
+        let (user_reward_info_key, _) = Pubkey::find_program_address(
+            &[
+                USER_REWARD_INFO_SEED.as_bytes(),
+                reward_info_key.to_bytes().as_ref(),
+                user.pubkey().to_bytes().as_ref(),
+            ],
+            &gamma::id(),
+        );
+
+        let (user_pool_liquidity, _) = Pubkey::find_program_address(
+            &[
+                USER_POOL_LIQUIDITY_SEED.as_bytes(),
+                pool_id.to_bytes().as_ref(),
+                user.pubkey().to_bytes().as_ref(),
+            ],
+            &gamma::id(),
+        );
+
+        let accounts = gamma::accounts::CloseUserRewardInfo {
+            user: user.pubkey(),
+            reward_info: reward_info_key,
+            user_reward_info: user_reward_info_key,
+            user_pool_liquidity: Some(user_pool_liquidity),
+        };
+
+        let data = gamma::instruction::CloseUserRewardInfo {};
+
+        let transaction = self
+            .encode_instruction_and_sign_transaction(data, accounts, user)
+            .await;
+
+        self.program_test_context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+    }
+
+    pub async fn set_price_feed_config(
+        &mut self,
+        admin: &Keypair,
+        pool_id: Pubkey,
+        token_0_feed_id: [u8; 32],
+        token_1_feed_id: [u8; 32],
+        max_price_age: u64,
+        max_confidence_rate: u64,
+    ) -> Pubkey {
+        let (price_feed_config, _) = Pubkey::find_program_address(
+            &[PRICE_FEED_CONFIG_SEED.as_bytes(), pool_id.as_ref()],
+            &gamma::ID,
+        );
+
+        let accounts = gamma::accounts::SetPriceFeedConfig {
+            owner: admin.pubkey(),
+            pool_state: pool_id,
+            price_feed_config,
+            system_program: system_program::ID,
+        };
+
+        let data = gamma::instruction::SetPriceFeedConfig {
+            token_0_feed_id,
+            token_1_feed_id,
+            max_price_age,
+            max_confidence_rate,
+        };
+
+        let transaction = self
+            .encode_instruction_and_sign_transaction(data, accounts, admin)
+            .await;
+
+        self.program_test_context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap();
+
+        price_feed_config
+    }
+
+    // Writes a pyth PriceUpdateV2 account, fully verified.
+    pub fn set_price_update(
+        &mut self,
+        address: Pubkey,
+        feed_id: [u8; 32],
+        price: i64,
+        conf: u64,
+        exponent: i32,
+        publish_time: i64,
+    ) {
+        let mut data = vec![34, 241, 35, 99, 157, 126, 244, 205];
+        data.extend_from_slice(Pubkey::new_unique().as_ref());
+        // VerificationLevel::Full
+        data.push(1);
+        data.extend_from_slice(&feed_id);
+        data.extend_from_slice(&price.to_le_bytes());
+        data.extend_from_slice(&conf.to_le_bytes());
+        data.extend_from_slice(&exponent.to_le_bytes());
+        data.extend_from_slice(&publish_time.to_le_bytes());
+        data.extend_from_slice(&publish_time.to_le_bytes());
+        data.extend_from_slice(&price.to_le_bytes());
+        data.extend_from_slice(&conf.to_le_bytes());
+        data.extend_from_slice(&0u64.to_le_bytes());
+
+        self.program_test_context.set_account(
+            &address,
+            &Account {
+                lamports: Rent::default().minimum_balance(data.len()),
+                owner: solana_sdk::pubkey!("rec5EKMGg6MxZYaMdyBfgwp4d5rB9T1VQH5pJv5LtFJ"),
+                data,
+                ..Default::default()
+            }
+            .into(),
+        );
+    }
+
+    pub async fn oracle_swap_base_input(
+        &mut self,
+        user: &Keypair,
+        pool_id: Pubkey,
+        amm_config_index: u16,
+        amount_in: u64,
+        minimum_amount_out: u64,
+        trade_direction: TradeDirection,
+        price_feed_config: Pubkey,
+        price_updates: Vec<Pubkey>,
+    ) -> ProcessTransactionResult {
+        let (authority, __bump) =
+            Pubkey::find_program_address(&[AUTH_SEED.as_bytes()], &gamma::id());
+        let (amm_config_key, __bump) = Pubkey::find_program_address(
+            &[AMM_CONFIG_SEED.as_bytes(), &amm_config_index.to_be_bytes()],
+            &gamma::ID,
+        );
+
+        let user_token_0_account: Pubkey = self
+            .get_or_create_associated_token_account(user.pubkey(), self.token_0_mint.clone(), &user)
+            .await;
+
+        let user_token_1_account = self
+            .get_or_create_associated_token_account(user.pubkey(), self.token_1_mint.clone(), &user)
+            .await;
+
+        let (token_0_vault, __bump) = Pubkey::find_program_address(
+            &[
+                POOL_VAULT_SEED.as_bytes(),
+                pool_id.to_bytes().as_ref(),
+                self.token_0_mint.to_bytes().as_ref(),
+            ],
+            &gamma::ID,
+        );
+        let (token_1_vault, __bump) = Pubkey::find_program_address(
+            &[
+                POOL_VAULT_SEED.as_bytes(),
+                pool_id.to_bytes().as_ref(),
+                self.token_1_mint.to_bytes().as_ref(),
+            ],
+            &gamma::ID,
+        );
+        let (observation_key, __bump) = Pubkey::find_program_address(
+            &[OBSERVATION_SEED.as_bytes(), pool_id.to_bytes().as_ref()],
+            &gamma::ID,
+        );
+
+        let (
+            input_token_account,
+            output_token_account,
             input_token_mint,
             output_token_mint,
             input_vault,
             output_vault,
-            input_token_program,
-            output_token_program,
         ) = match trade_direction {
             TradeDirection::ZeroForOne => (
                 user_token_0_account,
@@ -1253,8 +2893,6 @@ impl TestEnv {
                 self.token_1_mint,
                 token_0_vault,
                 token_1_vault,
-                spl_token::id(),
-                spl_token::id(),
             ),
             TradeDirection::OneForZero => (
                 user_token_1_account,
@@ -1263,34 +2901,379 @@ impl TestEnv {
                 self.token_0_mint,
                 token_1_vault,
                 token_0_vault,
-                spl_token::id(),
-                spl_token::id(),
             ),
         };
 
-        let accounts = gamma::accounts::Swap {
-            payer: user.pubkey(),
-            authority,
-            amm_config: amm_config_key,
+        let accounts = gamma::accounts::Swap {
+            payer: user.pubkey(),
+            authority,
+            amm_config: amm_config_key,
+            pool_state: pool_id,
+            observation_state: observation_key,
+            input_token_account,
+            output_token_account,
+            input_vault,
+            output_vault,
+            input_token_program: spl_token::id(),
+            output_token_program: spl_token::id(),
+            input_token_mint,
+            output_token_mint,
+        };
+
+        let data = gamma::instruction::OracleBasedSwapBaseInput {
+            amount_in,
+            minimum_amount_out,
+        };
+
+        let mut instruction = get_instruction(data, accounts);
+        // segmenter, registry, referral account and referral token account are not used
+        for _ in 0..4 {
+            instruction
+                .accounts
+                .push(AccountMeta::new_readonly(gamma::id(), false));
+        }
+        instruction
+            .accounts
+            .push(AccountMeta::new_readonly(price_feed_config, false));
+        for price_update in price_updates {
+            instruction
+                .accounts
+                .push(AccountMeta::new_readonly(price_update, false));
+        }
+
+        let transaction =
+            get_signed_transaction(&mut self.program_test_context, &[instruction], user).await;
+
+        self.program_test_context
+            .banks_client
+            .process_transaction_with_metadata(transaction)
+            .await
+    }
+
+    /// Swaps through the given pools in order, every hop is `(pool_id, amm_config_index, trade_direction)`.
+    pub async fn swap_route(
+        &mut self,
+        user: &Keypair,
+        hops: Vec<(Pubkey, u16, TradeDirection)>,
+        amount_in: u64,
+        minimum_amount_out: u64,
+    ) -> Result<(), BanksClientError> {
+        let (authority, __bump) =
+            Pubkey::find_program_address(&[AUTH_SEED.as_bytes()], &gamma::id());
+        let (token_0_mint, token_1_mint) = (self.token_0_mint, self.token_1_mint);
+        let token_mint =
+            |trade_direction: TradeDirection, input: bool| match (trade_direction, input) {
+                (TradeDirection::ZeroForOne, true) | (TradeDirection::OneForZero, false) => {
+                    token_0_mint
+                }
+                (TradeDirection::ZeroForOne, false) | (TradeDirection::OneForZero, true) => {
+                    token_1_mint
+                }
+            };
+        let input_token_mint = token_mint(hops[0].2, true);
+        let output_token_mint = token_mint(hops[hops.len() - 1].2, false);
+
+        let input_token_account = self
+            .get_or_create_associated_token_account(user.pubkey(), input_token_mint, &user)
+            .await;
+        let output_token_account = self
+            .get_or_create_associated_token_account(user.pubkey(), output_token_mint, &user)
+            .await;
+
+        let accounts = gamma::accounts::SwapRoute {
+            payer: user.pubkey(),
+            authority,
+            input_token_account,
+            output_token_account,
+            input_token_program: spl_token::id(),
+            input_token_mint,
+        };
+        let data = gamma::instruction::SwapRoute {
+            amount_in,
+            minimum_amount_out,
+        };
+
+        let mut instruction = get_instruction(data, accounts);
+        for (pool_id, amm_config_index, trade_direction) in hops {
+            let (amm_config_key, __bump) = Pubkey::find_program_address(
+                &[AMM_CONFIG_SEED.as_bytes(), &amm_config_index.to_be_bytes()],
+                &gamma::ID,
+            );
+            let (observation_key, __bump) = Pubkey::find_program_address(
+                &[OBSERVATION_SEED.as_bytes(), pool_id.to_bytes().as_ref()],
+                &gamma::ID,
+            );
+            let vault = |mint: Pubkey| {
+                Pubkey::find_program_address(
+                    &[
+                        POOL_VAULT_SEED.as_bytes(),
+                        pool_id.to_bytes().as_ref(),
+                        mint.to_bytes().as_ref(),
+                    ],
+                    &gamma::ID,
+                )
+                .0
+            };
+            let hop_output_mint = token_mint(trade_direction, false);
+
+            instruction.accounts.extend([
+                AccountMeta::new_readonly(amm_config_key, false),
+                AccountMeta::new(pool_id, false),
+                AccountMeta::new(vault(token_mint(trade_direction, true)), false),
+                AccountMeta::new(vault(hop_output_mint), false),
+                AccountMeta::new_readonly(hop_output_mint, false),
+                AccountMeta::new_readonly(spl_token::id(), false),
+                AccountMeta::new(observation_key, false),
+            ]);
+        }
+
+        let transaction =
+            get_signed_transaction(&mut self.program_test_context, &[instruction], user).await;
+
+        self.program_test_context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+    }
+
+    pub async fn update_amm_config(
+        &mut self,
+        admin: &Keypair,
+        amm_index: u16,
+        param: u16,
+        value: u64,
+    ) {
+        let (amm_config_key, __bump) = Pubkey::find_program_address(
+            &[AMM_CONFIG_SEED.as_bytes(), &amm_index.to_be_bytes()],
+            &gamma::ID,
+        );
+
+        let accounts = gamma::accounts::UpdateAmmConfig {
+            owner: admin.pubkey(),
+            amm_config: amm_config_key,
+        };
+        let data = gamma::instruction::UpdateAmmConfig { param, value };
+
+        let transaction = self
+            .encode_instruction_and_sign_transaction(data, accounts, admin)
+            .await;
+
+        self.program_test_context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap();
+    }
+
+    /// Updates a param of the amm config that is set to the key of `account`
+    pub async fn update_amm_config_with_account(
+        &mut self,
+        admin: &Keypair,
+        amm_index: u16,
+        param: u16,
+        account: Pubkey,
+    ) {
+        let (amm_config_key, __bump) = Pubkey::find_program_address(
+            &[AMM_CONFIG_SEED.as_bytes(), &amm_index.to_be_bytes()],
+            &gamma::ID,
+        );
+
+        let accounts = gamma::accounts::UpdateAmmConfig {
+            owner: admin.pubkey(),
+            amm_config: amm_config_key,
+        };
+        let data = gamma::instruction::UpdateAmmConfig { param, value: 0 };
+
+        let mut instruction = get_instruction(data, accounts);
+        instruction
+            .accounts
+            .push(AccountMeta::new_readonly(account, false));
+        let transaction =
+            get_signed_transaction(&mut self.program_test_context, &[instruction], admin).await;
+
+        self.program_test_context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap();
+    }
+
+    /// Creates a segmenter registry owned by `registry_program` with `segmenters` registered in it
+    pub fn create_segmenter_registry(
+        &mut self,
+        registry_program: Pubkey,
+        mut segmenters: Vec<Pubkey>,
+    ) -> Pubkey {
+        let address = Pubkey::new_unique();
+        // The registry is searched with a binary search, unused entries are default pubkeys
+        segmenters.resize(64, Pubkey::default());
+        segmenters.sort();
+
+        let mut data = Registry::DISCRIMINATOR.to_vec();
+        for segmenter in segmenters {
+            data.extend_from_slice(segmenter.as_ref());
+        }
+
+        self.program_test_context.set_account(
+            &address,
+            &Account {
+                lamports: Rent::default().minimum_balance(data.len()),
+                owner: registry_program,
+                data,
+                ..Default::default()
+            }
+            .into(),
+        );
+        address
+    }
+
+    /// Borrows `amount` from the vault of the pool and repays it in the same transaction if `repay` is set.
+    pub async fn flash_loan(
+        &mut self,
+        user: &Keypair,
+        pool_id: Pubkey,
+        amm_config_index: u16,
+        amount: u64,
+        borrow_token_0: bool,
+        repay: bool,
+    ) -> Result<(), BanksClientError> {
+        let (authority, __bump) =
+            Pubkey::find_program_address(&[AUTH_SEED.as_bytes()], &gamma::id());
+        let (amm_config_key, __bump) = Pubkey::find_program_address(
+            &[AMM_CONFIG_SEED.as_bytes(), &amm_config_index.to_be_bytes()],
+            &gamma::ID,
+        );
+        let (token_0_vault, token_1_vault) = self.pool_vaults(pool_id);
+        let (token_mint, token_vault) = if borrow_token_0 {
+            (self.token_0_mint, token_0_vault)
+        } else {
+            (self.token_1_mint, token_1_vault)
+        };
+        let token_account = self
+            .get_or_create_associated_token_account(user.pubkey(), token_mint, &user)
+            .await;
+
+        let accounts = gamma::accounts::FlashLoan {
+            payer: user.pubkey(),
+            authority,
+            amm_config: amm_config_key,
+            pool_state: pool_id,
+            token_vault,
+            token_account,
+            token_program: spl_token::id(),
+            token_mint,
+            instruction_sysvar_account: sysvar::instructions::id(),
+        };
+
+        let account_metas = accounts.to_account_metas(None);
+        let mut instructions = vec![Instruction {
+            program_id: gamma::ID,
+            accounts: account_metas.clone(),
+            data: gamma::instruction::FlashLoan { amount }.data(),
+        }];
+        if repay {
+            instructions.push(Instruction {
+                program_id: gamma::ID,
+                accounts: account_metas,
+                data: gamma::instruction::FlashRepay {
+                    flash_loan_instruction_index: 0,
+                }
+                .data(),
+            });
+        }
+
+        let transaction =
+            get_signed_transaction(&mut self.program_test_context, &instructions, user).await;
+
+        self.program_test_context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+    }
+
+    pub async fn get_twap(&mut self, user: &Keypair, pool_id: Pubkey, window_seconds: u64) -> Twap {
+        let (observation_key, __bump) = Pubkey::find_program_address(
+            &[OBSERVATION_SEED.as_bytes(), pool_id.to_bytes().as_ref()],
+            &gamma::ID,
+        );
+
+        let accounts = gamma::accounts::GetTwap {
             pool_state: pool_id,
             observation_state: observation_key,
-            input_token_account,
-            output_token_account,
-            input_vault,
-            output_vault,
-            input_token_program,
-            output_token_program,
-            input_token_mint,
-            output_token_mint,
         };
+        let data = gamma::instruction::GetTwap { window_seconds };
 
-        let data = gamma::instruction::SwapBaseOutput {
-            amount_out,
-            max_amount_in,
+        let transaction = self
+            .encode_instruction_and_sign_transaction(data, accounts, user)
+            .await;
+
+        let result = self
+            .program_test_context
+            .banks_client
+            .process_transaction_with_metadata(transaction)
+            .await
+            .unwrap();
+        let return_data = result.metadata.unwrap().return_data.unwrap();
+        Twap::try_from_slice(&return_data.data).unwrap()
+    }
+
+    pub async fn get_fees_earned(
+        &mut self,
+        payer: &Keypair,
+        pool_id: Pubkey,
+        user: Pubkey,
+    ) -> FeesEarned {
+        let user_pool_liquidity = Pubkey::find_program_address(
+            &[
+                USER_POOL_LIQUIDITY_SEED.as_bytes(),
+                pool_id.to_bytes().as_ref(),
+                user.to_bytes().as_ref(),
+            ],
+            &gamma::ID,
+        )
+        .0;
+        let accounts = gamma::accounts::GetFeesEarned {
+            pool_state: pool_id,
+            user_pool_liquidity,
         };
+        let data = gamma::instruction::GetFeesEarned {};
 
         let transaction = self
-            .encode_instruction_and_sign_transaction(data, accounts, user)
+            .encode_instruction_and_sign_transaction(data, accounts, payer)
+            .await;
+
+        let result = self
+            .program_test_context
+            .banks_client
+            .process_transaction_with_metadata(transaction)
+            .await
+            .unwrap();
+        let return_data = result.metadata.unwrap().return_data.unwrap();
+        FeesEarned::try_from_slice(&return_data.data).unwrap()
+    }
+
+    pub async fn update_pool(
+        &mut self,
+        admin: &Keypair,
+        pool_id: Pubkey,
+        amm_index: u16,
+        param: u32,
+        value: u64,
+    ) {
+        let (amm_config_key, __bump) = Pubkey::find_program_address(
+            &[AMM_CONFIG_SEED.as_bytes(), &amm_index.to_be_bytes()],
+            &gamma::ID,
+        );
+
+        let accounts = gamma::accounts::UpdatePool {
+            authority: admin.pubkey(),
+            pool_state: pool_id,
+            amm_config: amm_config_key,
+        };
+        let data = gamma::instruction::UpdatePool { param, value };
+
+        let transaction = self
+            .encode_instruction_and_sign_transaction(data, accounts, admin)
             .await;
 
         self.program_test_context
@@ -1300,87 +3283,125 @@ impl TestEnv {
             .unwrap();
     }
 
-    pub async fn create_rewards(
+    pub async fn expand_observation_state(
         &mut self,
-        user: &Keypair,
+        payer: &Keypair,
         pool_id: Pubkey,
-        start_time: u64,
-        end_time: u64,
-        reward_mint: Pubkey,
-        reward_amount: u64,
-    ) {
+        additional_observations: u16,
+    ) -> Result<(), BanksClientError> {
+        let (observation_key, __bump) = Pubkey::find_program_address(
+            &[OBSERVATION_SEED.as_bytes(), pool_id.to_bytes().as_ref()],
+            &gamma::ID,
+        );
+
+        let accounts = gamma::accounts::ExpandObservationState {
+            payer: payer.pubkey(),
+            pool_state: pool_id,
+            observation_state: observation_key,
+            system_program: system_program::ID,
+        };
+        let data = gamma::instruction::ExpandObservationState {
+            additional_observations,
+        };
+
+        let transaction = self
+            .encode_instruction_and_sign_transaction(data, accounts, payer)
+            .await;
+
+        self.program_test_context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+    }
+
+    pub async fn collect_creator_fee(
+        &mut self,
+        owner: &Keypair,
+        pool_id: Pubkey,
+        amount_0_requested: u64,
+        amount_1_requested: u64,
+    ) -> Result<(), BanksClientError> {
         let (authority, __bump) =
             Pubkey::find_program_address(&[AUTH_SEED.as_bytes()], &gamma::id());
-
-        let (reward_info_key, _) = Pubkey::find_program_address(
+        let (token_0_vault, __bump) = Pubkey::find_program_address(
             &[
-                REWARD_INFO_SEED.as_bytes(),
+                POOL_VAULT_SEED.as_bytes(),
                 pool_id.to_bytes().as_ref(),
-                &start_time.to_le_bytes(),
-                reward_mint.to_bytes().as_ref(),
+                self.token_0_mint.to_bytes().as_ref(),
             ],
-            &gamma::id(),
+            &gamma::ID,
         );
-
-        let (reward_vault_key, _) = Pubkey::find_program_address(
+        let (token_1_vault, __bump) = Pubkey::find_program_address(
             &[
-                REWARD_VAULT_SEED.as_bytes(),
-                reward_info_key.to_bytes().as_ref(),
+                POOL_VAULT_SEED.as_bytes(),
+                pool_id.to_bytes().as_ref(),
+                self.token_1_mint.to_bytes().as_ref(),
             ],
-            &gamma::id(),
+            &gamma::ID,
         );
-
-        let reward_providers_token_account = self
-            .get_or_create_associated_token_account(user.pubkey(), reward_mint, user)
+        let recipient_token_0_account = self
+            .get_or_create_associated_token_account(owner.pubkey(), self.token_0_mint, owner)
+            .await;
+        let recipient_token_1_account = self
+            .get_or_create_associated_token_account(owner.pubkey(), self.token_1_mint, owner)
             .await;
 
-        let accounts = gamma::accounts::CreateRewards {
-            reward_provider: user.pubkey(),
+        let accounts = gamma::accounts::CollectCreatorFee {
+            owner: owner.pubkey(),
             authority,
             pool_state: pool_id,
-            reward_info: reward_info_key,
-            reward_vault: reward_vault_key,
-            reward_providers_token_account,
-            reward_mint,
+            token_0_vault,
+            token_1_vault,
+            vault_0_mint: self.token_0_mint,
+            vault_1_mint: self.token_1_mint,
+            recipient_token_0_account,
+            recipient_token_1_account,
             token_program: spl_token::id(),
             token_program_2022: spl_token_2022::id(),
-            system_program: system_program::ID,
         };
-
-        let data = gamma::instruction::CreateRewards {
-            start_time,
-            end_time,
-            reward_amount,
+        let data = gamma::instruction::CollectCreatorFee {
+            amount_0_requested,
+            amount_1_requested,
         };
 
         let transaction = self
-            .encode_instruction_and_sign_transaction(data, accounts, user)
+            .encode_instruction_and_sign_transaction(data, accounts, owner)
             .await;
 
         self.program_test_context
             .banks_client
             .process_transaction(transaction)
             .await
-            .unwrap();
     }
 
-    pub async fn calculate_rewards(
+    pub async fn resize_pool_state(
         &mut self,
-        user: &Keypair,
+        payer: &Keypair,
         pool_id: Pubkey,
-        reward_info_key: Pubkey,
-    ) {
-        self.calculate_rewards_with_signer(user.pubkey(), pool_id, reward_info_key, user)
+    ) -> Result<(), BanksClientError> {
+        let accounts = gamma::accounts::ResizePoolState {
+            payer: payer.pubkey(),
+            pool_state: pool_id,
+            system_program: system_program::ID,
+        };
+        let data = gamma::instruction::ResizePoolState {};
+
+        let transaction = self
+            .encode_instruction_and_sign_transaction(data, accounts, payer)
             .await;
+
+        self.program_test_context
+            .banks_client
+            .process_transaction(transaction)
+            .await
     }
 
-    pub async fn calculate_rewards_with_signer(
+    pub async fn resize_user_pool_liquidity(
         &mut self,
-        user: Pubkey,
+        payer: &Keypair,
         pool_id: Pubkey,
-        reward_info_key: Pubkey,
-        signer: &Keypair,
-    ) {
+        user: Pubkey,
+    ) -> Result<(), BanksClientError> {
         let user_pool_liquidity = Pubkey::find_program_address(
             &[
                 USER_POOL_LIQUIDITY_SEED.as_bytes(),
@@ -1390,94 +3411,104 @@ impl TestEnv {
             &gamma::ID,
         )
         .0;
+        let accounts = gamma::accounts::ResizeUserPoolLiquidity {
+            payer: payer.pubkey(),
+            pool_state: pool_id,
+            user_pool_liquidity,
+            system_program: system_program::ID,
+        };
+        let data = gamma::instruction::ResizeUserPoolLiquidity {};
 
-        let (user_reward_info_key, _) = Pubkey::find_program_address(
-            &[
-                USER_REWARD_INFO_SEED.as_bytes(),
-                reward_info_key.to_bytes().as_ref(),
-                user.to_bytes().as_ref(),
-            ],
-            &gamma::id(),
-        );
+        let transaction = self
+            .encode_instruction_and_sign_transaction(data, accounts, payer)
+            .await;
 
-        let accounts = gamma::accounts::CalculateRewards {
-            signer: signer.pubkey(),
-            user,
-            user_reward_info: user_reward_info_key,
-            user_pool_liquidity,
+        self.program_test_context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+    }
+
+    pub async fn migrate_reward_info(
+        &mut self,
+        payer: &Keypair,
+        pool_id: Pubkey,
+        reward_info: Pubkey,
+    ) -> Result<(), BanksClientError> {
+        let accounts = gamma::accounts::MigrateRewardInfo {
+            payer: payer.pubkey(),
             pool_state: pool_id,
-            reward_info: reward_info_key,
+            reward_info,
             system_program: system_program::ID,
         };
-
-        let data = gamma::instruction::CalculateRewards {};
+        let data = gamma::instruction::MigrateRewardInfo {};
 
         let transaction = self
-            .encode_instruction_and_sign_transaction(data, accounts, signer)
+            .encode_instruction_and_sign_transaction(data, accounts, payer)
             .await;
 
         self.program_test_context
             .banks_client
             .process_transaction(transaction)
             .await
-            .unwrap();
     }
 
-    pub async fn claim_rewards(
+    pub async fn migrate_user_reward_info(
         &mut self,
-        user: &Keypair,
+        payer: &Keypair,
         pool_id: Pubkey,
-        reward_info_key: Pubkey,
-        reward_mint: Pubkey,
-    ) {
-        let (authority, __bump) =
-            Pubkey::find_program_address(&[AUTH_SEED.as_bytes()], &gamma::id());
-
-        let (user_reward_info_key, _) = Pubkey::find_program_address(
+        reward_info: Pubkey,
+        user: Pubkey,
+    ) -> Result<(), BanksClientError> {
+        let user_pool_liquidity = Pubkey::find_program_address(
             &[
-                USER_REWARD_INFO_SEED.as_bytes(),
-                reward_info_key.to_bytes().as_ref(),
-                user.pubkey().to_bytes().as_ref(),
+                USER_POOL_LIQUIDITY_SEED.as_bytes(),
+                pool_id.to_bytes().as_ref(),
+                user.to_bytes().as_ref(),
             ],
-            &gamma::id(),
-        );
-
-        let (reward_vault_key, _) = Pubkey::find_program_address(
+            &gamma::ID,
+        )
+        .0;
+        let user_reward_info = Pubkey::find_program_address(
             &[
-                REWARD_VAULT_SEED.as_bytes(),
-                reward_info_key.to_bytes().as_ref(),
+                USER_REWARD_INFO_SEED.as_bytes(),
+                reward_info.to_bytes().as_ref(),
+                user.to_bytes().as_ref(),
             ],
-            &gamma::id(),
-        );
-
-        let user_token_account = self
-            .get_or_create_associated_token_account(user.pubkey(), reward_mint, user)
-            .await;
-
-        let accounts = gamma::accounts::ClaimRewards {
-            user: user.pubkey(),
-            user_reward_info: user_reward_info_key,
-            authority,
+            &gamma::ID,
+        )
+        .0;
+        let accounts = gamma::accounts::MigrateUserRewardInfo {
+            payer: payer.pubkey(),
+            user,
             pool_state: pool_id,
-            reward_info: reward_info_key,
-            reward_vault: reward_vault_key,
-            user_token_account,
-            reward_mint,
-            token_program: spl_token::id(),
-            token_program_2022: spl_token_2022::id(),
+            reward_info,
+            user_reward_info,
+            user_pool_liquidity,
             system_program: system_program::ID,
         };
-
-        let data = gamma::instruction::ClaimRewards {};
+        let data = gamma::instruction::MigrateUserRewardInfo {};
 
         let transaction = self
-            .encode_instruction_and_sign_transaction(data, accounts, user)
+            .encode_instruction_and_sign_transaction(data, accounts, payer)
             .await;
 
         self.program_test_context
             .banks_client
             .process_transaction(transaction)
             .await
-            .unwrap();
+    }
+
+    /// Returns the raw data of the observation account of the pool, see `Observations::from_account_data`.
+    pub async fn fetch_observation_data(&mut self, pool_id: Pubkey) -> Vec<u8> {
+        let (observation_key, __bump) = Pubkey::find_program_address(
+            &[OBSERVATION_SEED.as_bytes(), pool_id.to_bytes().as_ref()],
+            &gamma::ID,
+        );
+        self.get_account_info(observation_key)
+            .await
+            .unwrap()
+            .unwrap()
+            .data
     }
 }
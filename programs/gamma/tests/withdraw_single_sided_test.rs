@@ -0,0 +1,93 @@
+use anchor_spl::token::TokenAccount;
+use gamma::states::{PoolState, POOL_VAULT_SEED};
+use solana_program_test::tokio;
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer};
+mod utils;
+
+use utils::*;
+
+#[tokio::test]
+async fn should_withdraw_in_a_single_token() {
+    let user = Keypair::new();
+    let admin = get_admin();
+    let amm_index = 0;
+    let mut test_env = TestEnv::new(vec![user.pubkey(), admin.pubkey()]).await;
+    test_env
+        .create_config(&admin, amm_index, 100, 20, 5, 0)
+        .await;
+
+    let user_token_0_account = test_env
+        .get_or_create_associated_token_account(user.pubkey(), test_env.token_0_mint, &user)
+        .await;
+    test_env
+        .mint_base_tokens(user_token_0_account, 10000000000, test_env.token_0_mint)
+        .await;
+    let user_token_1_account = test_env
+        .get_or_create_associated_token_account(user.pubkey(), test_env.token_1_mint, &user)
+        .await;
+    test_env
+        .mint_base_tokens(user_token_1_account, 10000000000, test_env.token_1_mint)
+        .await;
+
+    let pool_id = test_env
+        .initialize_pool(
+            &user,
+            amm_index,
+            1000000000,
+            1000000000,
+            0,
+            gamma::create_pool_fee_reveiver::id(),
+        )
+        .await;
+
+    let (token_1_vault, _) = Pubkey::find_program_address(
+        &[
+            POOL_VAULT_SEED.as_bytes(),
+            pool_id.as_ref(),
+            test_env.token_1_mint.as_ref(),
+        ],
+        &gamma::ID,
+    );
+    let user_token_0_before: TokenAccount = test_env.fetch_account(user_token_0_account).await;
+    let user_token_1_before: TokenAccount = test_env.fetch_account(user_token_1_account).await;
+    let token_1_vault_before: TokenAccount = test_env.fetch_account(token_1_vault).await;
+    let pool_state_before: PoolState = test_env.fetch_account(pool_id).await;
+
+    // 10% of the pool is 100000000 of each token, the token_1 share is swapped
+    // against the remaining 900000000/900000000 reserves for about 90000000 token_0
+    let lp_token_amount = 100000000;
+    let minimum_amount_out = 189990000;
+    test_env
+        .withdraw_single_sided(
+            &user,
+            pool_id,
+            amm_index,
+            lp_token_amount,
+            minimum_amount_out,
+            true,
+        )
+        .await;
+
+    let user_token_0_after: TokenAccount = test_env.fetch_account(user_token_0_account).await;
+    let amount_received = user_token_0_after.amount - user_token_0_before.amount;
+    assert!(amount_received >= minimum_amount_out);
+    assert!(amount_received < 190000000);
+
+    // Nothing is withdrawn in token_1
+    let user_token_1_after: TokenAccount = test_env.fetch_account(user_token_1_account).await;
+    assert_eq!(user_token_1_after.amount, user_token_1_before.amount);
+    let token_1_vault_after: TokenAccount = test_env.fetch_account(token_1_vault).await;
+    assert_eq!(token_1_vault_after.amount, token_1_vault_before.amount);
+
+    let pool_state: PoolState = test_env.fetch_account(pool_id).await;
+    assert_eq_with_copy!(
+        pool_state.lp_supply,
+        pool_state_before.lp_supply - lp_token_amount
+    );
+    assert_eq_with_copy!(
+        pool_state.token_0_vault_amount,
+        pool_state_before.token_0_vault_amount - amount_received
+    );
+    // Fees are charged on the swapped token_1
+    assert!({ pool_state.cumulative_trade_fees_token_1 } > 0);
+}